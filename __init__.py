@@ -4,8 +4,9 @@ from __future__ import annotations
 from homeassistant.config_entries import ConfigEntry
 from homeassistant.const import Platform
 from homeassistant.core import HomeAssistant
+from homeassistant.helpers.event import async_track_time_interval
 
-from .const import DOMAIN
+from .const import DOMAIN, EVENT_POLL_INTERVAL
 
 import logging
 import serial
@@ -17,7 +18,16 @@ from .python.connection import BuddyConnection
 LOGGER = logging.getLogger(DOMAIN)
 
 # For your initial PR, limit it to 1 platform.
-PLATFORMS: list[Platform] = [Platform.SENSOR, Platform.SWITCH]
+PLATFORMS: list[Platform] = [
+    Platform.SENSOR,
+    Platform.SWITCH,
+    Platform.BINARY_SENSOR,
+    Platform.NUMBER,
+    Platform.BUTTON,
+    Platform.LIGHT,
+    Platform.COVER,
+    Platform.SELECT,
+]
 
 
 async def async_setup(hass: HomeAssistant, config):
@@ -28,6 +38,13 @@ async def async_setup(hass: HomeAssistant, config):
         return False
 
     hass.data[DOMAIN] = connection
+
+    # Nodes report changes between the regular polls of the entities
+    async def poll_events(now) -> None:
+        await hass.async_add_executor_job(connection.poll_events)
+
+    async_track_time_interval(hass, poll_events, EVENT_POLL_INTERVAL)
+
     return True
 
 
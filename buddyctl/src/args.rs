@@ -0,0 +1,94 @@
+use std::ops::Range;
+
+use ha_buddy_proto::{
+    command::SWITCH_ID_ALL,
+    datalink::{Serial, BROADCAST_ADDR, SERIAL_LEN},
+};
+
+/// Parses an address or index, accepting `0x` (hex), `0o` (octal),
+/// `0b` (binary) prefixes or a plain decimal number
+/// # Arguments
+/// * `s` - The string to parse
+pub fn parse_number(s: &str) -> Result<u32, String> {
+    let (digits, radix) = match s.get(0..2) {
+        Some("0x") | Some("0X") => (&s[2..], 16),
+        Some("0o") | Some("0O") => (&s[2..], 8),
+        Some("0b") | Some("0B") => (&s[2..], 2),
+        _ => (s, 10),
+    };
+
+    u32::from_str_radix(digits, radix).map_err(|e| format!("Invalid number '{}': {}", s, e))
+}
+
+/// Parses a bus address, `broadcast` is the broadcast address
+/// # Arguments
+/// * `s` - The string to parse
+pub fn parse_addr(s: &str) -> Result<u16, String> {
+    if s == "broadcast" {
+        return Ok(BROADCAST_ADDR);
+    }
+
+    let addr = parse_number(s)?;
+    u16::try_from(addr).map_err(|_| format!("Address '{}' is out of range", s))
+}
+
+/// Parses the index of a switch, `all` selects all switches of a node
+/// # Arguments
+/// * `s` - The string to parse
+pub fn parse_switch_id(s: &str) -> Result<u32, String> {
+    match s {
+        "all" => Ok(SWITCH_ID_ALL),
+        _ => parse_number(s),
+    }
+}
+
+/// Parses a serial number given as 16 hex digits
+/// # Arguments
+/// * `s` - The string to parse
+pub fn parse_serial(s: &str) -> Result<Serial, String> {
+    let invalid = || format!("Invalid serial number '{}', expected 16 hex digits", s);
+
+    if s.len() != SERIAL_LEN * 2 {
+        return Err(invalid());
+    }
+
+    let mut serial = Serial::default();
+    for (i, byte) in serial.iter_mut().enumerate() {
+        let digits = s.get(i * 2..i * 2 + 2).ok_or_else(invalid)?;
+        *byte = u8::from_str_radix(digits, 16).map_err(|_| invalid())?;
+    }
+
+    Ok(serial)
+}
+
+/// Formats a serial number as hex digits, the way [`parse_serial`] accepts it
+/// # Arguments
+/// * `serial` - The serial number to format
+pub fn serial_str(serial: &Serial) -> String {
+    serial.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Parses an address range in the form `START..END` (exclusive) or `START..=END` (inclusive)
+/// # Arguments
+/// * `s` - The string to parse
+pub fn parse_range(s: &str) -> Result<Range<u32>, String> {
+    let (start, end, inclusive) = if let Some((start, end)) = s.split_once("..=") {
+        (start, end, true)
+    } else if let Some((start, end)) = s.split_once("..") {
+        (start, end, false)
+    } else {
+        return Err(format!(
+            "Invalid range '{}', expected START..END or START..=END",
+            s
+        ));
+    };
+
+    let start = parse_addr(start)? as u32;
+    let end = parse_addr(end)? as u32 + inclusive as u32;
+
+    if start >= end {
+        return Err(format!("Range '{}' is empty", s));
+    }
+
+    Ok(start..end)
+}
@@ -0,0 +1,1255 @@
+//! `buddyctl` - commissioning and diagnostics for nodes on the HA-Buddy bus
+
+use std::{
+    ops::Range,
+    process::ExitCode,
+    thread,
+    time::{Duration, Instant},
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use ha_buddy_client::{Client, DiscoveredNode, Error, NodeEvent, Value};
+use ha_buddy_proto::{
+    command::SWITCH_ID_ALL,
+    datalink::{is_multicast, Serial, BROADCAST_ADDR, FACTORY_ADDR},
+    device::{
+        CAP_BINARY_SENSOR, CAP_BUTTON, CAP_COVER, CAP_LIGHT, CAP_NUMBER, CAP_SELECT, CAP_SENSOR,
+        CAP_SWITCH,
+    },
+    homeassistant::{
+        cover::{CoverMotion, CoverRequest, CoverState},
+        light::{LightRequest, LightState},
+        switch::SwitchRequest,
+    },
+};
+use serde_json::json;
+use serialport::SerialPort;
+
+mod args;
+use args::*;
+
+/// The baudrate of the bus
+const BAUDRATE: u32 = 57600;
+
+#[derive(Parser)]
+#[command(version, about = "Commission and inspect nodes on the HA-Buddy bus")]
+struct Cli {
+    /// The serial port the bus is connected to
+    #[arg(short, long, default_value = "/dev/ttyUSB0")]
+    port: String,
+
+    /// The time to wait for a response in milliseconds
+    #[arg(short, long, default_value_t = 200)]
+    timeout: u64,
+
+    /// The amount of retries after a request timed out
+    #[arg(short, long, default_value_t = 2)]
+    retries: u8,
+
+    /// Seconds to wait after opening the port, e.g. for boards that reset on connect
+    #[arg(short, long, default_value_t = 0)]
+    wait: u64,
+
+    /// Print the results as JSON
+    #[arg(short, long)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Scan a range of addresses for nodes, e.g. `0x1000..0x1010`, group and
+    /// broadcast addresses are skipped
+    Scan {
+        #[arg(value_parser = parse_range)]
+        range: Range<u32>,
+    },
+    /// Discover nodes by their serial numbers, without knowing their addresses
+    Discover {
+        /// Discover all nodes instead of the ones at the factory address only
+        #[arg(short, long)]
+        all: bool,
+    },
+    /// Discover the nodes at the factory address and assign them addresses from a range
+    Enumerate {
+        #[arg(value_parser = parse_range)]
+        range: Range<u32>,
+    },
+    /// List all sensors of a node and their values
+    ListSensors {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+    },
+    /// List all switches of a node and their states
+    ListSwitches {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+    },
+    /// List all binary sensors of a node and their states
+    ListBinarySensors {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+    },
+    /// List all numbers of a node and their values
+    ListNumbers {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+    },
+    /// List all buttons of a node
+    ListButtons {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+    },
+    /// List all lights of a node and their states
+    ListLights {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+    },
+    /// List all covers of a node and their positions
+    ListCovers {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+    },
+    /// List all selects of a node, their options and the selected ones
+    ListSelects {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+    },
+    /// Read the value of a sensor
+    Read {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+        #[arg(value_parser = parse_number)]
+        sensor: u32,
+    },
+    /// Read the values of all sensors of a node in as few requests as possible
+    ReadAll {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+    },
+    /// Turn a switch on or off or toggle it
+    ///
+    /// `ID` may be `all` to select all switches of the node. Requests to
+    /// `broadcast` or group addresses are sent without waiting for a response.
+    Switch {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+        #[arg(value_parser = parse_switch_id)]
+        id: u32,
+        action: SwitchAction,
+    },
+    /// Set the value of a number
+    SetNumber {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+        #[arg(value_parser = parse_number)]
+        id: u32,
+        #[arg(allow_negative_numbers = true)]
+        value: f32,
+    },
+    /// Turn a light on or off or toggle it
+    Light {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+        #[arg(value_parser = parse_number)]
+        id: u32,
+        action: SwitchAction,
+        /// Fade to the new state within `TRANSITION` milliseconds
+        #[arg(short, long, default_value_t = 0)]
+        transition: u16,
+    },
+    /// Turn a light on at a brightness from `0` to `255`, `0` turns it off
+    Dim {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+        #[arg(value_parser = parse_number)]
+        id: u32,
+        brightness: u8,
+        /// Fade to the new brightness within `TRANSITION` milliseconds
+        #[arg(short, long, default_value_t = 0)]
+        transition: u16,
+    },
+    /// Open, close or stop a cover
+    Cover {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+        #[arg(value_parser = parse_number)]
+        id: u32,
+        action: CoverAction,
+    },
+    /// Move a cover to a position from `0` closed to `100` open
+    SetPosition {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+        #[arg(value_parser = parse_number)]
+        id: u32,
+        #[arg(value_parser = clap::value_parser!(u8).range(0..=100))]
+        position: u8,
+    },
+    /// Select an option of a select
+    ///
+    /// `OPTION` is either the index of the option or its name.
+    Select {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+        #[arg(value_parser = parse_number)]
+        id: u32,
+        option: String,
+    },
+    /// Press a button
+    ///
+    /// Requests to `broadcast` or group addresses are sent without waiting for a response.
+    Press {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+        #[arg(value_parser = parse_number)]
+        id: u32,
+    },
+    /// Fetch the changes a node reported since the last fetch
+    Events {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+        /// Keep fetching events every `interval` milliseconds
+        #[arg(short, long, value_name = "INTERVAL")]
+        follow: Option<u64>,
+    },
+    /// Show the device information of a node
+    Info {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+    },
+    /// Read the serial number of a node
+    Serial {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+    },
+    /// Assign a new address to a node
+    ///
+    /// The node has to be selected by its serial number or has to be in commissioning
+    /// mode, e.g. by pressing its commissioning button. Use `broadcast` as `ADDR` to
+    /// reach nodes sharing the factory address.
+    SetAddress {
+        /// The current address of the node
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+        /// The new address of the node
+        #[arg(value_parser = parse_addr)]
+        new_addr: u16,
+        /// The serial number of the node as 16 hex digits
+        #[arg(short, long, value_parser = parse_serial)]
+        serial: Option<Serial>,
+    },
+    /// Send an echo request to a node
+    Echo {
+        #[arg(value_parser = parse_addr)]
+        addr: u16,
+        /// The data to be echoed back
+        #[arg(default_value = "")]
+        data: String,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SwitchAction {
+    On,
+    Off,
+    Toggle,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum CoverAction {
+    Open,
+    Close,
+    Stop,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match run(&cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Opens the bus and runs the requested command
+fn run(cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let port = serialport::new(&cli.port, BAUDRATE)
+        .timeout(Duration::from_millis(10))
+        .open()
+        .map_err(|e| format!("Failed to open serial port {}: {}", cli.port, e))?;
+
+    if cli.wait > 0 {
+        thread::sleep(Duration::from_secs(cli.wait));
+    }
+
+    let mut client = Client::new(port);
+    client.set_timeout(Duration::from_millis(cli.timeout));
+    client.set_retries(cli.retries);
+
+    match &cli.command {
+        Command::Scan { range } => scan(&mut client, range.clone(), cli.json),
+        Command::Discover { all } => discover(&mut client, *all, cli.json),
+        Command::Enumerate { range } => enumerate(&mut client, range.clone(), cli.json),
+        Command::ListSensors { addr } => list_sensors(&mut client, *addr, cli.json),
+        Command::ListSwitches { addr } => list_switches(&mut client, *addr, cli.json),
+        Command::ListBinarySensors { addr } => list_binary_sensors(&mut client, *addr, cli.json),
+        Command::ListNumbers { addr } => list_numbers(&mut client, *addr, cli.json),
+        Command::ListButtons { addr } => list_buttons(&mut client, *addr, cli.json),
+        Command::ListLights { addr } => list_lights(&mut client, *addr, cli.json),
+        Command::ListCovers { addr } => list_covers(&mut client, *addr, cli.json),
+        Command::ListSelects { addr } => list_selects(&mut client, *addr, cli.json),
+        Command::Read { addr, sensor } => read(&mut client, *addr, *sensor, cli.json),
+        Command::ReadAll { addr } => read_all(&mut client, *addr, cli.json),
+        Command::Switch { addr, id, action } => switch(&mut client, *addr, *id, *action, cli.json),
+        Command::SetNumber { addr, id, value } => {
+            set_number(&mut client, *addr, *id, *value, cli.json)
+        }
+        Command::Press { addr, id } => press(&mut client, *addr, *id, cli.json),
+        Command::Light {
+            addr,
+            id,
+            action,
+            transition,
+        } => {
+            let transition_ms = *transition;
+            let req = match action {
+                SwitchAction::On => LightRequest::TurnOn {
+                    brightness: None,
+                    transition_ms,
+                },
+                SwitchAction::Off => LightRequest::TurnOff { transition_ms },
+                SwitchAction::Toggle => LightRequest::Toggle { transition_ms },
+            };
+            light(&mut client, *addr, *id, req, cli.json)
+        }
+        Command::Dim {
+            addr,
+            id,
+            brightness,
+            transition,
+        } => {
+            let req = LightRequest::TurnOn {
+                brightness: Some(*brightness),
+                transition_ms: *transition,
+            };
+            light(&mut client, *addr, *id, req, cli.json)
+        }
+        Command::Cover { addr, id, action } => {
+            let req = match action {
+                CoverAction::Open => CoverRequest::Open,
+                CoverAction::Close => CoverRequest::Close,
+                CoverAction::Stop => CoverRequest::Stop,
+            };
+            cover(&mut client, *addr, *id, req, cli.json)
+        }
+        Command::SetPosition { addr, id, position } => {
+            let req = CoverRequest::SetPosition(*position);
+            cover(&mut client, *addr, *id, req, cli.json)
+        }
+        Command::Select { addr, id, option } => {
+            // Resolving the name of an option may fail without the node failing
+            return select(&mut client, *addr, *id, option, cli.json);
+        }
+        Command::Events { addr, follow } => events(&mut client, *addr, *follow, cli.json),
+        Command::Info { addr } => info(&mut client, *addr, cli.json),
+        Command::Serial { addr } => serial(&mut client, *addr, cli.json),
+        Command::SetAddress {
+            addr,
+            new_addr,
+            serial,
+        } => set_address(&mut client, *addr, *new_addr, serial.as_ref(), cli.json),
+        Command::Echo { addr, data } => echo(&mut client, *addr, data, cli.json),
+    }?;
+
+    Ok(())
+}
+
+type BusClient = Client<Box<dyn SerialPort>>;
+
+/// Converts a sensor value to JSON
+fn value_json(value: &Option<Value>) -> serde_json::Value {
+    match value {
+        None => serde_json::Value::Null,
+        Some(Value::String(v)) => json!(v),
+        Some(Value::Int(v)) => json!(v),
+        Some(Value::UInt(v)) => json!(v),
+        Some(Value::Int64(v)) => json!(v),
+        Some(Value::UInt64(v)) => json!(v),
+        Some(Value::Bool(v)) => json!(v),
+        // Go through the shortest representation, `f32` to `f64` adds noise digits
+        Some(v @ (Value::Float(_) | Value::Fixed { .. })) => match v.to_string().parse::<f64>() {
+            Ok(v) => json!(v),
+            Err(_) => serde_json::Value::Null,
+        },
+    }
+}
+
+/// Converts a number value to JSON
+fn number_json(value: f32) -> serde_json::Value {
+    value_json(&Some(Value::Float(value)))
+}
+
+/// Formats a sensor value for humans
+fn value_str(value: &Option<Value>) -> String {
+    match value {
+        None => "-".to_string(),
+        Some(v) => v.to_string(),
+    }
+}
+
+/// Converts a light state to JSON
+fn light_json(state: &LightState) -> serde_json::Value {
+    json!({ "on": state.on, "brightness": state.brightness })
+}
+
+/// Formats a light state for humans
+fn light_str(state: &LightState) -> String {
+    format!(
+        "{}, brightness {}",
+        if state.on { "on" } else { "off" },
+        state.brightness
+    )
+}
+
+/// Returns the name of the direction a cover moves in
+fn motion_str(motion: CoverMotion) -> &'static str {
+    match motion {
+        CoverMotion::Stopped => "stopped",
+        CoverMotion::Opening => "opening",
+        CoverMotion::Closing => "closing",
+    }
+}
+
+/// Converts a cover state to JSON
+fn cover_json(state: &CoverState) -> serde_json::Value {
+    json!({ "position": state.position, "motion": motion_str(state.motion) })
+}
+
+/// Formats a cover state for humans
+fn cover_str(state: &CoverState) -> String {
+    format!("{} %, {}", state.position, motion_str(state.motion))
+}
+
+/// Returns the name of the selected option, the index if the option is unknown
+/// # Arguments
+/// * `options` - The options of the select
+/// * `index` - The index of the selected option
+fn option_str(options: &[String], index: u8) -> String {
+    match options.get(index as usize) {
+        Some(option) => option.clone(),
+        None => format!("option {}", index),
+    }
+}
+
+/// Formats a binary sensor state for humans
+fn state_str(state: &Option<bool>) -> &'static str {
+    match state {
+        None => "-",
+        Some(true) => "on",
+        Some(false) => "off",
+    }
+}
+
+fn scan(client: &mut BusClient, range: Range<u32>, as_json: bool) -> Result<(), Error> {
+    let mut found = Vec::new();
+
+    for addr in range {
+        let addr = addr as u16;
+        // No node answers on these, the client refuses to request them
+        if is_multicast(addr) {
+            continue;
+        }
+
+        match client.echo(addr, &[]) {
+            Ok(_) => found.push(addr),
+            Err(Error::Timeout { .. }) => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    if as_json {
+        println!("{}", json!(found));
+    } else {
+        for addr in &found {
+            println!("{:#06x}", addr);
+        }
+        println!("{} node(s) found", found.len());
+    }
+
+    Ok(())
+}
+
+/// Prints discovered nodes
+fn print_nodes(nodes: &[DiscoveredNode], as_json: bool) {
+    if as_json {
+        let nodes: Vec<_> = nodes
+            .iter()
+            .map(|node| json!({ "addr": node.addr, "serial": serial_str(&node.serial) }))
+            .collect();
+        println!("{}", json!(nodes));
+    } else {
+        for node in nodes {
+            println!("{:#06x}: {}", node.addr, serial_str(&node.serial));
+        }
+        println!("{} node(s)", nodes.len());
+    }
+}
+
+fn discover(client: &mut BusClient, all: bool, as_json: bool) -> Result<(), Error> {
+    let dst = if all { BROADCAST_ADDR } else { FACTORY_ADDR };
+    let nodes = client.discover(dst)?;
+
+    print_nodes(&nodes, as_json);
+
+    Ok(())
+}
+
+fn enumerate(client: &mut BusClient, range: Range<u32>, as_json: bool) -> Result<(), Error> {
+    let nodes = client.enumerate(range.map(|addr| addr as u16))?;
+
+    print_nodes(&nodes, as_json);
+
+    Ok(())
+}
+
+fn list_sensors(client: &mut BusClient, addr: u16, as_json: bool) -> Result<(), Error> {
+    let count = client.sensor_count(addr)?;
+    let mut sensors = Vec::new();
+
+    for id in 0..count {
+        sensors.push(client.describe_sensor(addr, id)?);
+    }
+
+    if as_json {
+        let sensors: Vec<_> = sensors
+            .iter()
+            .map(|(info, value)| {
+                json!({
+                    "id": info.id,
+                    "name": info.name,
+                    "unique_id": info.unique_id,
+                    "value": value_json(value),
+                    "native_unit_of_measurement": info.native_unit_of_measurement,
+                    "device_class": info.device_class,
+                    "state_class": info.state_class,
+                })
+            })
+            .collect();
+        println!("{}", json!(sensors));
+    } else {
+        println!("{} available sensors", count);
+        for (info, value) in &sensors {
+            println!();
+            println!("Sensor {}:", info.id);
+            println!("    name:                       {}", info.name);
+            println!("    unique_id:                  {}", info.unique_id);
+            println!("    value:                      {}", value_str(value));
+            println!(
+                "    native_unit_of_measurement: {}",
+                info.native_unit_of_measurement
+            );
+            println!("    device_class:               {}", info.device_class);
+            println!("    state_class:                {}", info.state_class);
+        }
+    }
+
+    Ok(())
+}
+
+fn list_switches(client: &mut BusClient, addr: u16, as_json: bool) -> Result<(), Error> {
+    let count = client.switch_count(addr)?;
+    let mut switches = Vec::new();
+
+    for id in 0..count {
+        switches.push(client.describe_switch(addr, id)?);
+    }
+
+    if as_json {
+        let switches: Vec<_> = switches
+            .iter()
+            .map(|(info, state)| {
+                json!({
+                    "id": info.id,
+                    "name": info.name,
+                    "unique_id": info.unique_id,
+                    "state": state,
+                })
+            })
+            .collect();
+        println!("{}", json!(switches));
+    } else {
+        println!("{} available switches", count);
+        for (info, state) in &switches {
+            println!();
+            println!("Switch {}:", info.id);
+            println!("    name:      {}", info.name);
+            println!("    unique_id: {}", info.unique_id);
+            println!("    state:     {}", if *state { "on" } else { "off" });
+        }
+    }
+
+    Ok(())
+}
+
+fn list_binary_sensors(client: &mut BusClient, addr: u16, as_json: bool) -> Result<(), Error> {
+    let count = client.binary_sensor_count(addr)?;
+    let mut binary_sensors = Vec::new();
+
+    for id in 0..count {
+        binary_sensors.push(client.describe_binary_sensor(addr, id)?);
+    }
+
+    if as_json {
+        let binary_sensors: Vec<_> = binary_sensors
+            .iter()
+            .map(|(info, state)| {
+                json!({
+                    "id": info.id,
+                    "name": info.name,
+                    "unique_id": info.unique_id,
+                    "state": state,
+                    "device_class": info.device_class,
+                })
+            })
+            .collect();
+        println!("{}", json!(binary_sensors));
+    } else {
+        println!("{} available binary sensors", count);
+        for (info, state) in &binary_sensors {
+            println!();
+            println!("Binary sensor {}:", info.id);
+            println!("    name:         {}", info.name);
+            println!("    unique_id:    {}", info.unique_id);
+            println!("    state:        {}", state_str(state));
+            println!("    device_class: {}", info.device_class);
+        }
+    }
+
+    Ok(())
+}
+
+fn list_numbers(client: &mut BusClient, addr: u16, as_json: bool) -> Result<(), Error> {
+    let count = client.number_count(addr)?;
+    let mut numbers = Vec::new();
+
+    for id in 0..count {
+        numbers.push(client.describe_number(addr, id)?);
+    }
+
+    if as_json {
+        let numbers: Vec<_> = numbers
+            .iter()
+            .map(|(info, value)| {
+                json!({
+                    "id": info.id,
+                    "name": info.name,
+                    "unique_id": info.unique_id,
+                    "value": number_json(*value),
+                    "native_unit_of_measurement": info.native_unit_of_measurement,
+                    "device_class": info.device_class,
+                    "min": number_json(info.min),
+                    "max": number_json(info.max),
+                    "step": number_json(info.step),
+                    "mode": info.mode,
+                })
+            })
+            .collect();
+        println!("{}", json!(numbers));
+    } else {
+        println!("{} available numbers", count);
+        for (info, value) in &numbers {
+            println!();
+            println!("Number {}:", info.id);
+            println!("    name:                       {}", info.name);
+            println!("    unique_id:                  {}", info.unique_id);
+            println!("    value:                      {}", value);
+            println!(
+                "    native_unit_of_measurement: {}",
+                info.native_unit_of_measurement
+            );
+            println!("    device_class:               {}", info.device_class);
+            println!(
+                "    range:                      {} ..= {}, step {}",
+                info.min, info.max, info.step
+            );
+            println!("    mode:                       {}", info.mode);
+        }
+    }
+
+    Ok(())
+}
+
+fn list_buttons(client: &mut BusClient, addr: u16, as_json: bool) -> Result<(), Error> {
+    let count = client.button_count(addr)?;
+    let mut buttons = Vec::new();
+
+    for id in 0..count {
+        buttons.push(client.describe_button(addr, id)?);
+    }
+
+    if as_json {
+        let buttons: Vec<_> = buttons
+            .iter()
+            .map(|info| {
+                json!({
+                    "id": info.id,
+                    "name": info.name,
+                    "unique_id": info.unique_id,
+                })
+            })
+            .collect();
+        println!("{}", json!(buttons));
+    } else {
+        println!("{} available buttons", count);
+        for info in &buttons {
+            println!();
+            println!("Button {}:", info.id);
+            println!("    name:      {}", info.name);
+            println!("    unique_id: {}", info.unique_id);
+        }
+    }
+
+    Ok(())
+}
+
+fn list_lights(client: &mut BusClient, addr: u16, as_json: bool) -> Result<(), Error> {
+    let count = client.light_count(addr)?;
+    let mut lights = Vec::new();
+
+    for id in 0..count {
+        lights.push(client.describe_light(addr, id)?);
+    }
+
+    if as_json {
+        let lights: Vec<_> = lights
+            .iter()
+            .map(|(info, state)| {
+                json!({
+                    "id": info.id,
+                    "name": info.name,
+                    "unique_id": info.unique_id,
+                    "on": state.on,
+                    "brightness": state.brightness,
+                })
+            })
+            .collect();
+        println!("{}", json!(lights));
+    } else {
+        println!("{} available lights", count);
+        for (info, state) in &lights {
+            println!();
+            println!("Light {}:", info.id);
+            println!("    name:      {}", info.name);
+            println!("    unique_id: {}", info.unique_id);
+            println!("    state:     {}", light_str(state));
+        }
+    }
+
+    Ok(())
+}
+
+fn list_covers(client: &mut BusClient, addr: u16, as_json: bool) -> Result<(), Error> {
+    let count = client.cover_count(addr)?;
+    let mut covers = Vec::new();
+
+    for id in 0..count {
+        covers.push(client.describe_cover(addr, id)?);
+    }
+
+    if as_json {
+        let covers: Vec<_> = covers
+            .iter()
+            .map(|(info, state)| {
+                json!({
+                    "id": info.id,
+                    "name": info.name,
+                    "unique_id": info.unique_id,
+                    "device_class": info.device_class,
+                    "position": state.position,
+                    "motion": motion_str(state.motion),
+                })
+            })
+            .collect();
+        println!("{}", json!(covers));
+    } else {
+        println!("{} available covers", count);
+        for (info, state) in &covers {
+            println!();
+            println!("Cover {}:", info.id);
+            println!("    name:         {}", info.name);
+            println!("    unique_id:    {}", info.unique_id);
+            println!("    device_class: {}", info.device_class);
+            println!("    state:        {}", cover_str(state));
+        }
+    }
+
+    Ok(())
+}
+
+fn list_selects(client: &mut BusClient, addr: u16, as_json: bool) -> Result<(), Error> {
+    let count = client.select_count(addr)?;
+    let mut selects = Vec::new();
+
+    for id in 0..count {
+        selects.push(client.describe_select(addr, id)?);
+    }
+
+    if as_json {
+        let selects: Vec<_> = selects
+            .iter()
+            .map(|(info, index)| {
+                json!({
+                    "id": info.id,
+                    "name": info.name,
+                    "unique_id": info.unique_id,
+                    "options": info.options,
+                    "index": index,
+                })
+            })
+            .collect();
+        println!("{}", json!(selects));
+    } else {
+        println!("{} available selects", count);
+        for (info, index) in &selects {
+            println!();
+            println!("Select {}:", info.id);
+            println!("    name:      {}", info.name);
+            println!("    unique_id: {}", info.unique_id);
+            println!("    options:   {}", info.options.join(", "));
+            println!("    state:     {}", option_str(&info.options, *index));
+        }
+    }
+
+    Ok(())
+}
+
+fn read(client: &mut BusClient, addr: u16, sensor: u32, as_json: bool) -> Result<(), Error> {
+    let value = client.read_sensor(addr, sensor)?;
+
+    if as_json {
+        println!("{}", value_json(&value));
+    } else {
+        println!("{}", value_str(&value));
+    }
+
+    Ok(())
+}
+
+fn read_all(client: &mut BusClient, addr: u16, as_json: bool) -> Result<(), Error> {
+    let readings = client.read_all_sensors(addr)?;
+
+    if as_json {
+        let readings: Vec<_> = readings
+            .iter()
+            .map(|reading| match &reading.value {
+                Ok(value) => json!({ "id": reading.id, "value": value_json(value) }),
+                Err(e) => json!({ "id": reading.id, "error": e.to_string() }),
+            })
+            .collect();
+        println!("{}", json!(readings));
+    } else {
+        for reading in &readings {
+            match &reading.value {
+                Ok(value) => println!("Sensor {}: {}", reading.id, value_str(value)),
+                Err(e) => println!("Sensor {}: {}", reading.id, e),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn switch(
+    client: &mut BusClient,
+    addr: u16,
+    id: u32,
+    action: SwitchAction,
+    as_json: bool,
+) -> Result<(), Error> {
+    let req = match action {
+        SwitchAction::On => SwitchRequest::TurnON,
+        SwitchAction::Off => SwitchRequest::TurnOFF,
+        SwitchAction::Toggle => SwitchRequest::Toggle,
+    };
+
+    if is_multicast(addr) {
+        client.multicast_switch_exec(addr, id, req)?;
+
+        if as_json {
+            println!("{}", json!({ "sent": true }));
+        } else {
+            println!("Sent to {:#06x}", addr);
+        }
+
+        return Ok(());
+    }
+
+    if id == SWITCH_ID_ALL {
+        let states = client.switch_exec_all(addr, req)?;
+
+        if as_json {
+            println!("{}", json!({ "states": states }));
+        } else {
+            for (id, state) in states.iter().enumerate() {
+                println!("Switch {}: {}", id, if *state { "on" } else { "off" });
+            }
+        }
+
+        return Ok(());
+    }
+
+    let state = client.switch_exec(addr, id, req)?;
+
+    if as_json {
+        println!("{}", json!({ "state": state }));
+    } else {
+        println!("{}", if state { "on" } else { "off" });
+    }
+
+    Ok(())
+}
+
+fn set_number(
+    client: &mut BusClient,
+    addr: u16,
+    id: u32,
+    value: f32,
+    as_json: bool,
+) -> Result<(), Error> {
+    let value = client.set_number(addr, id, value)?;
+
+    if as_json {
+        println!("{}", json!({ "value": number_json(value) }));
+    } else {
+        println!("{}", value);
+    }
+
+    Ok(())
+}
+
+fn press(client: &mut BusClient, addr: u16, id: u32, as_json: bool) -> Result<(), Error> {
+    if is_multicast(addr) {
+        client.multicast_press_button(addr, id)?;
+
+        if as_json {
+            println!("{}", json!({ "sent": true }));
+        } else {
+            println!("Sent to {:#06x}", addr);
+        }
+
+        return Ok(());
+    }
+
+    client.press_button(addr, id)?;
+
+    if as_json {
+        println!("{}", json!({ "pressed": true }));
+    } else {
+        println!("Pressed");
+    }
+
+    Ok(())
+}
+
+fn light(
+    client: &mut BusClient,
+    addr: u16,
+    id: u32,
+    req: LightRequest,
+    as_json: bool,
+) -> Result<(), Error> {
+    let state = client.light_exec(addr, id, req)?;
+
+    if as_json {
+        println!("{}", light_json(&state));
+    } else {
+        println!("{}", light_str(&state));
+    }
+
+    Ok(())
+}
+
+fn cover(
+    client: &mut BusClient,
+    addr: u16,
+    id: u32,
+    req: CoverRequest,
+    as_json: bool,
+) -> Result<(), Error> {
+    let state = client.cover_exec(addr, id, req)?;
+
+    if as_json {
+        println!("{}", cover_json(&state));
+    } else {
+        println!("{}", cover_str(&state));
+    }
+
+    Ok(())
+}
+
+fn select(
+    client: &mut BusClient,
+    addr: u16,
+    id: u32,
+    option: &str,
+    as_json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (info, _) = client.describe_select(addr, id)?;
+
+    // Indexes take precedence, options named like a number are selected by their index
+    let index = match parse_number(option) {
+        Ok(index) => u8::try_from(index).map_err(|_| format!("No option {}", index))?,
+        Err(_) => info
+            .options
+            .iter()
+            .position(|o| o == option)
+            .ok_or_else(|| {
+                format!(
+                    "No option '{}', expected one of: {}",
+                    option,
+                    info.options.join(", ")
+                )
+            })? as u8,
+    };
+
+    let index = client.set_select(addr, id, index)?;
+
+    if as_json {
+        println!(
+            "{}",
+            json!({ "index": index, "option": info.options.get(index as usize) })
+        );
+    } else {
+        println!("{}", option_str(&info.options, index));
+    }
+
+    Ok(())
+}
+
+fn events(
+    client: &mut BusClient,
+    addr: u16,
+    follow: Option<u64>,
+    as_json: bool,
+) -> Result<(), Error> {
+    loop {
+        let events = client.fetch_events(addr)?;
+
+        if as_json {
+            let list: Vec<_> = events
+                .events
+                .iter()
+                .map(|event| match event {
+                    NodeEvent::Sensor { id, value } => match value {
+                        Ok(value) => json!({ "sensor": id, "value": value_json(value) }),
+                        Err(e) => json!({ "sensor": id, "error": e.to_string() }),
+                    },
+                    NodeEvent::Switch { id, state } => match state {
+                        Ok(state) => json!({ "switch": id, "state": state }),
+                        Err(e) => json!({ "switch": id, "error": e.to_string() }),
+                    },
+                    NodeEvent::BinarySensor { id, state } => match state {
+                        Ok(state) => json!({ "binary_sensor": id, "state": state }),
+                        Err(e) => json!({ "binary_sensor": id, "error": e.to_string() }),
+                    },
+                    NodeEvent::Number { id, value } => match value {
+                        Ok(value) => json!({ "number": id, "value": number_json(*value) }),
+                        Err(e) => json!({ "number": id, "error": e.to_string() }),
+                    },
+                    NodeEvent::Light { id, state } => match state {
+                        Ok(state) => json!({ "light": id, "state": light_json(state) }),
+                        Err(e) => json!({ "light": id, "error": e.to_string() }),
+                    },
+                    NodeEvent::Cover { id, state } => match state {
+                        Ok(state) => json!({ "cover": id, "state": cover_json(state) }),
+                        Err(e) => json!({ "cover": id, "error": e.to_string() }),
+                    },
+                    NodeEvent::Select { id, index } => match index {
+                        Ok(index) => json!({ "select": id, "index": index }),
+                        Err(e) => json!({ "select": id, "error": e.to_string() }),
+                    },
+                    NodeEvent::Unknown { kind, id } => json!({ "kind": kind, "id": id }),
+                })
+                .collect();
+
+            // Following prints one line per fetch that has something to report
+            if follow.is_none() || events.overflowed || !list.is_empty() {
+                println!(
+                    "{}",
+                    json!({ "overflowed": events.overflowed, "events": list })
+                );
+            }
+        } else {
+            if events.overflowed {
+                println!("Events were dropped, all entities have to be polled");
+            }
+            for event in &events.events {
+                match event {
+                    NodeEvent::Sensor { id, value } => match value {
+                        Ok(value) => println!("Sensor {}: {}", id, value_str(value)),
+                        Err(e) => println!("Sensor {}: {}", id, e),
+                    },
+                    NodeEvent::Switch { id, state } => match state {
+                        Ok(state) => {
+                            println!("Switch {}: {}", id, if *state { "on" } else { "off" })
+                        }
+                        Err(e) => println!("Switch {}: {}", id, e),
+                    },
+                    NodeEvent::BinarySensor { id, state } => match state {
+                        Ok(state) => println!("Binary sensor {}: {}", id, state_str(state)),
+                        Err(e) => println!("Binary sensor {}: {}", id, e),
+                    },
+                    NodeEvent::Number { id, value } => match value {
+                        Ok(value) => println!("Number {}: {}", id, value),
+                        Err(e) => println!("Number {}: {}", id, e),
+                    },
+                    NodeEvent::Light { id, state } => match state {
+                        Ok(state) => println!("Light {}: {}", id, light_str(state)),
+                        Err(e) => println!("Light {}: {}", id, e),
+                    },
+                    NodeEvent::Cover { id, state } => match state {
+                        Ok(state) => println!("Cover {}: {}", id, cover_str(state)),
+                        Err(e) => println!("Cover {}: {}", id, e),
+                    },
+                    NodeEvent::Select { id, index } => match index {
+                        Ok(index) => println!("Select {}: option {}", id, index),
+                        Err(e) => println!("Select {}: {}", id, e),
+                    },
+                    NodeEvent::Unknown { kind, id } => {
+                        println!("Unknown event {:#04x} of entity {}", kind, id)
+                    }
+                }
+            }
+        }
+
+        match follow {
+            Some(interval) => thread::sleep(Duration::from_millis(interval)),
+            None => return Ok(()),
+        }
+    }
+}
+
+fn info(client: &mut BusClient, addr: u16, as_json: bool) -> Result<(), Error> {
+    let info = client.node_info(addr)?;
+
+    let mut capabilities = Vec::new();
+    if info.supports(CAP_SENSOR) {
+        capabilities.push("sensor");
+    }
+    if info.supports(CAP_SWITCH) {
+        capabilities.push("switch");
+    }
+    if info.supports(CAP_BINARY_SENSOR) {
+        capabilities.push("binary_sensor");
+    }
+    if info.supports(CAP_NUMBER) {
+        capabilities.push("number");
+    }
+    if info.supports(CAP_BUTTON) {
+        capabilities.push("button");
+    }
+    if info.supports(CAP_LIGHT) {
+        capabilities.push("light");
+    }
+    if info.supports(CAP_COVER) {
+        capabilities.push("cover");
+    }
+    if info.supports(CAP_SELECT) {
+        capabilities.push("select");
+    }
+
+    if as_json {
+        println!(
+            "{}",
+            json!({
+                "name": info.name,
+                "board": info.board,
+                "firmware_version": info.firmware_version,
+                "protocol_version": info.protocol_version,
+                "uptime_s": info.uptime.as_secs(),
+                "capabilities": capabilities,
+            })
+        );
+    } else {
+        println!("Node {:#06x}:", addr);
+        println!("    name:             {}", info.name);
+        println!("    board:            {}", info.board);
+        println!("    firmware_version: {}", info.firmware_version);
+        println!("    protocol_version: {}", info.protocol_version);
+        println!("    uptime:           {} s", info.uptime.as_secs());
+        println!("    capabilities:     {}", capabilities.join(", "));
+    }
+
+    Ok(())
+}
+
+fn serial(client: &mut BusClient, addr: u16, as_json: bool) -> Result<(), Error> {
+    let serial = client.node_serial(addr)?;
+
+    if as_json {
+        println!("{}", json!({ "serial": serial.as_ref().map(serial_str) }));
+    } else {
+        match serial {
+            Some(serial) => println!("{}", serial_str(&serial)),
+            None => println!("{:#06x} has no serial number", addr),
+        }
+    }
+
+    Ok(())
+}
+
+fn set_address(
+    client: &mut BusClient,
+    addr: u16,
+    new_addr: u16,
+    serial: Option<&Serial>,
+    as_json: bool,
+) -> Result<(), Error> {
+    client.assign_address(addr, new_addr, serial)?;
+
+    // Nodes do not respond to broadcasts, check if a node took the new address
+    if is_multicast(addr) {
+        client.echo(new_addr, &[])?;
+    }
+
+    if as_json {
+        println!("{}", json!({ "addr": new_addr }));
+    } else {
+        println!("Node is now at {:#06x}", new_addr);
+    }
+
+    Ok(())
+}
+
+fn echo(client: &mut BusClient, addr: u16, data: &str, as_json: bool) -> Result<(), Error> {
+    let start = Instant::now();
+    let response = client.echo(addr, data.as_bytes())?;
+    let elapsed = start.elapsed();
+
+    let response = String::from_utf8_lossy(&response);
+
+    if as_json {
+        println!(
+            "{}",
+            json!({
+                "data": response,
+                "time_ms": elapsed.as_secs_f64() * 1000.0,
+            })
+        );
+    } else {
+        println!(
+            "{:#06x} replied '{}' in {:.1} ms",
+            addr,
+            response,
+            elapsed.as_secs_f64() * 1000.0
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,634 @@
+//! Runs `buddyctl` against a stand-in node on a pseudo terminal
+
+use std::{
+    io::{Read, Write},
+    process::{Command, Output},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use ha_buddy_proto::{
+    device::{DeviceInfo, PROTOCOL_VERSION},
+    error::ErrorCode,
+    event::Event,
+    handler::{handle_request, HandlerPins},
+    homeassistant::{
+        binary_sensor::{BinarySensor, BinarySensorDeviceClass, BinarySensorRef},
+        button::{Button, ButtonRef},
+        cover::{Cover, CoverDeviceClass, CoverMotion, CoverRef, CoverRequest, CoverState},
+        entity::DeviceClass,
+        light::{Light, LightRef, LightRequest, LightState},
+        number::{Number, NumberMode, NumberRef, NumberRequest},
+        select::{Select, SelectRef, SelectRequest},
+        sensor::{Sensor, SensorRef, StateClass},
+        switch::{Switch, SwitchRef, SwitchRequest},
+        Entities,
+    },
+    node::Node,
+    DataFrame,
+};
+use serde_json::json;
+use serialport::{SerialPort, TTYPort};
+
+const NODE_ADDR: u16 = 0x1002;
+/// The serial number of the stand-in node
+const SERIAL: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+/// A stand-in node answering on the master side of a pseudo terminal
+struct StandIn {
+    path: String,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+    // Keep the slave side open so the pseudo terminal stays alive
+    _slave: TTYPort,
+}
+
+impl StandIn {
+    fn start() -> Self {
+        let (mut master, slave) = TTYPort::pair().expect("Failed to create pseudo terminal");
+        master.set_timeout(Duration::from_millis(10)).unwrap();
+
+        let path = slave.name().expect("Pseudo terminal has no name");
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread_stop = stop.clone();
+        let thread = thread::spawn(move || node(&mut master, &thread_stop));
+
+        Self {
+            path,
+            stop,
+            thread: Some(thread),
+            _slave: slave,
+        }
+    }
+
+    fn run(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_buddyctl"))
+            .args(["--port", &self.path, "--timeout", "50", "--retries", "0"])
+            .args(args)
+            .output()
+            .expect("Failed to run buddyctl")
+    }
+
+    fn json(&self, args: &[&str]) -> serde_json::Value {
+        let mut all = vec!["--json"];
+        all.extend(args);
+
+        let output = self.run(&all);
+        assert!(
+            output.status.success(),
+            "buddyctl failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON output")
+    }
+}
+
+impl Drop for StandIn {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            thread.join().unwrap();
+        }
+    }
+}
+
+/// Answers frames addressed to `NODE_ADDR` like the firmware does
+fn node(port: &mut TTYPort, stop: &AtomicBool) {
+    let temperature = Sensor::new(
+        "Temperature",
+        "temp_0",
+        "°C",
+        DeviceClass::Temperature,
+        StateClass::Measurement,
+        Some(21.5f32),
+    );
+    let humidity = Sensor::<i32>::new(
+        "Humidity",
+        "hum_0",
+        "%",
+        DeviceClass::Temperature,
+        StateClass::Measurement,
+        None,
+    );
+
+    let door = BinarySensor::new("Door", "door_0", BinarySensorDeviceClass::Door, Some(true));
+
+    let mut relay = false;
+    let mut setpoint = 21.0;
+    let mut pulsing = false;
+    let mut strip = LightState {
+        on: false,
+        brightness: 255,
+    };
+    let mut shutter = CoverState {
+        position: 20,
+        motion: CoverMotion::Stopped,
+    };
+    let mut pump = 0;
+    let mut node = Node {
+        serial: Some(SERIAL),
+        info: DeviceInfo {
+            firmware_version: "1.2.3",
+            board: "stand-in",
+            name: "Stand-in",
+            uptime: 42,
+        },
+        ..Node::new(NODE_ADDR)
+    };
+    node.events.push(Event::Sensor(0));
+    let mut frame = DataFrame::new();
+    let mut buf = [0u8; 64];
+
+    while !stop.load(Ordering::Relaxed) {
+        let len = match port.read(&mut buf) {
+            Ok(len) => len,
+            Err(_) => {
+                thread::sleep(Duration::from_millis(1));
+                continue;
+            }
+        };
+
+        for byte in &buf[0..len] {
+            if !frame.handle_byte(*byte) {
+                continue;
+            }
+
+            let sensors: [&dyn SensorRef; 2] = [&temperature, &humidity];
+            let mut switch = Switch::new("Relay", "relay_0", |req| {
+                match req {
+                    SwitchRequest::TurnON => relay = true,
+                    SwitchRequest::TurnOFF => relay = false,
+                    SwitchRequest::Toggle => relay = !relay,
+                    SwitchRequest::Get => {}
+                }
+                relay
+            });
+            let mut switches: [&mut dyn SwitchRef; 1] = [&mut switch];
+            let binary_sensors: [&dyn BinarySensorRef; 1] = [&door];
+            let mut number = Number::new(
+                "Setpoint",
+                "setpoint_0",
+                "°C",
+                DeviceClass::Temperature,
+                5.0..=30.0,
+                0.5,
+                |req| {
+                    if let NumberRequest::Set(value) = req {
+                        setpoint = value;
+                    }
+                    setpoint
+                },
+            )
+            .with_mode(NumberMode::Slider);
+            let mut numbers: [&mut dyn NumberRef; 1] = [&mut number];
+            // The pulse never ends, the node stays busy after the first press
+            let mut button = Button::new("Garage door", "garage_0", || {
+                if pulsing {
+                    return Err(ErrorCode::EntityBusy);
+                }
+                pulsing = true;
+                Ok(())
+            });
+            let mut buttons: [&mut dyn ButtonRef; 1] = [&mut button];
+            let mut light = Light::new("Strip", "strip_0", |req| {
+                match req {
+                    LightRequest::TurnOn { brightness, .. } => {
+                        strip.on = true;
+                        strip.brightness = brightness.unwrap_or(strip.brightness);
+                    }
+                    LightRequest::TurnOff { .. } => strip.on = false,
+                    LightRequest::Toggle { .. } => strip.on = !strip.on,
+                    LightRequest::Get => {}
+                }
+                strip
+            });
+            let mut lights: [&mut dyn LightRef; 1] = [&mut light];
+            // The shutter starts moving but never arrives
+            let mut cover = Cover::new("Shutter", "shutter_0", CoverDeviceClass::Shutter, |req| {
+                shutter.motion = match req {
+                    CoverRequest::Open => CoverMotion::Opening,
+                    CoverRequest::Close => CoverMotion::Closing,
+                    CoverRequest::SetPosition(p) if p > shutter.position => CoverMotion::Opening,
+                    CoverRequest::SetPosition(p) if p < shutter.position => CoverMotion::Closing,
+                    CoverRequest::SetPosition(_) | CoverRequest::Stop => CoverMotion::Stopped,
+                    CoverRequest::Get => shutter.motion,
+                };
+                shutter
+            });
+            let mut covers: [&mut dyn CoverRef; 1] = [&mut cover];
+            let mut select = Select::new("Pump", "pump_0", ["auto", "manual", "0"], |req| {
+                if let SelectRequest::Set(index) = req {
+                    pump = index;
+                }
+                pump
+            });
+            let mut selects: [&mut dyn SelectRef; 1] = [&mut select];
+
+            if handle_request(
+                &mut frame,
+                &mut node,
+                &mut HandlerPins {},
+                &mut Entities {
+                    sensors: &sensors,
+                    switches: &mut switches,
+                    binary_sensors: &binary_sensors,
+                    numbers: &mut numbers,
+                    buttons: &mut buttons,
+                    lights: &mut lights,
+                    covers: &mut covers,
+                    selects: &mut selects,
+                },
+            ) {
+                let mut writer = VecWriter::default();
+                frame.send(&mut writer).unwrap();
+                port.write_all(&writer.bytes).unwrap();
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct VecWriter {
+    bytes: Vec<u8>,
+}
+
+impl embedded_hal::serial::Write<u8> for VecWriter {
+    type Error = core::convert::Infallible;
+
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        self.bytes.push(word);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[test]
+fn scan() {
+    let node = StandIn::start();
+    assert_eq!(node.json(&["scan", "0x1000..0x1004"]), json!([NODE_ADDR]));
+    assert_eq!(node.json(&["scan", "0x1000..=0x1001"]), json!([]));
+    // The group and broadcast addresses are skipped
+    assert_eq!(node.json(&["scan", "0xfeff..=0xffff"]), json!([]));
+}
+
+#[test]
+fn list_sensors() {
+    let node = StandIn::start();
+    assert_eq!(
+        node.json(&["list-sensors", "0x1002"]),
+        json!([
+            {
+                "id": 0,
+                "name": "Temperature",
+                "unique_id": "temp_0",
+                "value": 21.5,
+                "native_unit_of_measurement": "°C",
+                "device_class": "DeviceClass.TEMPERATURE",
+                "state_class": "measurement",
+            },
+            {
+                "id": 1,
+                "name": "Humidity",
+                "unique_id": "hum_0",
+                "value": null,
+                "native_unit_of_measurement": "%",
+                "device_class": "DeviceClass.TEMPERATURE",
+                "state_class": "measurement",
+            }
+        ])
+    );
+}
+
+#[test]
+fn list_switches() {
+    let node = StandIn::start();
+    assert_eq!(
+        node.json(&["list-switches", "4098"]),
+        json!([{ "id": 0, "name": "Relay", "unique_id": "relay_0", "state": false }])
+    );
+}
+
+#[test]
+fn list_binary_sensors() {
+    let node = StandIn::start();
+    assert_eq!(
+        node.json(&["list-binary-sensors", "0x1002"]),
+        json!([{
+            "id": 0,
+            "name": "Door",
+            "unique_id": "door_0",
+            "state": true,
+            "device_class": "BinarySensorDeviceClass.DOOR",
+        }])
+    );
+}
+
+#[test]
+fn numbers() {
+    let node = StandIn::start();
+    assert_eq!(
+        node.json(&["list-numbers", "0x1002"]),
+        json!([{
+            "id": 0,
+            "name": "Setpoint",
+            "unique_id": "setpoint_0",
+            "value": 21.0,
+            "native_unit_of_measurement": "°C",
+            "device_class": "DeviceClass.TEMPERATURE",
+            "min": 5.0,
+            "max": 30.0,
+            "step": 0.5,
+            "mode": "slider",
+        }])
+    );
+
+    assert_eq!(
+        node.json(&["set-number", "0x1002", "0", "22.5"]),
+        json!({ "value": 22.5 })
+    );
+    assert!(!node
+        .run(&["set-number", "0x1002", "0", "-40"])
+        .status
+        .success());
+    assert_eq!(
+        node.json(&["list-numbers", "0x1002"])[0]["value"],
+        json!(22.5)
+    );
+}
+
+#[test]
+fn buttons() {
+    let node = StandIn::start();
+    assert_eq!(
+        node.json(&["list-buttons", "0x1002"]),
+        json!([{ "id": 0, "name": "Garage door", "unique_id": "garage_0" }])
+    );
+
+    assert_eq!(
+        node.json(&["press", "0x1002", "0"]),
+        json!({ "pressed": true })
+    );
+    let busy = node.run(&["press", "0x1002", "0"]);
+    assert!(!busy.status.success());
+    assert!(String::from_utf8_lossy(&busy.stderr).contains("entity busy"));
+}
+
+#[test]
+fn lights() {
+    let node = StandIn::start();
+    assert_eq!(
+        node.json(&["list-lights", "0x1002"]),
+        json!([{
+            "id": 0,
+            "name": "Strip",
+            "unique_id": "strip_0",
+            "on": false,
+            "brightness": 255,
+        }])
+    );
+
+    assert_eq!(
+        node.json(&["dim", "0x1002", "0", "128", "-t", "500"]),
+        json!({ "on": true, "brightness": 128 })
+    );
+    assert_eq!(
+        node.json(&["light", "0x1002", "0", "off"]),
+        json!({ "on": false, "brightness": 128 })
+    );
+    assert_eq!(
+        node.json(&["light", "0x1002", "0", "toggle", "--transition", "1000"]),
+        json!({ "on": true, "brightness": 128 })
+    );
+}
+
+#[test]
+fn covers() {
+    let node = StandIn::start();
+    assert_eq!(
+        node.json(&["list-covers", "0x1002"]),
+        json!([{
+            "id": 0,
+            "name": "Shutter",
+            "unique_id": "shutter_0",
+            "device_class": "CoverDeviceClass.SHUTTER",
+            "position": 20,
+            "motion": "stopped",
+        }])
+    );
+
+    assert_eq!(
+        node.json(&["cover", "0x1002", "0", "open"]),
+        json!({ "position": 20, "motion": "opening" })
+    );
+    assert_eq!(
+        node.json(&["cover", "0x1002", "0", "stop"]),
+        json!({ "position": 20, "motion": "stopped" })
+    );
+    assert_eq!(
+        node.json(&["set-position", "0x1002", "0", "5"]),
+        json!({ "position": 20, "motion": "closing" })
+    );
+    assert!(!node
+        .run(&["set-position", "0x1002", "0", "101"])
+        .status
+        .success());
+}
+
+#[test]
+fn selects() {
+    let node = StandIn::start();
+    assert_eq!(
+        node.json(&["list-selects", "0x1002"]),
+        json!([{
+            "id": 0,
+            "name": "Pump",
+            "unique_id": "pump_0",
+            "options": ["auto", "manual", "0"],
+            "index": 0,
+        }])
+    );
+
+    assert_eq!(
+        node.json(&["select", "0x1002", "0", "manual"]),
+        json!({ "index": 1, "option": "manual" })
+    );
+    // Numbers are indexes, even if an option has the same name
+    assert_eq!(
+        node.json(&["select", "0x1002", "0", "0"]),
+        json!({ "index": 0, "option": "auto" })
+    );
+
+    let output = node.run(&["select", "0x1002", "0", "off"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No option 'off'"));
+    assert!(!node.run(&["select", "0x1002", "0", "3"]).status.success());
+}
+
+#[test]
+fn read() {
+    let node = StandIn::start();
+    assert_eq!(node.json(&["read", "0x1002", "0"]), json!(21.5));
+    assert_eq!(node.json(&["read", "0x1002", "1"]), json!(null));
+}
+
+#[test]
+fn read_all() {
+    let node = StandIn::start();
+    assert_eq!(
+        node.json(&["read-all", "0x1002"]),
+        json!([{ "id": 0, "value": 21.5 }, { "id": 1, "value": null }])
+    );
+}
+
+#[test]
+fn events() {
+    let node = StandIn::start();
+    assert_eq!(
+        node.json(&["events", "0x1002"]),
+        json!({ "overflowed": false, "events": [{ "sensor": 0, "value": 21.5 }] })
+    );
+    assert_eq!(
+        node.json(&["events", "0x1002"]),
+        json!({ "overflowed": false, "events": [] })
+    );
+}
+
+#[test]
+fn switch() {
+    let node = StandIn::start();
+    assert_eq!(
+        node.json(&["switch", "0x1002", "0", "on"]),
+        json!({ "state": true })
+    );
+    assert_eq!(
+        node.json(&["switch", "0x1002", "0", "toggle"]),
+        json!({ "state": false })
+    );
+    assert_eq!(
+        node.json(&["list-switches", "0x1002"])[0]["state"],
+        json!(false)
+    );
+}
+
+#[test]
+fn switch_all() {
+    let node = StandIn::start();
+    assert_eq!(
+        node.json(&["switch", "0x1002", "all", "on"]),
+        json!({ "states": [true] })
+    );
+    assert_eq!(
+        node.json(&["switch", "broadcast", "all", "off"]),
+        json!({ "sent": true })
+    );
+    assert_eq!(
+        node.json(&["list-switches", "0x1002"])[0]["state"],
+        json!(false)
+    );
+}
+
+#[test]
+fn echo() {
+    let node = StandIn::start();
+    assert_eq!(
+        node.json(&["echo", "0x1002", "hello"])["data"],
+        json!("hello")
+    );
+
+    let output = node.run(&["echo", "0x1002", "hello"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("0x1002 replied 'hello'"));
+}
+
+#[test]
+fn offline_node_fails() {
+    let node = StandIn::start();
+    let output = node.run(&["read", "0x1003", "0"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("did not respond"));
+}
+
+#[test]
+fn invalid_address() {
+    let node = StandIn::start();
+    let output = node.run(&["read", "0x10000", "0"]);
+
+    assert!(!output.status.success());
+}
+
+#[test]
+fn node_error() {
+    let node = StandIn::start();
+    let output = node.run(&["read", "0x1002", "7"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("entity index out of range"));
+}
+
+#[test]
+fn serial() {
+    let node = StandIn::start();
+    assert_eq!(
+        node.json(&["serial", "0x1002"]),
+        json!({ "serial": "0102030405060708" })
+    );
+}
+
+#[test]
+fn set_address() {
+    let node = StandIn::start();
+    assert_eq!(
+        node.json(&[
+            "set-address",
+            "broadcast",
+            "0x1003",
+            "--serial",
+            "0102030405060708"
+        ]),
+        json!({ "addr": 0x1003 })
+    );
+    assert_eq!(node.json(&["scan", "0x1000..0x1004"]), json!([0x1003]));
+
+    let output = node.run(&["set-address", "0x1003", "0x1004"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not permitted"));
+
+    let output = node.run(&["set-address", "0x1003", "0x1004", "--serial", "0102"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn discover() {
+    let node = StandIn::start();
+    assert_eq!(
+        node.json(&["discover", "--all"]),
+        json!([{ "addr": NODE_ADDR, "serial": "0102030405060708" }])
+    );
+    assert_eq!(node.json(&["discover"]), json!([]));
+}
+
+#[test]
+fn info() {
+    let node = StandIn::start();
+    assert_eq!(
+        node.json(&["info", "0x1002"]),
+        json!({
+            "name": "Stand-in",
+            "board": "stand-in",
+            "firmware_version": "1.2.3",
+            "protocol_version": PROTOCOL_VERSION,
+            "uptime_s": 42,
+            "capabilities": ["sensor", "switch", "binary_sensor", "number", "button", "light", "cover", "select"],
+        })
+    );
+}
@@ -0,0 +1,26 @@
+"""Platform for button integration."""
+from __future__ import annotations
+
+from homeassistant.core import HomeAssistant
+
+import logging
+
+from . import DOMAIN
+from .const import *
+
+LOGGER = logging.getLogger(DOMAIN)
+
+
+async def async_setup_entry(hass: HomeAssistant, config_entry, async_add_devices):
+    """Setup button platform."""
+
+    if hass.data[DOMAIN] is None:
+        LOGGER.error("Can't setup ha_buddy buttons: NO CONNECTION")
+        return
+
+    LOGGER.info("Setting up buttons")
+
+    for device in hass.data[DOMAIN].devices:
+        async_add_devices(device.get_buttons())
+
+    LOGGER.info("Done setting up buttons")
@@ -0,0 +1,207 @@
+use std::{
+    io::{Read, Write},
+    time::Instant,
+};
+
+use ha_buddy_proto::{
+    command::{CMD_NODE_DISCOVER, CMD_NODE_SERIAL},
+    datalink::{is_multicast, serial_has_prefix, Serial, BROADCAST_ADDR, FACTORY_ADDR, SERIAL_LEN},
+    DataFrame,
+};
+
+use crate::{Client, Error};
+
+/// The length of a complete serial number in bits
+const SERIAL_BITS: usize = SERIAL_LEN * 8;
+
+/// A node found by its serial number
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiscoveredNode {
+    /// The address the node responded from
+    pub addr: u16,
+    /// The serial number of the node
+    pub serial: Serial,
+}
+
+/// What the master sees on the bus after a discovery request
+#[derive(PartialEq)]
+enum Probe {
+    /// No node responded
+    Silent,
+    /// Many nodes responded at once, garbling their responses
+    Collision,
+    /// A single node responded
+    Response(DiscoveredNode),
+}
+
+impl<T: Read + Write> Client<T> {
+    /// Discovers all nodes listening on `dst` with a binary search on their serial numbers
+    ///
+    /// All nodes whose serial number starts with a prefix respond at the same time.
+    /// A clean response means a single node matched, garbled responses extend the
+    /// prefix by one bit for each half. Every probe listens for the full timeout, a
+    /// response followed by more bytes is a collision as well. Consider lowering the
+    /// timeout with [`Client::set_timeout`].
+    /// # Arguments
+    /// * `dst` - The address to discover nodes on, [`FACTORY_ADDR`](ha_buddy_proto::datalink::FACTORY_ADDR)
+    ///   for nodes that have not been assigned an address or
+    ///   [`BROADCAST_ADDR`](ha_buddy_proto::datalink::BROADCAST_ADDR) for all nodes
+    pub fn discover(&mut self, dst: u16) -> Result<Vec<DiscoveredNode>, Error> {
+        let mut found = Vec::new();
+        self.discover_prefix(dst, Serial::default(), 0, &mut found)?;
+        Ok(found)
+    }
+
+    /// Discovers all nodes at [`FACTORY_ADDR`] and assigns them addresses
+    /// # Arguments
+    /// * `addrs` - The addresses to assign in order, addresses another node
+    ///   responds on are skipped
+    /// # Returns
+    /// The nodes with their new addresses
+    pub fn enumerate(
+        &mut self,
+        addrs: impl IntoIterator<Item = u16>,
+    ) -> Result<Vec<DiscoveredNode>, Error> {
+        let mut addrs = addrs
+            .into_iter()
+            .filter(|addr| *addr != FACTORY_ADDR && !is_multicast(*addr));
+        let mut assigned = Vec::new();
+
+        for node in self.discover(FACTORY_ADDR)? {
+            let addr = loop {
+                let addr = addrs.next().ok_or(Error::AddressesExhausted)?;
+                match self.echo(addr, &[]) {
+                    Err(Error::Timeout { .. }) => break addr,
+                    Err(e) => return Err(e),
+                    Ok(_) => continue,
+                }
+            };
+
+            // Other nodes sharing the factory address would respond with errors
+            // to a unicast request, broadcast it and check the node at its new address
+            self.assign_address(BROADCAST_ADDR, addr, Some(&node.serial))?;
+            if self.node_serial(addr)? != Some(node.serial) {
+                return Err(Error::InvalidPayload {
+                    cmd: CMD_NODE_SERIAL,
+                    reason: "node at the assigned address has another serial number",
+                });
+            }
+
+            assigned.push(DiscoveredNode {
+                addr,
+                serial: node.serial,
+            });
+        }
+
+        Ok(assigned)
+    }
+
+    /// Discovers the nodes whose serial number starts with the first `bits` bits of `prefix`
+    fn discover_prefix(
+        &mut self,
+        dst: u16,
+        prefix: Serial,
+        bits: usize,
+        found: &mut Vec<DiscoveredNode>,
+    ) -> Result<(), Error> {
+        match self.probe(dst, &prefix, bits)? {
+            Probe::Silent => {}
+            Probe::Response(node) => {
+                // Colliding responses may form a valid frame by chance,
+                // the node with the serial number has to confirm it
+                if bits == SERIAL_BITS
+                    || self.probe(dst, &node.serial, SERIAL_BITS)? == Probe::Response(node.clone())
+                {
+                    found.push(node);
+                } else {
+                    self.discover_halves(dst, prefix, bits, found)?;
+                }
+            }
+            Probe::Collision => {
+                if bits == SERIAL_BITS {
+                    return Err(Error::SerialCollision(prefix));
+                }
+                self.discover_halves(dst, prefix, bits, found)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Extends `prefix` by a `0` and a `1` bit and discovers the nodes of both
+    fn discover_halves(
+        &mut self,
+        dst: u16,
+        prefix: Serial,
+        bits: usize,
+        found: &mut Vec<DiscoveredNode>,
+    ) -> Result<(), Error> {
+        let mut low = prefix;
+        low[bits / 8] &= !(0x80 >> (bits % 8));
+        self.discover_prefix(dst, low, bits + 1, found)?;
+
+        let mut high = prefix;
+        high[bits / 8] |= 0x80 >> (bits % 8);
+        self.discover_prefix(dst, high, bits + 1, found)
+    }
+
+    /// Sends a discovery request and listens to the bus until the timeout
+    /// # Arguments
+    /// * `dst` - The address to discover nodes on
+    /// * `prefix` - The serial number prefix nodes have to match
+    /// * `bits` - The length of the prefix in bits
+    fn probe(&mut self, dst: u16, prefix: &Serial, bits: usize) -> Result<Probe, Error> {
+        let mut payload = vec![bits as u8];
+        payload.extend_from_slice(prefix);
+        self.send(dst, CMD_NODE_DISCOVER, &payload)?;
+
+        let deadline = Instant::now() + self.timeout;
+        let mut buf = [0u8; 64];
+        let mut activity = false;
+        // The single response, a node sends nothing after it
+        let mut response = None;
+
+        self.frame = DataFrame::new();
+
+        while Instant::now() < deadline {
+            let len = self.read_some(&mut buf)?;
+            activity |= len > 0;
+
+            for byte in &buf[0..len] {
+                if response.is_some() {
+                    return Ok(Probe::Collision);
+                }
+                if !self.frame.handle_byte(*byte) {
+                    continue;
+                }
+
+                let frame = &self.frame;
+                if !frame.check_crc()
+                    || frame.dst != self.addr
+                    || frame.cmd != CMD_NODE_DISCOVER.wrapping_add(1)
+                    || frame.payload_len as usize != SERIAL_LEN
+                {
+                    continue;
+                }
+
+                let mut serial = Serial::default();
+                serial.copy_from_slice(&frame.payload[0..SERIAL_LEN]);
+
+                if serial_has_prefix(&serial, prefix, bits) {
+                    response = Some(DiscoveredNode {
+                        addr: frame.src,
+                        serial,
+                    });
+                }
+            }
+        }
+
+        if let Some(node) = response {
+            Ok(Probe::Response(node))
+        } else if activity {
+            Ok(Probe::Collision)
+        } else {
+            Ok(Probe::Silent)
+        }
+    }
+}
@@ -0,0 +1,427 @@
+use std::fmt;
+
+use ha_buddy_proto::{
+    command::SENSOR_VALUE_ERROR,
+    homeassistant::{
+        cover::{CoverMotion, CoverState},
+        light::LightState,
+        sensor::PayloadType,
+    },
+};
+
+use crate::Error;
+
+/// The attributes of a sensor
+#[derive(Clone, Debug, PartialEq)]
+pub struct SensorInfo {
+    /// The index of the sensor on its node
+    pub id: u32,
+    /// The friendly name for the sensor
+    pub name: String,
+    /// The `unique_id` for this sensor
+    pub unique_id: String,
+    /// The unit of measurement for this sensor
+    pub native_unit_of_measurement: String,
+    /// The `device_class` for this sensor
+    pub device_class: String,
+    /// The `state_class` for this sensor
+    pub state_class: String,
+}
+
+/// The attributes of a switch
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwitchInfo {
+    /// The index of the switch on its node
+    pub id: u32,
+    /// The friendly name for the switch
+    pub name: String,
+    /// The `unique_id` for this switch
+    pub unique_id: String,
+}
+
+/// The attributes of a binary sensor
+#[derive(Clone, Debug, PartialEq)]
+pub struct BinarySensorInfo {
+    /// The index of the binary sensor on its node
+    pub id: u32,
+    /// The friendly name for the binary sensor
+    pub name: String,
+    /// The `unique_id` for this binary sensor
+    pub unique_id: String,
+    /// The `device_class` for this binary sensor, empty if it has none
+    pub device_class: String,
+}
+
+/// The attributes of a number
+#[derive(Clone, Debug, PartialEq)]
+pub struct NumberInfo {
+    /// The index of the number on its node
+    pub id: u32,
+    /// The friendly name for the number
+    pub name: String,
+    /// The `unique_id` for this number
+    pub unique_id: String,
+    /// The unit of measurement for this number
+    pub native_unit_of_measurement: String,
+    /// The `device_class` for this number
+    pub device_class: String,
+    /// The minimum accepted value
+    pub min: f32,
+    /// The maximum accepted value
+    pub max: f32,
+    /// The resolution of the value
+    pub step: f32,
+    /// How HomeAssistant renders the number
+    pub mode: String,
+}
+
+/// The attributes of a button
+#[derive(Clone, Debug, PartialEq)]
+pub struct ButtonInfo {
+    /// The index of the button on its node
+    pub id: u32,
+    /// The friendly name for the button
+    pub name: String,
+    /// The `unique_id` for this button
+    pub unique_id: String,
+}
+
+/// The attributes of a light
+#[derive(Clone, Debug, PartialEq)]
+pub struct LightInfo {
+    /// The index of the light on its node
+    pub id: u32,
+    /// The friendly name for the light
+    pub name: String,
+    /// The `unique_id` for this light
+    pub unique_id: String,
+}
+
+/// The attributes of a cover
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoverInfo {
+    /// The index of the cover on its node
+    pub id: u32,
+    /// The friendly name for the cover
+    pub name: String,
+    /// The `unique_id` for this cover
+    pub unique_id: String,
+    /// The `device_class` for this cover, empty if it has none
+    pub device_class: String,
+}
+
+/// The attributes of a select
+#[derive(Clone, Debug, PartialEq)]
+pub struct SelectInfo {
+    /// The index of the select on its node
+    pub id: u32,
+    /// The friendly name for the select
+    pub name: String,
+    /// The `unique_id` for this select
+    pub unique_id: String,
+    /// The options to choose from, the state is an index into them
+    pub options: Vec<String>,
+}
+
+/// A value reported by a sensor
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    String(String),
+    Int(i32),
+    Float(f32),
+    UInt(u32),
+    Int64(i64),
+    UInt64(u64),
+    Bool(bool),
+    /// A decimal fixed-point value, `value * 10^exponent`
+    Fixed {
+        value: i32,
+        exponent: i8,
+    },
+}
+
+impl Value {
+    /// Decodes a sensor value from a response payload
+    /// # Arguments
+    /// * `cmd` - The command the payload is a response to, for error reporting
+    /// * `payload` - The payload to decode
+    /// # Returns
+    /// `None` if the sensor has no value
+    pub fn from_payload(cmd: u16, payload: &[u8]) -> Result<Option<Self>, Error> {
+        let (ty, data) = match payload.split_first() {
+            None => return Ok(None),
+            Some(v) => v,
+        };
+
+        let ty = PayloadType::from_u8(*ty).ok_or(Error::InvalidPayload {
+            cmd,
+            reason: "unknown payload type",
+        })?;
+
+        let value = match ty {
+            PayloadType::String => Value::String(String::from_utf8(data.to_vec())?),
+            PayloadType::Int => Value::Int(i32::from_le_bytes(fixed(cmd, data)?)),
+            PayloadType::Float => Value::Float(f32::from_le_bytes(fixed(cmd, data)?)),
+            PayloadType::UInt => Value::UInt(u32::from_le_bytes(fixed(cmd, data)?)),
+            PayloadType::Int64 => Value::Int64(i64::from_le_bytes(fixed(cmd, data)?)),
+            PayloadType::UInt64 => Value::UInt64(u64::from_le_bytes(fixed(cmd, data)?)),
+            PayloadType::Bool => match fixed::<1>(cmd, data)? {
+                [0] => Value::Bool(false),
+                [1] => Value::Bool(true),
+                _ => {
+                    return Err(Error::InvalidPayload {
+                        cmd,
+                        reason: "invalid bool value",
+                    })
+                }
+            },
+            PayloadType::Fixed => {
+                let [b0, b1, b2, b3, exponent] = fixed(cmd, data)?;
+                Value::Fixed {
+                    value: i32::from_le_bytes([b0, b1, b2, b3]),
+                    exponent: exponent as i8,
+                }
+            }
+        };
+
+        Ok(Some(value))
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::String(v) => write!(f, "{}", v),
+            Value::Int(v) => write!(f, "{}", v),
+            Value::Float(v) => write!(f, "{}", v),
+            Value::UInt(v) => write!(f, "{}", v),
+            Value::Int64(v) => write!(f, "{}", v),
+            Value::UInt64(v) => write!(f, "{}", v),
+            Value::Bool(v) => write!(f, "{}", v),
+            Value::Fixed { value, exponent } => {
+                let digits = value.unsigned_abs().to_string();
+                let sign = if *value < 0 { "-" } else { "" };
+
+                if *exponent >= 0 {
+                    let zeros = if *value == 0 { 0 } else { *exponent as usize };
+                    return write!(f, "{}{}{}", sign, digits, "0".repeat(zeros));
+                }
+
+                // Exact, a conversion to a float would round
+                let decimals = exponent.unsigned_abs() as usize;
+                let digits = format!("{:0>width$}", digits, width = decimals + 1);
+                let (int, frac) = digits.split_at(digits.len() - decimals);
+                write!(f, "{}{}.{}", sign, int, frac)
+            }
+        }
+    }
+}
+
+/// The value of a sensor read by [`Client::read_sensors`](crate::Client::read_sensors)
+#[derive(Debug)]
+pub struct SensorReading {
+    /// The index of the sensor on its node
+    pub id: u32,
+    /// The value or the error the node reported for this sensor
+    pub value: Result<Option<Value>, Error>,
+}
+
+/// The value bytes of an entry or the error the node reported instead
+pub(crate) type ValueEntry<'p> = Result<&'p [u8], Error>;
+
+/// Splits a length-prefixed value entry off a payload, see `ha_buddy_proto::command::CMD_SENSOR_VALUES`
+/// # Arguments
+/// * `addr` - The address of the node, for error reporting
+/// * `cmd` - The command the payload is a response to, for error reporting
+/// * `payload` - The payload starting with the entry
+/// # Returns
+/// The value or the error the node reported instead, and the rest of the payload
+pub(crate) fn split_value_entry(
+    addr: u16,
+    cmd: u16,
+    payload: &[u8],
+) -> Result<(ValueEntry<'_>, &[u8]), Error> {
+    let invalid = |reason| Error::InvalidPayload { cmd, reason };
+
+    match payload {
+        [SENSOR_VALUE_ERROR, code, rest @ ..] => {
+            let err = Error::Node {
+                addr,
+                cmd,
+                code: *code,
+            };
+            Ok((Err(err), rest))
+        }
+        [SENSOR_VALUE_ERROR] => Err(invalid("incomplete error entry")),
+        [len, rest @ ..] if rest.len() >= *len as usize => {
+            let (value, rest) = rest.split_at(*len as usize);
+            Ok((Ok(value), rest))
+        }
+        [_, ..] => Err(invalid("value exceeds the payload")),
+        [] => Err(invalid("missing value")),
+    }
+}
+
+/// The TLV entries of a describe response, see `ha_buddy_proto::tlv`
+pub(crate) struct Descriptor {
+    entries: Vec<(u8, Vec<u8>)>,
+}
+
+impl Descriptor {
+    /// Splits a describe response into its entries
+    /// # Arguments
+    /// * `cmd` - The command the payload is a response to, for error reporting
+    /// * `payload` - The payload to decode
+    pub fn from_payload(cmd: u16, mut payload: &[u8]) -> Result<Self, Error> {
+        let mut entries = Vec::new();
+
+        while let [tag, len, rest @ ..] = payload {
+            let len = *len as usize;
+            if rest.len() < len {
+                return Err(Error::InvalidPayload {
+                    cmd,
+                    reason: "entry exceeds the payload",
+                });
+            }
+
+            entries.push((*tag, rest[0..len].to_vec()));
+            payload = &rest[len..];
+        }
+
+        if !payload.is_empty() {
+            return Err(Error::InvalidPayload {
+                cmd,
+                reason: "incomplete entry",
+            });
+        }
+
+        Ok(Self { entries })
+    }
+
+    /// Returns the value of the first entry with `tag`
+    pub fn get(&self, tag: u8) -> Option<&[u8]> {
+        self.entries
+            .iter()
+            .find(|(t, _)| *t == tag)
+            .map(|(_, value)| value.as_slice())
+    }
+
+    /// Returns the values of all entries with `tag`, in order
+    pub fn all(&self, tag: u8) -> impl Iterator<Item = &[u8]> {
+        self.entries
+            .iter()
+            .filter(move |(t, _)| *t == tag)
+            .map(|(_, value)| value.as_slice())
+    }
+
+    /// Returns the string value of the entry with `tag`, empty if it is missing
+    pub fn string(&self, tag: u8) -> Result<String, Error> {
+        let value = self.get(tag).unwrap_or_default();
+        Ok(String::from_utf8(value.to_vec())?)
+    }
+
+    /// Returns the `f32` value of the entry with `tag`
+    /// # Arguments
+    /// * `cmd` - The command the payload is a response to, for error reporting
+    /// * `tag` - The tag of the entry
+    pub fn f32(&self, cmd: u16, tag: u8) -> Result<f32, Error> {
+        let value = self.get(tag).ok_or(Error::InvalidPayload {
+            cmd,
+            reason: "missing entry",
+        })?;
+        Ok(f32::from_le_bytes(fixed(cmd, value)?))
+    }
+}
+
+/// Decodes the state of a binary sensor, see `ha_buddy_proto::command::CMD_BINARY_SENSOR_STATE`
+/// # Arguments
+/// * `cmd` - The command the payload is a response to, for error reporting
+/// * `payload` - The payload to decode
+/// # Returns
+/// `None` if the state is unknown
+pub(crate) fn binary_sensor_state(cmd: u16, payload: &[u8]) -> Result<Option<bool>, Error> {
+    match payload {
+        [] => Ok(None),
+        [state] => Ok(Some(*state != 0)),
+        _ => Err(Error::InvalidPayload {
+            cmd,
+            reason: "binary sensor state is not a single byte",
+        }),
+    }
+}
+
+/// Decodes the value of a number, see `ha_buddy_proto::command::CMD_NUMBER_VALUE`
+/// # Arguments
+/// * `cmd` - The command the payload is a response to, for error reporting
+/// * `payload` - The payload to decode
+pub(crate) fn number_value(cmd: u16, payload: &[u8]) -> Result<f32, Error> {
+    match Value::from_payload(cmd, payload)? {
+        Some(Value::Float(value)) => Ok(value),
+        Some(Value::Int(value)) => Ok(value as f32),
+        _ => Err(Error::InvalidPayload {
+            cmd,
+            reason: "number value is not numeric",
+        }),
+    }
+}
+
+/// Decodes the state of a light, see `ha_buddy_proto::command::CMD_LIGHT_STATE`
+/// # Arguments
+/// * `cmd` - The command the payload is a response to, for error reporting
+/// * `payload` - The payload to decode
+pub(crate) fn light_state(cmd: u16, payload: &[u8]) -> Result<LightState, Error> {
+    match payload {
+        [on, brightness] => Ok(LightState {
+            on: *on != 0,
+            brightness: *brightness,
+        }),
+        _ => Err(Error::InvalidPayload {
+            cmd,
+            reason: "light state is not two bytes",
+        }),
+    }
+}
+
+/// Decodes the state of a cover, see `ha_buddy_proto::command::CMD_COVER_STATE`
+/// # Arguments
+/// * `cmd` - The command the payload is a response to, for error reporting
+/// * `payload` - The payload to decode
+pub(crate) fn cover_state(cmd: u16, payload: &[u8]) -> Result<CoverState, Error> {
+    match payload {
+        [position, motion] => Ok(CoverState {
+            position: *position,
+            motion: CoverMotion::from_u8(*motion).ok_or(Error::InvalidPayload {
+                cmd,
+                reason: "unknown cover motion",
+            })?,
+        }),
+        _ => Err(Error::InvalidPayload {
+            cmd,
+            reason: "cover state is not two bytes",
+        }),
+    }
+}
+
+/// Decodes the state of a select, see `ha_buddy_proto::command::CMD_SELECT_STATE`
+/// # Arguments
+/// * `cmd` - The command the payload is a response to, for error reporting
+/// * `payload` - The payload to decode
+/// # Returns
+/// The index of the selected option
+pub(crate) fn select_index(cmd: u16, payload: &[u8]) -> Result<u8, Error> {
+    match payload {
+        [index] => Ok(*index),
+        _ => Err(Error::InvalidPayload {
+            cmd,
+            reason: "select state is not a single byte",
+        }),
+    }
+}
+
+/// Extracts exactly `N` bytes from `data`
+fn fixed<const N: usize>(cmd: u16, data: &[u8]) -> Result<[u8; N], Error> {
+    data.try_into().map_err(|_| Error::InvalidPayload {
+        cmd,
+        reason: "unexpected value length",
+    })
+}
@@ -0,0 +1,118 @@
+use std::{fmt, io, string::FromUtf8Error};
+
+use ha_buddy_proto::{datalink::Serial, error::ErrorCode};
+
+/// An error that occurred while talking to a node
+#[derive(Debug)]
+pub enum Error {
+    /// The transport failed
+    Io(io::Error),
+    /// The node did not respond in time, even after all retries
+    Timeout {
+        /// The address of the node
+        addr: u16,
+        /// The command that was sent
+        cmd: u16,
+    },
+    /// The node responded with an error
+    Node {
+        /// The address of the node
+        addr: u16,
+        /// The command that failed
+        cmd: u16,
+        /// The raw error code, see [`ErrorCode`]
+        code: u8,
+    },
+    /// The payload of the response could not be interpreted
+    InvalidPayload {
+        /// The command whose response was invalid
+        cmd: u16,
+        /// A description of what is wrong with the payload
+        reason: &'static str,
+    },
+    /// A string in the response was not valid UTF-8
+    Utf8(FromUtf8Error),
+    /// The request payload does not fit into a frame
+    PayloadTooLong(usize),
+    /// A request expecting a response was sent to a broadcast or group address
+    MulticastRequest(u16),
+    /// Nodes could not be told apart, they respond with the same serial number
+    SerialCollision(Serial),
+    /// There are more nodes than addresses to assign
+    AddressesExhausted,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "Transport error: {}", e),
+            Error::Timeout { addr, cmd } => {
+                write!(
+                    f,
+                    "Node {:#06x} did not respond to command {:#06x}",
+                    addr, cmd
+                )
+            }
+            Error::Node { addr, cmd, code } => match ErrorCode::from_u8(*code) {
+                Some(e) => write!(
+                    f,
+                    "Node {:#06x} failed command {:#06x}: {}",
+                    addr,
+                    cmd,
+                    e.as_str()
+                ),
+                None => write!(
+                    f,
+                    "Node {:#06x} failed command {:#06x}: unknown error {}",
+                    addr, cmd, code
+                ),
+            },
+            Error::InvalidPayload { cmd, reason } => {
+                write!(f, "Invalid response to command {:#06x}: {}", cmd, reason)
+            }
+            Error::Utf8(e) => write!(f, "Invalid string in response: {}", e),
+            Error::PayloadTooLong(len) => {
+                write!(f, "Payload of {} bytes does not fit into a frame", len)
+            }
+            Error::MulticastRequest(addr) => {
+                write!(f, "Nodes do not respond to multicast address {:#06x}", addr)
+            }
+            Error::SerialCollision(serial) => {
+                write!(f, "Many nodes respond to serial number {:02x?}", serial)
+            }
+            Error::AddressesExhausted => write!(f, "No free address left to assign"),
+        }
+    }
+}
+
+impl Error {
+    /// Returns the error code if the node responded with an error
+    pub fn node_error(&self) -> Option<ErrorCode> {
+        match self {
+            Error::Node { code, .. } => ErrorCode::from_u8(*code),
+            _ => None,
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Utf8(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        Error::Io(value)
+    }
+}
+
+impl From<FromUtf8Error> for Error {
+    fn from(value: FromUtf8Error) -> Self {
+        Error::Utf8(value)
+    }
+}
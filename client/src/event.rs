@@ -0,0 +1,153 @@
+use ha_buddy_proto::{
+    command::CMD_EVENTS_FETCH,
+    event::{
+        EVENTS_OVERFLOWED, EVENTS_PENDING, EVENT_BINARY_SENSOR, EVENT_COVER, EVENT_LIGHT,
+        EVENT_NUMBER, EVENT_SELECT, EVENT_SENSOR, EVENT_SWITCH,
+    },
+    homeassistant::{cover::CoverState, light::LightState},
+};
+
+use crate::{
+    entity::{
+        binary_sensor_state, cover_state, light_state, number_value, select_index,
+        split_value_entry,
+    },
+    Error, Value,
+};
+
+/// A change a node reported, carrying the state at the time it was fetched
+#[derive(Debug)]
+pub enum NodeEvent {
+    /// The value of a sensor changed beyond its deadband
+    Sensor {
+        /// The index of the sensor on its node
+        id: u32,
+        /// The value or the error the node reported for this sensor
+        value: Result<Option<Value>, Error>,
+    },
+    /// The state of a switch changed without the master requesting it
+    Switch {
+        /// The index of the switch on its node
+        id: u32,
+        /// The state or the error the node reported for this switch
+        state: Result<bool, Error>,
+    },
+    /// The state of a binary sensor changed
+    BinarySensor {
+        /// The index of the binary sensor on its node
+        id: u32,
+        /// The state, `None` if unknown, or the error the node reported for this binary sensor
+        state: Result<Option<bool>, Error>,
+    },
+    /// The value of a number changed without the master requesting it
+    Number {
+        /// The index of the number on its node
+        id: u32,
+        /// The value or the error the node reported for this number
+        value: Result<f32, Error>,
+    },
+    /// The state of a light changed without the master requesting it
+    Light {
+        /// The index of the light on its node
+        id: u32,
+        /// The state or the error the node reported for this light
+        state: Result<LightState, Error>,
+    },
+    /// The state of a cover changed without the master requesting it, e.g. while it moves
+    Cover {
+        /// The index of the cover on its node
+        id: u32,
+        /// The state or the error the node reported for this cover
+        state: Result<CoverState, Error>,
+    },
+    /// The selected option of a select changed without the master requesting it
+    Select {
+        /// The index of the select on its node
+        id: u32,
+        /// The index of the selected option or the error the node reported for this select
+        index: Result<u8, Error>,
+    },
+    /// An event of a kind this client does not know
+    Unknown {
+        /// The event kind
+        kind: u8,
+        /// The index of the entity on its node
+        id: u32,
+    },
+}
+
+/// The events fetched from a node
+#[derive(Debug, Default)]
+pub struct NodeEvents {
+    /// The node dropped events, the master has to poll all entities to catch up
+    pub overflowed: bool,
+    /// The events, oldest first
+    pub events: Vec<NodeEvent>,
+}
+
+impl NodeEvents {
+    /// Decodes a [`CMD_EVENTS_FETCH`] response and appends its events
+    /// # Arguments
+    /// * `addr` - The address of the node, for error reporting
+    /// * `payload` - The payload to decode
+    /// # Returns
+    /// True if the node has more events pending
+    pub fn append_payload(&mut self, addr: u16, payload: &[u8]) -> Result<bool, Error> {
+        let invalid = |reason| Error::InvalidPayload {
+            cmd: CMD_EVENTS_FETCH,
+            reason,
+        };
+
+        let (flags, mut rest) = payload.split_first().ok_or(invalid("missing flags"))?;
+        self.overflowed |= flags & EVENTS_OVERFLOWED != 0;
+
+        while let [kind, a, b, c, d, tail @ ..] = rest {
+            let id = u32::from_le_bytes([*a, *b, *c, *d]);
+            let (value, tail) = split_value_entry(addr, CMD_EVENTS_FETCH, tail)?;
+
+            let event = match *kind {
+                EVENT_SENSOR => NodeEvent::Sensor {
+                    id,
+                    value: value.and_then(|value| Value::from_payload(CMD_EVENTS_FETCH, value)),
+                },
+                EVENT_SWITCH => NodeEvent::Switch {
+                    id,
+                    state: value.and_then(|value| match value {
+                        [state] => Ok(*state != 0),
+                        _ => Err(invalid("switch state is not a single byte")),
+                    }),
+                },
+                EVENT_BINARY_SENSOR => NodeEvent::BinarySensor {
+                    id,
+                    state: value.and_then(|value| binary_sensor_state(CMD_EVENTS_FETCH, value)),
+                },
+                EVENT_NUMBER => NodeEvent::Number {
+                    id,
+                    value: value.and_then(|value| number_value(CMD_EVENTS_FETCH, value)),
+                },
+                EVENT_LIGHT => NodeEvent::Light {
+                    id,
+                    state: value.and_then(|value| light_state(CMD_EVENTS_FETCH, value)),
+                },
+                EVENT_COVER => NodeEvent::Cover {
+                    id,
+                    state: value.and_then(|value| cover_state(CMD_EVENTS_FETCH, value)),
+                },
+                EVENT_SELECT => NodeEvent::Select {
+                    id,
+                    index: value.and_then(|value| select_index(CMD_EVENTS_FETCH, value)),
+                },
+                kind => NodeEvent::Unknown { kind, id },
+            };
+
+            self.events.push(event);
+            rest = tail;
+        }
+
+        if !rest.is_empty() {
+            return Err(invalid("incomplete event"));
+        }
+
+        Ok(flags & EVENTS_PENDING != 0)
+    }
+}
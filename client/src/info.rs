@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+use ha_buddy_proto::command::CMD_NODE_INFO;
+
+use crate::Error;
+
+/// The information a node reports about itself
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeInfo {
+    /// The protocol version the node implements
+    pub protocol_version: u8,
+    /// The entity types the firmware of the node supports, whether or not the node
+    /// has entities of them, see `ha_buddy_proto::device::CAP_*`
+    pub capabilities: u32,
+    /// The time since the node started
+    pub uptime: Duration,
+    /// The version of the firmware
+    pub firmware_version: String,
+    /// The board the firmware runs on
+    pub board: String,
+    /// The name of the node
+    pub name: String,
+}
+
+impl NodeInfo {
+    /// Decodes the device information from a response payload
+    /// # Arguments
+    /// * `payload` - The payload to decode
+    pub fn from_payload(payload: &[u8]) -> Result<Self, Error> {
+        let invalid = |reason| Error::InvalidPayload {
+            cmd: CMD_NODE_INFO,
+            reason,
+        };
+
+        if payload.len() < 9 {
+            return Err(invalid("expected at least 9 bytes"));
+        }
+
+        let mut rest = &payload[9..];
+        let mut strings = Vec::new();
+        for _ in 0..3 {
+            let (len, data) = rest.split_first().ok_or(invalid("missing string"))?;
+            let len = *len as usize;
+            if data.len() < len {
+                return Err(invalid("string exceeds the payload"));
+            }
+
+            strings.push(String::from_utf8(data[0..len].to_vec())?);
+            rest = &data[len..];
+        }
+
+        let mut strings = strings.into_iter();
+        Ok(Self {
+            protocol_version: payload[0],
+            capabilities: u32::from_le_bytes(payload[1..5].try_into().unwrap()),
+            uptime: Duration::from_secs(
+                u32::from_le_bytes(payload[5..9].try_into().unwrap()) as u64
+            ),
+            firmware_version: strings.next().unwrap(),
+            board: strings.next().unwrap(),
+            name: strings.next().unwrap(),
+        })
+    }
+
+    /// Checks if the node supports all entity types in `capabilities`
+    /// # Arguments
+    /// * `capabilities` - The `ha_buddy_proto::device::CAP_*` bits to check
+    pub fn supports(&self, capabilities: u32) -> bool {
+        self.capabilities & capabilities == capabilities
+    }
+}
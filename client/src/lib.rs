@@ -0,0 +1,1119 @@
+//! A host-side master for the HA-Buddy bus
+//!
+//! The [`Client`] talks to nodes over any [`Read`] + [`Write`] transport,
+//! using the same [`DataFrame`] implementation the firmware uses.
+//! The transport is expected to return from `read()` periodically, e.g.
+//! by having a read timeout configured, so the client can enforce its timeouts.
+
+use std::{
+    convert::Infallible,
+    io::{ErrorKind, Read, Write},
+    thread,
+    time::{Duration, Instant},
+};
+
+use ha_buddy_proto::{
+    command::*,
+    datalink::{is_multicast, Serial, BROADCAST_ADDR, SERIAL_LEN},
+    error::ErrorCode,
+    homeassistant::{
+        cover::{CoverRequest, CoverState},
+        light::{LightRequest, LightState},
+        switch::SwitchRequest,
+    },
+    tlv::*,
+    DataFrame,
+};
+
+mod discover;
+pub use discover::*;
+
+mod entity;
+pub use entity::*;
+
+mod error;
+pub use error::*;
+
+mod event;
+pub use event::*;
+
+mod info;
+pub use info::*;
+
+/// The address the master uses by default
+pub const MASTER_ADDR: u16 = 0x0000;
+/// The default time to wait for a response
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(200);
+/// The default amount of retries after the first attempt
+pub const DEFAULT_RETRIES: u8 = 2;
+
+/// A master on the HA-Buddy bus
+pub struct Client<T: Read + Write> {
+    transport: T,
+    addr: u16,
+    timeout: Duration,
+    retries: u8,
+    frame: DataFrame,
+}
+
+impl<T: Read + Write> Client<T> {
+    /// Creates a new client on the supplied transport
+    /// # Arguments
+    /// * `transport` - The transport to talk to the bus with
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            addr: MASTER_ADDR,
+            timeout: DEFAULT_TIMEOUT,
+            retries: DEFAULT_RETRIES,
+            frame: DataFrame::new(),
+        }
+    }
+
+    /// Sets the source address to use for requests
+    /// # Arguments
+    /// * `addr` - The new address
+    pub fn set_addr(&mut self, addr: u16) {
+        self.addr = addr;
+    }
+
+    /// Sets the time to wait for a response per attempt
+    /// # Arguments
+    /// * `timeout` - The new timeout
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Sets the amount of retries after the first attempt failed
+    /// # Arguments
+    /// * `retries` - The new amount of retries
+    pub fn set_retries(&mut self, retries: u8) {
+        self.retries = retries;
+    }
+
+    /// Returns a mutable reference to the underlying transport
+    pub fn transport_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// Consumes the client, returning the underlying transport
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+
+    /// Sends a command to a node and waits for the response, retrying on timeouts
+    ///
+    /// Error responses of the node are not retried
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `cmd` - The command to execute
+    /// * `payload` - The payload for the command
+    /// # Returns
+    /// The payload of the response
+    pub fn request(&mut self, dst: u16, cmd: u16, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        if payload.len() > u8::MAX as usize {
+            return Err(Error::PayloadTooLong(payload.len()));
+        }
+
+        if is_multicast(dst) {
+            return Err(Error::MulticastRequest(dst));
+        }
+
+        for _ in 0..=self.retries {
+            self.send(dst, cmd, payload)?;
+
+            if let Some(response) = self.receive(dst, cmd.wrapping_add(1))? {
+                return Ok(response);
+            }
+        }
+
+        Err(Error::Timeout { addr: dst, cmd })
+    }
+
+    /// Sends a frame without waiting for a response
+    ///
+    /// This is the way to talk to broadcast and multicast addresses,
+    /// as nodes do not respond to them
+    /// # Arguments
+    /// * `dst` - The address to send the frame to
+    /// * `cmd` - The command to send
+    /// * `payload` - The payload for the command
+    pub fn send(&mut self, dst: u16, cmd: u16, payload: &[u8]) -> Result<(), Error> {
+        if payload.len() > u8::MAX as usize {
+            return Err(Error::PayloadTooLong(payload.len()));
+        }
+
+        let mut frame = DataFrame::new();
+        frame.src = self.addr;
+        frame.dst = dst;
+        frame.cmd = cmd;
+        frame.payload_len = payload.len() as u8;
+        frame.payload[0..payload.len()].copy_from_slice(payload);
+
+        let mut buffer = FrameBuffer::default();
+        match frame.send(&mut buffer) {
+            Ok(()) => {}
+            Err(nb::Error::WouldBlock) => unreachable!(),
+            Err(nb::Error::Other(e)) => match e {},
+        }
+
+        self.transport.write_all(&buffer.bytes)?;
+        self.transport.flush()?;
+
+        Ok(())
+    }
+
+    /// Reads the bytes the transport has available
+    /// # Returns
+    /// The amount of bytes read, `0` if there was no data before the transport timed out
+    fn read_some(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        match self.transport.read(buf) {
+            Ok(0) => {
+                thread::sleep(Duration::from_millis(1));
+                Ok(0)
+            }
+            Ok(len) => Ok(len),
+            Err(e) => match e.kind() {
+                ErrorKind::TimedOut | ErrorKind::WouldBlock | ErrorKind::Interrupted => Ok(0),
+                _ => Err(e.into()),
+            },
+        }
+    }
+
+    /// Waits for a valid response frame from `src` with the command `cmd`,
+    /// discarding all other frames
+    /// # Returns
+    /// The payload of the response or `None` on timeout, `Error::Node` if
+    /// the node responded with an error
+    fn receive(&mut self, src: u16, cmd: u16) -> Result<Option<Vec<u8>>, Error> {
+        let deadline = Instant::now() + self.timeout;
+        let mut buf = [0u8; 64];
+
+        self.frame = DataFrame::new();
+
+        while Instant::now() < deadline {
+            let len = self.read_some(&mut buf)?;
+
+            for byte in &buf[0..len] {
+                if !self.frame.handle_byte(*byte) {
+                    continue;
+                }
+
+                let frame = &self.frame;
+                if !frame.check_crc() || frame.src != src || frame.dst != self.addr {
+                    continue;
+                }
+
+                let payload = &frame.payload[0..frame.payload_len as usize];
+
+                if frame.cmd == cmd {
+                    return Ok(Some(payload.to_vec()));
+                }
+
+                if frame.cmd == cmd | CMD_ERROR_FLAG {
+                    return Err(Error::Node {
+                        addr: src,
+                        cmd: cmd.wrapping_sub(1),
+                        code: payload.first().copied().unwrap_or(0),
+                    });
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Requests a string attribute of an entity
+    fn request_string(&mut self, dst: u16, cmd: u16, id: u32) -> Result<String, Error> {
+        Ok(String::from_utf8(self.request(
+            dst,
+            cmd,
+            &id.to_le_bytes(),
+        )?)?)
+    }
+
+    /// Requests a `u32` value
+    fn request_u32(&mut self, dst: u16, cmd: u16, payload: &[u8]) -> Result<u32, Error> {
+        let res = self.request(dst, cmd, payload)?;
+
+        let bytes: [u8; 4] = res
+            .as_slice()
+            .try_into()
+            .map_err(|_| Error::InvalidPayload {
+                cmd,
+                reason: "expected 4 bytes",
+            })?;
+
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    /// Requests a switch state
+    fn request_state(&mut self, dst: u16, cmd: u16, payload: &[u8]) -> Result<bool, Error> {
+        match self.request(dst, cmd, payload)?.as_slice() {
+            [state] => Ok(*state != 0),
+            _ => Err(Error::InvalidPayload {
+                cmd,
+                reason: "expected 1 byte",
+            }),
+        }
+    }
+
+    /// Sends an echo request to a node
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `data` - The data to be echoed back
+    /// # Returns
+    /// The data the node echoed back
+    pub fn echo(&mut self, dst: u16, data: &[u8]) -> Result<Vec<u8>, Error> {
+        self.request(dst, CMD_ECHO, data)
+    }
+
+    /// Reads the serial number of a node
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// # Returns
+    /// The serial number, `None` if the node has not been assigned one
+    pub fn node_serial(&mut self, dst: u16) -> Result<Option<Serial>, Error> {
+        let res = self.request(dst, CMD_NODE_SERIAL, &[])?;
+
+        match res.len() {
+            0 => Ok(None),
+            SERIAL_LEN => Ok(Some(res.try_into().unwrap())),
+            _ => Err(Error::InvalidPayload {
+                cmd: CMD_NODE_SERIAL,
+                reason: "expected 0 or 8 bytes",
+            }),
+        }
+    }
+
+    /// Reads the device information of a node
+    ///
+    /// Nodes with firmware older than protocol version 1 respond with
+    /// [`ErrorCode::UnknownCommand`](ha_buddy_proto::error::ErrorCode::UnknownCommand)
+    /// # Arguments
+    /// * `dst` - The address of the node
+    pub fn node_info(&mut self, dst: u16) -> Result<NodeInfo, Error> {
+        let res = self.request(dst, CMD_NODE_INFO, &[])?;
+        NodeInfo::from_payload(&res)
+    }
+
+    /// Assigns a new address to a node
+    ///
+    /// Without a serial number only a node in commissioning mode accepts the
+    /// new address. If `dst` is a broadcast or group address, the request
+    /// is sent without waiting for a response, check the new address with
+    /// [`Client::echo`] afterwards.
+    /// # Arguments
+    /// * `dst` - The current address of the node
+    /// * `addr` - The new address of the node
+    /// * `serial` - The serial number of the node
+    pub fn assign_address(
+        &mut self,
+        dst: u16,
+        addr: u16,
+        serial: Option<&Serial>,
+    ) -> Result<(), Error> {
+        let mut payload = addr.to_le_bytes().to_vec();
+        if let Some(serial) = serial {
+            payload.extend_from_slice(serial);
+        }
+
+        if is_multicast(dst) {
+            return self.send(dst, CMD_NODE_SET_ADDR, &payload);
+        }
+
+        self.request(dst, CMD_NODE_SET_ADDR, &payload)?;
+        Ok(())
+    }
+
+    /// Returns the amount of sensors a node has
+    /// # Arguments
+    /// * `dst` - The address of the node
+    pub fn sensor_count(&mut self, dst: u16) -> Result<u32, Error> {
+        self.request_u32(dst, CMD_SENSOR_COUNT, &[])
+    }
+
+    /// Retrieves all attributes of a sensor
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the sensor
+    pub fn sensor_info(&mut self, dst: u16, id: u32) -> Result<SensorInfo, Error> {
+        Ok(SensorInfo {
+            id,
+            name: self.request_string(dst, CMD_SENSOR_NAME, id)?,
+            unique_id: self.request_string(dst, CMD_SENSOR_UNIQUE_ID, id)?,
+            native_unit_of_measurement: self.request_string(
+                dst,
+                CMD_SENSOR_NATIVE_UNIT_OF_MEASUREMENT,
+                id,
+            )?,
+            device_class: self.request_string(dst, CMD_SENSOR_DEVICE_CLASS, id)?,
+            state_class: self.request_string(dst, CMD_SENSOR_STATE_CLASS, id)?,
+        })
+    }
+
+    /// Retrieves all attributes and the value of a sensor in a single request
+    ///
+    /// Falls back to [`Client::sensor_info`] and [`Client::read_sensor`] for
+    /// nodes with firmware before protocol version 2
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the sensor
+    pub fn describe_sensor(
+        &mut self,
+        dst: u16,
+        id: u32,
+    ) -> Result<(SensorInfo, Option<Value>), Error> {
+        let res = match self.request(dst, CMD_SENSOR_DESCRIBE, &id.to_le_bytes()) {
+            Err(e) if e.node_error() == Some(ErrorCode::UnknownCommand) => {
+                return Ok((self.sensor_info(dst, id)?, self.read_sensor(dst, id)?));
+            }
+            res => res?,
+        };
+        let desc = Descriptor::from_payload(CMD_SENSOR_DESCRIBE, &res)?;
+
+        let info = SensorInfo {
+            id,
+            name: desc.string(TAG_NAME)?,
+            unique_id: desc.string(TAG_UNIQUE_ID)?,
+            native_unit_of_measurement: desc.string(TAG_NATIVE_UNIT_OF_MEASUREMENT)?,
+            device_class: desc.string(TAG_DEVICE_CLASS)?,
+            state_class: desc.string(TAG_STATE_CLASS)?,
+        };
+
+        // The value is left out if the node failed to read it, ask for the error
+        let value = match desc.get(TAG_VALUE) {
+            Some(value) => Value::from_payload(CMD_SENSOR_DESCRIBE, value)?,
+            None => self.read_sensor(dst, id)?,
+        };
+
+        Ok((info, value))
+    }
+
+    /// Reads the current value of a sensor
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the sensor
+    /// # Returns
+    /// `None` if the sensor has no value
+    pub fn read_sensor(&mut self, dst: u16, id: u32) -> Result<Option<Value>, Error> {
+        let res = self.request(dst, CMD_SENSOR_VALUE, &id.to_le_bytes())?;
+        Value::from_payload(CMD_SENSOR_VALUE, &res)
+    }
+
+    /// Reads the values of many sensors with as few requests as possible
+    ///
+    /// Falls back to one [`Client::read_sensor`] per sensor for nodes with
+    /// firmware before protocol version 3
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `first` - The index of the first sensor to read
+    /// * `mask` - Selects the sensors from `first` on, bit `0` being `first`, `None` selects all
+    /// # Returns
+    /// The index of each selected sensor with its value or the error the node reported for it
+    pub fn read_sensors(
+        &mut self,
+        dst: u16,
+        mut first: u32,
+        mut mask: Option<u32>,
+    ) -> Result<Vec<SensorReading>, Error> {
+        let mut readings = Vec::new();
+
+        loop {
+            let mut payload = first.to_le_bytes().to_vec();
+            if let Some(mask) = mask {
+                payload.extend_from_slice(&mask.to_le_bytes());
+            }
+
+            let res = match self.request(dst, CMD_SENSOR_VALUES, &payload) {
+                Err(e) if e.node_error() == Some(ErrorCode::UnknownCommand) => {
+                    return self.read_sensors_each(dst, first, mask, readings);
+                }
+                res => res?,
+            };
+
+            let next = sensor_values(dst, &res, selected_sensors(first, mask), &mut readings)?;
+            if next == SENSOR_VALUES_COMPLETE {
+                return Ok(readings);
+            }
+            if next <= first {
+                return Err(Error::InvalidPayload {
+                    cmd: CMD_SENSOR_VALUES,
+                    reason: "continuation does not advance",
+                });
+            }
+
+            mask = mask.map(|mask| mask.checked_shr(next - first).unwrap_or(0));
+            first = next;
+        }
+    }
+
+    /// Reads the values of all sensors of a node, see [`Client::read_sensors`]
+    /// # Arguments
+    /// * `dst` - The address of the node
+    pub fn read_all_sensors(&mut self, dst: u16) -> Result<Vec<SensorReading>, Error> {
+        self.read_sensors(dst, 0, None)
+    }
+
+    /// Reads the selected sensors one by one, for nodes without [`CMD_SENSOR_VALUES`]
+    fn read_sensors_each(
+        &mut self,
+        dst: u16,
+        first: u32,
+        mask: Option<u32>,
+        mut readings: Vec<SensorReading>,
+    ) -> Result<Vec<SensorReading>, Error> {
+        let count = self.sensor_count(dst)?;
+
+        for id in selected_sensors(first, mask).take_while(|id| *id < count) {
+            let value = match self.read_sensor(dst, id) {
+                Err(e) if e.node_error().is_none() => return Err(e),
+                value => value,
+            };
+            readings.push(SensorReading { id, value });
+        }
+
+        Ok(readings)
+    }
+
+    /// Fetches the changes a node reported since the last fetch
+    ///
+    /// Repeats the request until the node has no more events pending. Events in
+    /// a lost response are gone, poll all entities after an error.
+    /// # Arguments
+    /// * `dst` - The address of the node
+    pub fn fetch_events(&mut self, dst: u16) -> Result<NodeEvents, Error> {
+        let mut events = NodeEvents::default();
+
+        loop {
+            let res = self.request(dst, CMD_EVENTS_FETCH, &[])?;
+            if !events.append_payload(dst, &res)? {
+                return Ok(events);
+            }
+        }
+    }
+
+    /// Returns the amount of switches a node has
+    /// # Arguments
+    /// * `dst` - The address of the node
+    pub fn switch_count(&mut self, dst: u16) -> Result<u32, Error> {
+        self.request_u32(dst, CMD_SWITCH_COUNT, &[])
+    }
+
+    /// Retrieves all attributes of a switch
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the switch
+    pub fn switch_info(&mut self, dst: u16, id: u32) -> Result<SwitchInfo, Error> {
+        Ok(SwitchInfo {
+            id,
+            name: self.request_string(dst, CMD_SWITCH_NAME, id)?,
+            unique_id: self.request_string(dst, CMD_SWITCH_UNIQUE_ID, id)?,
+        })
+    }
+
+    /// Retrieves all attributes and the state of a switch in a single request
+    ///
+    /// Falls back to [`Client::switch_info`] and [`Client::switch_state`] for
+    /// nodes with firmware before protocol version 2
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the switch
+    pub fn describe_switch(&mut self, dst: u16, id: u32) -> Result<(SwitchInfo, bool), Error> {
+        let res = match self.request(dst, CMD_SWITCH_DESCRIBE, &id.to_le_bytes()) {
+            Err(e) if e.node_error() == Some(ErrorCode::UnknownCommand) => {
+                return Ok((self.switch_info(dst, id)?, self.switch_state(dst, id)?));
+            }
+            res => res?,
+        };
+        let desc = Descriptor::from_payload(CMD_SWITCH_DESCRIBE, &res)?;
+
+        let info = SwitchInfo {
+            id,
+            name: desc.string(TAG_NAME)?,
+            unique_id: desc.string(TAG_UNIQUE_ID)?,
+        };
+
+        // The state is left out if the node failed to read it, ask for the error
+        let state = match desc.get(TAG_STATE) {
+            Some([state]) => *state != 0,
+            Some(_) => {
+                return Err(Error::InvalidPayload {
+                    cmd: CMD_SWITCH_DESCRIBE,
+                    reason: "expected 1 byte state",
+                })
+            }
+            None => self.switch_state(dst, id)?,
+        };
+
+        Ok((info, state))
+    }
+
+    /// Reads the current state of a switch
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the switch
+    pub fn switch_state(&mut self, dst: u16, id: u32) -> Result<bool, Error> {
+        self.request_state(dst, CMD_SWITCH_STATE, &id.to_le_bytes())
+    }
+
+    /// Executes a request on a switch
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the switch
+    /// * `req` - The request to execute
+    /// # Returns
+    /// The state of the switch after the request
+    pub fn switch_exec(&mut self, dst: u16, id: u32, req: SwitchRequest) -> Result<bool, Error> {
+        if req == SwitchRequest::Get {
+            return self.switch_state(dst, id);
+        }
+
+        self.request_state(dst, CMD_SWITCH_EXEC, &switch_exec_payload(id, req))
+    }
+
+    /// Executes a request on all switches of a node
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `req` - The request to execute
+    /// # Returns
+    /// The states of all switches after the request
+    pub fn switch_exec_all(&mut self, dst: u16, req: SwitchRequest) -> Result<Vec<bool>, Error> {
+        let res = self.request(
+            dst,
+            CMD_SWITCH_EXEC,
+            &switch_exec_payload(SWITCH_ID_ALL, req),
+        )?;
+        Ok(res.iter().map(|state| *state != 0).collect())
+    }
+
+    /// Executes a request on a switch of many nodes at once, without waiting for responses
+    /// # Arguments
+    /// * `dst` - The broadcast or group address of the nodes
+    /// * `id` - The index of the switch, [`SWITCH_ID_ALL`] for all switches
+    /// * `req` - The request to execute
+    pub fn multicast_switch_exec(
+        &mut self,
+        dst: u16,
+        id: u32,
+        req: SwitchRequest,
+    ) -> Result<(), Error> {
+        self.send(dst, CMD_SWITCH_EXEC, &switch_exec_payload(id, req))
+    }
+
+    /// Turns off every switch of every node on the bus with a single frame
+    pub fn all_switches_off(&mut self) -> Result<(), Error> {
+        self.multicast_switch_exec(BROADCAST_ADDR, SWITCH_ID_ALL, SwitchRequest::TurnOFF)
+    }
+
+    /// Returns the amount of binary sensors a node has
+    /// # Arguments
+    /// * `dst` - The address of the node
+    pub fn binary_sensor_count(&mut self, dst: u16) -> Result<u32, Error> {
+        self.request_u32(dst, CMD_BINARY_SENSOR_COUNT, &[])
+    }
+
+    /// Retrieves all attributes of a binary sensor
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the binary sensor
+    pub fn binary_sensor_info(&mut self, dst: u16, id: u32) -> Result<BinarySensorInfo, Error> {
+        Ok(BinarySensorInfo {
+            id,
+            name: self.request_string(dst, CMD_BINARY_SENSOR_NAME, id)?,
+            unique_id: self.request_string(dst, CMD_BINARY_SENSOR_UNIQUE_ID, id)?,
+            device_class: self.request_string(dst, CMD_BINARY_SENSOR_DEVICE_CLASS, id)?,
+        })
+    }
+
+    /// Retrieves all attributes and the state of a binary sensor in a single request
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the binary sensor
+    pub fn describe_binary_sensor(
+        &mut self,
+        dst: u16,
+        id: u32,
+    ) -> Result<(BinarySensorInfo, Option<bool>), Error> {
+        let res = self.request(dst, CMD_BINARY_SENSOR_DESCRIBE, &id.to_le_bytes())?;
+        let desc = Descriptor::from_payload(CMD_BINARY_SENSOR_DESCRIBE, &res)?;
+
+        let info = BinarySensorInfo {
+            id,
+            name: desc.string(TAG_NAME)?,
+            unique_id: desc.string(TAG_UNIQUE_ID)?,
+            device_class: desc.string(TAG_DEVICE_CLASS)?,
+        };
+
+        // The state is left out if the node failed to read it, ask for the error
+        let state = match desc.get(TAG_STATE) {
+            Some(state) => binary_sensor_state(CMD_BINARY_SENSOR_DESCRIBE, state)?,
+            None => self.binary_sensor_state(dst, id)?,
+        };
+
+        Ok((info, state))
+    }
+
+    /// Reads the current state of a binary sensor
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the binary sensor
+    /// # Returns
+    /// `None` if the state is unknown
+    pub fn binary_sensor_state(&mut self, dst: u16, id: u32) -> Result<Option<bool>, Error> {
+        let res = self.request(dst, CMD_BINARY_SENSOR_STATE, &id.to_le_bytes())?;
+        binary_sensor_state(CMD_BINARY_SENSOR_STATE, &res)
+    }
+
+    /// Returns the amount of numbers a node has
+    /// # Arguments
+    /// * `dst` - The address of the node
+    pub fn number_count(&mut self, dst: u16) -> Result<u32, Error> {
+        self.request_u32(dst, CMD_NUMBER_COUNT, &[])
+    }
+
+    /// Retrieves all attributes and the value of a number in a single request
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the number
+    pub fn describe_number(&mut self, dst: u16, id: u32) -> Result<(NumberInfo, f32), Error> {
+        let res = self.request(dst, CMD_NUMBER_DESCRIBE, &id.to_le_bytes())?;
+        let desc = Descriptor::from_payload(CMD_NUMBER_DESCRIBE, &res)?;
+
+        let info = NumberInfo {
+            id,
+            name: desc.string(TAG_NAME)?,
+            unique_id: desc.string(TAG_UNIQUE_ID)?,
+            native_unit_of_measurement: desc.string(TAG_NATIVE_UNIT_OF_MEASUREMENT)?,
+            device_class: desc.string(TAG_DEVICE_CLASS)?,
+            min: desc.f32(CMD_NUMBER_DESCRIBE, TAG_MIN)?,
+            max: desc.f32(CMD_NUMBER_DESCRIBE, TAG_MAX)?,
+            step: desc.f32(CMD_NUMBER_DESCRIBE, TAG_STEP)?,
+            mode: desc.string(TAG_MODE)?,
+        };
+
+        // The value is left out if the node failed to read it, ask for the error
+        let value = match desc.get(TAG_VALUE) {
+            Some(value) => number_value(CMD_NUMBER_DESCRIBE, value)?,
+            None => self.number_value(dst, id)?,
+        };
+
+        Ok((info, value))
+    }
+
+    /// Reads the current value of a number
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the number
+    pub fn number_value(&mut self, dst: u16, id: u32) -> Result<f32, Error> {
+        let res = self.request(dst, CMD_NUMBER_VALUE, &id.to_le_bytes())?;
+        number_value(CMD_NUMBER_VALUE, &res)
+    }
+
+    /// Sets the value of a number
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the number
+    /// * `value` - The new value, within the range of the number
+    /// # Returns
+    /// The value of the number after the request
+    pub fn set_number(&mut self, dst: u16, id: u32, value: f32) -> Result<f32, Error> {
+        let mut payload = id.to_le_bytes().to_vec();
+        payload.extend_from_slice(&value.to_le_bytes());
+
+        let res = self.request(dst, CMD_NUMBER_SET, &payload)?;
+        number_value(CMD_NUMBER_SET, &res)
+    }
+
+    /// Returns the amount of buttons a node has
+    /// # Arguments
+    /// * `dst` - The address of the node
+    pub fn button_count(&mut self, dst: u16) -> Result<u32, Error> {
+        self.request_u32(dst, CMD_BUTTON_COUNT, &[])
+    }
+
+    /// Retrieves all attributes of a button in a single request
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the button
+    pub fn describe_button(&mut self, dst: u16, id: u32) -> Result<ButtonInfo, Error> {
+        let res = self.request(dst, CMD_BUTTON_DESCRIBE, &id.to_le_bytes())?;
+        let desc = Descriptor::from_payload(CMD_BUTTON_DESCRIBE, &res)?;
+
+        Ok(ButtonInfo {
+            id,
+            name: desc.string(TAG_NAME)?,
+            unique_id: desc.string(TAG_UNIQUE_ID)?,
+        })
+    }
+
+    /// Presses a button, the node responds once the action started
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the button
+    pub fn press_button(&mut self, dst: u16, id: u32) -> Result<(), Error> {
+        self.request(dst, CMD_BUTTON_PRESS, &id.to_le_bytes())?;
+        Ok(())
+    }
+
+    /// Presses a button of many nodes at once, without waiting for responses
+    /// # Arguments
+    /// * `dst` - The broadcast or group address of the nodes
+    /// * `id` - The index of the button
+    pub fn multicast_press_button(&mut self, dst: u16, id: u32) -> Result<(), Error> {
+        self.send(dst, CMD_BUTTON_PRESS, &id.to_le_bytes())
+    }
+
+    /// Returns the amount of lights a node has
+    /// # Arguments
+    /// * `dst` - The address of the node
+    pub fn light_count(&mut self, dst: u16) -> Result<u32, Error> {
+        self.request_u32(dst, CMD_LIGHT_COUNT, &[])
+    }
+
+    /// Retrieves all attributes and the state of a light in a single request
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the light
+    pub fn describe_light(&mut self, dst: u16, id: u32) -> Result<(LightInfo, LightState), Error> {
+        let res = self.request(dst, CMD_LIGHT_DESCRIBE, &id.to_le_bytes())?;
+        let desc = Descriptor::from_payload(CMD_LIGHT_DESCRIBE, &res)?;
+
+        let info = LightInfo {
+            id,
+            name: desc.string(TAG_NAME)?,
+            unique_id: desc.string(TAG_UNIQUE_ID)?,
+        };
+
+        // The state is left out if the node failed to read it, ask for the error
+        let state = match (desc.get(TAG_STATE), desc.get(TAG_BRIGHTNESS)) {
+            (Some([on]), Some([brightness])) => LightState {
+                on: *on != 0,
+                brightness: *brightness,
+            },
+            _ => self.light_state(dst, id)?,
+        };
+
+        Ok((info, state))
+    }
+
+    /// Reads the current state of a light, the state it fades to during a transition
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the light
+    pub fn light_state(&mut self, dst: u16, id: u32) -> Result<LightState, Error> {
+        let res = self.request(dst, CMD_LIGHT_STATE, &id.to_le_bytes())?;
+        light_state(CMD_LIGHT_STATE, &res)
+    }
+
+    /// Executes a request on a light
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the light
+    /// * `req` - The request to execute, turning on at the brightness `0` turns the light off
+    /// # Returns
+    /// The state of the light after the request
+    pub fn light_exec(
+        &mut self,
+        dst: u16,
+        id: u32,
+        req: LightRequest,
+    ) -> Result<LightState, Error> {
+        let mut payload = id.to_le_bytes().to_vec();
+
+        let (cmd, transition_ms) = match req {
+            LightRequest::TurnOn {
+                brightness: Some(brightness),
+                transition_ms,
+            } => {
+                payload.push(brightness);
+                (CMD_LIGHT_BRIGHTNESS, transition_ms)
+            }
+            LightRequest::TurnOn {
+                brightness: None,
+                transition_ms,
+            } => {
+                payload.push(SWITCH_EXEC_TURN_ON);
+                (CMD_LIGHT_EXEC, transition_ms)
+            }
+            LightRequest::TurnOff { transition_ms } => {
+                payload.push(SWITCH_EXEC_TURN_OFF);
+                (CMD_LIGHT_EXEC, transition_ms)
+            }
+            LightRequest::Toggle { transition_ms } => {
+                payload.push(SWITCH_EXEC_TOGGLE);
+                (CMD_LIGHT_EXEC, transition_ms)
+            }
+            LightRequest::Get => return self.light_state(dst, id),
+        };
+
+        // Nodes treat a missing transition as an instant change
+        if transition_ms != 0 {
+            payload.extend_from_slice(&transition_ms.to_le_bytes());
+        }
+
+        let res = self.request(dst, cmd, &payload)?;
+        light_state(cmd, &res)
+    }
+
+    /// Returns the amount of covers a node has
+    /// # Arguments
+    /// * `dst` - The address of the node
+    pub fn cover_count(&mut self, dst: u16) -> Result<u32, Error> {
+        self.request_u32(dst, CMD_COVER_COUNT, &[])
+    }
+
+    /// Retrieves all attributes and the state of a cover in a single request
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the cover
+    pub fn describe_cover(&mut self, dst: u16, id: u32) -> Result<(CoverInfo, CoverState), Error> {
+        let res = self.request(dst, CMD_COVER_DESCRIBE, &id.to_le_bytes())?;
+        let desc = Descriptor::from_payload(CMD_COVER_DESCRIBE, &res)?;
+
+        let info = CoverInfo {
+            id,
+            name: desc.string(TAG_NAME)?,
+            unique_id: desc.string(TAG_UNIQUE_ID)?,
+            device_class: desc.string(TAG_DEVICE_CLASS)?,
+        };
+
+        // The state is left out if the node failed to read it, ask for the error
+        let state = match (desc.get(TAG_POSITION), desc.get(TAG_STATE)) {
+            (Some(&[position]), Some(&[motion])) => {
+                cover_state(CMD_COVER_DESCRIBE, &[position, motion])?
+            }
+            _ => self.cover_state(dst, id)?,
+        };
+
+        Ok((info, state))
+    }
+
+    /// Reads the current position of a cover and the direction it moves in
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the cover
+    pub fn cover_state(&mut self, dst: u16, id: u32) -> Result<CoverState, Error> {
+        let res = self.request(dst, CMD_COVER_STATE, &id.to_le_bytes())?;
+        cover_state(CMD_COVER_STATE, &res)
+    }
+
+    /// Executes a request on a cover
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the cover
+    /// * `req` - The request to execute
+    /// # Returns
+    /// The state of the cover after the request, it usually just started moving
+    pub fn cover_exec(
+        &mut self,
+        dst: u16,
+        id: u32,
+        req: CoverRequest,
+    ) -> Result<CoverState, Error> {
+        let mut payload = id.to_le_bytes().to_vec();
+
+        let cmd = match req {
+            CoverRequest::Open => {
+                payload.push(COVER_EXEC_OPEN);
+                CMD_COVER_EXEC
+            }
+            CoverRequest::Close => {
+                payload.push(COVER_EXEC_CLOSE);
+                CMD_COVER_EXEC
+            }
+            CoverRequest::Stop => {
+                payload.push(COVER_EXEC_STOP);
+                CMD_COVER_EXEC
+            }
+            CoverRequest::SetPosition(position) => {
+                payload.push(position);
+                CMD_COVER_POSITION
+            }
+            CoverRequest::Get => return self.cover_state(dst, id),
+        };
+
+        let res = self.request(dst, cmd, &payload)?;
+        cover_state(cmd, &res)
+    }
+
+    /// Returns the amount of selects a node has
+    /// # Arguments
+    /// * `dst` - The address of the node
+    pub fn select_count(&mut self, dst: u16) -> Result<u32, Error> {
+        self.request_u32(dst, CMD_SELECT_COUNT, &[])
+    }
+
+    /// Retrieves all attributes and the state of a select
+    ///
+    /// Options that did not fit into the describe response are requested one by one.
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the select
+    /// # Returns
+    /// The attributes and the index of the selected option
+    pub fn describe_select(&mut self, dst: u16, id: u32) -> Result<(SelectInfo, u8), Error> {
+        let res = self.request(dst, CMD_SELECT_DESCRIBE, &id.to_le_bytes())?;
+        let desc = Descriptor::from_payload(CMD_SELECT_DESCRIBE, &res)?;
+
+        let count = match desc.get(TAG_OPTION_COUNT) {
+            Some(&[count]) => count,
+            _ => {
+                return Err(Error::InvalidPayload {
+                    cmd: CMD_SELECT_DESCRIBE,
+                    reason: "missing option count",
+                })
+            }
+        };
+
+        let mut options = desc
+            .all(TAG_OPTION)
+            .take(count as usize)
+            .map(|option| String::from_utf8(option.to_vec()))
+            .collect::<Result<Vec<_>, _>>()?;
+        for index in options.len() as u8..count {
+            options.push(self.select_option(dst, id, index)?);
+        }
+
+        let info = SelectInfo {
+            id,
+            name: desc.string(TAG_NAME)?,
+            unique_id: desc.string(TAG_UNIQUE_ID)?,
+            options,
+        };
+
+        // The state is left out if the node failed to read it, ask for the error
+        let index = match desc.get(TAG_STATE) {
+            Some(&[index]) => index,
+            _ => self.select_state(dst, id)?,
+        };
+
+        Ok((info, index))
+    }
+
+    /// Reads a single option of a select
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the select
+    /// * `index` - The index of the option
+    pub fn select_option(&mut self, dst: u16, id: u32, index: u8) -> Result<String, Error> {
+        let mut payload = id.to_le_bytes().to_vec();
+        payload.push(index);
+
+        Ok(String::from_utf8(self.request(
+            dst,
+            CMD_SELECT_OPTION,
+            &payload,
+        )?)?)
+    }
+
+    /// Reads the index of the selected option of a select
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the select
+    pub fn select_state(&mut self, dst: u16, id: u32) -> Result<u8, Error> {
+        let res = self.request(dst, CMD_SELECT_STATE, &id.to_le_bytes())?;
+        select_index(CMD_SELECT_STATE, &res)
+    }
+
+    /// Selects an option of a select
+    /// # Arguments
+    /// * `dst` - The address of the node
+    /// * `id` - The index of the select
+    /// * `index` - The index of the option to select
+    /// # Returns
+    /// The index of the selected option after the request
+    pub fn set_select(&mut self, dst: u16, id: u32, index: u8) -> Result<u8, Error> {
+        let mut payload = id.to_le_bytes().to_vec();
+        payload.push(index);
+
+        let res = self.request(dst, CMD_SELECT_SET, &payload)?;
+        select_index(CMD_SELECT_SET, &res)
+    }
+}
+
+/// Returns the indices of the sensors selected by a [`CMD_SENSOR_VALUES`] request
+/// # Arguments
+/// * `first` - The index of the first sensor
+/// * `mask` - Selects the sensors from `first` on, `None` selects all
+fn selected_sensors(first: u32, mask: Option<u32>) -> impl Iterator<Item = u32> {
+    (first..SENSOR_VALUES_COMPLETE)
+        .take(if mask.is_some() {
+            u32::BITS as usize
+        } else {
+            usize::MAX
+        })
+        .filter(move |id| mask.is_none_or(|mask| mask & (1 << (id - first)) != 0))
+}
+
+/// Decodes a [`CMD_SENSOR_VALUES`] response
+/// # Arguments
+/// * `dst` - The address of the node, for error reporting
+/// * `payload` - The payload to decode
+/// * `ids` - The indices of the requested sensors, in order
+/// * `readings` - Collects the decoded values
+/// # Returns
+/// The index of the sensor to continue with
+fn sensor_values(
+    dst: u16,
+    payload: &[u8],
+    mut ids: impl Iterator<Item = u32>,
+    readings: &mut Vec<SensorReading>,
+) -> Result<u32, Error> {
+    let invalid = |reason| Error::InvalidPayload {
+        cmd: CMD_SENSOR_VALUES,
+        reason,
+    };
+
+    let (next, mut rest) = match payload {
+        [a, b, c, d, rest @ ..] => (u32::from_le_bytes([*a, *b, *c, *d]), rest),
+        _ => return Err(invalid("missing continuation")),
+    };
+
+    while !rest.is_empty() {
+        let id = ids.next().ok_or(invalid("more values than requested"))?;
+
+        let (value, tail) = split_value_entry(dst, CMD_SENSOR_VALUES, rest)?;
+        let value = value.and_then(|value| Value::from_payload(CMD_SENSOR_VALUES, value));
+
+        readings.push(SensorReading { id, value });
+        rest = tail;
+    }
+
+    Ok(next)
+}
+
+/// Builds the payload for [`CMD_SWITCH_EXEC`]
+/// # Arguments
+/// * `id` - The index of the switch
+/// * `req` - The request to execute, `SwitchRequest::Get` is sent as an invalid request
+fn switch_exec_payload(id: u32, req: SwitchRequest) -> [u8; 5] {
+    let req = match req {
+        SwitchRequest::TurnOFF => SWITCH_EXEC_TURN_OFF,
+        SwitchRequest::TurnON => SWITCH_EXEC_TURN_ON,
+        SwitchRequest::Toggle => SWITCH_EXEC_TOGGLE,
+        SwitchRequest::Get => u8::MAX,
+    };
+
+    let id = id.to_le_bytes();
+    [id[0], id[1], id[2], id[3], req]
+}
+
+/// Collects the bytes of a frame for writing them to the transport at once
+#[derive(Default)]
+struct FrameBuffer {
+    bytes: Vec<u8>,
+}
+
+impl embedded_hal::serial::Write<u8> for FrameBuffer {
+    type Error = Infallible;
+
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        self.bytes.push(word);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        Ok(())
+    }
+}
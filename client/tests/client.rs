@@ -0,0 +1,690 @@
+mod common;
+
+use common::*;
+use std::time::Duration;
+
+use ha_buddy_client::{
+    BinarySensorInfo, ButtonInfo, Client, CoverInfo, DiscoveredNode, Error, LightInfo, NodeEvent,
+    NodeInfo, NumberInfo, SelectInfo, SensorInfo, SwitchInfo, Value,
+};
+use ha_buddy_proto::{
+    command::{CMD_SENSOR_DESCRIBE, CMD_SENSOR_VALUES, CMD_SWITCH_DESCRIBE},
+    datalink::BROADCAST_ADDR,
+    device::{CAP_BINARY_SENSOR, CAP_SENSOR, CAP_SWITCH, PROTOCOL_VERSION},
+    error::ErrorCode,
+    event::{Event, EVENT_QUEUE_LEN},
+    homeassistant::{
+        cover::{CoverMotion, CoverRequest, CoverState},
+        entity::DeviceClass,
+        light::{LightRequest, LightState},
+        sensor::{Sensor, StateClass},
+        switch::SwitchRequest,
+    },
+};
+
+#[test]
+fn echo() {
+    let mut client = client();
+    assert_eq!(client.echo(NODE_ADDR, b"ping").unwrap(), b"ping");
+}
+
+#[test]
+fn sensor_count() {
+    let mut client = client();
+    assert_eq!(client.sensor_count(NODE_ADDR).unwrap(), 1);
+}
+
+#[test]
+fn sensor_info() {
+    let mut client = client();
+    assert_eq!(
+        client.sensor_info(NODE_ADDR, 0).unwrap(),
+        SensorInfo {
+            id: 0,
+            name: "Temperature".into(),
+            unique_id: "temp_0".into(),
+            native_unit_of_measurement: "°C".into(),
+            device_class: "DeviceClass.TEMPERATURE".into(),
+            state_class: "measurement".into(),
+        }
+    );
+}
+
+#[test]
+fn read_sensor() {
+    let mut client = client();
+    assert_eq!(
+        client.read_sensor(NODE_ADDR, 0).unwrap(),
+        Some(Value::Float(21.5))
+    );
+
+    client.transport_mut().temperature.set_value(None);
+    assert_eq!(client.read_sensor(NODE_ADDR, 0).unwrap(), None);
+}
+
+#[test]
+fn switches() {
+    let mut client = client();
+    assert_eq!(client.switch_count(NODE_ADDR).unwrap(), 1);
+    assert_eq!(
+        client.switch_info(NODE_ADDR, 0).unwrap(),
+        SwitchInfo {
+            id: 0,
+            name: "Relay".into(),
+            unique_id: "relay_0".into(),
+        }
+    );
+
+    assert!(!client.switch_state(NODE_ADDR, 0).unwrap());
+    assert!(client
+        .switch_exec(NODE_ADDR, 0, SwitchRequest::TurnON)
+        .unwrap());
+    assert!(client.transport_mut().relay);
+    assert!(!client
+        .switch_exec(NODE_ADDR, 0, SwitchRequest::Toggle)
+        .unwrap());
+    assert!(!client
+        .switch_exec(NODE_ADDR, 0, SwitchRequest::Get)
+        .unwrap());
+}
+
+#[test]
+fn ignores_noise_before_response() {
+    let mut client = client();
+    client.transport_mut().noise = vec![0x00, 0xaa, 0x13, 0x37];
+    assert_eq!(client.echo(NODE_ADDR, b"ping").unwrap(), b"ping");
+}
+
+#[test]
+fn retries_on_timeout() {
+    let mut client = client();
+    client.set_retries(2);
+    client.transport_mut().drop = 2;
+
+    assert_eq!(client.echo(NODE_ADDR, b"ping").unwrap(), b"ping");
+    assert_eq!(client.transport_mut().requests, 3);
+}
+
+#[test]
+fn times_out_after_retries() {
+    let mut client = client();
+    client.set_retries(1);
+    client.transport_mut().drop = 2;
+
+    match client.echo(NODE_ADDR, b"ping") {
+        Err(Error::Timeout { addr, cmd }) => {
+            assert_eq!(addr, NODE_ADDR);
+            assert_eq!(cmd, 0x0000);
+        }
+        res => panic!("Expected timeout, got {:?}", res),
+    }
+    assert_eq!(client.transport_mut().requests, 2);
+}
+
+#[test]
+fn absent_node_times_out() {
+    let mut client = client();
+    client.set_retries(0);
+
+    assert!(matches!(
+        client.echo(0x1001, &[]),
+        Err(Error::Timeout { addr: 0x1001, .. })
+    ));
+}
+
+#[test]
+fn payload_too_long() {
+    let mut client = client();
+    assert!(matches!(
+        client.echo(NODE_ADDR, &[0; 256]),
+        Err(Error::PayloadTooLong(256))
+    ));
+}
+
+#[test]
+fn decode_values() {
+    assert_eq!(Value::from_payload(0x0112, &[]).unwrap(), None);
+    assert_eq!(
+        Value::from_payload(0x0112, &[0, b'o', b'n']).unwrap(),
+        Some(Value::String("on".into()))
+    );
+    assert_eq!(
+        Value::from_payload(0x0112, &[1, 0xff, 0xff, 0xff, 0xff]).unwrap(),
+        Some(Value::Int(-1))
+    );
+    assert!(Value::from_payload(0x0112, &[1, 0]).is_err());
+    assert!(Value::from_payload(0x0112, &[0x7f]).is_err());
+
+    assert_eq!(
+        Value::from_payload(0x0112, &[3, 0xff, 0xff, 0xff, 0xff]).unwrap(),
+        Some(Value::UInt(u32::MAX))
+    );
+    let energy = [&[4], &(-1i64 << 40).to_le_bytes()[..]].concat();
+    assert_eq!(
+        Value::from_payload(0x0112, &energy).unwrap(),
+        Some(Value::Int64(-1 << 40))
+    );
+    let energy = [&[5], &u64::MAX.to_le_bytes()[..]].concat();
+    assert_eq!(
+        Value::from_payload(0x0112, &energy).unwrap(),
+        Some(Value::UInt64(u64::MAX))
+    );
+    assert_eq!(
+        Value::from_payload(0x0112, &[6, 1]).unwrap(),
+        Some(Value::Bool(true))
+    );
+    assert!(Value::from_payload(0x0112, &[6, 2]).is_err());
+    assert_eq!(
+        Value::from_payload(0x0112, &[7, 0xa7, 0xf7, 0xff, 0xff, 0xfe]).unwrap(),
+        Some(Value::Fixed {
+            value: -2137,
+            exponent: -2
+        })
+    );
+    assert!(Value::from_payload(0x0112, &[7, 0, 0, 0, 0]).is_err());
+}
+
+#[test]
+fn display_fixed_values() {
+    let fixed = |value, exponent| Value::Fixed { value, exponent }.to_string();
+
+    assert_eq!(fixed(-2137, -2), "-21.37");
+    assert_eq!(fixed(5, -3), "0.005");
+    assert_eq!(fixed(-5, -1), "-0.5");
+    assert_eq!(fixed(42, 0), "42");
+    assert_eq!(fixed(42, 2), "4200");
+    assert_eq!(fixed(0, 3), "0");
+    assert_eq!(fixed(i32::MIN, -10), "-0.2147483648");
+}
+
+#[test]
+fn node_error_is_not_retried() {
+    let mut client = client();
+    client.set_retries(2);
+
+    let err = client.read_sensor(NODE_ADDR, 1).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Node {
+            addr: NODE_ADDR,
+            cmd: 0x0112,
+            ..
+        }
+    ));
+    assert_eq!(err.node_error(), Some(ErrorCode::EntityOutOfRange));
+    assert_eq!(client.transport_mut().requests, 1);
+}
+
+#[test]
+fn node_error_unknown_command() {
+    let mut client = client();
+
+    let err = client.request(NODE_ADDR, 0x0ff0, &[]).unwrap_err();
+    assert_eq!(err.node_error(), Some(ErrorCode::UnknownCommand));
+    assert_eq!(
+        err.to_string(),
+        "Node 0x1000 failed command 0x0ff0: unknown command"
+    );
+}
+
+#[test]
+fn switch_exec_all() {
+    let mut client = client();
+    assert_eq!(
+        client
+            .switch_exec_all(NODE_ADDR, SwitchRequest::TurnON)
+            .unwrap(),
+        vec![true]
+    );
+    assert!(client.transport_mut().relay);
+}
+
+#[test]
+fn broadcast_has_no_response() {
+    let mut client = client();
+    client.transport_mut().relay = true;
+
+    client.all_switches_off().unwrap();
+    assert!(!client.transport_mut().relay);
+    assert!(client.transport_mut().rx.is_empty());
+
+    assert!(matches!(
+        client.switch_state(BROADCAST_ADDR, 0),
+        Err(Error::MulticastRequest(BROADCAST_ADDR))
+    ));
+    assert_eq!(client.transport_mut().requests, 1);
+}
+
+#[test]
+fn node_without_serial() {
+    let mut client = client();
+    assert_eq!(client.node_serial(NODE_ADDR).unwrap(), None);
+
+    let err = client.assign_address(NODE_ADDR, 0x1001, None).unwrap_err();
+    assert_eq!(err.node_error(), Some(ErrorCode::NotPermitted));
+}
+
+#[test]
+fn discover_single_node() {
+    let mut client = client();
+    client.set_timeout(Duration::from_millis(1));
+    client.transport_mut().serial = Some(*b"\x12\x34\x56\x78\x9a\xbc\xde\xf0");
+
+    assert_eq!(
+        client.discover(NODE_ADDR).unwrap(),
+        [DiscoveredNode {
+            addr: NODE_ADDR,
+            serial: *b"\x12\x34\x56\x78\x9a\xbc\xde\xf0",
+        }]
+    );
+}
+
+#[test]
+fn discover_response_followed_by_bytes() {
+    // Another node answering right after the first one is no single response
+    let mut client = client();
+    client.set_timeout(Duration::from_millis(1));
+    client.transport_mut().serial = Some(*b"\x12\x34\x56\x78\x9a\xbc\xde\xf0");
+    client.transport_mut().trailing = vec![0xaa, 0x55, 0x00];
+
+    assert!(matches!(
+        client.discover(NODE_ADDR),
+        Err(Error::SerialCollision(serial)) if serial == *b"\x12\x34\x56\x78\x9a\xbc\xde\xf0"
+    ));
+}
+
+#[test]
+fn node_info() {
+    let mut client = client();
+    let info = client.node_info(NODE_ADDR).unwrap();
+
+    assert_eq!(info.protocol_version, PROTOCOL_VERSION);
+    assert!(info.supports(CAP_SENSOR | CAP_SWITCH | CAP_BINARY_SENSOR));
+    assert_eq!(info.firmware_version, "");
+}
+
+#[test]
+fn node_info_invalid_payload() {
+    assert!(NodeInfo::from_payload(&[1, 0, 0, 0, 0, 0, 0, 0]).is_err());
+    assert!(NodeInfo::from_payload(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 1, b'a', 5, b'b']).is_err());
+
+    let info = NodeInfo::from_payload(&[1, 3, 0, 0, 0, 60, 0, 0, 0, 1, b'a', 0, 1, b'c']).unwrap();
+    assert_eq!(info.uptime, Duration::from_secs(60));
+    assert_eq!(info.firmware_version, "a");
+    assert_eq!(info.board, "");
+    assert_eq!(info.name, "c");
+    assert!(info.supports(CAP_SWITCH));
+}
+
+#[test]
+fn describe() {
+    let mut client = client();
+    let (info, value) = client.describe_sensor(NODE_ADDR, 0).unwrap();
+    assert_eq!(info, client.sensor_info(NODE_ADDR, 0).unwrap());
+    assert_eq!(value, Some(Value::Float(21.5)));
+
+    client.transport_mut().relay = true;
+    let (info, state) = client.describe_switch(NODE_ADDR, 0).unwrap();
+    assert_eq!(info, client.switch_info(NODE_ADDR, 0).unwrap());
+    assert!(state);
+
+    // A single request each
+    client.transport_mut().requests = 0;
+    client.describe_sensor(NODE_ADDR, 0).unwrap();
+    client.describe_switch(NODE_ADDR, 0).unwrap();
+    assert_eq!(client.transport_mut().requests, 2);
+}
+
+#[test]
+fn describe_older_firmware() {
+    let mut client = client();
+    client.transport_mut().unsupported = vec![CMD_SENSOR_DESCRIBE, CMD_SWITCH_DESCRIBE];
+
+    let (info, value) = client.describe_sensor(NODE_ADDR, 0).unwrap();
+    assert_eq!(info.unique_id, "temp_0");
+    assert_eq!(info.state_class, "measurement");
+    assert_eq!(value, Some(Value::Float(21.5)));
+
+    let (info, state) = client.describe_switch(NODE_ADDR, 0).unwrap();
+    assert_eq!(info.name, "Relay");
+    assert!(!state);
+}
+
+#[test]
+fn binary_sensors() {
+    let mut client = client();
+    assert_eq!(client.binary_sensor_count(NODE_ADDR).unwrap(), 1);
+
+    let info = BinarySensorInfo {
+        id: 0,
+        name: "Door".to_string(),
+        unique_id: "door_0".to_string(),
+        device_class: "BinarySensorDeviceClass.DOOR".to_string(),
+    };
+    assert_eq!(client.binary_sensor_info(NODE_ADDR, 0).unwrap(), info);
+    assert_eq!(
+        client.describe_binary_sensor(NODE_ADDR, 0).unwrap(),
+        (info, Some(false))
+    );
+
+    client.transport_mut().door.set_state(None);
+    assert_eq!(client.binary_sensor_state(NODE_ADDR, 0).unwrap(), None);
+
+    // The change is reported as an event
+    client.transport_mut().door.set_state(Some(true));
+    match &client.fetch_events(NODE_ADDR).unwrap().events[..] {
+        [NodeEvent::BinarySensor { id: 0, state }] => {
+            assert_eq!(state.as_ref().unwrap(), &Some(true))
+        }
+        events => panic!("unexpected events {:?}", events),
+    }
+
+    assert!(matches!(
+        client.binary_sensor_state(NODE_ADDR, 1),
+        Err(e) if e.node_error() == Some(ErrorCode::EntityOutOfRange)
+    ));
+}
+
+#[test]
+fn numbers() {
+    let mut client = client();
+    assert_eq!(client.number_count(NODE_ADDR).unwrap(), 1);
+
+    let (info, value) = client.describe_number(NODE_ADDR, 0).unwrap();
+    assert_eq!(
+        info,
+        NumberInfo {
+            id: 0,
+            name: "Setpoint".to_string(),
+            unique_id: "setpoint_0".to_string(),
+            native_unit_of_measurement: "°C".to_string(),
+            device_class: "DeviceClass.TEMPERATURE".to_string(),
+            min: 5.0,
+            max: 30.0,
+            step: 0.5,
+            mode: "auto".to_string(),
+        }
+    );
+    assert_eq!(value, 20.0);
+
+    assert_eq!(client.set_number(NODE_ADDR, 0, 21.5).unwrap(), 21.5);
+    assert_eq!(client.number_value(NODE_ADDR, 0).unwrap(), 21.5);
+    assert!(matches!(
+        client.set_number(NODE_ADDR, 0, 31.0),
+        Err(e) if e.node_error() == Some(ErrorCode::InvalidArgument)
+    ));
+
+    // Set by the master, no event
+    assert!(client.fetch_events(NODE_ADDR).unwrap().events.is_empty());
+}
+
+#[test]
+fn buttons() {
+    let mut client = client();
+    assert_eq!(client.button_count(NODE_ADDR).unwrap(), 1);
+
+    assert_eq!(
+        client.describe_button(NODE_ADDR, 0).unwrap(),
+        ButtonInfo {
+            id: 0,
+            name: "Restart".to_string(),
+            unique_id: "restart_0".to_string(),
+        }
+    );
+
+    client.press_button(NODE_ADDR, 0).unwrap();
+    client.multicast_press_button(BROADCAST_ADDR, 0).unwrap();
+    assert_eq!(client.transport_mut().presses, 2);
+
+    assert!(matches!(
+        client.press_button(NODE_ADDR, 1),
+        Err(e) if e.node_error() == Some(ErrorCode::EntityOutOfRange)
+    ));
+}
+
+#[test]
+fn lights() {
+    let mut client = client();
+    assert_eq!(client.light_count(NODE_ADDR).unwrap(), 1);
+
+    let off = LightState {
+        on: false,
+        brightness: 255,
+    };
+    assert_eq!(
+        client.describe_light(NODE_ADDR, 0).unwrap(),
+        (
+            LightInfo {
+                id: 0,
+                name: "Strip".to_string(),
+                unique_id: "strip_0".to_string(),
+            },
+            off
+        )
+    );
+
+    let dimmed = LightState {
+        on: true,
+        brightness: 100,
+    };
+    let req = LightRequest::TurnOn {
+        brightness: Some(100),
+        transition_ms: 1500,
+    };
+    assert_eq!(client.light_exec(NODE_ADDR, 0, req).unwrap(), dimmed);
+
+    let req = LightRequest::Toggle { transition_ms: 0 };
+    let toggled = client.light_exec(NODE_ADDR, 0, req).unwrap();
+    assert_eq!(
+        toggled,
+        LightState {
+            on: false,
+            ..dimmed
+        }
+    );
+
+    let req = LightRequest::TurnOn {
+        brightness: None,
+        transition_ms: 0,
+    };
+    assert_eq!(client.light_exec(NODE_ADDR, 0, req).unwrap(), dimmed);
+    assert_eq!(client.light_state(NODE_ADDR, 0).unwrap(), dimmed);
+    assert_eq!(client.transport_mut().strip, dimmed);
+}
+
+#[test]
+fn covers() {
+    let mut client = client();
+    assert_eq!(client.cover_count(NODE_ADDR).unwrap(), 1);
+
+    assert_eq!(
+        client.describe_cover(NODE_ADDR, 0).unwrap(),
+        (
+            CoverInfo {
+                id: 0,
+                name: "Shutter".to_string(),
+                unique_id: "shutter_0".to_string(),
+                device_class: "CoverDeviceClass.SHUTTER".to_string(),
+            },
+            CoverState::default()
+        )
+    );
+
+    let state = client.cover_exec(NODE_ADDR, 0, CoverRequest::Open).unwrap();
+    assert_eq!(state.position, 100);
+    let state = client
+        .cover_exec(NODE_ADDR, 0, CoverRequest::SetPosition(35))
+        .unwrap();
+    assert_eq!(state.position, 35);
+
+    client.transport_mut().shutter.motion = CoverMotion::Closing;
+    assert_eq!(
+        client.cover_state(NODE_ADDR, 0).unwrap(),
+        CoverState {
+            position: 35,
+            motion: CoverMotion::Closing
+        }
+    );
+
+    // Positions beyond open are rejected by the node
+    assert!(matches!(
+        client.cover_exec(NODE_ADDR, 0, CoverRequest::SetPosition(101)),
+        Err(e) if e.node_error() == Some(ErrorCode::InvalidArgument)
+    ));
+}
+
+#[test]
+fn selects() {
+    let mut client = client();
+    assert_eq!(client.select_count(NODE_ADDR).unwrap(), 1);
+
+    let mut info = SelectInfo {
+        id: 0,
+        name: "Heating".to_string(),
+        unique_id: "heating_0".to_string(),
+        options: vec!["Off".to_string(), "Eco".to_string(), "Comfort".to_string()],
+    };
+    assert_eq!(
+        client.describe_select(NODE_ADDR, 0).unwrap(),
+        (info.clone(), 0)
+    );
+
+    assert_eq!(client.set_select(NODE_ADDR, 0, 2).unwrap(), 2);
+    assert_eq!(client.select_state(NODE_ADDR, 0).unwrap(), 2);
+    assert_eq!(client.transport_mut().mode, 2);
+    assert_eq!(client.select_option(NODE_ADDR, 0, 1).unwrap(), "Eco");
+
+    // Indexes beyond the options are rejected by the node
+    assert!(matches!(
+        client.set_select(NODE_ADDR, 0, 3),
+        Err(e) if e.node_error() == Some(ErrorCode::InvalidArgument)
+    ));
+
+    // Options that do not fit into the describe response are fetched one by one
+    info.options = (0..20)
+        .map(|i| format!("Program {:02} {}", i, "-".repeat(20)))
+        .collect();
+    client.transport_mut().modes = info.options.clone();
+    let requests = client.transport_mut().requests;
+    assert_eq!(
+        client.describe_select(NODE_ADDR, 0).unwrap(),
+        (info.clone(), 2)
+    );
+    assert_eq!(client.transport_mut().requests - requests, 1 + 20 - 6);
+
+    // Changes on the node are reported as events
+    client.transport_mut().events.push(Event::Select(0));
+    match &client.fetch_events(NODE_ADDR).unwrap().events[..] {
+        [NodeEvent::Select { id: 0, index }] => assert_eq!(*index.as_ref().unwrap(), 2),
+        events => panic!("unexpected events {:?}", events),
+    }
+}
+
+/// Adds `count` counter sensors behind the temperature sensor of the mocked node
+fn add_counters(client: &mut Client<MockNode>, count: i32) {
+    client.transport_mut().counters = (0..count)
+        .map(|i| {
+            Sensor::new(
+                "Counter",
+                "counter",
+                "",
+                DeviceClass::Temperature,
+                StateClass::TotalIncreasing,
+                Some(i),
+            )
+        })
+        .collect();
+}
+
+#[test]
+fn read_all_sensors() {
+    let mut client = client();
+    client.transport_mut().temperature.set_value(None);
+    add_counters(&mut client, 60);
+
+    let readings = client.read_all_sensors(NODE_ADDR).unwrap();
+    assert_eq!(readings.len(), 61);
+    assert_eq!(readings[0].value.as_ref().unwrap(), &None);
+    for (i, reading) in readings[1..].iter().enumerate() {
+        assert_eq!(reading.id, i as u32 + 1);
+        assert_eq!(reading.value.as_ref().unwrap(), &Some(Value::Int(i as i32)));
+    }
+
+    // The values do not fit into a single frame
+    assert_eq!(client.transport_mut().requests, 2);
+}
+
+#[test]
+fn read_sensors_mask() {
+    let mut client = client();
+    add_counters(&mut client, 60);
+
+    let readings = client.read_sensors(NODE_ADDR, 10, Some(0b101)).unwrap();
+    let ids: Vec<_> = readings.iter().map(|r| r.id).collect();
+    assert_eq!(ids, [10, 12]);
+    assert_eq!(readings[1].value.as_ref().unwrap(), &Some(Value::Int(11)));
+
+    // Masks continue like all sensors
+    let readings = client.read_sensors(NODE_ADDR, 0, Some(u32::MAX)).unwrap();
+    assert_eq!(readings.len(), 32);
+    assert_eq!(readings[31].id, 31);
+}
+
+#[test]
+fn read_sensors_older_firmware() {
+    let mut client = client();
+    add_counters(&mut client, 3);
+    client.transport_mut().unsupported = vec![CMD_SENSOR_VALUES];
+
+    let readings = client.read_sensors(NODE_ADDR, 1, Some(0b1101)).unwrap();
+    let ids: Vec<_> = readings.iter().map(|r| r.id).collect();
+    assert_eq!(ids, [1, 3]);
+    assert_eq!(readings[1].value.as_ref().unwrap(), &Some(Value::Int(2)));
+}
+
+#[test]
+fn fetch_events() {
+    let mut client = client();
+    assert!(client.fetch_events(NODE_ADDR).unwrap().events.is_empty());
+
+    let node = client.transport_mut();
+    node.temperature = Sensor::new(
+        "Temperature",
+        "temp_0",
+        "°C",
+        DeviceClass::Temperature,
+        StateClass::Measurement,
+        None,
+    )
+    .with_deadband(1.0);
+    node.temperature.set_value(Some(22.0));
+    node.relay = true;
+    node.events.push(Event::Switch(0));
+
+    let events = client.fetch_events(NODE_ADDR).unwrap();
+    assert!(!events.overflowed);
+    match &events.events[..] {
+        [NodeEvent::Switch { id: 0, state }, NodeEvent::Sensor { id: 0, value }] => {
+            assert!(state.as_ref().unwrap());
+            assert_eq!(value.as_ref().unwrap(), &Some(Value::Float(22.0)));
+        }
+        events => panic!("unexpected events {:?}", events),
+    }
+
+    // Within the deadband
+    client.transport_mut().temperature.set_value(Some(22.5));
+    assert!(client.fetch_events(NODE_ADDR).unwrap().events.is_empty());
+}
+
+#[test]
+fn fetch_events_overflowed() {
+    let mut client = client();
+    for id in 0..=EVENT_QUEUE_LEN as u32 {
+        client.transport_mut().events.push(Event::Sensor(id));
+    }
+
+    // Events of sensors the node does not have are dropped
+    let events = client.fetch_events(NODE_ADDR).unwrap();
+    assert!(events.overflowed);
+    assert_eq!(events.events.len(), 1);
+}
@@ -0,0 +1,309 @@
+#![allow(dead_code)]
+
+use std::{
+    collections::VecDeque,
+    io::{self, ErrorKind, Read, Write},
+};
+
+use ha_buddy_client::Client;
+use ha_buddy_proto::{
+    command::CMD_ERROR_FLAG,
+    datalink::Serial,
+    error::ErrorCode,
+    event::EventQueue,
+    handler::{handle_request, HandlerPins},
+    homeassistant::{
+        binary_sensor::{BinarySensor, BinarySensorDeviceClass, BinarySensorRef},
+        button::{Button, ButtonRef},
+        cover::{Cover, CoverDeviceClass, CoverRef, CoverRequest, CoverState, COVER_POSITION_OPEN},
+        entity::DeviceClass,
+        light::{Light, LightRef, LightRequest, LightState},
+        number::{Number, NumberRef, NumberRequest},
+        select::{Select, SelectRef, SelectRequest},
+        sensor::{Sensor, SensorRef, StateClass},
+        switch::{Switch, SwitchRef, SwitchRequest},
+        Entities,
+    },
+    node::Node,
+    DataFrame,
+};
+
+/// The address of the mocked node
+pub const NODE_ADDR: u16 = 0x1000;
+
+/// A transport that has a single node running `handle_frame` on the other end
+pub struct MockNode {
+    /// The bytes the node has sent and not yet read by the master
+    pub rx: VecDeque<u8>,
+    /// The frame the node assembles requests in
+    frame: DataFrame,
+    /// The amount of requests the node received
+    pub requests: usize,
+    /// The amount of requests to ignore before responding
+    pub drop: usize,
+    /// Bytes to send before each response
+    pub noise: Vec<u8>,
+    /// Bytes to send after each response, like another node talking
+    pub trailing: Vec<u8>,
+    /// The serial number of this node
+    pub serial: Option<Serial>,
+    /// The temperature sensor of this node
+    pub temperature: Sensor<'static, f32>,
+    /// More sensors of this node, behind the temperature sensor
+    pub counters: Vec<Sensor<'static, i32>>,
+    /// The state of the switch of this node
+    pub relay: bool,
+    /// The door contact of this node
+    pub door: BinarySensor<'static>,
+    /// The value of the setpoint number of this node
+    pub setpoint: f32,
+    /// The amount of times the button of this node was pressed
+    pub presses: usize,
+    /// The state of the light of this node
+    pub strip: LightState,
+    /// The state of the cover of this node, it arrives at once
+    pub shutter: CoverState,
+    /// The options of the select of this node
+    pub modes: Vec<String>,
+    /// The index of the selected option of the select of this node
+    pub mode: u8,
+    /// Commands the node does not know, like a node with older firmware
+    pub unsupported: Vec<u16>,
+    /// The changes waiting to be fetched
+    pub events: EventQueue,
+}
+
+impl Default for MockNode {
+    fn default() -> Self {
+        Self {
+            rx: VecDeque::new(),
+            frame: DataFrame::new(),
+            requests: 0,
+            drop: 0,
+            noise: Vec::new(),
+            trailing: Vec::new(),
+            serial: None,
+            temperature: Sensor::new(
+                "Temperature",
+                "temp_0",
+                "°C",
+                DeviceClass::Temperature,
+                StateClass::Measurement,
+                Some(21.5),
+            ),
+            counters: Vec::new(),
+            relay: false,
+            door: BinarySensor::new("Door", "door_0", BinarySensorDeviceClass::Door, Some(false)),
+            setpoint: 20.0,
+            presses: 0,
+            strip: LightState {
+                on: false,
+                brightness: 255,
+            },
+            shutter: CoverState::default(),
+            modes: ["Off", "Eco", "Comfort"].map(String::from).to_vec(),
+            mode: 0,
+            unsupported: Vec::new(),
+            events: EventQueue::new(),
+        }
+    }
+}
+
+impl MockNode {
+    /// Handles a completed frame like the firmware main loop does
+    fn handle(&mut self) {
+        if !self.frame.check_crc() || Node::new(NODE_ADDR).delivery(self.frame.dst).is_none() {
+            return;
+        }
+
+        self.requests += 1;
+        if self.drop > 0 {
+            self.drop -= 1;
+            return;
+        }
+
+        if self.unsupported.contains(&self.frame.cmd) {
+            self.frame.dst = self.frame.src;
+            self.frame.src = NODE_ADDR;
+            self.frame.cmd = self.frame.cmd.wrapping_add(1) | CMD_ERROR_FLAG;
+            self.frame.payload_len = 1;
+            self.frame.payload[0] = ErrorCode::UnknownCommand as u8;
+            self.respond();
+            return;
+        }
+
+        let mut sensors: Vec<&dyn SensorRef> = vec![&self.temperature];
+        sensors.extend(self.counters.iter().map(|c| c as &dyn SensorRef));
+
+        let relay = &mut self.relay;
+        let mut switch = Switch::new("Relay", "relay_0", |req| {
+            match req {
+                SwitchRequest::TurnON => *relay = true,
+                SwitchRequest::TurnOFF => *relay = false,
+                SwitchRequest::Toggle => *relay = !*relay,
+                SwitchRequest::Get => {}
+            }
+            *relay
+        });
+        let mut switches: [&mut dyn SwitchRef; 1] = [&mut switch];
+        let binary_sensors: [&dyn BinarySensorRef; 1] = [&self.door];
+
+        let setpoint = &mut self.setpoint;
+        let mut number = Number::new(
+            "Setpoint",
+            "setpoint_0",
+            "°C",
+            DeviceClass::Temperature,
+            5.0..=30.0,
+            0.5,
+            |req| {
+                if let NumberRequest::Set(value) = req {
+                    *setpoint = value;
+                }
+                *setpoint
+            },
+        );
+        let mut numbers: [&mut dyn NumberRef; 1] = [&mut number];
+
+        let presses = &mut self.presses;
+        let mut button = Button::new("Restart", "restart_0", || {
+            *presses += 1;
+            Ok(())
+        });
+        let mut buttons: [&mut dyn ButtonRef; 1] = [&mut button];
+
+        let strip = &mut self.strip;
+        let mut light = Light::new("Strip", "strip_0", |req| {
+            match req {
+                LightRequest::TurnOn { brightness, .. } => {
+                    strip.on = true;
+                    strip.brightness = brightness.unwrap_or(strip.brightness);
+                }
+                LightRequest::TurnOff { .. } => strip.on = false,
+                LightRequest::Toggle { .. } => strip.on = !strip.on,
+                LightRequest::Get => {}
+            }
+            *strip
+        });
+        let mut lights: [&mut dyn LightRef; 1] = [&mut light];
+
+        let shutter = &mut self.shutter;
+        let mut cover = Cover::new("Shutter", "shutter_0", CoverDeviceClass::Shutter, |req| {
+            match req {
+                CoverRequest::Open => shutter.position = COVER_POSITION_OPEN,
+                CoverRequest::Close => shutter.position = 0,
+                CoverRequest::SetPosition(position) => shutter.position = position,
+                CoverRequest::Stop | CoverRequest::Get => {}
+            }
+            *shutter
+        });
+        let mut covers: [&mut dyn CoverRef; 1] = [&mut cover];
+
+        let options: Vec<&str> = self.modes.iter().map(String::as_str).collect();
+        let mode = &mut self.mode;
+        let mut select = Select::new("Heating", "heating_0", options, |req| {
+            if let SelectRequest::Set(index) = req {
+                *mode = index;
+            }
+            *mode
+        });
+        let mut selects: [&mut dyn SelectRef; 1] = [&mut select];
+
+        let mut node = Node {
+            serial: self.serial,
+            events: std::mem::take(&mut self.events),
+            ..Node::new(NODE_ADDR)
+        };
+        let respond = handle_request(
+            &mut self.frame,
+            &mut node,
+            &mut HandlerPins {},
+            &mut Entities {
+                sensors: &sensors,
+                switches: &mut switches,
+                binary_sensors: &binary_sensors,
+                numbers: &mut numbers,
+                buttons: &mut buttons,
+                lights: &mut lights,
+                covers: &mut covers,
+                selects: &mut selects,
+            },
+        );
+        self.events = node.events;
+
+        if respond {
+            self.respond();
+        }
+    }
+
+    /// Sends the response in `frame`
+    fn respond(&mut self) {
+        let mut writer = VecWriter::default();
+        self.frame.send(&mut writer).unwrap();
+
+        self.rx.extend(self.noise.iter());
+        self.rx.extend(writer.bytes);
+        self.rx.extend(self.trailing.iter());
+    }
+}
+
+impl Read for MockNode {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.rx.is_empty() {
+            return Err(ErrorKind::TimedOut.into());
+        }
+
+        let mut len = 0;
+        while len < buf.len() {
+            match self.rx.pop_front() {
+                Some(byte) => buf[len] = byte,
+                None => break,
+            }
+            len += 1;
+        }
+
+        Ok(len)
+    }
+}
+
+impl Write for MockNode {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for byte in buf {
+            if self.frame.handle_byte(*byte) {
+                self.handle();
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A serial writer that collects all written bytes
+#[derive(Default)]
+pub struct VecWriter {
+    pub bytes: Vec<u8>,
+}
+
+impl embedded_hal::serial::Write<u8> for VecWriter {
+    type Error = core::convert::Infallible;
+
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        self.bytes.push(word);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Creates a client talking to a default `MockNode`
+pub fn client() -> Client<MockNode> {
+    let mut client = Client::new(MockNode::default());
+    client.set_timeout(std::time::Duration::from_millis(20));
+    client
+}
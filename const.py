@@ -1,3 +1,8 @@
 """Constants for the HomeAssistant Buddy integration."""
 
+from datetime import timedelta
+
 DOMAIN = "ha_buddy"
+
+# How often the nodes are asked for changes of their entities
+EVENT_POLL_INTERVAL = timedelta(seconds=1)
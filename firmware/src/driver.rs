@@ -0,0 +1,4 @@
+mod common;
+pub use common::InterruptFree;
+
+pub use ha_buddy_proto::driver::{bme280, dht, ds18b20, i2c, onewire};
@@ -0,0 +1,34 @@
+use embedded_hal::{
+    blocking::delay::DelayUs,
+    digital::v2::{InputPin, OutputPin},
+};
+
+use super::onewire::{OneWire, OneWirePin};
+
+/// A 1-Wire bus with the timing critical parts generated with interrupts disabled
+///
+/// A slot is timed by busy waits, an interrupt within it shifts the sampling of the
+/// bus. Interrupts are disabled for the `70 µs` of each slot and, of the reset, only
+/// from the release of the bus to the sampling of the presence pulse.
+pub struct InterruptFree<P, D>(pub OneWirePin<P, D>);
+
+impl<P, D, E> OneWire for InterruptFree<P, D>
+where
+    P: InputPin<Error = E> + OutputPin<Error = E>,
+    D: DelayUs<u16>,
+{
+    type Error = E;
+
+    fn reset(&mut self) -> Result<bool, E> {
+        self.0
+            .reset_with(|critical| avr_device::interrupt::free(|_| critical()))
+    }
+
+    fn write_bit(&mut self, bit: bool) -> Result<(), E> {
+        avr_device::interrupt::free(|_| self.0.write_bit(bit))
+    }
+
+    fn read_bit(&mut self) -> Result<bool, E> {
+        avr_device::interrupt::free(|_| self.0.read_bit())
+    }
+}
@@ -0,0 +1,7 @@
+pub use ha_buddy_proto::homeassistant::{entity, number, select, sensor, Entities};
+
+pub mod binary_sensor;
+pub mod button;
+pub mod cover;
+pub mod light;
+pub mod switch;
@@ -0,0 +1,94 @@
+use core::cell::Cell;
+
+use arduino_hal::port::{
+    mode::{AnyInput, Input, Io},
+    Pin, PinOps,
+};
+
+use ha_buddy_proto::error::ErrorCode;
+pub use ha_buddy_proto::homeassistant::binary_sensor::*;
+
+use super::entity::{DeviceClass, Entity};
+
+/// A binary sensor that reads an input pin directly, e.g. a door contact or a PIR detector
+pub struct PinBinarySensor<'a, PIN> {
+    /// The friendly name for the entity
+    pub name: &'a str,
+    /// The `unique_id` for this entity
+    pub unique_id: &'a str,
+    /// The `device_class` for this entity
+    pub device_class: BinarySensorDeviceClass,
+    /// If the pin state should be inverted, reporting a low pin as on
+    pub invert: bool,
+    /// The pin to read
+    pin: Pin<Input<AnyInput>, PIN>,
+    /// The state last reported as an event, `None` before the first poll
+    reported: Cell<Option<bool>>,
+}
+
+impl<'a, PIN: PinOps> PinBinarySensor<'a, PIN> {
+    /// Creates a new PinBinarySensor
+    /// # Arguments
+    /// * `name` - The friendly name for the binary sensor
+    /// * `unique_id` - The unique id for the binary sensor
+    /// * `device_class` - What the binary sensor detects
+    /// * `pin` - The pin to read, configured as an input
+    /// * `pull_up` - Enables the internal pull-up, e.g. for contacts switching to ground
+    /// * `invert` - Inverts the pin, reporting a low pin as on
+    pub fn new<MODE: Io>(
+        name: &'a str,
+        unique_id: &'a str,
+        device_class: BinarySensorDeviceClass,
+        pin: Pin<MODE, PIN>,
+        pull_up: bool,
+        invert: bool,
+    ) -> Self {
+        let pin = if pull_up {
+            pin.into_pull_up_input().forget_imode()
+        } else {
+            pin.into_floating_input().forget_imode()
+        };
+
+        Self {
+            name,
+            unique_id,
+            device_class,
+            invert,
+            pin,
+            reported: Cell::new(None),
+        }
+    }
+
+    /// Reads the state of the pin, honoring `invert`
+    fn state(&self) -> bool {
+        self.pin.is_high() != self.invert
+    }
+}
+
+impl<'a, PIN: PinOps> Entity<'a> for PinBinarySensor<'a, PIN> {
+    fn get_unique_id(&self) -> &'a str {
+        self.unique_id
+    }
+
+    fn get_name(&self) -> &'a str {
+        self.name
+    }
+
+    fn get_device_class(&self) -> DeviceClass {
+        DeviceClass::BinarySensor(self.device_class)
+    }
+}
+
+impl<'a, PIN: PinOps> BinarySensorRef<'a> for PinBinarySensor<'a, PIN> {
+    fn get_state(&self) -> Result<Option<bool>, ErrorCode> {
+        Ok(Some(self.state()))
+    }
+
+    fn take_changed(&self) -> bool {
+        let state = self.state();
+        let changed = matches!(self.reported.get(), Some(reported) if reported != state);
+
+        self.reported.set(Some(state));
+        changed
+    }
+}
@@ -0,0 +1,102 @@
+use arduino_hal::port::{mode::Output, Pin, PinOps};
+
+use ha_buddy_proto::error::ErrorCode;
+pub use ha_buddy_proto::homeassistant::button::*;
+
+use super::entity::{DeviceClass, Entity};
+
+/// A button that pulses an output pin, e.g. a relay of a garage door opener
+///
+/// The pulse is started by the press and ended by a later tick, the receive loop
+/// keeps running in between.
+pub struct PulsePinButton<'a, PIN> {
+    /// The friendly name for the entity
+    pub name: &'a str,
+    /// The `unique_id` for this entity
+    pub unique_id: &'a str,
+    /// If the pin state should be negated, pulsing the pin low
+    pub negate: bool,
+    /// The duration of a pulse in milliseconds
+    pub duration_ms: u16,
+    /// The pin to operate on
+    pin: Pin<Output, PIN>,
+    /// The milliseconds the running pulse lasted, `None` if no pulse is running
+    elapsed_ms: Option<u16>,
+}
+
+impl<'a, PIN: PinOps> PulsePinButton<'a, PIN> {
+    /// Creates a new PulsePinButton, the pin starts inactive
+    /// # Arguments
+    /// * `name` - The friendly name for the button
+    /// * `unique_id` - The unique id for the button
+    /// * `pin` - The pin to operate on
+    /// * `duration_ms` - The duration of a pulse, lasting up to one tick longer
+    /// * `negate` - Negates the pin, pulsing it low
+    pub fn new(
+        name: &'a str,
+        unique_id: &'a str,
+        pin: Pin<Output, PIN>,
+        duration_ms: u16,
+        negate: bool,
+    ) -> Self {
+        let mut button = Self {
+            name,
+            unique_id,
+            negate,
+            duration_ms,
+            pin,
+            elapsed_ms: None,
+        };
+
+        button.set_active(false);
+        button
+    }
+
+    /// Drives the pin, honoring `negate`
+    fn set_active(&mut self, active: bool) {
+        if active != self.negate {
+            self.pin.set_high();
+        } else {
+            self.pin.set_low();
+        }
+    }
+}
+
+impl<'a, PIN: PinOps> Entity<'a> for PulsePinButton<'a, PIN> {
+    fn get_unique_id(&self) -> &'a str {
+        self.unique_id
+    }
+
+    fn get_name(&self) -> &'a str {
+        self.name
+    }
+
+    fn get_device_class(&self) -> DeviceClass {
+        DeviceClass::Button
+    }
+}
+
+impl<'a, PIN: PinOps> ButtonRef<'a> for PulsePinButton<'a, PIN> {
+    fn press(&mut self) -> Result<(), ErrorCode> {
+        if self.elapsed_ms.is_some() {
+            return Err(ErrorCode::EntityBusy);
+        }
+
+        self.set_active(true);
+        self.elapsed_ms = Some(0);
+        Ok(())
+    }
+
+    fn tick(&mut self, elapsed_ms: u16) {
+        // The press happened somewhere between two ticks, the first tick is not
+        // counted to not cut the pulse short
+        match self.elapsed_ms {
+            Some(elapsed) if elapsed >= self.duration_ms => {
+                self.set_active(false);
+                self.elapsed_ms = None;
+            }
+            Some(elapsed) => self.elapsed_ms = Some(elapsed.saturating_add(elapsed_ms)),
+            None => {}
+        }
+    }
+}
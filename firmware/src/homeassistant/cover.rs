@@ -0,0 +1,136 @@
+use arduino_hal::port::{mode::Output, Pin, PinOps};
+
+use ha_buddy_proto::error::ErrorCode;
+pub use ha_buddy_proto::homeassistant::cover::*;
+
+use super::entity::{DeviceClass, Entity};
+
+/// A cover moved by an opening and a closing relay, e.g. a roller shutter motor
+///
+/// The position is tracked by the time the relays are energised, see [`Travel`]. The
+/// relays are never energised together: the released relay is switched before the
+/// energised one and both stay released for a while before the motor reverses. Wire
+/// the relays with a hardware interlock as well, the firmware may be reset mid-switch.
+pub struct RelayCover<'a, OPEN, CLOSE> {
+    /// The friendly name for the entity
+    pub name: &'a str,
+    /// The `unique_id` for this entity
+    pub unique_id: &'a str,
+    /// The `device_class` for this entity
+    pub device_class: CoverDeviceClass,
+    /// If the pin states should be negated, energising the relays with a low pin
+    pub negate: bool,
+    /// The pin of the relay moving the cover towards open
+    open_pin: Pin<Output, OPEN>,
+    /// The pin of the relay moving the cover towards closed
+    close_pin: Pin<Output, CLOSE>,
+    /// The tracked position and the relay to energise
+    travel: Travel,
+    /// The state last reported as an event, `None` before the first poll
+    reported: Option<CoverState>,
+}
+
+impl<'a, OPEN: PinOps, CLOSE: PinOps> RelayCover<'a, OPEN, CLOSE> {
+    /// Creates a new RelayCover, both relays start released
+    /// # Arguments
+    /// * `name` - The friendly name for the cover
+    /// * `unique_id` - The unique id for the cover
+    /// * `device_class` - What kind of cover it is
+    /// * `open_pin` - The pin of the relay moving the cover towards open
+    /// * `close_pin` - The pin of the relay moving the cover towards closed
+    /// * `travel_ms` - The milliseconds the cover takes from closed to open
+    /// * `negate` - Negates the pins, energising the relays with a low pin
+    pub fn new(
+        name: &'a str,
+        unique_id: &'a str,
+        device_class: CoverDeviceClass,
+        open_pin: Pin<Output, OPEN>,
+        close_pin: Pin<Output, CLOSE>,
+        travel_ms: u32,
+        negate: bool,
+    ) -> Self {
+        let mut cover = Self {
+            name,
+            unique_id,
+            device_class,
+            negate,
+            open_pin,
+            close_pin,
+            // The position is unknown after a reset, the first move to an end corrects it
+            travel: Travel::new(travel_ms, 0),
+            reported: None,
+        };
+
+        cover.update_relays();
+        cover
+    }
+
+    /// Drives the pins to the relay the travel wants energised, releasing before energising
+    fn update_relays(&mut self) {
+        let relay = self.travel.relay();
+
+        if relay != CoverMotion::Opening {
+            set_pin(&mut self.open_pin, false, self.negate);
+        }
+        if relay != CoverMotion::Closing {
+            set_pin(&mut self.close_pin, false, self.negate);
+        }
+
+        match relay {
+            CoverMotion::Opening => set_pin(&mut self.open_pin, true, self.negate),
+            CoverMotion::Closing => set_pin(&mut self.close_pin, true, self.negate),
+            CoverMotion::Stopped => {}
+        }
+    }
+}
+
+/// Energises or releases the relay of a pin, honoring `negate`
+fn set_pin<PIN: PinOps>(pin: &mut Pin<Output, PIN>, energised: bool, negate: bool) {
+    if energised != negate {
+        pin.set_high();
+    } else {
+        pin.set_low();
+    }
+}
+
+impl<'a, OPEN: PinOps, CLOSE: PinOps> Entity<'a> for RelayCover<'a, OPEN, CLOSE> {
+    fn get_unique_id(&self) -> &'a str {
+        self.unique_id
+    }
+
+    fn get_name(&self) -> &'a str {
+        self.name
+    }
+
+    fn get_device_class(&self) -> DeviceClass {
+        DeviceClass::Cover(self.device_class)
+    }
+}
+
+impl<'a, OPEN: PinOps, CLOSE: PinOps> CoverRef<'a> for RelayCover<'a, OPEN, CLOSE> {
+    fn exec_request(&mut self, req: CoverRequest) -> Result<CoverState, ErrorCode> {
+        match req {
+            CoverRequest::Open => self.travel.move_to(COVER_POSITION_OPEN),
+            CoverRequest::Close => self.travel.move_to(0),
+            CoverRequest::Stop => self.travel.stop(),
+            CoverRequest::SetPosition(position) => self.travel.move_to(position),
+            CoverRequest::Get => return Ok(self.travel.state()),
+        }
+
+        self.update_relays();
+        Ok(self.travel.state())
+    }
+
+    fn take_changed(&mut self) -> bool {
+        let state = self.travel.state();
+        let changed = matches!(self.reported, Some(reported) if reported != state);
+
+        self.reported = Some(state);
+        changed
+    }
+
+    fn tick(&mut self, elapsed_ms: u16) {
+        self.travel.tick(elapsed_ms);
+        self.update_relays();
+    }
+}
@@ -0,0 +1,134 @@
+use arduino_hal::{
+    port::{mode::PwmOutput, Pin},
+    simple_pwm::PwmPinOps,
+};
+
+use ha_buddy_proto::error::ErrorCode;
+pub use ha_buddy_proto::homeassistant::light::*;
+
+use super::entity::{DeviceClass, Entity};
+
+/// A light dimmed by a hardware PWM pin, e.g. a LED strip behind a MOSFET
+///
+/// Transitions fade the duty cycle with every tick of the main loop. Timer 1 drives the
+/// tick, so the light has to use a pin of another timer, e.g. `Timer4Pwm` on `D6`..`D8`.
+pub struct PwmLight<'a, TC, PIN> {
+    /// The friendly name for the entity
+    pub name: &'a str,
+    /// The `unique_id` for this entity
+    pub unique_id: &'a str,
+    /// The pin to dim
+    pin: Pin<PwmOutput<TC>, PIN>,
+    /// The state the light is at or fades to
+    state: LightState,
+    /// The running fade of the duty cycle
+    fade: Fade,
+    /// The state last reported as an event, `None` before the first poll
+    reported: Option<LightState>,
+}
+
+impl<'a, TC, PIN: PwmPinOps<TC>> PwmLight<'a, TC, PIN> {
+    /// Creates a new PwmLight, the light starts off at full brightness
+    /// # Arguments
+    /// * `name` - The friendly name for the light
+    /// * `unique_id` - The unique id for the light
+    /// * `pin` - The pin to dim, e.g. `pins.d6.into_output().into_pwm(&timer4)`
+    pub fn new(name: &'a str, unique_id: &'a str, mut pin: Pin<PwmOutput<TC>, PIN>) -> Self {
+        pin.set_duty(0);
+        pin.disable();
+
+        Self {
+            name,
+            unique_id,
+            pin,
+            state: LightState {
+                on: false,
+                brightness: u8::MAX,
+            },
+            fade: Fade::new(0),
+            reported: None,
+        }
+    }
+
+    /// Fades to the current state
+    /// # Arguments
+    /// * `transition_ms` - The duration of the fade
+    fn apply(&mut self, transition_ms: u16) {
+        let level = if self.state.on {
+            self.state.brightness
+        } else {
+            0
+        };
+
+        self.fade.start(level, transition_ms);
+        self.update_pin();
+    }
+
+    /// Sets the duty cycle to the level of the fade, the pin stays low while it is `0`
+    fn update_pin(&mut self) {
+        let level = self.fade.level();
+
+        self.pin.set_duty(level);
+        if level == 0 {
+            self.pin.disable();
+        } else {
+            self.pin.enable();
+        }
+    }
+}
+
+impl<'a, TC, PIN: PwmPinOps<TC>> Entity<'a> for PwmLight<'a, TC, PIN> {
+    fn get_unique_id(&self) -> &'a str {
+        self.unique_id
+    }
+
+    fn get_name(&self) -> &'a str {
+        self.name
+    }
+
+    fn get_device_class(&self) -> DeviceClass {
+        DeviceClass::Light
+    }
+}
+
+impl<'a, TC, PIN: PwmPinOps<TC>> LightRef<'a> for PwmLight<'a, TC, PIN> {
+    fn exec_request(&mut self, req: LightRequest) -> Result<LightState, ErrorCode> {
+        match req {
+            LightRequest::TurnOn {
+                brightness,
+                transition_ms,
+            } => {
+                self.state.on = true;
+                if let Some(brightness) = brightness {
+                    self.state.brightness = brightness;
+                }
+                self.apply(transition_ms);
+            }
+            LightRequest::TurnOff { transition_ms } => {
+                self.state.on = false;
+                self.apply(transition_ms);
+            }
+            LightRequest::Toggle { transition_ms } => {
+                self.state.on = !self.state.on;
+                self.apply(transition_ms);
+            }
+            LightRequest::Get => {}
+        }
+
+        Ok(self.state)
+    }
+
+    fn take_changed(&mut self) -> bool {
+        let changed = matches!(self.reported, Some(reported) if reported != self.state);
+
+        self.reported = Some(self.state);
+        changed
+    }
+
+    fn tick(&mut self, elapsed_ms: u16) {
+        if self.fade.is_running() {
+            self.fade.tick(elapsed_ms);
+            self.update_pin();
+        }
+    }
+}
@@ -1,31 +1,9 @@
 use arduino_hal::port::{mode::Output, Pin, PinOps};
 
-mod switch_ref;
-pub use switch_ref::*;
+use ha_buddy_proto::error::ErrorCode;
+pub use ha_buddy_proto::homeassistant::switch::*;
 
-/// Commands a switch can execute
-pub enum SwitchRequest {
-    /// Turns the switch on
-    TurnON,
-    /// Turns the switch off
-    TurnOFF,
-    /// Toggles the switch state
-    Toggle,
-    /// Returns the current switch state
-    Get,
-}
-
-/// A HomeAssistant Switch
-///
-/// https://developers.home-assistant.io/docs/core/entity/switch for more information
-pub struct Switch<'a, F: FnMut(SwitchRequest) -> bool> {
-    /// The friendly name for the entity
-    pub name: &'a str,
-    /// The `unique_id` for this entity
-    pub unique_id: &'a str,
-    /// Update the state of the switch
-    pub callback: F,
-}
+use super::entity::{DeviceClass, Entity};
 
 /// A switch that uses a pin directly
 pub struct PinSwitch<'a, PIN> {
@@ -37,21 +15,8 @@ pub struct PinSwitch<'a, PIN> {
     pub negate: bool,
     /// The pin to operate on
     pin: Pin<Output, PIN>,
-}
-
-impl<'a, F: FnMut(SwitchRequest) -> bool> Switch<'a, F> {
-    /// Create a new switch
-    /// # Arguments
-    /// * `name` - The friendly name for the switch
-    /// * `unique_id` - The unique id for the switch
-    /// * `callback` - The callback to use for incoming SwitchRequests
-    pub fn new(name: &'a str, unique_id: &'a str, callback: F) -> Self {
-        Self {
-            name,
-            unique_id,
-            callback,
-        }
-    }
+    /// The state last reported as an event, `None` before the first poll
+    reported: Option<bool>,
 }
 
 impl<'a, PIN: PinOps> PinSwitch<'a, PIN> {
@@ -67,6 +32,7 @@ impl<'a, PIN: PinOps> PinSwitch<'a, PIN> {
             unique_id,
             negate,
             pin,
+            reported: None,
         }
     }
 
@@ -103,3 +69,31 @@ impl<'a, PIN: PinOps> PinSwitch<'a, PIN> {
         }
     }
 }
+
+impl<'a, PIN: PinOps> Entity<'a> for PinSwitch<'a, PIN> {
+    fn get_unique_id(&self) -> &'a str {
+        self.unique_id
+    }
+
+    fn get_name(&self) -> &'a str {
+        self.name
+    }
+
+    fn get_device_class(&self) -> DeviceClass {
+        DeviceClass::Switch
+    }
+}
+
+impl<'a, PIN: PinOps> SwitchRef<'a> for PinSwitch<'a, PIN> {
+    fn exec_request(&mut self, req: SwitchRequest) -> Result<bool, ErrorCode> {
+        Ok(self.callback(req))
+    }
+
+    fn take_changed(&mut self) -> bool {
+        let state = self.callback(SwitchRequest::Get);
+        let changed = matches!(self.reported, Some(reported) if reported != state);
+
+        self.reported = Some(state);
+        changed
+    }
+}
@@ -0,0 +1,372 @@
+#![no_std]
+#![no_main]
+#![feature(exclusive_range_pattern)]
+#![feature(abi_avr_interrupt)]
+
+mod driver;
+mod homeassistant;
+mod int;
+mod panic;
+mod storage;
+
+use arduino_hal::{
+    delay_ms,
+    hal::{
+        port::Dynamic,
+        usart::{BaudrateArduinoExt, Event},
+    },
+    port::{mode::Output, Pin},
+};
+
+use driver::{
+    bme280::{self, Bme280, Bme280Sensors},
+    dht::{Dht, DhtSensors, Model},
+    ds18b20::{self, Config, Measurement, Probe, Resolution},
+    i2c::{self, I2cDriver},
+    onewire::OneWirePin,
+    InterruptFree,
+};
+use ha_buddy_proto::{
+    device::DeviceInfo,
+    event::EventQueue,
+    handler::{handle_request, HandlerPins},
+    node::Node,
+    DataFrame,
+};
+use homeassistant::{
+    binary_sensor::{BinarySensor, BinarySensorRef},
+    button::ButtonRef,
+    cover::CoverRef,
+    light::LightRef,
+    number::NumberRef,
+    select::SelectRef,
+    sensor::{Sensor, SensorRef},
+    switch::SwitchRef,
+    Entities,
+};
+use int::*;
+use storage::Storage;
+
+const BAUDRATE: u32 = 57600;
+/// The multicast groups this node is a member of
+const MY_GROUPS: [u16; 0] = [];
+/// The board reported in the device information
+const BOARD: &str = "arduino-mega2560";
+/// The name reported in the device information
+const NODE_NAME: &str = "HA-Buddy";
+/// How long the node stays in commissioning mode after the button has been released
+const COMMISSIONING_QUARTER_SECONDS: u32 = 4 * 30;
+/// The milliseconds between two timer interrupts
+const TICK_MS: u16 = 10;
+/// The timer interrupts per quarter second
+const TICKS_PER_QUARTER_SECOND: u8 = 25;
+/// The kind of the DHT sensor on `D6`
+const DHT_MODEL: Model = Model::Dht22;
+/// How long a due DHT reading waits for the bus to be idle, a frame that was cut
+/// short leaves it busy until the next one starts
+const DHT_IDLE_WAIT_QUARTER_SECONDS: u32 = 4;
+/// The most DS18B20 probes on the 1-Wire bus
+const MAX_PROBES: usize = 4;
+/// How often the search for DS18B20 probes is tried when it fails
+const PROBE_SEARCH_ATTEMPTS: u8 = 3;
+/// The most sensor entities: the DHT, the BME280 and the DS18B20 probes
+const MAX_SENSORS: usize = 2 + 3 + MAX_PROBES;
+/// The clock of the I2C bus in Hz
+const I2C_SPEED: u32 = 50_000;
+/// The friendly name of the DS18B20 sensors, they are told apart by their ROM codes
+const PROBE_NAME: &str = "Temperature";
+/// The friendly name of the DS18B20 alarm binary sensors
+const PROBE_ALARM_NAME: &str = "Temperature Alarm";
+/// The settings of the DS18B20 probes, the alarm triggers outside of 5 - 30 °C
+const PROBE_CONFIG: Config = Config {
+    resolution: Resolution::Bits12,
+    alarm_high: 30,
+    alarm_low: 5,
+};
+
+/// A static reference to the current frame, to not store it on the stack
+static mut FRAME: DataFrame = DataFrame::new();
+
+static mut QUARTER_SECONDS_RUNNING: u32 = 0;
+/// The timer interrupts so far, wrapping, a single byte is read atomically
+static mut TICKS: u8 = 0;
+/// The timer interrupts since the last quarter second
+static mut QUARTER_SECOND_TICKS: u8 = 0;
+
+#[avr_device::interrupt(atmega328p)]
+fn TIMER1_COMPA() {
+    unsafe {
+        TICKS = TICKS.wrapping_add(1);
+
+        QUARTER_SECOND_TICKS += 1;
+        if QUARTER_SECOND_TICKS == TICKS_PER_QUARTER_SECOND {
+            QUARTER_SECOND_TICKS = 0;
+            QUARTER_SECONDS_RUNNING += 1;
+        }
+    }
+}
+
+#[arduino_hal::entry]
+fn main() -> ! {
+    let dp = arduino_hal::Peripherals::take().unwrap();
+    let pins = arduino_hal::pins!(dp);
+
+    // The DS18B20 probes on the 1-Wire bus, it needs a 4.7 kΩ pull-up
+    let mut one_wire = InterruptFree(
+        OneWirePin::new(pins.d5.into_opendrain_high(), arduino_hal::Delay::new()).unwrap(),
+    );
+    let mut probes = [Probe::default(); MAX_PROBES];
+    let mut probe_count = 0;
+    for _ in 0..PROBE_SEARCH_ATTEMPTS {
+        // A failed search keeps the probes found before the error, keep the
+        // attempt that found the most
+        let mut found = [Probe::default(); MAX_PROBES];
+        let (count, error) = ds18b20::find_probes(&mut one_wire, &mut found);
+        if count >= probe_count {
+            probes = found;
+            probe_count = count;
+        }
+        if error.is_none() {
+            break;
+        }
+    }
+    for probe in &probes[0..probe_count] {
+        // Probes that can't be configured keep their settings
+        let _ = probe.ensure_config(&mut one_wire, &PROBE_CONFIG);
+    }
+    let temperatures: [Sensor<Option<f32>>; MAX_PROBES] =
+        core::array::from_fn(|i| probes[i].sensor(PROBE_NAME));
+    let alarms: [BinarySensor; MAX_PROBES] =
+        core::array::from_fn(|i| probes[i].alarm_sensor(PROBE_ALARM_NAME));
+
+    // The humidity and temperature of the DHT sensor, it needs a pull-up as well
+    let mut dht = Dht::new(
+        pins.d6.into_opendrain_high(),
+        arduino_hal::Delay::new(),
+        DHT_MODEL,
+    )
+    .unwrap();
+    let climate = DhtSensors::new(("Humidity", "humidity"), ("Temperature", "temperature"));
+
+    // The BME280 on the I2C bus, a BMP280 reports no humidity. The chip is set up on
+    // the first measurement, its sensors report errors until it answers
+    let mut i2c = arduino_hal::I2c::new(
+        dp.TWI,
+        pins.d20.into_pull_up_input(),
+        pins.d21.into_pull_up_input(),
+        I2C_SPEED,
+    );
+    let weather = Bme280Sensors::new(
+        ("Temperature", "bme280-temperature"),
+        ("Pressure", "bme280-pressure"),
+        ("Humidity", "bme280-humidity"),
+    );
+    let mut weather_driver = Bme280::new(bme280::ADDRESS_PRIMARY, &weather);
+
+    let mut sensors: [&dyn SensorRef; MAX_SENSORS] = [&climate.humidity; MAX_SENSORS];
+    let mut sensor_count = 0;
+    let found = [
+        &climate.humidity as &dyn SensorRef,
+        &climate.temperature,
+        &weather.temperature,
+        &weather.pressure,
+        &weather.humidity,
+    ]
+    .into_iter()
+    .chain(
+        temperatures[0..probe_count]
+            .iter()
+            .map(|t| t as &dyn SensorRef),
+    );
+    for sensor in found {
+        sensors[sensor_count] = sensor;
+        sensor_count += 1;
+    }
+    let mut measurement = Measurement::new(&probes[0..probe_count], &temperatures[0..probe_count])
+        .with_alarms(&alarms[0..probe_count]);
+    let mut i2c_drivers: [&mut dyn I2cDriver<_, _>; 1] = [&mut weather_driver];
+
+    let mut switches: [&mut dyn SwitchRef; 0] = [];
+    let binary_sensors: [&dyn BinarySensorRef; MAX_PROBES] =
+        core::array::from_fn(|i| &alarms[i] as &dyn BinarySensorRef);
+    let mut numbers: [&mut dyn NumberRef; 0] = [];
+    let mut buttons: [&mut dyn ButtonRef; 0] = [];
+    let mut lights: [&mut dyn LightRef; 0] = [];
+    let mut covers: [&mut dyn CoverRef; 0] = [];
+    let mut selects: [&mut dyn SelectRef; 0] = [];
+    let mut entities = Entities {
+        sensors: &sensors[0..sensor_count],
+        switches: &mut switches,
+        binary_sensors: &binary_sensors[0..probe_count],
+        numbers: &mut numbers,
+        buttons: &mut buttons,
+        lights: &mut lights,
+        covers: &mut covers,
+        selects: &mut selects,
+    };
+
+    let mut serial = arduino_hal::Usart::new(
+        dp.USART2,
+        pins.d17,
+        pins.d16.into_output(),
+        BAUDRATE.into_baudrate(),
+    );
+    serial.listen(Event::RxComplete);
+    serial.flush();
+
+    let mut handler_pins = HandlerPins {};
+
+    let mut storage = Storage::new(arduino_hal::Eeprom::new(dp.EEPROM));
+    let mut node = Node {
+        addr: storage.addr(),
+        groups: &MY_GROUPS,
+        serial: storage.serial(),
+        commissioning: false,
+        info: DeviceInfo {
+            firmware_version: env!("CARGO_PKG_VERSION"),
+            board: BOARD,
+            name: NODE_NAME,
+            uptime: 0,
+        },
+        events: EventQueue::new(),
+    };
+
+    // Pressing the button lets the node accept a new address without its serial number
+    let p_commission = pins.d4.into_pull_up_input();
+    let mut commissioning_start: u32 = 0;
+
+    let mut led_status = pins.d13.into_output().downgrade();
+    let mut p_de = pins.d2.into_output().downgrade();
+    let mut p_re = pins.d3.into_output().downgrade();
+
+    p_re.set_low();
+    p_de.set_low();
+
+    // Timer Configuration:
+    // - WGM = 4: CTC mode (Clear Timer on Compare Match)
+    // - Prescaler 256
+    // - OCR1A = 624
+    //
+    // => F = 16 MHz / (256 * (1 + 624)) = 100 Hz
+    //
+    let tmr1 = dp.TC1;
+    tmr1.tccr1a.write(|w| w.wgm1().bits(0b00));
+    tmr1.tccr1b
+        .write(|w| w.cs1().prescale_256().wgm1().bits(0b01));
+    tmr1.ocr1a.write(|w| w.bits(624));
+
+    // Enable the timer interrupt
+    tmr1.timsk1.write(|w| w.ocie1a().set_bit());
+
+    // Hold the last time the timer interrupt triggered
+    let mut last_time: u32 = 0;
+    // Hold the last timer interrupt the entities were ticked for
+    let mut last_tick: u8 = 0;
+    // Hold when the DHT reading became due, it waits for the bus to be idle
+    let mut dht_due: Option<u32> = None;
+
+    // Enable interrupts
+    unsafe {
+        avr_device::interrupt::enable();
+    }
+
+    loop {
+        'recv_loop: loop {
+            avr_device::asm::sleep();
+
+            if unsafe { QUARTER_SECONDS_RUNNING } - last_time >= 4 {
+                last_time += 4;
+
+                // This will fire every second, the probes are read on later ticks
+                let _ = measurement.start(&mut one_wire);
+                // Devices that failed are left without a value and set up again
+                let _ = i2c::measure_all(&mut i2c, &mut i2c_drivers);
+                if last_time % (DHT_MODEL.interval_ms() as u32 / 250) == 0 {
+                    dht_due.get_or_insert(last_time);
+                }
+                node.events.poll(&mut entities);
+            }
+
+            let now = unsafe { QUARTER_SECONDS_RUNNING };
+            node.info.uptime = now / 4;
+
+            if let Some(since) = dht_due {
+                // The pulses are timed by polling with interrupts disabled for about
+                // 5 ms, so the reading is taken between frames to not lose a request
+                let idle = UART2::available() == 0 && unsafe { FRAME.is_idle() };
+                if idle || now - since >= DHT_IDLE_WAIT_QUARTER_SECONDS {
+                    dht_due = None;
+                    let reading = dht
+                        .start()
+                        .and_then(|()| avr_device::interrupt::free(|_| dht.receive()));
+                    // A failed reading leaves the DHT sensors without a value
+                    climate.set_reading(reading.as_ref().ok());
+                }
+            }
+
+            // Ends relay pulses, fades lights, moves covers and reads the DS18B20 probes,
+            // this will fire every 10 ms
+            let ticks = unsafe { TICKS };
+            while last_tick != ticks {
+                last_tick = last_tick.wrapping_add(1);
+                entities.tick(TICK_MS);
+                // Probes that failed are left without a value
+                let _ = measurement.tick(&mut one_wire, TICK_MS);
+            }
+
+            if p_commission.is_low() {
+                node.commissioning = true;
+                commissioning_start = now;
+            } else if now - commissioning_start >= COMMISSIONING_QUARTER_SECONDS {
+                node.commissioning = false;
+            }
+
+            let byte = match UART2::pop() {
+                Some(b) => b,
+                None => continue 'recv_loop,
+            };
+
+            if unsafe { FRAME.handle_byte(byte) } {
+                let addr = node.addr;
+
+                let respond = handle_request(
+                    unsafe { &mut FRAME },
+                    &mut node,
+                    &mut handler_pins,
+                    &mut entities,
+                );
+
+                if node.addr != addr {
+                    storage.set_addr(node.addr);
+                }
+
+                if respond {
+                    led_status.set_high();
+
+                    // Enable RS485 driver
+                    p_de.set_high();
+
+                    unsafe { FRAME.send(&mut serial).unwrap() };
+
+                    // Flush contents, wait for data send and disable RS485 driver
+                    serial.flush();
+                    delay_ms(1);
+                    p_de.set_low();
+                }
+            }
+            led_status.set_low();
+        }
+    }
+}
+
+trait Blink {
+    fn blink(&mut self, ms: u16);
+}
+
+impl Blink for Pin<Output, Dynamic> {
+    fn blink(&mut self, ms: u16) {
+        self.set_high();
+        delay_ms(ms);
+        self.set_low();
+    }
+}
@@ -0,0 +1,75 @@
+//! The node configuration persisted in the EEPROM
+//!
+//! Layout:
+//! - `0x00`: [`MAGIC`] if the address has been written
+//! - `0x01..0x03`: The address of the node (`u16`, little endian)
+//! - `0x03..0x0b`: The serial number of the node, written when flashing the board,
+//!   all `0xff` (erased) if the node has no serial number
+
+use arduino_hal::Eeprom;
+use ha_buddy_proto::datalink::{Serial, FACTORY_ADDR, SERIAL_LEN};
+
+/// Marks a written configuration, erased EEPROM cells read `0xff`
+const MAGIC: u8 = 0xb5;
+
+const OFFSET_MAGIC: u16 = 0x00;
+const OFFSET_ADDR: u16 = 0x01;
+const OFFSET_SERIAL: u16 = 0x03;
+
+pub struct Storage {
+    eeprom: Eeprom,
+}
+
+impl Storage {
+    pub fn new(eeprom: Eeprom) -> Self {
+        Self { eeprom }
+    }
+
+    /// Reads the address of the node
+    /// # Returns
+    /// The stored address or [`FACTORY_ADDR`] if none has been stored yet
+    pub fn addr(&self) -> u16 {
+        if self.eeprom.read_byte(OFFSET_MAGIC) != MAGIC {
+            return FACTORY_ADDR;
+        }
+
+        u16::from_le_bytes([
+            self.eeprom.read_byte(OFFSET_ADDR),
+            self.eeprom.read_byte(OFFSET_ADDR + 1),
+        ])
+    }
+
+    /// Stores the address of the node, only changed bytes get written
+    /// # Arguments
+    /// * `addr` - The new address
+    pub fn set_addr(&mut self, addr: u16) {
+        let bytes = addr.to_le_bytes();
+
+        self.update_byte(OFFSET_ADDR, bytes[0]);
+        self.update_byte(OFFSET_ADDR + 1, bytes[1]);
+        self.update_byte(OFFSET_MAGIC, MAGIC);
+    }
+
+    /// Reads the serial number of the node
+    /// # Returns
+    /// The serial number or `None` if the EEPROM cells are erased
+    pub fn serial(&self) -> Option<Serial> {
+        let mut serial = [0u8; SERIAL_LEN];
+        for (i, byte) in serial.iter_mut().enumerate() {
+            *byte = self.eeprom.read_byte(OFFSET_SERIAL + i as u16);
+        }
+
+        if serial.iter().all(|b| *b == 0xff) {
+            None
+        } else {
+            Some(serial)
+        }
+    }
+
+    /// Writes a byte if it differs, to spare the EEPROM write cycles
+    fn update_byte(&mut self, offset: u16, byte: u8) {
+        if self.eeprom.read_byte(offset) != byte {
+            self.eeprom.write_byte(offset, byte);
+        }
+    }
+}
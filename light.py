@@ -0,0 +1,26 @@
+"""Platform for light integration."""
+from __future__ import annotations
+
+from homeassistant.core import HomeAssistant
+
+import logging
+
+from . import DOMAIN
+from .const import *
+
+LOGGER = logging.getLogger(DOMAIN)
+
+
+async def async_setup_entry(hass: HomeAssistant, config_entry, async_add_devices):
+    """Setup light platform."""
+
+    if hass.data[DOMAIN] is None:
+        LOGGER.error("Can't setup ha_buddy lights: NO CONNECTION")
+        return
+
+    LOGGER.info("Setting up lights")
+
+    for device in hass.data[DOMAIN].devices:
+        async_add_devices(device.get_lights())
+
+    LOGGER.info("Done setting up lights")
@@ -0,0 +1,26 @@
+"""Platform for number integration."""
+from __future__ import annotations
+
+from homeassistant.core import HomeAssistant
+
+import logging
+
+from . import DOMAIN
+from .const import *
+
+LOGGER = logging.getLogger(DOMAIN)
+
+
+async def async_setup_entry(hass: HomeAssistant, config_entry, async_add_devices):
+    """Setup number platform."""
+
+    if hass.data[DOMAIN] is None:
+        LOGGER.error("Can't setup ha_buddy numbers: NO CONNECTION")
+        return
+
+    LOGGER.info("Setting up numbers")
+
+    for device in hass.data[DOMAIN].devices:
+        async_add_devices(device.get_numbers())
+
+    LOGGER.info("Done setting up numbers")
@@ -0,0 +1,212 @@
+//! The commands understood by [`handle_frame`](crate::handler::handle_frame)
+//!
+//! The response to a command uses the command identifier + 1, if
+//! the command fails, [`CMD_ERROR_FLAG`] is set in the response command
+
+/// Set in the response command if the node failed to handle the request,
+/// the payload is the [`ErrorCode`](crate::error::ErrorCode)
+pub const CMD_ERROR_FLAG: u16 = 0x8000;
+
+/// Echo the payload back to the master
+pub const CMD_ECHO: u16 = 0x0000;
+
+/// Serial number of the node, response: [`SERIAL_LEN`](crate::datalink::SERIAL_LEN) bytes,
+/// empty if the node has no serial number
+pub const CMD_NODE_SERIAL: u16 = 0x0010;
+/// Set the address of the node, request: `u16` address + optional serial number,
+/// response: `u16` new address
+///
+/// The node only accepts the new address if the serial number matches its own or,
+/// without a serial number, while it is in commissioning mode
+pub const CMD_NODE_SET_ADDR: u16 = 0x0012;
+/// Discover nodes by their serial number, request: `u8` prefix length in bits +
+/// [`SERIAL_LEN`](crate::datalink::SERIAL_LEN) bytes prefix, response: serial number
+///
+/// Only nodes with a serial number starting with the prefix respond, even to
+/// broadcasts. Nodes that do not match or receive a malformed request stay silent.
+pub const CMD_NODE_DISCOVER: u16 = 0x0014;
+/// Device information, response: see [`DeviceInfo::to_payload`](crate::device::DeviceInfo::to_payload)
+pub const CMD_NODE_INFO: u16 = 0x0016;
+/// Fetch the pending events, response: `u8` flags + events, see [`event`](crate::event)
+///
+/// Every event is the `u8` kind, the `u32` entity id and the current value like in
+/// [`CMD_SENSOR_VALUES`]. Fetched events are removed from the queue of the node.
+pub const CMD_EVENTS_FETCH: u16 = 0x0018;
+
+/// Number of sensors, response: `u32`
+pub const CMD_SENSOR_COUNT: u16 = 0x0100;
+/// Sensor `unique_id`, request: `u32` sensor id, response: string
+pub const CMD_SENSOR_UNIQUE_ID: u16 = 0x0102;
+/// Sensor `native_unit_of_measurement`, request: `u32` sensor id, response: string
+pub const CMD_SENSOR_NATIVE_UNIT_OF_MEASUREMENT: u16 = 0x0104;
+/// Sensor `device_class`, request: `u32` sensor id, response: string
+pub const CMD_SENSOR_DEVICE_CLASS: u16 = 0x0106;
+/// Sensor `state_class`, request: `u32` sensor id, response: string
+pub const CMD_SENSOR_STATE_CLASS: u16 = 0x0108;
+/// Sensor name, request: `u32` sensor id, response: string
+pub const CMD_SENSOR_NAME: u16 = 0x0110;
+/// Sensor value, request: `u32` sensor id, response: [`PayloadType`](crate::homeassistant::sensor::PayloadType) + value
+pub const CMD_SENSOR_VALUE: u16 = 0x0112;
+/// Describe a sensor in one frame, request: `u32` sensor id, response: TLV entries,
+/// see [`tlv`](crate::tlv)
+pub const CMD_SENSOR_DESCRIBE: u16 = 0x0114;
+/// Values of many sensors, request: `u32` first sensor id + optional `u32` mask selecting
+/// the sensors from the first one, response: `u32` sensor id to continue with + the values,
+/// each prefixed by its `u8` length or [`SENSOR_VALUE_ERROR`] + error code
+pub const CMD_SENSOR_VALUES: u16 = 0x0116;
+
+/// [`CMD_SENSOR_VALUES`] sensor id to continue with once all selected values are sent
+pub const SENSOR_VALUES_COMPLETE: u32 = u32::MAX;
+/// [`CMD_SENSOR_VALUES`] length marking a sensor that failed, followed by the error code
+pub const SENSOR_VALUE_ERROR: u8 = u8::MAX;
+
+/// Number of switches, response: `u32`
+pub const CMD_SWITCH_COUNT: u16 = 0x0200;
+/// Switch `unique_id`, request: `u32` switch id, response: string
+pub const CMD_SWITCH_UNIQUE_ID: u16 = 0x0202;
+/// Switch name, request: `u32` switch id, response: string
+pub const CMD_SWITCH_NAME: u16 = 0x0204;
+/// Switch state, request: `u32` switch id, response: `u8` state
+pub const CMD_SWITCH_STATE: u16 = 0x0206;
+/// Switch exec, request: `u32` switch id + `u8` request, response: `u8` state
+///
+/// With the switch id [`SWITCH_ID_ALL`] the request is executed on all switches
+/// and the response holds one `u8` state per switch
+pub const CMD_SWITCH_EXEC: u16 = 0x0208;
+/// Describe a switch in one frame, request: `u32` switch id, response: TLV entries,
+/// see [`tlv`](crate::tlv)
+pub const CMD_SWITCH_DESCRIBE: u16 = 0x020a;
+
+/// Number of binary sensors, response: `u32`
+pub const CMD_BINARY_SENSOR_COUNT: u16 = 0x0300;
+/// Binary sensor `unique_id`, request: `u32` binary sensor id, response: string
+pub const CMD_BINARY_SENSOR_UNIQUE_ID: u16 = 0x0302;
+/// Binary sensor name, request: `u32` binary sensor id, response: string
+pub const CMD_BINARY_SENSOR_NAME: u16 = 0x0304;
+/// Binary sensor `device_class`, request: `u32` binary sensor id, response: string,
+/// empty if the binary sensor has no device class
+pub const CMD_BINARY_SENSOR_DEVICE_CLASS: u16 = 0x0306;
+/// Binary sensor state, request: `u32` binary sensor id, response: `u8` state,
+/// empty if the state is unknown
+pub const CMD_BINARY_SENSOR_STATE: u16 = 0x0308;
+/// Describe a binary sensor in one frame, request: `u32` binary sensor id,
+/// response: TLV entries, see [`tlv`](crate::tlv)
+pub const CMD_BINARY_SENSOR_DESCRIBE: u16 = 0x030a;
+
+/// Number of numbers, response: `u32`
+pub const CMD_NUMBER_COUNT: u16 = 0x0400;
+/// Number `unique_id`, request: `u32` number id, response: string
+pub const CMD_NUMBER_UNIQUE_ID: u16 = 0x0402;
+/// Number name, request: `u32` number id, response: string
+pub const CMD_NUMBER_NAME: u16 = 0x0404;
+/// Number `native_unit_of_measurement`, request: `u32` number id, response: string
+pub const CMD_NUMBER_NATIVE_UNIT_OF_MEASUREMENT: u16 = 0x0406;
+/// Number `device_class`, request: `u32` number id, response: string
+pub const CMD_NUMBER_DEVICE_CLASS: u16 = 0x0408;
+/// Number value, request: `u32` number id, response: value like [`CMD_SENSOR_VALUE`]
+pub const CMD_NUMBER_VALUE: u16 = 0x040a;
+/// Set a number, request: `u32` number id + `f32` value, response: value like [`CMD_SENSOR_VALUE`]
+///
+/// Values outside of `min..=max` are rejected with
+/// [`ErrorCode::InvalidArgument`](crate::error::ErrorCode::InvalidArgument)
+pub const CMD_NUMBER_SET: u16 = 0x040c;
+/// Describe a number in one frame, request: `u32` number id, response: TLV entries,
+/// see [`tlv`](crate::tlv)
+pub const CMD_NUMBER_DESCRIBE: u16 = 0x040e;
+
+/// Number of buttons, response: `u32`
+pub const CMD_BUTTON_COUNT: u16 = 0x0500;
+/// Button `unique_id`, request: `u32` button id, response: string
+pub const CMD_BUTTON_UNIQUE_ID: u16 = 0x0502;
+/// Button name, request: `u32` button id, response: string
+pub const CMD_BUTTON_NAME: u16 = 0x0504;
+/// Press a button, request: `u32` button id, response: empty
+///
+/// The response is sent once the action started, actions like relay pulses finish later
+pub const CMD_BUTTON_PRESS: u16 = 0x0506;
+/// Describe a button in one frame, request: `u32` button id, response: TLV entries,
+/// see [`tlv`](crate::tlv)
+pub const CMD_BUTTON_DESCRIBE: u16 = 0x0508;
+
+/// Number of lights, response: `u32`
+pub const CMD_LIGHT_COUNT: u16 = 0x0600;
+/// Light `unique_id`, request: `u32` light id, response: string
+pub const CMD_LIGHT_UNIQUE_ID: u16 = 0x0602;
+/// Light name, request: `u32` light id, response: string
+pub const CMD_LIGHT_NAME: u16 = 0x0604;
+/// Light state, request: `u32` light id, response: `u8` on + `u8` brightness
+pub const CMD_LIGHT_STATE: u16 = 0x0606;
+/// Turn a light on or off or toggle it, request: `u32` light id + `u8` action like
+/// [`CMD_SWITCH_EXEC`] + optional `u16` transition in milliseconds, response: state like
+/// [`CMD_LIGHT_STATE`]
+pub const CMD_LIGHT_EXEC: u16 = 0x0608;
+/// Turn a light on at a brightness, request: `u32` light id + `u8` brightness + optional
+/// `u16` transition in milliseconds, response: state like [`CMD_LIGHT_STATE`]
+///
+/// The brightness `0` turns the light off, keeping its brightness
+pub const CMD_LIGHT_BRIGHTNESS: u16 = 0x060a;
+/// Describe a light in one frame, request: `u32` light id, response: TLV entries,
+/// see [`tlv`](crate::tlv)
+pub const CMD_LIGHT_DESCRIBE: u16 = 0x060c;
+
+/// Number of covers, response: `u32`
+pub const CMD_COVER_COUNT: u16 = 0x0700;
+/// Cover `unique_id`, request: `u32` cover id, response: string
+pub const CMD_COVER_UNIQUE_ID: u16 = 0x0702;
+/// Cover name, request: `u32` cover id, response: string
+pub const CMD_COVER_NAME: u16 = 0x0704;
+/// Cover `device_class`, request: `u32` cover id, response: string,
+/// empty if the cover has no device class
+pub const CMD_COVER_DEVICE_CLASS: u16 = 0x0706;
+/// Cover state, request: `u32` cover id, response: `u8` position +
+/// `u8` [`CoverMotion`](crate::homeassistant::cover::CoverMotion)
+pub const CMD_COVER_STATE: u16 = 0x0708;
+/// Open, close or stop a cover, request: `u32` cover id + `u8` action, response: state
+/// like [`CMD_COVER_STATE`]
+pub const CMD_COVER_EXEC: u16 = 0x070a;
+/// Move a cover to a position, request: `u32` cover id + `u8` position, response: state
+/// like [`CMD_COVER_STATE`]
+///
+/// Positions above `100` are rejected with
+/// [`ErrorCode::InvalidArgument`](crate::error::ErrorCode::InvalidArgument)
+pub const CMD_COVER_POSITION: u16 = 0x070c;
+/// Describe a cover in one frame, request: `u32` cover id, response: TLV entries,
+/// see [`tlv`](crate::tlv)
+pub const CMD_COVER_DESCRIBE: u16 = 0x070e;
+
+/// Number of selects, response: `u32`
+pub const CMD_SELECT_COUNT: u16 = 0x0800;
+/// Select `unique_id`, request: `u32` select id, response: string
+pub const CMD_SELECT_UNIQUE_ID: u16 = 0x0802;
+/// Select name, request: `u32` select id, response: string
+pub const CMD_SELECT_NAME: u16 = 0x0804;
+/// Option of a select, request: `u32` select id + `u8` option index, response: string
+pub const CMD_SELECT_OPTION: u16 = 0x0806;
+/// Select state, request: `u32` select id, response: `u8` index of the selected option
+pub const CMD_SELECT_STATE: u16 = 0x0808;
+/// Select an option, request: `u32` select id + `u8` option index, response: state like
+/// [`CMD_SELECT_STATE`]
+///
+/// Indexes beyond the options are rejected with
+/// [`ErrorCode::InvalidArgument`](crate::error::ErrorCode::InvalidArgument)
+pub const CMD_SELECT_SET: u16 = 0x080a;
+/// Describe a select in one frame, request: `u32` select id, response: TLV entries,
+/// see [`tlv`](crate::tlv)
+pub const CMD_SELECT_DESCRIBE: u16 = 0x080c;
+
+/// [`CMD_SWITCH_EXEC`] switch id addressing all switches of a node
+pub const SWITCH_ID_ALL: u32 = u32::MAX;
+
+/// [`CMD_SWITCH_EXEC`] request: turn the switch off
+pub const SWITCH_EXEC_TURN_OFF: u8 = 0;
+/// [`CMD_SWITCH_EXEC`] request: turn the switch on
+pub const SWITCH_EXEC_TURN_ON: u8 = 1;
+/// [`CMD_SWITCH_EXEC`] request: toggle the switch
+pub const SWITCH_EXEC_TOGGLE: u8 = 2;
+
+/// [`CMD_COVER_EXEC`] request: close the cover
+pub const COVER_EXEC_CLOSE: u8 = 0;
+/// [`CMD_COVER_EXEC`] request: open the cover
+pub const COVER_EXEC_OPEN: u8 = 1;
+/// [`CMD_COVER_EXEC`] request: stop the cover
+pub const COVER_EXEC_STOP: u8 = 2;
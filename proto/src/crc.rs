@@ -5,6 +5,11 @@ pub const CRC8_AUTOSAR_POLY: u8 = 0x2f;
 /// The value to XOR the final result with
 pub const CRC8_AUTOSAR_XOROUT: u8 = 0xff;
 
+/// The initial value for the CRC calculation of 1-Wire devices
+pub const CRC8_MAXIM_INIT: u8 = 0x00;
+/// The polynomial `0x31` of 1-Wire devices, reflected as the data is sent LSB first
+pub const CRC8_MAXIM_POLY_REFLECTED: u8 = 0x8c;
+
 pub trait CRC<T> {
     /// Creates a new CRC algorithm and computing instance
     fn new() -> Self;
@@ -57,3 +62,39 @@ impl CRC<u8> for CRC8Autosar {
         self.crc ^ CRC8_AUTOSAR_XOROUT
     }
 }
+
+/// An implementation of the Maxim (Dallas) CRC8 algorithm used by 1-Wire devices
+///
+/// The CRC over data followed by its CRC is `0`
+pub struct CRC8Maxim {
+    pub crc: u8,
+}
+
+impl CRC<u8> for CRC8Maxim {
+    fn new() -> Self {
+        Self {
+            crc: CRC8_MAXIM_INIT,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.crc = CRC8_MAXIM_INIT;
+    }
+
+    fn update(&mut self, t: &[u8]) {
+        for t in t {
+            self.crc ^= t;
+            for _ in 0..8 {
+                if self.crc & 0x01 != 0 {
+                    self.crc = (self.crc >> 1) ^ CRC8_MAXIM_POLY_REFLECTED;
+                } else {
+                    self.crc >>= 1;
+                }
+            }
+        }
+    }
+
+    fn finalize(&self) -> u8 {
+        self.crc
+    }
+}
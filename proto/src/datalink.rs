@@ -1,14 +1,68 @@
 use embedded_hal::serial::Write;
 use nb::block;
 
-use crate::crc::{CRC8Autosar, CRC, CRC8_AUTOSAR_INIT};
+use crate::crc::{CRC8Autosar, CRC};
 
-const START_BYTE_0: u8 = 0xaa;
-const START_BYTE_1: u8 = 0x55;
+/// The first start byte of every frame
+pub const START_BYTE_0: u8 = 0xaa;
+/// The second start byte of every frame
+pub const START_BYTE_1: u8 = 0x55;
 
-static mut CRC_CALCULATOR: CRC8Autosar = CRC8Autosar {
-    crc: CRC8_AUTOSAR_INIT,
-};
+/// The address every node acts on without responding
+pub const BROADCAST_ADDR: u16 = 0xffff;
+/// The first address of the range reserved for multicast groups, the range
+/// ends right before [`BROADCAST_ADDR`]
+pub const GROUP_ADDR_FIRST: u16 = 0xff00;
+
+/// Checks if `addr` is an address multiple nodes act on, either
+/// [`BROADCAST_ADDR`] or a multicast group
+/// # Arguments
+/// * `addr` - The address to check
+pub fn is_multicast(addr: u16) -> bool {
+    addr >= GROUP_ADDR_FIRST
+}
+
+/// The address of nodes that have not been assigned an address yet
+pub const FACTORY_ADDR: u16 = 0x1000;
+
+/// The length of the serial number identifying a node
+pub const SERIAL_LEN: usize = 8;
+
+/// The serial number identifying a node, unique per board
+pub type Serial = [u8; SERIAL_LEN];
+
+/// Checks if the first `bits` bits of `serial` match `prefix`, most significant bit first
+/// # Arguments
+/// * `serial` - The serial number to check
+/// * `prefix` - The prefix, bits after the first `bits` bits are ignored
+/// * `bits` - The length of the prefix in bits, at most `SERIAL_LEN * 8`
+pub fn serial_has_prefix(serial: &Serial, prefix: &Serial, bits: usize) -> bool {
+    if bits > SERIAL_LEN * 8 {
+        return false;
+    }
+
+    let bytes = bits / 8;
+    if serial[0..bytes] != prefix[0..bytes] {
+        return false;
+    }
+
+    match bits % 8 {
+        0 => true,
+        rem => {
+            let mask = 0xffu8 << (8 - rem);
+            serial[bytes] & mask == prefix[bytes] & mask
+        }
+    }
+}
+
+/// How a frame reaches a node
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Delivery {
+    /// The frame is addressed to this node only, the node responds
+    Unicast,
+    /// The frame is addressed to many nodes, the node acts on it without responding
+    Multicast,
+}
 
 /// A frame from the Data Link Layer
 pub struct DataFrame {
@@ -30,11 +84,32 @@ pub struct DataFrame {
     pub in_len: u16,
 }
 
+impl Default for DataFrame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DataFrame {
+    /// Creates a new, empty DataFrame
+    ///
+    /// This is a `const fn` so the frame can be placed in a `static`
+    pub const fn new() -> Self {
+        Self {
+            src: 0,
+            dst: 0,
+            cmd: 0,
+            payload_len: 0,
+            h_crc: 0,
+            payload: [0; u8::MAX as usize + 1],
+            f_crc: 0,
+            in_len: 0,
+        }
+    }
+
     /// Calculates the frame checksum for this DataFrame
     pub fn f_crc(&self) -> u8 {
-        let digest = unsafe { &mut CRC_CALCULATOR };
-        digest.reset();
+        let mut digest = CRC8Autosar::new();
 
         digest.update(&[START_BYTE_0, START_BYTE_1]);
         digest.update(&[(self.src & 0xff) as u8, (self.src >> 8 & 0xff) as u8]);
@@ -52,8 +127,7 @@ impl DataFrame {
 
     /// Calculates the header checksum for this DataFrame
     pub fn h_crc(&self) -> u8 {
-        let digest = unsafe { &mut CRC_CALCULATOR };
-        digest.reset();
+        let mut digest = CRC8Autosar::new();
 
         digest.update(&[START_BYTE_0, START_BYTE_1]);
         digest.update(&[(self.src & 0xff) as u8, (self.src >> 8 & 0xff) as u8]);
@@ -123,18 +197,28 @@ impl DataFrame {
         self.in_len = 0;
     }
 
+    /// Returns true if no frame is part-way received, the bus is between frames
+    pub fn is_idle(&self) -> bool {
+        self.in_len == 0
+    }
+
+    /// Feeds one received byte into the frame assembler
+    /// # Arguments
+    /// * `byte` - The byte received from the bus
+    /// # Returns
+    /// `true` if this byte completed a frame, the CRC still has to be checked
     pub fn handle_byte(&mut self, byte: u8) -> bool {
         match self.in_len {
             0 => {
                 // Start byte 0: 0xaa
-                if byte != 0xaa {
+                if byte != START_BYTE_0 {
                     self.reset();
                     return false;
                 }
             }
             1 => {
                 // Start byte 1: 0x55
-                if byte != 0x55 {
+                if byte != START_BYTE_1 {
                     self.reset();
                     return false;
                 }
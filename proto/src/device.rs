@@ -0,0 +1,101 @@
+//! Information about a node for the device registry of the master
+
+use crate::{homeassistant::sensor::truncate_str, DataFrame};
+
+/// The version of the protocol implemented by this crate, incremented on
+/// incompatible changes and new commands
+pub const PROTOCOL_VERSION: u8 = 10;
+
+/// Capability bit: the node handles sensor commands
+pub const CAP_SENSOR: u32 = 1 << 0;
+/// Capability bit: the node handles switch commands
+pub const CAP_SWITCH: u32 = 1 << 1;
+/// Capability bit: the node handles binary sensor commands
+pub const CAP_BINARY_SENSOR: u32 = 1 << 2;
+/// Capability bit: the node handles number commands
+pub const CAP_NUMBER: u32 = 1 << 3;
+/// Capability bit: the node handles button commands
+pub const CAP_BUTTON: u32 = 1 << 4;
+/// Capability bit: the node handles light commands
+pub const CAP_LIGHT: u32 = 1 << 5;
+/// Capability bit: the node handles cover commands
+pub const CAP_COVER: u32 = 1 << 6;
+/// Capability bit: the node handles select commands
+pub const CAP_SELECT: u32 = 1 << 7;
+
+/// The entity types the handler of this crate supports, reported by every node
+/// running it
+///
+/// A bit means the node answers the commands of the entity type, not that it has
+/// entities of that type, e.g. [`CMD_SWITCH_COUNT`](crate::command::CMD_SWITCH_COUNT)
+/// is 0 on a node without switches.
+pub const CAPABILITIES: u32 = CAP_SENSOR
+    | CAP_SWITCH
+    | CAP_BINARY_SENSOR
+    | CAP_NUMBER
+    | CAP_BUTTON
+    | CAP_LIGHT
+    | CAP_COVER
+    | CAP_SELECT;
+
+/// The maximum length of each string in the payload, all three fit into a frame
+pub const INFO_MAX_STR_LEN: usize = 80;
+
+/// Describes a node, reported by [`CMD_NODE_INFO`](crate::command::CMD_NODE_INFO)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeviceInfo<'a> {
+    /// The version of the firmware, e.g. `env!("CARGO_PKG_VERSION")`
+    pub firmware_version: &'a str,
+    /// The board the firmware runs on
+    pub board: &'a str,
+    /// The name of the node
+    pub name: &'a str,
+    /// The seconds since the node started
+    pub uptime: u32,
+}
+
+impl<'a> DeviceInfo<'a> {
+    /// Creates an empty device info
+    pub const fn new() -> Self {
+        Self {
+            firmware_version: "",
+            board: "",
+            name: "",
+            uptime: 0,
+        }
+    }
+
+    /// Fills the payload of `frame` with the device info
+    ///
+    /// Layout: `u8` protocol version, `u32` [`CAPABILITIES`], `u32` uptime and
+    /// the firmware version, board and name, each as `u8` length + string.
+    /// The strings get truncated to at most [`INFO_MAX_STR_LEN`] bytes, at a
+    /// character boundary.
+    /// # Arguments
+    /// * `frame` - The frame to fill the payload of
+    pub fn to_payload(&self, frame: &mut DataFrame) {
+        let payload = &mut frame.payload;
+
+        payload[0] = PROTOCOL_VERSION;
+        payload[1..5].copy_from_slice(&CAPABILITIES.to_le_bytes());
+        payload[5..9].copy_from_slice(&self.uptime.to_le_bytes());
+        let mut len = 9;
+
+        for string in [self.firmware_version, self.board, self.name] {
+            let bytes = truncate_str(string, INFO_MAX_STR_LEN).as_bytes();
+            let str_len = bytes.len();
+
+            payload[len] = str_len as u8;
+            payload[len + 1..len + 1 + str_len].copy_from_slice(bytes);
+            len += 1 + str_len;
+        }
+
+        frame.payload_len = len as u8;
+    }
+}
+
+impl Default for DeviceInfo<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
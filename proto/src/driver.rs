@@ -0,0 +1,8 @@
+//! Drivers for the hardware behind the entities, written against `embedded-hal`
+//! so they run on the nodes and in host tests alike
+
+pub mod bme280;
+pub mod dht;
+pub mod ds18b20;
+pub mod i2c;
+pub mod onewire;
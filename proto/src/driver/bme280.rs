@@ -0,0 +1,417 @@
+//! The Bosch BME280 humidity, pressure and temperature sensor and the BMP280 without
+//! humidity, on an I2C bus
+//!
+//! The chip measures on its own in normal mode, a measurement only reads the latest
+//! raw values and compensates them with the calibration stored in the chip, using
+//! the integer formulas of the datasheet.
+
+use embedded_hal::blocking::i2c::{Write, WriteRead};
+
+use super::i2c::{read_registers, write_register, Error, I2cDriver};
+use crate::homeassistant::{
+    entity::DeviceClass,
+    sensor::{Sensor, StateClass},
+};
+
+/// The address with `SDO` connected to `GND`
+pub const ADDRESS_PRIMARY: u8 = 0x76;
+/// The address with `SDO` connected to `VDDIO`
+pub const ADDRESS_SECONDARY: u8 = 0x77;
+
+/// The chip id of the BME280
+pub const CHIP_ID_BME280: u8 = 0x60;
+/// The chip ids of BMP280 samples and mass production
+pub const CHIP_IDS_BMP280: [u8; 3] = [0x56, 0x57, 0x58];
+
+/// The chip id
+pub const REG_ID: u8 = 0xd0;
+/// The first calibration block, temperature and pressure and `H1`
+pub const REG_CALIB_00: u8 = 0x88;
+/// The second calibration block, the rest of humidity
+pub const REG_CALIB_26: u8 = 0xe1;
+/// The humidity oversampling, applied with the next write of [`REG_CTRL_MEAS`]
+pub const REG_CTRL_HUM: u8 = 0xf2;
+/// The temperature and pressure oversampling and the mode
+pub const REG_CTRL_MEAS: u8 = 0xf4;
+/// The standby time between measurements and the filter
+pub const REG_CONFIG: u8 = 0xf5;
+/// The raw values, pressure, temperature and humidity, most significant byte first
+pub const REG_DATA: u8 = 0xf7;
+
+/// The length of the first calibration block
+const CALIB_00_LEN: usize = 26;
+/// The length of the second calibration block
+const CALIB_26_LEN: usize = 7;
+/// The length of the raw values, the BMP280 leaves out the last two bytes of humidity
+const DATA_LEN: usize = 8;
+/// Oversampling x1 for humidity
+const CTRL_HUM: u8 = 0b001;
+/// Oversampling x1 for temperature and pressure in bits 7-5 and 4-2, normal mode
+const CTRL_MEAS: u8 = 0b0010_0111;
+/// A measurement every 500 ms in bits 7-5, no filter
+const CONFIG: u8 = 0b1000_0000;
+/// A raw temperature or pressure not measured yet
+const SKIPPED: i32 = 0x80000;
+
+/// The kind of chip, found by its chip id
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Chip {
+    /// Humidity, pressure and temperature
+    Bme280,
+    /// Pressure and temperature
+    Bmp280,
+}
+
+impl Chip {
+    /// Returns the kind of chip with the chip id `id`
+    /// # Arguments
+    /// * `id` - The chip id read from [`REG_ID`]
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            CHIP_ID_BME280 => Some(Chip::Bme280),
+            id if CHIP_IDS_BMP280.contains(&id) => Some(Chip::Bmp280),
+            _ => None,
+        }
+    }
+}
+
+/// The trimming parameters of a single chip, named as in the datasheet
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Calibration {
+    pub t1: u16,
+    pub t2: i16,
+    pub t3: i16,
+    pub p1: u16,
+    pub p2: i16,
+    pub p3: i16,
+    pub p4: i16,
+    pub p5: i16,
+    pub p6: i16,
+    pub p7: i16,
+    pub p8: i16,
+    pub p9: i16,
+    pub h1: u8,
+    pub h2: i16,
+    pub h3: u8,
+    pub h4: i16,
+    pub h5: i16,
+    pub h6: i8,
+}
+
+impl Calibration {
+    /// Parses the calibration blocks
+    /// # Arguments
+    /// * `calib_00` - The registers from [`REG_CALIB_00`]
+    /// * `calib_26` - The registers from [`REG_CALIB_26`], all zero on a BMP280
+    pub fn from_registers(calib_00: &[u8; CALIB_00_LEN], calib_26: &[u8; CALIB_26_LEN]) -> Self {
+        let u16_at = |i: usize| u16::from_le_bytes([calib_00[i], calib_00[i + 1]]);
+        let i16_at = |i: usize| u16_at(i) as i16;
+
+        Self {
+            t1: u16_at(0),
+            t2: i16_at(2),
+            t3: i16_at(4),
+            p1: u16_at(6),
+            p2: i16_at(8),
+            p3: i16_at(10),
+            p4: i16_at(12),
+            p5: i16_at(14),
+            p6: i16_at(16),
+            p7: i16_at(18),
+            p8: i16_at(20),
+            p9: i16_at(22),
+            h1: calib_00[25],
+            h2: i16::from_le_bytes([calib_26[0], calib_26[1]]),
+            h3: calib_26[2],
+            // Two 12 bit values sharing the nibbles of 0xe5
+            h4: (calib_26[3] as i8 as i16) << 4 | (calib_26[4] & 0x0f) as i16,
+            h5: (calib_26[5] as i8 as i16) << 4 | (calib_26[4] >> 4) as i16,
+            h6: calib_26[6] as i8,
+        }
+    }
+
+    /// Checks if the calibration may have been read from a trimmed chip, a failed read
+    /// leaves `T1` and `P1` all zero or all one
+    pub fn is_valid(&self) -> bool {
+        ![0, u16::MAX].contains(&self.t1) && ![0, u16::MAX].contains(&self.p1)
+    }
+
+    /// Returns the fine temperature the other values are compensated with
+    /// # Arguments
+    /// * `adc_t` - The raw temperature
+    pub fn t_fine(&self, adc_t: i32) -> i32 {
+        // The squared difference overflows 32 bits with a bad calibration, the result
+        // fits for any calibration
+        let adc_t = adc_t as i64;
+        let t1 = self.t1 as i64;
+        let var1 = (((adc_t >> 3) - (t1 << 1)) * self.t2 as i64) >> 11;
+        let var2 = (((((adc_t >> 4) - t1) * ((adc_t >> 4) - t1)) >> 12) * self.t3 as i64) >> 14;
+        (var1 + var2) as i32
+    }
+
+    /// Returns the temperature in °C
+    /// # Arguments
+    /// * `t_fine` - The fine temperature
+    pub fn temperature(&self, t_fine: i32) -> f32 {
+        // In hundredths of a degree
+        ((t_fine * 5 + 128) >> 8) as f32 / 100.0
+    }
+
+    /// Returns the pressure in hPa
+    /// # Arguments
+    /// * `t_fine` - The fine temperature
+    /// * `adc_p` - The raw pressure
+    pub fn pressure(&self, t_fine: i32, adc_p: i32) -> f32 {
+        let mut var1 = t_fine as i64 - 128000;
+        let mut var2 = var1 * var1 * self.p6 as i64;
+        var2 += (var1 * self.p5 as i64) << 17;
+        var2 += (self.p4 as i64) << 35;
+        var1 = ((var1 * var1 * self.p3 as i64) >> 8) + ((var1 * self.p2 as i64) << 12);
+        var1 = (((1i64 << 47) + var1) * self.p1 as i64) >> 33;
+        if var1 == 0 {
+            // Not calibrated, avoids the division by zero
+            return 0.0;
+        }
+
+        let mut p = 1048576 - adc_p as i64;
+        p = (((p << 31) - var2) * 3125) / var1;
+        var1 = (self.p9 as i64 * (p >> 13) * (p >> 13)) >> 25;
+        var2 = (self.p8 as i64 * p) >> 19;
+        p = ((p + var1 + var2) >> 8) + ((self.p7 as i64) << 4);
+
+        // In 1/256 Pa
+        p as f32 / 25600.0
+    }
+
+    /// Returns the relative humidity in %
+    /// # Arguments
+    /// * `t_fine` - The fine temperature
+    /// * `adc_h` - The raw humidity
+    pub fn humidity(&self, t_fine: i32, adc_h: i32) -> f32 {
+        // The 32 bit formula of the datasheet, in 64 bits for the same reason as
+        // `t_fine`
+        let adc_h = adc_h as i64;
+        let x = t_fine as i64 - 76800;
+        let mut x = ((((adc_h << 14) - ((self.h4 as i64) << 20) - (self.h5 as i64 * x)) + 16384)
+            >> 15)
+            * (((((((x * self.h6 as i64) >> 10) * (((x * self.h3 as i64) >> 11) + 32768)) >> 10)
+                + 2097152)
+                * self.h2 as i64
+                + 8192)
+                >> 14);
+        x -= ((((x >> 15) * (x >> 15)) >> 7) * self.h1 as i64) >> 4;
+        let x = x.clamp(0, 419430400);
+
+        // In 1/1024 %
+        (x >> 12) as f32 / 1024.0
+    }
+}
+
+/// A reading of a BME280 or BMP280
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Reading {
+    /// The temperature in °C
+    pub temperature: f32,
+    /// The pressure in hPa
+    pub pressure: f32,
+    /// The relative humidity in %, `None` on a BMP280
+    pub humidity: Option<f32>,
+}
+
+/// The sensors of a BME280 or BMP280
+pub struct Bme280Sensors<'a> {
+    /// The temperature in °C
+    pub temperature: Sensor<'a, Option<f32>>,
+    /// The pressure in hPa
+    pub pressure: Sensor<'a, Option<f32>>,
+    /// The relative humidity in %, without a value on a BMP280
+    pub humidity: Sensor<'a, Option<f32>>,
+}
+
+impl<'a> Bme280Sensors<'a> {
+    /// Creates the sensors, without a value until the first reading
+    /// # Arguments
+    /// * `temperature` - The friendly name and the `unique_id` of the temperature sensor
+    /// * `pressure` - The friendly name and the `unique_id` of the pressure sensor
+    /// * `humidity` - The friendly name and the `unique_id` of the humidity sensor
+    pub fn new(
+        temperature: (&'a str, &'a str),
+        pressure: (&'a str, &'a str),
+        humidity: (&'a str, &'a str),
+    ) -> Self {
+        Self {
+            temperature: Sensor::new(
+                temperature.0,
+                temperature.1,
+                "°C",
+                DeviceClass::Temperature,
+                StateClass::Measurement,
+                None,
+            ),
+            pressure: Sensor::new(
+                pressure.0,
+                pressure.1,
+                "hPa",
+                DeviceClass::Pressure,
+                StateClass::Measurement,
+                None,
+            ),
+            humidity: Sensor::new(
+                humidity.0,
+                humidity.1,
+                "%",
+                DeviceClass::Humidity,
+                StateClass::Measurement,
+                None,
+            ),
+        }
+    }
+
+    /// Sets the values of all sensors
+    /// # Arguments
+    /// * `reading` - The reading, `None` if it failed
+    pub fn set_reading(&self, reading: Option<&Reading>) {
+        self.temperature
+            .set_value(Some(reading.map(|r| r.temperature)));
+        self.pressure.set_value(Some(reading.map(|r| r.pressure)));
+        self.humidity
+            .set_value(Some(reading.and_then(|r| r.humidity)));
+    }
+}
+
+/// A BME280 or BMP280 at a single address, bound to its sensors
+///
+/// The chip is set up on the first measurement and again after it failed, so it may
+/// be connected after the node started.
+pub struct Bme280<'s, 'a> {
+    address: u8,
+    sensors: &'s Bme280Sensors<'a>,
+    /// The kind of chip and its calibration, `None` until set up
+    chip: Option<(Chip, Calibration)>,
+}
+
+impl<'s, 'a> Bme280<'s, 'a> {
+    /// Creates the driver for the chip at `address`
+    /// # Arguments
+    /// * `address` - [`ADDRESS_PRIMARY`] or [`ADDRESS_SECONDARY`]
+    /// * `sensors` - The sensors to update
+    pub fn new(address: u8, sensors: &'s Bme280Sensors<'a>) -> Self {
+        Self {
+            address,
+            sensors,
+            chip: None,
+        }
+    }
+
+    /// Returns the kind of chip, `None` until it has been set up
+    pub fn chip(&self) -> Option<Chip> {
+        self.chip.map(|(chip, _)| chip)
+    }
+
+    /// Identifies the chip, reads its calibration and starts measuring in normal mode
+    /// # Arguments
+    /// * `bus` - The bus the chip is on
+    pub fn init<I2C, E>(&mut self, bus: &mut I2C) -> Result<Chip, Error<E>>
+    where
+        I2C: Write<Error = E> + WriteRead<Error = E>,
+    {
+        self.chip = None;
+
+        let mut id = [0];
+        read_registers(bus, self.address, REG_ID, &mut id)?;
+        let chip = Chip::from_id(id[0]).ok_or(Error::UnknownChip(id[0]))?;
+
+        let mut calib_00 = [0; CALIB_00_LEN];
+        read_registers(bus, self.address, REG_CALIB_00, &mut calib_00)?;
+        let mut calib_26 = [0; CALIB_26_LEN];
+        if chip == Chip::Bme280 {
+            read_registers(bus, self.address, REG_CALIB_26, &mut calib_26)?;
+        }
+        let calibration = Calibration::from_registers(&calib_00, &calib_26);
+        if !calibration.is_valid() {
+            return Err(Error::InvalidCalibration);
+        }
+
+        // The configuration is only taken reliably in sleep mode
+        write_register(bus, self.address, REG_CTRL_MEAS, 0)?;
+        if chip == Chip::Bme280 {
+            write_register(bus, self.address, REG_CTRL_HUM, CTRL_HUM)?;
+        }
+        write_register(bus, self.address, REG_CONFIG, CONFIG)?;
+        write_register(bus, self.address, REG_CTRL_MEAS, CTRL_MEAS)?;
+
+        self.chip = Some((chip, calibration));
+        Ok(chip)
+    }
+
+    /// Reads the latest measurement, setting up the chip first if needed
+    /// # Arguments
+    /// * `bus` - The bus the chip is on
+    /// # Returns
+    /// The reading, `None` if the chip has not measured since it was set up
+    pub fn read<I2C, E>(&mut self, bus: &mut I2C) -> Result<Option<Reading>, Error<E>>
+    where
+        I2C: Write<Error = E> + WriteRead<Error = E>,
+    {
+        let (chip, calibration) = match self.chip {
+            Some(chip) => chip,
+            None => {
+                self.init(bus)?;
+                return Ok(None);
+            }
+        };
+
+        let mut data = [0; DATA_LEN];
+        let len = match chip {
+            Chip::Bme280 => DATA_LEN,
+            Chip::Bmp280 => DATA_LEN - 2,
+        };
+        if let Err(e) = read_registers(bus, self.address, REG_DATA, &mut data[0..len]) {
+            // Set up again, the chip may have lost power
+            self.chip = None;
+            return Err(e);
+        }
+
+        let raw20 = |i: usize| {
+            (data[i] as i32) << 12 | (data[i + 1] as i32) << 4 | (data[i + 2] as i32) >> 4
+        };
+        let adc_p = raw20(0);
+        let adc_t = raw20(3);
+        if adc_t == SKIPPED || adc_p == SKIPPED {
+            return Ok(None);
+        }
+
+        let t_fine = calibration.t_fine(adc_t);
+        Ok(Some(Reading {
+            temperature: calibration.temperature(t_fine),
+            pressure: calibration.pressure(t_fine, adc_p),
+            humidity: match chip {
+                Chip::Bme280 => {
+                    let adc_h = (data[6] as i32) << 8 | data[7] as i32;
+                    Some(calibration.humidity(t_fine, adc_h))
+                }
+                Chip::Bmp280 => None,
+            },
+        }))
+    }
+}
+
+impl<'s, 'a, I2C, E> I2cDriver<I2C, E> for Bme280<'s, 'a>
+where
+    I2C: Write<Error = E> + WriteRead<Error = E>,
+{
+    fn measure(&mut self, bus: &mut I2C) -> Result<(), Error<E>> {
+        match self.read(bus) {
+            // Keep the last values until the chip measured
+            Ok(None) => Ok(()),
+            Ok(Some(reading)) => {
+                self.sensors.set_reading(Some(&reading));
+                Ok(())
+            }
+            Err(e) => {
+                self.sensors.set_reading(None);
+                Err(e)
+            }
+        }
+    }
+}
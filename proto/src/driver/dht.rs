@@ -0,0 +1,258 @@
+//! The DHT11 and DHT22 (AM2302) humidity and temperature sensors on a single data pin
+//!
+//! The node pulls the pin low to request a reading, the sensor answers with 40 bits:
+//! humidity, temperature and a checksum. Every bit starts with a `50 µs` low pulse,
+//! followed by a high pulse of `26 - 28 µs` for a `0` or `70 µs` for a `1`. The high
+//! pulse is measured against the low pulse before it, so the decoding holds up on slow
+//! cores. An interrupt within a pulse still corrupts the reading, on a node with
+//! interrupts the data is received by [`Dht::receive`] with them disabled.
+
+use embedded_hal::{
+    blocking::delay::DelayUs,
+    digital::v2::{InputPin, OutputPin},
+};
+
+use crate::homeassistant::{
+    entity::DeviceClass,
+    sensor::{Sensor, StateClass},
+};
+
+/// The bits of a reading, the checksum included
+pub const FRAME_BITS: usize = 40;
+/// How often a pulse is polled until the sensor is considered gone, a poll takes at
+/// least `1 µs` and the longest pulse `80 µs`
+pub const PULSE_TIMEOUT_POLLS: u16 = 200;
+
+/// An error reading a DHT sensor
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error<E> {
+    /// The pin failed
+    Bus(E),
+    /// The sensor did not answer the start signal
+    NoResponse,
+    /// The sensor stopped sending within the reading
+    Timeout,
+    /// The reading does not match its checksum
+    Checksum,
+}
+
+impl<E> From<E> for Error<E> {
+    fn from(e: E) -> Self {
+        Error::Bus(e)
+    }
+}
+
+/// The kind of sensor, they differ in the start signal and the encoding of a reading
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Model {
+    /// 1 % and 1 °C steps, readable once a second
+    Dht11,
+    /// 0.1 % and 0.1 °C steps, readable every two seconds
+    Dht22,
+}
+
+impl Model {
+    /// Returns how long the start signal holds the pin low in microseconds
+    pub fn start_us(&self) -> u16 {
+        match self {
+            Model::Dht11 => 18_000,
+            Model::Dht22 => 1_100,
+        }
+    }
+
+    /// Returns the shortest time between two readings in milliseconds
+    pub fn interval_ms(&self) -> u16 {
+        match self {
+            Model::Dht11 => 1_000,
+            Model::Dht22 => 2_000,
+        }
+    }
+
+    /// Decodes the humidity and the temperature of a reading with a valid checksum
+    /// # Arguments
+    /// * `data` - The bytes received, the checksum last
+    pub fn decode(&self, data: &[u8; FRAME_BITS / 8]) -> Reading {
+        match self {
+            Model::Dht11 => {
+                // Integral and decimal part, the sign is the top bit of the decimal part
+                let temperature = data[2] as f32 + (data[3] & 0x0f) as f32 / 10.0;
+                Reading {
+                    humidity: data[0] as f32 + data[1] as f32 / 10.0,
+                    temperature: match data[3] & 0x80 {
+                        0 => temperature,
+                        _ => -temperature,
+                    },
+                }
+            }
+            Model::Dht22 => {
+                // Tenths, the temperature in sign and magnitude
+                let temperature = u16::from_be_bytes([data[2] & 0x7f, data[3]]) as f32 / 10.0;
+                Reading {
+                    humidity: u16::from_be_bytes([data[0], data[1]]) as f32 / 10.0,
+                    temperature: match data[2] & 0x80 {
+                        0 => temperature,
+                        _ => -temperature,
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// A reading of a DHT sensor
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Reading {
+    /// The relative humidity in %
+    pub humidity: f32,
+    /// The temperature in °C
+    pub temperature: f32,
+}
+
+/// A DHT sensor on an open drain pin, the timing is generated with busy waits
+///
+/// The pin needs a pull-up resistor, `4.7 kΩ` to `10 kΩ`. A reading blocks for the
+/// start signal and about `5 ms` of data.
+pub struct Dht<P, D> {
+    pin: P,
+    delay: D,
+    model: Model,
+}
+
+impl<P, D, E> Dht<P, D>
+where
+    P: InputPin<Error = E> + OutputPin<Error = E>,
+    D: DelayUs<u16>,
+{
+    /// Creates the sensor on `pin`, releasing it
+    /// # Arguments
+    /// * `pin` - The open drain pin the data line is connected to
+    /// * `delay` - Generates the start signal and paces the polling
+    /// * `model` - The kind of sensor
+    pub fn new(mut pin: P, delay: D, model: Model) -> Result<Self, E> {
+        pin.set_high()?;
+        Ok(Self { pin, delay, model })
+    }
+
+    /// Returns the kind of sensor
+    pub fn model(&self) -> Model {
+        self.model
+    }
+
+    /// Returns the pin and the delay of the sensor
+    pub fn release(self) -> (P, D) {
+        (self.pin, self.delay)
+    }
+
+    /// Reads the humidity and the temperature, at most once per [`Model::interval_ms`]
+    pub fn read(&mut self) -> Result<Reading, Error<E>> {
+        self.start()?;
+        self.receive()
+    }
+
+    /// Sends the start signal of a reading, [`Dht::receive`] has to follow right away
+    ///
+    /// The signal is not timing critical, it may be stretched by interrupts.
+    pub fn start(&mut self) -> Result<(), Error<E>> {
+        self.pin.set_low()?;
+        self.delay.delay_us(self.model.start_us());
+        Ok(())
+    }
+
+    /// Ends the start signal and receives the reading, in about `5 ms`
+    pub fn receive(&mut self) -> Result<Reading, Error<E>> {
+        self.pin.set_high()?;
+
+        // The sensor answers 20 - 40 µs later with 80 µs low and 80 µs high
+        self.pulse(true).map_err(|_| Error::NoResponse)?;
+        self.pulse(false).map_err(|_| Error::NoResponse)?;
+        self.pulse(true)?;
+
+        let mut data = [0u8; FRAME_BITS / 8];
+        for bit in 0..FRAME_BITS {
+            let low = self.pulse(false)?;
+            let high = self.pulse(true)?;
+            if high > low {
+                data[bit / 8] |= 0x80 >> (bit % 8);
+            }
+        }
+
+        let sum = data[0..4]
+            .iter()
+            .fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        if sum != data[4] {
+            return Err(Error::Checksum);
+        }
+
+        Ok(self.model.decode(&data))
+    }
+
+    /// Reads the sensor and updates the sensors bound to it, they are left without a
+    /// value if the reading failed
+    /// # Arguments
+    /// * `sensors` - The sensors of the readings
+    pub fn measure(&mut self, sensors: &DhtSensors) -> Result<(), Error<E>> {
+        let reading = self.read();
+        sensors.set_reading(reading.as_ref().ok());
+        reading.map(|_| ())
+    }
+
+    /// Waits for the pin to leave `level`
+    /// # Arguments
+    /// * `level` - The level of the pulse, `true` for high
+    /// # Returns
+    /// The length of the pulse in polls
+    fn pulse(&mut self, level: bool) -> Result<u16, Error<E>> {
+        for polls in 0..PULSE_TIMEOUT_POLLS {
+            if self.pin.is_high()? != level {
+                return Ok(polls);
+            }
+            self.delay.delay_us(1);
+        }
+
+        Err(Error::Timeout)
+    }
+}
+
+/// The humidity and temperature sensors of a DHT sensor
+pub struct DhtSensors<'a> {
+    /// The relative humidity in %
+    pub humidity: Sensor<'a, Option<f32>>,
+    /// The temperature in °C
+    pub temperature: Sensor<'a, Option<f32>>,
+}
+
+impl<'a> DhtSensors<'a> {
+    /// Creates the sensors, without a value until the first reading
+    /// # Arguments
+    /// * `humidity` - The friendly name and the `unique_id` of the humidity sensor
+    /// * `temperature` - The friendly name and the `unique_id` of the temperature sensor
+    pub fn new(humidity: (&'a str, &'a str), temperature: (&'a str, &'a str)) -> Self {
+        Self {
+            humidity: Sensor::new(
+                humidity.0,
+                humidity.1,
+                "%",
+                DeviceClass::Humidity,
+                StateClass::Measurement,
+                None,
+            ),
+            temperature: Sensor::new(
+                temperature.0,
+                temperature.1,
+                "°C",
+                DeviceClass::Temperature,
+                StateClass::Measurement,
+                None,
+            ),
+        }
+    }
+
+    /// Sets the values of both sensors
+    /// # Arguments
+    /// * `reading` - The reading, `None` if it failed
+    pub fn set_reading(&self, reading: Option<&Reading>) {
+        self.humidity.set_value(Some(reading.map(|r| r.humidity)));
+        self.temperature
+            .set_value(Some(reading.map(|r| r.temperature)));
+    }
+}
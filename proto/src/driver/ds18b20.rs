@@ -0,0 +1,489 @@
+//! The DS18B20 1-Wire thermometer, any number of them on one bus
+//!
+//! Probes are found with a ROM search and addressed by their ROM code. All probes
+//! convert at once, each one is read on its own afterwards. Probes whose temperature
+//! reached their alarm thresholds answer an alarm search.
+
+use super::onewire::{check_crc, match_rom, skip_rom, Error, OneWire, Rom, RomSearch, ROM_ID_LEN};
+use crate::homeassistant::{
+    binary_sensor::{BinarySensor, BinarySensorDeviceClass},
+    entity::DeviceClass,
+    sensor::{Sensor, StateClass},
+};
+
+/// The family code of the DS18B20
+pub const FAMILY_CODE: u8 = 0x28;
+/// Starts a temperature conversion
+pub const CMD_CONVERT_T: u8 = 0x44;
+/// Reads the 9 byte scratchpad, the temperature first and the CRC last
+pub const CMD_READ_SCRATCHPAD: u8 = 0xbe;
+/// Writes the alarm thresholds and the configuration register to the scratchpad
+pub const CMD_WRITE_SCRATCHPAD: u8 = 0x4e;
+/// Copies the alarm thresholds and the configuration register to the EEPROM
+pub const CMD_COPY_SCRATCHPAD: u8 = 0x48;
+
+/// The length of the scratchpad, the CRC included
+pub const SCRATCHPAD_LEN: usize = 9;
+/// How long a conversion may take until the probes are considered gone, a 12 bit
+/// conversion takes up to 750 ms
+pub const CONVERSION_TIMEOUT_MS: u16 = 1_000;
+/// How often the bus is polled for the end of a copy to the EEPROM, a poll takes about
+/// 70 µs and a copy up to 10 ms
+pub const COPY_POLLS: u16 = 200;
+/// Appended to the ROM code for the `unique_id` of the alarm binary sensor
+const ALARM_SUFFIX: &[u8] = b"-alarm";
+
+/// The resolution of the temperature, a finer one takes longer to convert
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Resolution {
+    /// 0.5 °C in 93.75 ms
+    Bits9 = 0,
+    /// 0.25 °C in 187.5 ms
+    Bits10 = 1,
+    /// 0.125 °C in 375 ms
+    Bits11 = 2,
+    /// 0.0625 °C in 750 ms, the power-up default
+    Bits12 = 3,
+}
+
+impl Resolution {
+    /// Returns the resolution set in a configuration register
+    /// # Arguments
+    /// * `register` - The configuration register
+    pub fn from_register(register: u8) -> Self {
+        match register >> 5 & 0b11 {
+            0 => Resolution::Bits9,
+            1 => Resolution::Bits10,
+            2 => Resolution::Bits11,
+            _ => Resolution::Bits12,
+        }
+    }
+
+    /// Returns the configuration register setting this resolution
+    pub fn register(&self) -> u8 {
+        (*self as u8) << 5 | 0x1f
+    }
+
+    /// Returns the maximum conversion time in milliseconds
+    pub fn conversion_ms(&self) -> u16 {
+        match self {
+            Resolution::Bits9 => 94,
+            Resolution::Bits10 => 188,
+            Resolution::Bits11 => 375,
+            Resolution::Bits12 => 750,
+        }
+    }
+}
+
+/// The settings of a probe, kept in its EEPROM
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// The resolution of the temperature
+    pub resolution: Resolution,
+    /// The alarm condition is met at and above this temperature in °C, `TH`
+    pub alarm_high: i8,
+    /// The alarm condition is met at and below this temperature in °C, `TL`
+    pub alarm_low: i8,
+}
+
+impl Config {
+    /// Returns the settings stored in a scratchpad
+    /// # Arguments
+    /// * `scratchpad` - The scratchpad read from the probe
+    pub fn from_scratchpad(scratchpad: &[u8; SCRATCHPAD_LEN]) -> Self {
+        Self {
+            resolution: Resolution::from_register(scratchpad[4]),
+            alarm_high: scratchpad[2] as i8,
+            alarm_low: scratchpad[3] as i8,
+        }
+    }
+}
+
+/// A DS18B20 found on the bus
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Probe {
+    /// The ROM code of the probe
+    pub rom: Rom,
+    /// The ROM code in text form followed by [`ALARM_SUFFIX`], the start of it is the
+    /// `unique_id` of the temperature sensor, all of it the one of the alarm
+    unique_id: [u8; ROM_ID_LEN + ALARM_SUFFIX.len()],
+}
+
+impl Probe {
+    /// Creates the probe with the ROM code `rom`
+    /// # Arguments
+    /// * `rom` - The ROM code of the probe
+    pub fn new(rom: Rom) -> Self {
+        let mut id = [0; ROM_ID_LEN];
+        rom.unique_id(&mut id);
+
+        let mut unique_id = [0; ROM_ID_LEN + ALARM_SUFFIX.len()];
+        unique_id[0..ROM_ID_LEN].copy_from_slice(&id);
+        unique_id[ROM_ID_LEN..].copy_from_slice(ALARM_SUFFIX);
+
+        Self { rom, unique_id }
+    }
+
+    /// Returns the ROM code in text form, e.g. `28-0316a2794bff`
+    pub fn unique_id(&self) -> &str {
+        // Only ASCII characters were written
+        core::str::from_utf8(&self.unique_id[0..ROM_ID_LEN]).unwrap_or_default()
+    }
+
+    /// Returns the `unique_id` of the alarm binary sensor, e.g. `28-0316a2794bff-alarm`
+    pub fn alarm_unique_id(&self) -> &str {
+        core::str::from_utf8(&self.unique_id).unwrap_or_default()
+    }
+
+    /// Creates the temperature sensor for this probe, its `unique_id` is the ROM code
+    /// # Arguments
+    /// * `name` - The friendly name for the sensor
+    pub fn sensor<'a>(&'a self, name: &'a str) -> Sensor<'a, Option<f32>> {
+        Sensor::new(
+            name,
+            self.unique_id(),
+            "°C",
+            DeviceClass::Temperature,
+            StateClass::Measurement,
+            None,
+        )
+    }
+
+    /// Creates the binary sensor reporting the alarm condition of this probe, the
+    /// temperature reached one of its alarm thresholds
+    /// # Arguments
+    /// * `name` - The friendly name for the binary sensor
+    pub fn alarm_sensor<'a>(&'a self, name: &'a str) -> BinarySensor<'a> {
+        BinarySensor::new(
+            name,
+            self.alarm_unique_id(),
+            BinarySensorDeviceClass::Problem,
+            None,
+        )
+    }
+
+    /// Reads the temperature of the last conversion
+    /// # Arguments
+    /// * `bus` - The bus the probe is on
+    pub fn read<B: OneWire>(&self, bus: &mut B) -> Result<f32, Error<B::Error>> {
+        Ok(temperature(&read_scratchpad(bus, &self.rom)?))
+    }
+
+    /// Reads the settings of the probe
+    /// # Arguments
+    /// * `bus` - The bus the probe is on
+    pub fn read_config<B: OneWire>(&self, bus: &mut B) -> Result<Config, Error<B::Error>> {
+        Ok(Config::from_scratchpad(&read_scratchpad(bus, &self.rom)?))
+    }
+
+    /// Changes the settings of the probe and stores them in its EEPROM, so they survive
+    /// a loss of power
+    ///
+    /// The probe needs an external power supply, parasite powered probes are not
+    /// supplied during the copy to the EEPROM.
+    /// # Arguments
+    /// * `bus` - The bus the probe is on
+    /// * `config` - The new settings
+    pub fn configure<B: OneWire>(
+        &self,
+        bus: &mut B,
+        config: &Config,
+    ) -> Result<(), Error<B::Error>> {
+        match_rom(bus, &self.rom)?;
+        bus.write_byte(CMD_WRITE_SCRATCHPAD)?;
+        bus.write_bytes(&[
+            config.alarm_high as u8,
+            config.alarm_low as u8,
+            config.resolution.register(),
+        ])?;
+
+        // Only copy what arrived intact
+        if self.read_config(bus)? != *config {
+            return Err(Error::Verify);
+        }
+
+        match_rom(bus, &self.rom)?;
+        bus.write_byte(CMD_COPY_SCRATCHPAD)?;
+        for _ in 0..COPY_POLLS {
+            // The probe sends `0` until the copy is done
+            if bus.read_bit()? {
+                return Ok(());
+            }
+        }
+
+        Err(Error::NoDevice)
+    }
+
+    /// Changes the settings of the probe unless it has them already, sparing the EEPROM
+    /// a write on every start of the node
+    /// # Arguments
+    /// * `bus` - The bus the probe is on
+    /// * `config` - The settings the probe is to have
+    pub fn ensure_config<B: OneWire>(
+        &self,
+        bus: &mut B,
+        config: &Config,
+    ) -> Result<(), Error<B::Error>> {
+        if self.read_config(bus)? == *config {
+            return Ok(());
+        }
+
+        self.configure(bus, config)
+    }
+}
+
+/// Searches the bus for DS18B20, other devices are skipped
+/// # Arguments
+/// * `bus` - The bus to search
+/// * `probes` - Receives the probes found, the search stops once it is full
+/// # Returns
+/// The amount of probes found and the error that stopped the search early, the
+/// probes found before the error are kept
+pub fn find_probes<B: OneWire>(
+    bus: &mut B,
+    probes: &mut [Probe],
+) -> (usize, Option<Error<B::Error>>) {
+    let mut search = RomSearch::new();
+    let mut found = 0;
+
+    while found < probes.len() {
+        match search.next(bus) {
+            Ok(Some(rom)) if rom.family() == FAMILY_CODE => {
+                probes[found] = Probe::new(rom);
+                found += 1;
+            }
+            Ok(Some(_)) => {}
+            Ok(None) => break,
+            Err(e) => return (found, Some(e)),
+        }
+    }
+
+    (found, None)
+}
+
+/// Starts a temperature conversion on all probes of the bus at once
+/// # Arguments
+/// * `bus` - The bus to start the conversion on
+pub fn start_conversion<B: OneWire>(bus: &mut B) -> Result<(), Error<B::Error>> {
+    skip_rom(bus)?;
+    bus.write_byte(CMD_CONVERT_T)?;
+    Ok(())
+}
+
+/// Returns true once the probes finished their conversion, they send `0` until then
+/// # Arguments
+/// * `bus` - The bus the conversion runs on
+pub fn conversion_done<B: OneWire>(bus: &mut B) -> Result<bool, Error<B::Error>> {
+    Ok(bus.read_bit()?)
+}
+
+/// Reads the scratchpad of a single probe and checks its CRC
+/// # Arguments
+/// * `bus` - The bus the probe is on
+/// * `rom` - The ROM code of the probe
+pub fn read_scratchpad<B: OneWire>(
+    bus: &mut B,
+    rom: &Rom,
+) -> Result<[u8; SCRATCHPAD_LEN], Error<B::Error>> {
+    let mut scratchpad = [0; SCRATCHPAD_LEN];
+
+    match_rom(bus, rom)?;
+    bus.write_byte(CMD_READ_SCRATCHPAD)?;
+    bus.read_bytes(&mut scratchpad)?;
+
+    // A probe that left the bus reads all '1's, which is no valid scratchpad
+    if scratchpad == [0xff; SCRATCHPAD_LEN] {
+        return Err(Error::NoDevice);
+    }
+    if !check_crc(&scratchpad) {
+        return Err(Error::Crc);
+    }
+
+    Ok(scratchpad)
+}
+
+/// Returns the temperature in °C stored in a scratchpad
+/// # Arguments
+/// * `scratchpad` - The scratchpad read from the probe
+pub fn temperature(scratchpad: &[u8; SCRATCHPAD_LEN]) -> f32 {
+    let resolution = Resolution::from_register(scratchpad[4]);
+
+    // Two's complement in sixteenths of a degree, the bits below the resolution are undefined
+    let undefined = (1 << (Resolution::Bits12 as u8 - resolution as u8)) - 1;
+    let raw = i16::from_le_bytes([scratchpad[0], scratchpad[1]]) & !undefined;
+
+    raw as f32 / 16.0
+}
+
+/// The progress of a [`Measurement`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MeasurementState {
+    /// No measurement is running
+    Idle,
+    /// The probes convert, for `elapsed_ms` so far
+    Converting { elapsed_ms: u16 },
+    /// The conversion is done, the probe `next` is read on the next tick
+    Reading { next: usize },
+    /// The probes are read, the next tick finds the next probe with an alarm condition
+    SearchingAlarms,
+}
+
+/// Measures the temperature of all probes of a bus without blocking, updating the bound
+/// sensors once the probes are read
+///
+/// [`Measurement::start`] starts the conversion on all probes and returns at once,
+/// [`Measurement::tick`] polls for its end and reads a single probe per tick, so the
+/// firmware keeps serving the bus in between. Sensors of probes that failed are left
+/// without a value.
+///
+/// With alarm binary sensors bound, an alarm search follows the reads, finding a
+/// single probe per tick. Alarms are reported for the first 32 probes.
+pub struct Measurement<'m, 'a> {
+    probes: &'m [Probe],
+    sensors: &'m [Sensor<'a, Option<f32>>],
+    alarms: &'m [BinarySensor<'a>],
+    state: MeasurementState,
+    alarm_search: RomSearch,
+    /// The probes found by the running alarm search, bit `0` being the first probe
+    alarmed: u32,
+}
+
+impl<'m, 'a> Measurement<'m, 'a> {
+    /// Creates a measurement for `probes`, updating `sensors`
+    /// # Arguments
+    /// * `probes` - The probes to read
+    /// * `sensors` - The sensors of `probes`, in the same order
+    pub fn new(probes: &'m [Probe], sensors: &'m [Sensor<'a, Option<f32>>]) -> Self {
+        Self {
+            probes,
+            sensors,
+            alarms: &[],
+            state: MeasurementState::Idle,
+            alarm_search: RomSearch::alarmed(),
+            alarmed: 0,
+        }
+    }
+
+    /// Reports the alarm conditions of the probes after every measurement
+    /// # Arguments
+    /// * `alarms` - The alarm binary sensors of the probes, in the same order
+    pub fn with_alarms(mut self, alarms: &'m [BinarySensor<'a>]) -> Self {
+        self.alarms = alarms;
+        self
+    }
+
+    /// Returns the progress of the measurement
+    pub fn state(&self) -> MeasurementState {
+        self.state
+    }
+
+    /// Starts the conversion on all probes, does nothing while a measurement is running
+    /// # Arguments
+    /// * `bus` - The bus the probes are on
+    pub fn start<B: OneWire>(&mut self, bus: &mut B) -> Result<(), Error<B::Error>> {
+        if self.state != MeasurementState::Idle || self.probes.is_empty() {
+            return Ok(());
+        }
+
+        match start_conversion(bus) {
+            Ok(()) => {
+                self.state = MeasurementState::Converting { elapsed_ms: 0 };
+                Ok(())
+            }
+            Err(e) => {
+                self.fail();
+                Err(e)
+            }
+        }
+    }
+
+    /// Advances the running measurement, call it regularly from the main loop
+    /// # Arguments
+    /// * `bus` - The bus the probes are on
+    /// * `elapsed_ms` - The milliseconds since the last tick
+    pub fn tick<B: OneWire>(
+        &mut self,
+        bus: &mut B,
+        elapsed_ms: u16,
+    ) -> Result<(), Error<B::Error>> {
+        match self.state {
+            MeasurementState::Idle => Ok(()),
+            MeasurementState::Converting {
+                elapsed_ms: elapsed,
+            } => {
+                let elapsed = elapsed.saturating_add(elapsed_ms);
+
+                match conversion_done(bus) {
+                    Ok(true) => self.state = MeasurementState::Reading { next: 0 },
+                    Ok(false) if elapsed < CONVERSION_TIMEOUT_MS => {
+                        self.state = MeasurementState::Converting {
+                            elapsed_ms: elapsed,
+                        }
+                    }
+                    Ok(false) => {
+                        self.fail();
+                        return Err(Error::NoDevice);
+                    }
+                    Err(e) => {
+                        self.fail();
+                        return Err(e);
+                    }
+                }
+                Ok(())
+            }
+            MeasurementState::Reading { next } => {
+                let read = self.probes[next].read(bus);
+                if let Some(sensor) = self.sensors.get(next) {
+                    sensor.set_value(Some(read.as_ref().ok().copied()));
+                }
+
+                self.state = match next + 1 {
+                    next if next < self.probes.len() => MeasurementState::Reading { next },
+                    _ if !self.alarms.is_empty() => {
+                        self.alarm_search = RomSearch::alarmed();
+                        self.alarmed = 0;
+                        MeasurementState::SearchingAlarms
+                    }
+                    _ => MeasurementState::Idle,
+                };
+                read.map(|_| ())
+            }
+            MeasurementState::SearchingAlarms => match self.alarm_search.next(bus) {
+                Ok(Some(rom)) => {
+                    if let Some(id) = self.probes.iter().position(|p| p.rom == rom) {
+                        self.alarmed |= 1u32.checked_shl(id as u32).unwrap_or(0);
+                    }
+                    Ok(())
+                }
+                Ok(None) => {
+                    for (id, alarm) in self.alarms.iter().enumerate().take(32) {
+                        alarm.set_state(Some(self.alarmed & 1 << id != 0));
+                    }
+                    self.state = MeasurementState::Idle;
+                    Ok(())
+                }
+                Err(e) => {
+                    self.set_alarms_unknown();
+                    self.state = MeasurementState::Idle;
+                    Err(e)
+                }
+            },
+        }
+    }
+
+    /// Ends the measurement, leaving all sensors without a value
+    fn fail(&mut self) {
+        for sensor in self.sensors {
+            sensor.set_value(Some(None));
+        }
+        self.set_alarms_unknown();
+        self.state = MeasurementState::Idle;
+    }
+
+    /// Sets the alarm conditions to unknown
+    fn set_alarms_unknown(&self) {
+        for alarm in self.alarms {
+            alarm.set_state(None);
+        }
+    }
+}
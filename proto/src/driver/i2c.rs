@@ -0,0 +1,91 @@
+//! Drivers for sensors on a shared I2C bus
+//!
+//! The bus is owned by the node and lent to each driver for a measurement, so any
+//! number of sensors at different addresses share it. Drivers implement [`I2cDriver`]
+//! and are measured in turn with [`measure_all`].
+
+use embedded_hal::blocking::i2c::{Write, WriteRead};
+
+/// An error talking to an I2C device
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error<E> {
+    /// The bus failed or no device acknowledged its address
+    Bus(E),
+    /// The device at the address is not a chip the driver supports, its chip id
+    UnknownChip(u8),
+    /// The calibration read from the device can't be valid
+    InvalidCalibration,
+}
+
+impl<E> From<E> for Error<E> {
+    fn from(e: E) -> Self {
+        Error::Bus(e)
+    }
+}
+
+/// A driver for a device on an I2C bus, updating the sensors bound to it
+pub trait I2cDriver<I2C, E> {
+    /// Reads the device and updates its sensors, they are left without a value if
+    /// the device failed
+    /// # Arguments
+    /// * `bus` - The bus the device is on
+    fn measure(&mut self, bus: &mut I2C) -> Result<(), Error<E>>;
+}
+
+/// Measures all drivers, a failing device does not keep the others from being read
+/// # Arguments
+/// * `bus` - The bus the devices are on
+/// * `drivers` - The drivers of the devices
+/// # Returns
+/// The first error of a device
+pub fn measure_all<I2C, E>(
+    bus: &mut I2C,
+    drivers: &mut [&mut dyn I2cDriver<I2C, E>],
+) -> Result<(), Error<E>> {
+    let mut result = Ok(());
+    for driver in drivers {
+        let measured = driver.measure(bus);
+        if result.is_ok() {
+            result = measured;
+        }
+    }
+    result
+}
+
+/// Reads consecutive registers, the device increments the register address
+/// # Arguments
+/// * `bus` - The bus the device is on
+/// * `address` - The 7 bit address of the device
+/// * `register` - The first register to read
+/// * `buf` - Receives the registers
+pub fn read_registers<I2C, E>(
+    bus: &mut I2C,
+    address: u8,
+    register: u8,
+    buf: &mut [u8],
+) -> Result<(), Error<E>>
+where
+    I2C: WriteRead<Error = E>,
+{
+    bus.write_read(address, &[register], buf)?;
+    Ok(())
+}
+
+/// Writes a single register
+/// # Arguments
+/// * `bus` - The bus the device is on
+/// * `address` - The 7 bit address of the device
+/// * `register` - The register to write
+/// * `value` - The value to write
+pub fn write_register<I2C, E>(
+    bus: &mut I2C,
+    address: u8,
+    register: u8,
+    value: u8,
+) -> Result<(), Error<E>>
+where
+    I2C: Write<Error = E>,
+{
+    bus.write(address, &[register, value])?;
+    Ok(())
+}
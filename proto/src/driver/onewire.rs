@@ -0,0 +1,379 @@
+//! The 1-Wire bus: bit-banged timing, ROM search and device addressing
+//!
+//! Every transaction starts with a reset, followed by a ROM command selecting the
+//! devices that take the following function command. Devices are told apart by their
+//! 64 bit ROM code: the family code, a 48 bit serial number and a CRC over both.
+
+use embedded_hal::{
+    blocking::delay::DelayUs,
+    digital::v2::{InputPin, OutputPin},
+};
+
+use crate::crc::{CRC8Maxim, CRC};
+
+/// Enumerates the ROM codes of all devices, see [`RomSearch`]
+pub const CMD_SEARCH_ROM: u8 = 0xf0;
+/// Selects the single device with the ROM code that follows
+pub const CMD_MATCH_ROM: u8 = 0x55;
+/// Selects all devices on the bus
+pub const CMD_SKIP_ROM: u8 = 0xcc;
+/// Enumerates the ROM codes of the devices with an alarm condition
+pub const CMD_ALARM_SEARCH: u8 = 0xec;
+
+/// The length of [`Rom::unique_id`], e.g. `28-0316a2794bff`
+pub const ROM_ID_LEN: usize = 15;
+
+/// An error talking to 1-Wire devices
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error<E> {
+    /// The pin of the bus failed
+    Bus(E),
+    /// No device answered
+    NoDevice,
+    /// The data read does not match its CRC
+    Crc,
+    /// The data read back differs from the data written
+    Verify,
+}
+
+impl<E> From<E> for Error<E> {
+    fn from(e: E) -> Self {
+        Error::Bus(e)
+    }
+}
+
+/// A 1-Wire bus master
+///
+/// Only the bit level has to be implemented, bytes are sent LSB first.
+pub trait OneWire {
+    /// The error of the underlying pin
+    type Error;
+
+    /// Resets all devices on the bus
+    /// # Returns
+    /// `true` if at least one device answered with a presence pulse
+    fn reset(&mut self) -> Result<bool, Self::Error>;
+
+    /// Writes a single bit
+    /// # Arguments
+    /// * `bit` - The bit to write
+    fn write_bit(&mut self, bit: bool) -> Result<(), Self::Error>;
+
+    /// Reads a single bit, the bus reads `true` unless a device pulls it low
+    fn read_bit(&mut self) -> Result<bool, Self::Error>;
+
+    /// Writes a byte, LSB first
+    /// # Arguments
+    /// * `byte` - The byte to write
+    fn write_byte(&mut self, byte: u8) -> Result<(), Self::Error> {
+        for i in 0..8 {
+            self.write_bit(byte & 1 << i != 0)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a byte, LSB first
+    fn read_byte(&mut self) -> Result<u8, Self::Error> {
+        let mut byte = 0;
+        for i in 0..8 {
+            if self.read_bit()? {
+                byte |= 1 << i;
+            }
+        }
+        Ok(byte)
+    }
+
+    /// Writes all bytes of `bytes`
+    /// # Arguments
+    /// * `bytes` - The bytes to write
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        for byte in bytes {
+            self.write_byte(*byte)?;
+        }
+        Ok(())
+    }
+
+    /// Reads as many bytes as fit into `bytes`
+    /// # Arguments
+    /// * `bytes` - Receives the bytes read
+    fn read_bytes(&mut self, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        for byte in bytes {
+            *byte = self.read_byte()?;
+        }
+        Ok(())
+    }
+}
+
+/// A 1-Wire bus on an open drain pin, the timing is generated with busy waits
+///
+/// The bus needs a pull-up resistor, `4.7 kΩ` for the usual cable lengths. An interrupt
+/// within a time slot corrupts it, a node with interrupts disables them for each slot.
+pub struct OneWirePin<P, D> {
+    pin: P,
+    delay: D,
+}
+
+impl<P, D, E> OneWirePin<P, D>
+where
+    P: InputPin<Error = E> + OutputPin<Error = E>,
+    D: DelayUs<u16>,
+{
+    /// Creates a bus on `pin`, releasing it
+    /// # Arguments
+    /// * `pin` - The open drain pin the bus is connected to
+    /// * `delay` - Generates the timing of the bus
+    pub fn new(mut pin: P, delay: D) -> Result<Self, E> {
+        pin.set_high()?;
+        Ok(Self { pin, delay })
+    }
+
+    /// Returns the pin and the delay of the bus
+    pub fn release(self) -> (P, D) {
+        (self.pin, self.delay)
+    }
+
+    /// Resets the bus like [`OneWire::reset`], only the release of the bus and the
+    /// sampling of the presence pulse are timing critical
+    /// # Arguments
+    /// * `critical` - Runs the critical part it is given, e.g. with interrupts disabled
+    /// # Returns
+    /// True if a device answered with a presence pulse
+    pub fn reset_with<C>(&mut self, critical: C) -> Result<bool, E>
+    where
+        C: FnOnce(&mut dyn FnMut() -> Result<bool, E>) -> Result<bool, E>,
+    {
+        // A longer reset pulse or recovery time does no harm
+        self.pin.set_low()?;
+        self.delay.delay_us(480);
+
+        // Devices answer 15 - 60 µs later by pulling the bus low for 60 - 240 µs
+        let present = critical(&mut || {
+            self.pin.set_high()?;
+            self.delay.delay_us(70);
+            self.pin.is_low()
+        })?;
+        self.delay.delay_us(410);
+
+        Ok(present)
+    }
+}
+
+impl<P, D, E> OneWire for OneWirePin<P, D>
+where
+    P: InputPin<Error = E> + OutputPin<Error = E>,
+    D: DelayUs<u16>,
+{
+    type Error = E;
+
+    fn reset(&mut self) -> Result<bool, E> {
+        self.reset_with(|critical| critical())
+    }
+
+    fn write_bit(&mut self, bit: bool) -> Result<(), E> {
+        self.pin.set_low()?;
+        if bit {
+            self.delay.delay_us(6);
+            self.pin.set_high()?;
+            self.delay.delay_us(64);
+        } else {
+            self.delay.delay_us(60);
+            self.pin.set_high()?;
+            self.delay.delay_us(10);
+        }
+        Ok(())
+    }
+
+    fn read_bit(&mut self) -> Result<bool, E> {
+        self.pin.set_low()?;
+        self.delay.delay_us(6);
+        self.pin.set_high()?;
+
+        // A device sending a `0` holds the bus low for at least 15 µs from the start
+        self.delay.delay_us(9);
+        let bit = self.pin.is_high()?;
+        self.delay.delay_us(55);
+
+        Ok(bit)
+    }
+}
+
+/// The ROM code of a 1-Wire device, the family code first
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Rom(pub [u8; 8]);
+
+impl Rom {
+    /// Returns the family code, the kind of device
+    pub fn family(&self) -> u8 {
+        self.0[0]
+    }
+
+    /// Returns true if the CRC of the ROM code matches
+    pub fn is_valid(&self) -> bool {
+        check_crc(&self.0)
+    }
+
+    /// Writes the ROM code as the family code and the serial number in hex, the way
+    /// Linux names 1-Wire devices, e.g. `28-0316a2794bff`
+    /// # Arguments
+    /// * `buf` - Receives the characters
+    /// # Returns
+    /// The ROM code as a string in `buf`
+    pub fn unique_id<'b>(&self, buf: &'b mut [u8; ROM_ID_LEN]) -> &'b str {
+        const HEX: &[u8; 16] = b"0123456789abcdef";
+
+        buf[0] = HEX[(self.0[0] >> 4) as usize];
+        buf[1] = HEX[(self.0[0] & 0xf) as usize];
+        buf[2] = b'-';
+        // The serial number is sent LSB first
+        for (i, byte) in self.0[1..7].iter().rev().enumerate() {
+            buf[3 + 2 * i] = HEX[(byte >> 4) as usize];
+            buf[4 + 2 * i] = HEX[(byte & 0xf) as usize];
+        }
+
+        // Only ASCII hex digits were written
+        core::str::from_utf8(buf).unwrap_or_default()
+    }
+}
+
+/// Returns true if the last byte of `data` is the CRC of the bytes before it
+/// # Arguments
+/// * `data` - The data followed by its CRC
+pub fn check_crc(data: &[u8]) -> bool {
+    let mut crc = CRC8Maxim::new();
+    crc.update(data);
+    !data.is_empty() && crc.finalize() == 0
+}
+
+/// Enumerates the ROM codes of the devices on a bus, one device per call to
+/// [`RomSearch::next`]
+///
+/// Implements the search algorithm of Maxim application note 187: every bit of the
+/// ROM code is read from all devices at once, taking the `1` branch at the last
+/// position both values were seen in the previous pass.
+#[derive(Clone, Debug)]
+pub struct RomSearch {
+    command: u8,
+    rom: [u8; 8],
+    /// The bit position of the last branch that took `0` in the previous pass, `0` for none
+    last_discrepancy: u8,
+    done: bool,
+}
+
+impl RomSearch {
+    /// Creates a search for all devices
+    pub const fn new() -> Self {
+        Self::with_command(CMD_SEARCH_ROM)
+    }
+
+    /// Creates a search for the devices with an alarm condition
+    pub const fn alarmed() -> Self {
+        Self::with_command(CMD_ALARM_SEARCH)
+    }
+
+    const fn with_command(command: u8) -> Self {
+        Self {
+            command,
+            rom: [0; 8],
+            last_discrepancy: 0,
+            done: false,
+        }
+    }
+
+    /// Finds the next device
+    /// # Arguments
+    /// * `bus` - The bus to search
+    /// # Returns
+    /// The ROM code of the next device, `None` if all devices have been found
+    pub fn next<B: OneWire>(&mut self, bus: &mut B) -> Result<Option<Rom>, Error<B::Error>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        if !bus.reset()? {
+            self.done = true;
+            return Ok(None);
+        }
+        bus.write_byte(self.command)?;
+
+        let mut last_zero = 0;
+        for position in 1..=64u8 {
+            let byte = ((position - 1) / 8) as usize;
+            let mask = 1 << ((position - 1) % 8);
+
+            let bit = bus.read_bit()?;
+            let complement = bus.read_bit()?;
+            let direction = match (bit, complement) {
+                (true, true) => {
+                    // No device takes part, the devices left the bus during the search
+                    self.done = true;
+                    return match position {
+                        1 => Ok(None),
+                        _ => Err(Error::NoDevice),
+                    };
+                }
+                (false, false) => {
+                    // Devices with both values, repeat the previous pass up to the
+                    // last discrepancy and take the `1` branch there
+                    let direction = match position.cmp(&self.last_discrepancy) {
+                        core::cmp::Ordering::Less => self.rom[byte] & mask != 0,
+                        core::cmp::Ordering::Equal => true,
+                        core::cmp::Ordering::Greater => false,
+                    };
+                    if !direction {
+                        last_zero = position;
+                    }
+                    direction
+                }
+                (bit, _) => bit,
+            };
+
+            if direction {
+                self.rom[byte] |= mask;
+            } else {
+                self.rom[byte] &= !mask;
+            }
+            bus.write_bit(direction)?;
+        }
+
+        self.last_discrepancy = last_zero;
+        self.done = last_zero == 0;
+
+        let rom = Rom(self.rom);
+        if !rom.is_valid() {
+            return Err(Error::Crc);
+        }
+        Ok(Some(rom))
+    }
+}
+
+impl Default for RomSearch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Resets the bus and selects a single device for the following function command
+/// # Arguments
+/// * `bus` - The bus the device is on
+/// * `rom` - The ROM code of the device
+pub fn match_rom<B: OneWire>(bus: &mut B, rom: &Rom) -> Result<(), Error<B::Error>> {
+    if !bus.reset()? {
+        return Err(Error::NoDevice);
+    }
+
+    bus.write_byte(CMD_MATCH_ROM)?;
+    bus.write_bytes(&rom.0)?;
+    Ok(())
+}
+
+/// Resets the bus and selects all devices for the following function command
+/// # Arguments
+/// * `bus` - The bus to address
+pub fn skip_rom<B: OneWire>(bus: &mut B) -> Result<(), Error<B::Error>> {
+    if !bus.reset()? {
+        return Err(Error::NoDevice);
+    }
+
+    bus.write_byte(CMD_SKIP_ROM)?;
+    Ok(())
+}
@@ -0,0 +1,53 @@
+/// The error codes a node responds with if it can't handle a request
+///
+/// Error responses use the response command with [`CMD_ERROR_FLAG`](crate::command::CMD_ERROR_FLAG)
+/// set and carry the error code as the only payload byte
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ErrorCode {
+    /// The command is not known to the node
+    UnknownCommand = 1,
+    /// The payload is too short for the command
+    BadPayloadLength = 2,
+    /// The requested entity index does not exist
+    EntityOutOfRange = 3,
+    /// The entity can't handle the request at the moment
+    EntityBusy = 4,
+    /// The hardware behind the entity failed
+    HardwareFault = 5,
+    /// A value in the payload is not valid for the command
+    InvalidArgument = 6,
+    /// The node refuses the request, e.g. a serial number does not match
+    NotPermitted = 7,
+}
+
+impl ErrorCode {
+    /// Returns the ErrorCode for its identifier on the wire
+    /// # Arguments
+    /// * `value` - The identifier to look up
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(ErrorCode::UnknownCommand),
+            2 => Some(ErrorCode::BadPayloadLength),
+            3 => Some(ErrorCode::EntityOutOfRange),
+            4 => Some(ErrorCode::EntityBusy),
+            5 => Some(ErrorCode::HardwareFault),
+            6 => Some(ErrorCode::InvalidArgument),
+            7 => Some(ErrorCode::NotPermitted),
+            _ => None,
+        }
+    }
+
+    /// Returns a human readable description of the error
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::UnknownCommand => "unknown command",
+            ErrorCode::BadPayloadLength => "bad payload length",
+            ErrorCode::EntityOutOfRange => "entity index out of range",
+            ErrorCode::EntityBusy => "entity busy",
+            ErrorCode::HardwareFault => "hardware fault",
+            ErrorCode::InvalidArgument => "invalid argument",
+            ErrorCode::NotPermitted => "not permitted",
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Changes of entities a node reports without being polled for every entity
+//!
+//! Entities flag changes the master did not request, e.g. a sensor value moving beyond
+//! its deadband. The node collects them into its [`EventQueue`] and the master fetches
+//! them with [`CMD_EVENTS_FETCH`](crate::command::CMD_EVENTS_FETCH).
+
+use crate::homeassistant::Entities;
+
+/// The amount of events a node keeps until the master fetches them
+pub const EVENT_QUEUE_LEN: usize = 16;
+
+/// Event kind of a sensor value change
+pub const EVENT_SENSOR: u8 = 0x01;
+/// Event kind of a switch state change
+pub const EVENT_SWITCH: u8 = 0x02;
+/// Event kind of a binary sensor state change
+pub const EVENT_BINARY_SENSOR: u8 = 0x03;
+/// Event kind of a number value change
+pub const EVENT_NUMBER: u8 = 0x04;
+/// Event kind of a light state change
+pub const EVENT_LIGHT: u8 = 0x05;
+/// Event kind of a cover state change
+pub const EVENT_COVER: u8 = 0x06;
+/// Event kind of a select option change
+pub const EVENT_SELECT: u8 = 0x07;
+
+/// Response flag: events were dropped as the queue was full, the master has to poll all entities
+pub const EVENTS_OVERFLOWED: u8 = 1 << 0;
+/// Response flag: more events are pending than fit into the response
+pub const EVENTS_PENDING: u8 = 1 << 1;
+
+/// An entity that changed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// The value of the sensor with the index changed
+    Sensor(u32),
+    /// The state of the switch with the index changed
+    Switch(u32),
+    /// The state of the binary sensor with the index changed
+    BinarySensor(u32),
+    /// The value of the number with the index changed
+    Number(u32),
+    /// The state of the light with the index changed
+    Light(u32),
+    /// The state of the cover with the index changed
+    Cover(u32),
+    /// The selected option of the select with the index changed
+    Select(u32),
+}
+
+impl Event {
+    /// Returns the event kind identifier on the wire
+    pub fn kind(&self) -> u8 {
+        match self {
+            Event::Sensor(_) => EVENT_SENSOR,
+            Event::Switch(_) => EVENT_SWITCH,
+            Event::BinarySensor(_) => EVENT_BINARY_SENSOR,
+            Event::Number(_) => EVENT_NUMBER,
+            Event::Light(_) => EVENT_LIGHT,
+            Event::Cover(_) => EVENT_COVER,
+            Event::Select(_) => EVENT_SELECT,
+        }
+    }
+
+    /// Returns the index of the entity that changed
+    pub fn id(&self) -> u32 {
+        match self {
+            Event::Sensor(id)
+            | Event::Switch(id)
+            | Event::BinarySensor(id)
+            | Event::Number(id)
+            | Event::Light(id)
+            | Event::Cover(id)
+            | Event::Select(id) => *id,
+        }
+    }
+}
+
+/// The changes waiting to be fetched by the master
+///
+/// Every entity is queued at most once, the master reads the current value when fetching.
+pub struct EventQueue {
+    events: [Event; EVENT_QUEUE_LEN],
+    len: usize,
+    /// Events were dropped since the last fetch
+    pub overflowed: bool,
+}
+
+impl EventQueue {
+    /// Creates an empty queue
+    pub const fn new() -> Self {
+        Self {
+            events: [Event::Sensor(0); EVENT_QUEUE_LEN],
+            len: 0,
+            overflowed: false,
+        }
+    }
+
+    /// Queues an event, unless the entity is already queued
+    /// # Arguments
+    /// * `event` - The event to queue
+    /// # Returns
+    /// False if the queue is full and the event got dropped
+    pub fn push(&mut self, event: Event) -> bool {
+        if self.events().contains(&event) {
+            return true;
+        }
+
+        if self.len == EVENT_QUEUE_LEN {
+            self.overflowed = true;
+            return false;
+        }
+
+        self.events[self.len] = event;
+        self.len += 1;
+
+        true
+    }
+
+    /// Queues the changes the entities flagged since the last poll
+    /// # Arguments
+    /// * `entities` - The entities of the node
+    pub fn poll(&mut self, entities: &mut Entities) {
+        for (id, sensor) in entities.sensors.iter().enumerate() {
+            if sensor.take_changed() {
+                self.push(Event::Sensor(id as u32));
+            }
+        }
+
+        for (id, switch) in entities.switches.iter_mut().enumerate() {
+            if switch.take_changed() {
+                self.push(Event::Switch(id as u32));
+            }
+        }
+
+        for (id, binary_sensor) in entities.binary_sensors.iter().enumerate() {
+            if binary_sensor.take_changed() {
+                self.push(Event::BinarySensor(id as u32));
+            }
+        }
+
+        for (id, number) in entities.numbers.iter_mut().enumerate() {
+            if number.take_changed() {
+                self.push(Event::Number(id as u32));
+            }
+        }
+
+        for (id, light) in entities.lights.iter_mut().enumerate() {
+            if light.take_changed() {
+                self.push(Event::Light(id as u32));
+            }
+        }
+
+        for (id, cover) in entities.covers.iter_mut().enumerate() {
+            if cover.take_changed() {
+                self.push(Event::Cover(id as u32));
+            }
+        }
+
+        for (id, select) in entities.selects.iter_mut().enumerate() {
+            if select.take_changed() {
+                self.push(Event::Select(id as u32));
+            }
+        }
+    }
+
+    /// Returns the queued events, oldest first
+    pub fn events(&self) -> &[Event] {
+        &self.events[0..self.len]
+    }
+
+    /// Removes the `count` oldest events
+    /// # Arguments
+    /// * `count` - The amount of events to remove
+    pub fn remove(&mut self, count: usize) {
+        let count = count.min(self.len);
+
+        self.events.copy_within(count..self.len, 0);
+        self.len -= count;
+    }
+
+    /// Returns the amount of queued events
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if no events are queued
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Default for EventQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,1113 @@
+use crate::{
+    command::*,
+    datalink::{serial_has_prefix, Delivery, Serial, SERIAL_LEN},
+    error::ErrorCode,
+    event::*,
+    homeassistant::{
+        binary_sensor::BinarySensorRef,
+        cover::{CoverRequest, CoverState, COVER_POSITION_OPEN},
+        light::{LightRequest, LightState},
+        number::NumberRequest,
+        select::SelectRequest,
+        sensor::{PayloadType, SensorRef, SensorValue},
+        switch::SwitchRequest,
+        Entities,
+    },
+    node::Node,
+    tlv::*,
+    DataFrame,
+};
+
+pub struct HandlerPins {}
+
+/// Processes a received frame the way a node does
+///
+/// Drops frames with an invalid CRC or for other addresses, runs
+/// [`handle_frame`] and addresses the response back to the sender.
+/// If the handler fails, the response becomes an error response.
+/// Broadcast and multicast frames are handled, but never responded to.
+/// # Arguments
+/// * `frame` - The received frame to process and mutate for responses
+/// * `node` - This node, its address is changed by [`CMD_NODE_SET_ADDR`]
+/// * `pins` - Pins that are exposed for the handler
+/// * `entities` - The entities to handle
+/// # Returns
+/// True if the modified frame is to be sent
+pub fn handle_request(
+    frame: &mut DataFrame,
+    node: &mut Node,
+    pins: &mut HandlerPins,
+    entities: &mut Entities,
+) -> bool {
+    if !frame.check_crc() {
+        return false;
+    }
+
+    let delivery = match node.delivery(frame.dst) {
+        None => return false,
+        Some(delivery) => delivery,
+    };
+
+    // Respond from the address the request was sent to, even if it changes
+    let src = node.addr;
+    let mut cmd = frame.cmd.wrapping_add(1);
+
+    if frame.cmd == CMD_NODE_DISCOVER {
+        // Discovery is the only request matching nodes respond to even when
+        // broadcast, it is silent on errors as many nodes may share an address
+        if !handle_discover(frame, node) {
+            return false;
+        }
+    } else {
+        // Fetched events are removed, they would get lost without a response
+        if frame.cmd == CMD_EVENTS_FETCH && delivery == Delivery::Multicast {
+            return false;
+        }
+
+        let res = match frame.cmd {
+            CMD_NODE_SERIAL | CMD_NODE_SET_ADDR | CMD_NODE_INFO => handle_node_frame(frame, node),
+            CMD_EVENTS_FETCH => {
+                node.events.poll(entities);
+                set_payload_events(frame, &mut node.events, entities);
+                Ok(())
+            }
+            _ => handle_frame(frame, pins, entities),
+        };
+
+        if let Err(e) = res {
+            cmd |= CMD_ERROR_FLAG;
+            frame.payload_len = 1;
+            frame.payload[0] = e as u8;
+        }
+
+        if delivery == Delivery::Multicast {
+            return false;
+        }
+    }
+
+    // Set addresses
+    frame.dst = frame.src;
+    frame.src = src;
+    frame.cmd = cmd;
+
+    true
+}
+
+/// Handles [`CMD_NODE_DISCOVER`]
+/// # Arguments
+/// * `frame` - The frame to process and mutate for responses
+/// * `node` - This node
+/// # Returns
+/// True if the serial number of this node matches and the response is to be sent
+fn handle_discover(frame: &mut DataFrame, node: &Node) -> bool {
+    let serial = match node.serial {
+        Some(serial) => serial,
+        None => return false,
+    };
+
+    if frame.payload_len as usize != 1 + SERIAL_LEN {
+        return false;
+    }
+
+    let mut prefix = Serial::default();
+    prefix.copy_from_slice(&frame.payload[1..1 + SERIAL_LEN]);
+
+    if !serial_has_prefix(&serial, &prefix, frame.payload[0] as usize) {
+        return false;
+    }
+
+    frame.payload_len = SERIAL_LEN as u8;
+    frame.payload[0..SERIAL_LEN].copy_from_slice(&serial);
+
+    true
+}
+
+/// Handles the commands managing the node itself
+/// # Arguments
+/// * `frame` - The frame to process and mutate for responses
+/// * `node` - This node
+/// # Returns
+/// The error to respond with if the request can't be handled
+fn handle_node_frame(frame: &mut DataFrame, node: &mut Node) -> Result<(), ErrorCode> {
+    match frame.cmd {
+        CMD_NODE_SERIAL => {
+            // Serial number
+            let serial = node.serial.unwrap_or_default();
+            let len = if node.serial.is_some() { SERIAL_LEN } else { 0 };
+
+            frame.payload_len = len as u8;
+            frame.payload[0..len].copy_from_slice(&serial[0..len]);
+        }
+        CMD_NODE_SET_ADDR => {
+            // Set address
+            let serial = match frame.payload_len as usize {
+                2 => None,
+                len if len == 2 + SERIAL_LEN => Some(&frame.payload[2..len]),
+                _ => return Err(ErrorCode::BadPayloadLength),
+            };
+            let addr = u16::from_le_bytes([frame.payload[0], frame.payload[1]]);
+
+            node.change(addr, serial)?;
+
+            frame.payload_len = 2;
+        }
+        CMD_NODE_INFO => {
+            // Device information
+            node.info.to_payload(frame);
+        }
+        _ => return Err(ErrorCode::UnknownCommand),
+    }
+
+    Ok(())
+}
+
+/// Handles an incoming frame and mutates the incoming frame for a response.
+///
+/// The `frame` argument gets mutated and prepared as the response payload.
+/// # Arguments
+/// * `frame` - The frame to process and mutate for responses
+/// * `pins` - Pins that are exposed for the handler
+/// * `entities` - The entities to handle
+/// # Returns
+/// The error to respond with if the request can't be handled
+pub fn handle_frame(
+    frame: &mut DataFrame,
+    _pins: &mut HandlerPins,
+    entities: &mut Entities,
+) -> Result<(), ErrorCode> {
+    let sensors = entities.sensors;
+    let switches = &mut *entities.switches;
+    let binary_sensors = entities.binary_sensors;
+    let numbers = &mut *entities.numbers;
+    let buttons = &mut *entities.buttons;
+    let lights = &mut *entities.lights;
+    let covers = &mut *entities.covers;
+    let selects = &mut *entities.selects;
+
+    match frame.cmd {
+        CMD_ECHO => {
+            // Echo
+
+            // We do not do anything, the payload stays the same
+        }
+        CMD_SENSOR_COUNT => {
+            // sensor count
+            let num_sensors = sensors.len() as u32;
+
+            frame.payload_len = 4;
+            pack_u32(num_sensors, &mut frame.payload[0..4]);
+        }
+        CMD_SENSOR_UNIQUE_ID => {
+            // Sensor unique_id
+            let sensor_id = entity_id(frame, sensors.len())?;
+
+            set_payload_str(frame, sensors[sensor_id].get_unique_id());
+        }
+        CMD_SENSOR_NATIVE_UNIT_OF_MEASUREMENT => {
+            // Sensor native_unit_of_measurement
+            let sensor_id = entity_id(frame, sensors.len())?;
+
+            set_payload_str(frame, sensors[sensor_id].get_native_unit_of_measurement());
+        }
+        CMD_SENSOR_DEVICE_CLASS => {
+            // Sensor device_class
+            let sensor_id = entity_id(frame, sensors.len())?;
+
+            set_payload_str(frame, sensors[sensor_id].get_device_class().as_str());
+        }
+        CMD_SENSOR_STATE_CLASS => {
+            // Sensor state_class
+            let sensor_id = entity_id(frame, sensors.len())?;
+
+            set_payload_str(frame, sensors[sensor_id].get_state_class().as_str());
+        }
+        CMD_SENSOR_NAME => {
+            // Sensor name
+            let sensor_id = entity_id(frame, sensors.len())?;
+
+            set_payload_str(frame, sensors[sensor_id].get_name());
+        }
+        CMD_SENSOR_VALUE => {
+            // Sensor value
+            let sensor_id = entity_id(frame, sensors.len())?;
+
+            sensors[sensor_id].get_payload(&mut frame.payload_len, &mut frame.payload)?;
+        }
+        CMD_SENSOR_DESCRIBE => {
+            // Sensor descriptor
+            let sensor = sensors[entity_id(frame, sensors.len())?];
+
+            // The value goes first, it is written to the start of the payload
+            // and moved behind its tag and length
+            frame.payload_len = 0;
+            let value_len = match sensor.get_payload(&mut frame.payload_len, &mut frame.payload) {
+                Ok(()) => Some(fitting_len(
+                    &frame.payload,
+                    frame.payload_len as usize,
+                    u8::MAX as usize - 2,
+                )),
+                Err(_) => None,
+            };
+            frame.payload_len = 0;
+
+            if let Some(len) = value_len {
+                frame.payload.copy_within(0..len, 2);
+                frame.payload[0] = TAG_VALUE;
+                frame.payload[1] = len as u8;
+                frame.payload_len = 2 + len as u8;
+            }
+
+            let mut tlv = TlvWriter::new(frame);
+            tlv.push_str(TAG_UNIQUE_ID, sensor.get_unique_id());
+            tlv.push_str(TAG_NAME, sensor.get_name());
+            tlv.push_str(
+                TAG_NATIVE_UNIT_OF_MEASUREMENT,
+                sensor.get_native_unit_of_measurement(),
+            );
+            tlv.push_str(TAG_DEVICE_CLASS, sensor.get_device_class().as_str());
+            tlv.push_str(TAG_STATE_CLASS, sensor.get_state_class().as_str());
+        }
+        CMD_SENSOR_VALUES => {
+            // Values of many sensors
+            let first = unpack_u32(&frame.payload[0..frame.payload_len as usize])
+                .ok_or(ErrorCode::BadPayloadLength)? as usize;
+            let mask = match frame.payload_len {
+                4 => None,
+                8 => unpack_u32(&frame.payload[4..8]),
+                _ => return Err(ErrorCode::BadPayloadLength),
+            };
+
+            // Starting behind the last sensor is allowed to read nodes without sensors
+            if first > sensors.len() {
+                return Err(ErrorCode::EntityOutOfRange);
+            }
+
+            set_payload_sensor_values(frame, sensors, first, mask);
+        }
+        CMD_SWITCH_COUNT => {
+            // Switch discovery
+            let num = switches.len() as u32;
+
+            frame.payload_len = 4;
+            pack_u32(num, &mut frame.payload[0..4]);
+        }
+        CMD_SWITCH_UNIQUE_ID => {
+            // Switch unique_id
+            let switch_id = entity_id(frame, switches.len())?;
+
+            set_payload_str(frame, switches[switch_id].get_unique_id());
+        }
+        CMD_SWITCH_NAME => {
+            // Switch name
+            let switch_id = entity_id(frame, switches.len())?;
+
+            set_payload_str(frame, switches[switch_id].get_name());
+        }
+        CMD_SWITCH_STATE => {
+            // Switch state
+            let switch_id = entity_id(frame, switches.len())?;
+
+            frame.payload_len = 1;
+            frame.payload[0] = switches[switch_id].exec_request(SwitchRequest::Get)? as u8;
+        }
+        CMD_SWITCH_EXEC => {
+            // Switch exec
+            if frame.payload_len < 5 {
+                return Err(ErrorCode::BadPayloadLength);
+            }
+
+            let req = match frame.payload[4] {
+                SWITCH_EXEC_TURN_OFF => SwitchRequest::TurnOFF,
+                SWITCH_EXEC_TURN_ON => SwitchRequest::TurnON,
+                SWITCH_EXEC_TOGGLE => SwitchRequest::Toggle,
+                _ => return Err(ErrorCode::InvalidArgument),
+            };
+
+            if unpack_u32(&frame.payload[0..4]) == Some(SWITCH_ID_ALL) {
+                // Execute on all switches, respond with all states
+                // The master knows about the changes it requested, they are no events
+                for switch in switches.iter_mut() {
+                    switch.exec_request(req)?;
+                    switch.take_changed();
+                }
+
+                let len = switches.len().min(u8::MAX as usize);
+                for (i, switch) in switches[0..len].iter_mut().enumerate() {
+                    frame.payload[i] = switch.exec_request(SwitchRequest::Get)? as u8;
+                }
+                frame.payload_len = len as u8;
+
+                return Ok(());
+            }
+
+            let switch_id = entity_id(frame, switches.len())?;
+
+            switches[switch_id].exec_request(req)?;
+            switches[switch_id].take_changed();
+
+            frame.payload_len = 1;
+            frame.payload[0] = switches[switch_id].exec_request(SwitchRequest::Get)? as u8;
+        }
+        CMD_SWITCH_DESCRIBE => {
+            // Switch descriptor
+            let switch = &mut switches[entity_id(frame, switches.len())?];
+
+            frame.payload_len = 0;
+            let mut tlv = TlvWriter::new(frame);
+            if let Ok(state) = switch.exec_request(SwitchRequest::Get) {
+                tlv.push(TAG_STATE, &[state as u8]);
+            }
+            tlv.push_str(TAG_UNIQUE_ID, switch.get_unique_id());
+            tlv.push_str(TAG_NAME, switch.get_name());
+        }
+        CMD_BINARY_SENSOR_COUNT => {
+            // Binary sensor count
+            let num = binary_sensors.len() as u32;
+
+            frame.payload_len = 4;
+            pack_u32(num, &mut frame.payload[0..4]);
+        }
+        CMD_BINARY_SENSOR_UNIQUE_ID => {
+            // Binary sensor unique_id
+            let binary_sensor_id = entity_id(frame, binary_sensors.len())?;
+
+            set_payload_str(frame, binary_sensors[binary_sensor_id].get_unique_id());
+        }
+        CMD_BINARY_SENSOR_NAME => {
+            // Binary sensor name
+            let binary_sensor_id = entity_id(frame, binary_sensors.len())?;
+
+            set_payload_str(frame, binary_sensors[binary_sensor_id].get_name());
+        }
+        CMD_BINARY_SENSOR_DEVICE_CLASS => {
+            // Binary sensor device_class
+            let binary_sensor_id = entity_id(frame, binary_sensors.len())?;
+
+            set_payload_str(
+                frame,
+                binary_sensors[binary_sensor_id].get_device_class().as_str(),
+            );
+        }
+        CMD_BINARY_SENSOR_STATE => {
+            // Binary sensor state
+            let binary_sensor_id = entity_id(frame, binary_sensors.len())?;
+
+            let mut state = [0u8; 1];
+            let len = binary_sensor_state(binary_sensors[binary_sensor_id], &mut state)?;
+
+            frame.payload_len = len as u8;
+            frame.payload[0..len].copy_from_slice(&state[0..len]);
+        }
+        CMD_BINARY_SENSOR_DESCRIBE => {
+            // Binary sensor descriptor
+            let binary_sensor = binary_sensors[entity_id(frame, binary_sensors.len())?];
+
+            frame.payload_len = 0;
+            let mut tlv = TlvWriter::new(frame);
+            let mut state = [0u8; 1];
+            if let Ok(len) = binary_sensor_state(binary_sensor, &mut state) {
+                tlv.push(TAG_STATE, &state[0..len]);
+            }
+            tlv.push_str(TAG_UNIQUE_ID, binary_sensor.get_unique_id());
+            tlv.push_str(TAG_NAME, binary_sensor.get_name());
+            tlv.push_str(TAG_DEVICE_CLASS, binary_sensor.get_device_class().as_str());
+        }
+        CMD_NUMBER_COUNT => {
+            // Number count
+            let num = numbers.len() as u32;
+
+            frame.payload_len = 4;
+            pack_u32(num, &mut frame.payload[0..4]);
+        }
+        CMD_NUMBER_UNIQUE_ID => {
+            // Number unique_id
+            let number_id = entity_id(frame, numbers.len())?;
+
+            set_payload_str(frame, numbers[number_id].get_unique_id());
+        }
+        CMD_NUMBER_NAME => {
+            // Number name
+            let number_id = entity_id(frame, numbers.len())?;
+
+            set_payload_str(frame, numbers[number_id].get_name());
+        }
+        CMD_NUMBER_NATIVE_UNIT_OF_MEASUREMENT => {
+            // Number native_unit_of_measurement
+            let number_id = entity_id(frame, numbers.len())?;
+
+            set_payload_str(frame, numbers[number_id].get_native_unit_of_measurement());
+        }
+        CMD_NUMBER_DEVICE_CLASS => {
+            // Number device_class
+            let number_id = entity_id(frame, numbers.len())?;
+
+            set_payload_str(frame, numbers[number_id].get_device_class().as_str());
+        }
+        CMD_NUMBER_VALUE => {
+            // Number value
+            let number_id = entity_id(frame, numbers.len())?;
+
+            let value = numbers[number_id].exec_request(NumberRequest::Get)?;
+            Some(value).to_payload(&mut frame.payload_len, &mut frame.payload);
+        }
+        CMD_NUMBER_SET => {
+            // Set number
+            let number_id = entity_id(frame, numbers.len())?;
+            if frame.payload_len != 8 {
+                return Err(ErrorCode::BadPayloadLength);
+            }
+
+            let number = &mut numbers[number_id];
+            let mut bytes = [0u8; 4];
+            bytes.copy_from_slice(&frame.payload[4..8]);
+            let value = f32::from_le_bytes(bytes);
+
+            // Also rejects NaN
+            if !(number.get_min()..=number.get_max()).contains(&value) {
+                return Err(ErrorCode::InvalidArgument);
+            }
+
+            // The master knows about the change it requested, it is no event
+            number.exec_request(NumberRequest::Set(value))?;
+            number.take_changed();
+
+            let value = number.exec_request(NumberRequest::Get)?;
+            Some(value).to_payload(&mut frame.payload_len, &mut frame.payload);
+        }
+        CMD_NUMBER_DESCRIBE => {
+            // Number descriptor
+            let number = &mut numbers[entity_id(frame, numbers.len())?];
+
+            frame.payload_len = 0;
+            let read = number.exec_request(NumberRequest::Get);
+
+            let mut tlv = TlvWriter::new(frame);
+            if let Ok(v) = read {
+                // The value as a float sensor value, the type and the bytes
+                let [b0, b1, b2, b3] = v.to_le_bytes();
+                tlv.push(TAG_VALUE, &[PayloadType::Float as u8, b0, b1, b2, b3]);
+            }
+            tlv.push_str(TAG_UNIQUE_ID, number.get_unique_id());
+            tlv.push_str(TAG_NAME, number.get_name());
+            tlv.push_str(
+                TAG_NATIVE_UNIT_OF_MEASUREMENT,
+                number.get_native_unit_of_measurement(),
+            );
+            tlv.push_str(TAG_DEVICE_CLASS, number.get_device_class().as_str());
+            tlv.push(TAG_MIN, &number.get_min().to_le_bytes());
+            tlv.push(TAG_MAX, &number.get_max().to_le_bytes());
+            tlv.push(TAG_STEP, &number.get_step().to_le_bytes());
+            tlv.push_str(TAG_MODE, number.get_mode().as_str());
+        }
+        CMD_BUTTON_COUNT => {
+            // Button count
+            let num = buttons.len() as u32;
+
+            frame.payload_len = 4;
+            pack_u32(num, &mut frame.payload[0..4]);
+        }
+        CMD_BUTTON_UNIQUE_ID => {
+            // Button unique_id
+            let button_id = entity_id(frame, buttons.len())?;
+
+            set_payload_str(frame, buttons[button_id].get_unique_id());
+        }
+        CMD_BUTTON_NAME => {
+            // Button name
+            let button_id = entity_id(frame, buttons.len())?;
+
+            set_payload_str(frame, buttons[button_id].get_name());
+        }
+        CMD_BUTTON_PRESS => {
+            // Press button
+            let button_id = entity_id(frame, buttons.len())?;
+
+            buttons[button_id].press()?;
+
+            frame.payload_len = 0;
+        }
+        CMD_BUTTON_DESCRIBE => {
+            // Button descriptor
+            let button = &buttons[entity_id(frame, buttons.len())?];
+
+            frame.payload_len = 0;
+            let mut tlv = TlvWriter::new(frame);
+            tlv.push_str(TAG_UNIQUE_ID, button.get_unique_id());
+            tlv.push_str(TAG_NAME, button.get_name());
+        }
+        CMD_LIGHT_COUNT => {
+            // Light count
+            let num = lights.len() as u32;
+
+            frame.payload_len = 4;
+            pack_u32(num, &mut frame.payload[0..4]);
+        }
+        CMD_LIGHT_UNIQUE_ID => {
+            // Light unique_id
+            let light_id = entity_id(frame, lights.len())?;
+
+            set_payload_str(frame, lights[light_id].get_unique_id());
+        }
+        CMD_LIGHT_NAME => {
+            // Light name
+            let light_id = entity_id(frame, lights.len())?;
+
+            set_payload_str(frame, lights[light_id].get_name());
+        }
+        CMD_LIGHT_STATE => {
+            // Light state
+            let light_id = entity_id(frame, lights.len())?;
+
+            let state = lights[light_id].exec_request(LightRequest::Get)?;
+            set_payload_light_state(frame, state);
+        }
+        CMD_LIGHT_EXEC | CMD_LIGHT_BRIGHTNESS => {
+            // Light exec and brightness
+            let light_id = entity_id(frame, lights.len())?;
+            let transition_ms = match frame.payload_len {
+                5 => 0,
+                7 => u16::from_le_bytes([frame.payload[5], frame.payload[6]]),
+                _ => return Err(ErrorCode::BadPayloadLength),
+            };
+
+            let req = match (frame.cmd, frame.payload[4]) {
+                (CMD_LIGHT_EXEC, SWITCH_EXEC_TURN_OFF) | (CMD_LIGHT_BRIGHTNESS, 0) => {
+                    LightRequest::TurnOff { transition_ms }
+                }
+                (CMD_LIGHT_EXEC, SWITCH_EXEC_TURN_ON) => LightRequest::TurnOn {
+                    brightness: None,
+                    transition_ms,
+                },
+                (CMD_LIGHT_EXEC, SWITCH_EXEC_TOGGLE) => LightRequest::Toggle { transition_ms },
+                (CMD_LIGHT_EXEC, _) => return Err(ErrorCode::InvalidArgument),
+                (_, brightness) => LightRequest::TurnOn {
+                    brightness: Some(brightness),
+                    transition_ms,
+                },
+            };
+
+            // The master knows about the change it requested, it is no event
+            let light = &mut lights[light_id];
+            light.exec_request(req)?;
+            light.take_changed();
+
+            let state = light.exec_request(LightRequest::Get)?;
+            set_payload_light_state(frame, state);
+        }
+        CMD_LIGHT_DESCRIBE => {
+            // Light descriptor
+            let light = &mut lights[entity_id(frame, lights.len())?];
+
+            frame.payload_len = 0;
+            let mut tlv = TlvWriter::new(frame);
+            if let Ok(state) = light.exec_request(LightRequest::Get) {
+                tlv.push(TAG_STATE, &[state.on as u8]);
+                tlv.push(TAG_BRIGHTNESS, &[state.brightness]);
+            }
+            tlv.push_str(TAG_UNIQUE_ID, light.get_unique_id());
+            tlv.push_str(TAG_NAME, light.get_name());
+        }
+        CMD_COVER_COUNT => {
+            // Cover count
+            let num = covers.len() as u32;
+
+            frame.payload_len = 4;
+            pack_u32(num, &mut frame.payload[0..4]);
+        }
+        CMD_COVER_UNIQUE_ID => {
+            // Cover unique_id
+            let cover_id = entity_id(frame, covers.len())?;
+
+            set_payload_str(frame, covers[cover_id].get_unique_id());
+        }
+        CMD_COVER_NAME => {
+            // Cover name
+            let cover_id = entity_id(frame, covers.len())?;
+
+            set_payload_str(frame, covers[cover_id].get_name());
+        }
+        CMD_COVER_DEVICE_CLASS => {
+            // Cover device_class
+            let cover_id = entity_id(frame, covers.len())?;
+
+            set_payload_str(frame, covers[cover_id].get_device_class().as_str());
+        }
+        CMD_COVER_STATE => {
+            // Cover state
+            let cover_id = entity_id(frame, covers.len())?;
+
+            let state = covers[cover_id].exec_request(CoverRequest::Get)?;
+            set_payload_cover_state(frame, state);
+        }
+        CMD_COVER_EXEC | CMD_COVER_POSITION => {
+            // Cover exec and position
+            let cover_id = entity_id(frame, covers.len())?;
+            if frame.payload_len != 5 {
+                return Err(ErrorCode::BadPayloadLength);
+            }
+
+            let req = match (frame.cmd, frame.payload[4]) {
+                (CMD_COVER_EXEC, COVER_EXEC_CLOSE) => CoverRequest::Close,
+                (CMD_COVER_EXEC, COVER_EXEC_OPEN) => CoverRequest::Open,
+                (CMD_COVER_EXEC, COVER_EXEC_STOP) => CoverRequest::Stop,
+                (CMD_COVER_EXEC, _) => return Err(ErrorCode::InvalidArgument),
+                (_, position) if position > COVER_POSITION_OPEN => {
+                    return Err(ErrorCode::InvalidArgument)
+                }
+                (_, position) => CoverRequest::SetPosition(position),
+            };
+
+            // The master knows about the change it requested, it is no event
+            let cover = &mut covers[cover_id];
+            cover.exec_request(req)?;
+            cover.take_changed();
+
+            let state = cover.exec_request(CoverRequest::Get)?;
+            set_payload_cover_state(frame, state);
+        }
+        CMD_COVER_DESCRIBE => {
+            // Cover descriptor
+            let cover = &mut covers[entity_id(frame, covers.len())?];
+
+            frame.payload_len = 0;
+            let mut tlv = TlvWriter::new(frame);
+            if let Ok(state) = cover.exec_request(CoverRequest::Get) {
+                tlv.push(TAG_POSITION, &[state.position]);
+                tlv.push(TAG_STATE, &[state.motion as u8]);
+            }
+            tlv.push_str(TAG_UNIQUE_ID, cover.get_unique_id());
+            tlv.push_str(TAG_NAME, cover.get_name());
+            tlv.push_str(TAG_DEVICE_CLASS, cover.get_device_class().as_str());
+        }
+        CMD_SELECT_COUNT => {
+            // Select count
+            let num = selects.len() as u32;
+
+            frame.payload_len = 4;
+            pack_u32(num, &mut frame.payload[0..4]);
+        }
+        CMD_SELECT_UNIQUE_ID => {
+            // Select unique_id
+            let select_id = entity_id(frame, selects.len())?;
+
+            set_payload_str(frame, selects[select_id].get_unique_id());
+        }
+        CMD_SELECT_NAME => {
+            // Select name
+            let select_id = entity_id(frame, selects.len())?;
+
+            set_payload_str(frame, selects[select_id].get_name());
+        }
+        CMD_SELECT_OPTION => {
+            // Select option
+            let select_id = entity_id(frame, selects.len())?;
+            if frame.payload_len != 5 {
+                return Err(ErrorCode::BadPayloadLength);
+            }
+
+            let option = *selects[select_id]
+                .get_options()
+                .get(frame.payload[4] as usize)
+                .ok_or(ErrorCode::InvalidArgument)?;
+            set_payload_str(frame, option);
+        }
+        CMD_SELECT_STATE => {
+            // Select state
+            let select_id = entity_id(frame, selects.len())?;
+
+            frame.payload[0] = selects[select_id].exec_request(SelectRequest::Get)?;
+            frame.payload_len = 1;
+        }
+        CMD_SELECT_SET => {
+            // Select option by index
+            let select_id = entity_id(frame, selects.len())?;
+            if frame.payload_len != 5 {
+                return Err(ErrorCode::BadPayloadLength);
+            }
+
+            let select = &mut selects[select_id];
+            let index = frame.payload[4];
+            if index as usize >= select.get_options().len() {
+                return Err(ErrorCode::InvalidArgument);
+            }
+
+            // The master knows about the change it requested, it is no event
+            select.exec_request(SelectRequest::Set(index))?;
+            select.take_changed();
+
+            frame.payload[0] = select.exec_request(SelectRequest::Get)?;
+            frame.payload_len = 1;
+        }
+        CMD_SELECT_DESCRIBE => {
+            // Select descriptor
+            let select = &mut selects[entity_id(frame, selects.len())?];
+            let state = select.exec_request(SelectRequest::Get);
+            let options = select.get_options();
+
+            frame.payload_len = 0;
+            let mut tlv = TlvWriter::new(frame);
+            if let Ok(index) = state {
+                tlv.push(TAG_STATE, &[index]);
+            }
+            tlv.push_str(TAG_UNIQUE_ID, select.get_unique_id());
+            tlv.push_str(TAG_NAME, select.get_name());
+            tlv.push(TAG_OPTION_COUNT, &[options.len() as u8]);
+
+            // Truncated options would be mistaken for others, the master fetches the
+            // missing ones one by one
+            for option in options {
+                if tlv.room() < option.len() {
+                    break;
+                }
+                tlv.push_str(TAG_OPTION, option);
+            }
+        }
+        _ => return Err(ErrorCode::UnknownCommand),
+    }
+
+    Ok(())
+}
+
+/// Fills the payload of `frame` with the values of the selected sensors
+///
+/// The values that do not fit are left out, the sensor id in front of the values tells the
+/// master where to continue. The first value is always sent, truncated if need be, so
+/// every request makes progress.
+/// # Arguments
+/// * `frame` - The frame to fill the payload of
+/// * `sensors` - All sensors of the node
+/// * `first` - The index of the first sensor to send
+/// * `mask` - Selects the sensors from `first` on, bit `0` being `first`, `None` selects all
+fn set_payload_sensor_values(
+    frame: &mut DataFrame,
+    sensors: &[&dyn SensorRef],
+    first: usize,
+    mask: Option<u32>,
+) {
+    let mut value = [0u8; u8::MAX as usize + 1];
+    let mut pos = 4;
+    let mut next = SENSOR_VALUES_COMPLETE;
+
+    for (id, sensor) in sensors.iter().enumerate().skip(first) {
+        if let Some(mask) = mask {
+            match mask.checked_shr((id - first) as u32) {
+                None | Some(0) => break,
+                Some(bits) if bits & 1 == 0 => continue,
+                _ => {}
+            }
+        }
+
+        let mut len = sensor_value_entry(*sensor, &mut value);
+        let entry_len = 1 + entry_value_len(len);
+
+        let room = u8::MAX as usize - pos;
+        if entry_len > room {
+            if pos > 4 {
+                next = id as u32;
+                break;
+            }
+
+            // Only a string can be that long
+            len = fitting_len(&value, len as usize, room - 1) as u8;
+        }
+
+        let value_len = entry_value_len(len);
+        frame.payload[pos] = len;
+        frame.payload[pos + 1..pos + 1 + value_len].copy_from_slice(&value[0..value_len]);
+        pos += 1 + value_len;
+    }
+
+    pack_u32(next, &mut frame.payload[0..4]);
+    frame.payload_len = pos as u8;
+}
+
+/// Fills the payload of `frame` with the flags and as many queued events as fit,
+/// removing the events that got sent
+/// # Arguments
+/// * `frame` - The frame to fill the payload of
+/// * `events` - The queued events
+/// * `entities` - All entities of the node
+fn set_payload_events(frame: &mut DataFrame, events: &mut EventQueue, entities: &mut Entities) {
+    let mut value = [0u8; u8::MAX as usize + 1];
+    let mut pos = 1;
+    let mut done = 0;
+
+    for event in events.events() {
+        let mut len = match *event {
+            Event::Sensor(id) => match entities.sensors.get(id as usize) {
+                Some(sensor) => sensor_value_entry(*sensor, &mut value),
+                None => {
+                    done += 1;
+                    continue;
+                }
+            },
+            Event::Switch(id) => match entities.switches.get_mut(id as usize) {
+                Some(switch) => match switch.exec_request(SwitchRequest::Get) {
+                    Ok(state) => {
+                        value[0] = state as u8;
+                        1
+                    }
+                    Err(e) => {
+                        value[0] = e as u8;
+                        SENSOR_VALUE_ERROR
+                    }
+                },
+                None => {
+                    done += 1;
+                    continue;
+                }
+            },
+            Event::BinarySensor(id) => match entities.binary_sensors.get(id as usize) {
+                Some(binary_sensor) => match binary_sensor_state(*binary_sensor, &mut value) {
+                    Ok(len) => len as u8,
+                    Err(e) => {
+                        value[0] = e as u8;
+                        SENSOR_VALUE_ERROR
+                    }
+                },
+                None => {
+                    done += 1;
+                    continue;
+                }
+            },
+            Event::Number(id) => match entities.numbers.get_mut(id as usize) {
+                Some(number) => match number.exec_request(NumberRequest::Get) {
+                    Ok(v) => {
+                        let mut len = 0;
+                        v.to_payload(&mut len, &mut value);
+                        len
+                    }
+                    Err(e) => {
+                        value[0] = e as u8;
+                        SENSOR_VALUE_ERROR
+                    }
+                },
+                None => {
+                    done += 1;
+                    continue;
+                }
+            },
+            Event::Light(id) => match entities.lights.get_mut(id as usize) {
+                Some(light) => match light.exec_request(LightRequest::Get) {
+                    Ok(state) => {
+                        value[0] = state.on as u8;
+                        value[1] = state.brightness;
+                        2
+                    }
+                    Err(e) => {
+                        value[0] = e as u8;
+                        SENSOR_VALUE_ERROR
+                    }
+                },
+                None => {
+                    done += 1;
+                    continue;
+                }
+            },
+            Event::Cover(id) => match entities.covers.get_mut(id as usize) {
+                Some(cover) => match cover.exec_request(CoverRequest::Get) {
+                    Ok(state) => {
+                        value[0] = state.position;
+                        value[1] = state.motion as u8;
+                        2
+                    }
+                    Err(e) => {
+                        value[0] = e as u8;
+                        SENSOR_VALUE_ERROR
+                    }
+                },
+                None => {
+                    done += 1;
+                    continue;
+                }
+            },
+            Event::Select(id) => match entities.selects.get_mut(id as usize) {
+                Some(select) => match select.exec_request(SelectRequest::Get) {
+                    Ok(index) => {
+                        value[0] = index;
+                        1
+                    }
+                    Err(e) => {
+                        value[0] = e as u8;
+                        SENSOR_VALUE_ERROR
+                    }
+                },
+                None => {
+                    done += 1;
+                    continue;
+                }
+            },
+        };
+
+        let room = (u8::MAX as usize - pos).saturating_sub(6);
+        if entry_value_len(len) > room {
+            if pos > 1 {
+                break;
+            }
+
+            // The first event is always sent to not block the queue
+            len = fitting_len(&value, len as usize, room) as u8;
+        }
+
+        let value_len = entry_value_len(len);
+        frame.payload[pos] = event.kind();
+        pack_u32(event.id(), &mut frame.payload[pos + 1..pos + 5]);
+        frame.payload[pos + 5] = len;
+        frame.payload[pos + 6..pos + 6 + value_len].copy_from_slice(&value[0..value_len]);
+        pos += 6 + value_len;
+        done += 1;
+    }
+
+    events.remove(done);
+
+    let mut flags = 0;
+    if events.overflowed {
+        flags |= EVENTS_OVERFLOWED;
+        events.overflowed = false;
+    }
+    if !events.is_empty() {
+        flags |= EVENTS_PENDING;
+    }
+
+    frame.payload[0] = flags;
+    frame.payload_len = pos as u8;
+}
+
+/// Returns the length of a value truncated to `max` bytes, strings are cut at a
+/// character boundary to stay valid UTF-8
+/// # Arguments
+/// * `value` - The value, starting with its payload type
+/// * `len` - The length of the value
+/// * `max` - The maximum length
+fn fitting_len(value: &[u8], len: usize, max: usize) -> usize {
+    if len <= max {
+        return len;
+    }
+
+    let mut len = max;
+    if value[0] == PayloadType::String as u8 {
+        // Back off while the first byte cut off continues a character
+        while len > 1 && value[len] & 0xc0 == 0x80 {
+            len -= 1;
+        }
+    }
+    len
+}
+
+/// Reads the value of a sensor for a length-prefixed entry, see [`CMD_SENSOR_VALUES`]
+/// # Arguments
+/// * `sensor` - The sensor to read
+/// * `value` - Receives the value or the error code
+/// # Returns
+/// The length of the value or [`SENSOR_VALUE_ERROR`] if reading it failed
+fn sensor_value_entry(sensor: &dyn SensorRef, value: &mut [u8; u8::MAX as usize + 1]) -> u8 {
+    let mut len = 0;
+
+    match sensor.get_payload(&mut len, value) {
+        Ok(()) => len,
+        Err(e) => {
+            value[0] = e as u8;
+            SENSOR_VALUE_ERROR
+        }
+    }
+}
+
+/// Reads the state of a binary sensor, see [`CMD_BINARY_SENSOR_STATE`]
+/// # Arguments
+/// * `binary_sensor` - The binary sensor to read
+/// * `state` - Receives the `u8` state if it is known, at least 1 byte long
+/// # Returns
+/// The length of the state, `0` if it is unknown
+fn binary_sensor_state(
+    binary_sensor: &dyn BinarySensorRef,
+    state: &mut [u8],
+) -> Result<usize, ErrorCode> {
+    match binary_sensor.get_state()? {
+        Some(on) => {
+            state[0] = on as u8;
+            Ok(1)
+        }
+        None => Ok(0),
+    }
+}
+
+/// Fills the payload of `frame` with the state of a light, see [`CMD_LIGHT_STATE`]
+/// # Arguments
+/// * `frame` - The frame to fill the payload of
+/// * `state` - The state of the light
+fn set_payload_light_state(frame: &mut DataFrame, state: LightState) {
+    frame.payload_len = 2;
+    frame.payload[0] = state.on as u8;
+    frame.payload[1] = state.brightness;
+}
+
+/// Fills the payload of `frame` with the state of a cover, see [`CMD_COVER_STATE`]
+/// # Arguments
+/// * `frame` - The frame to fill the payload of
+/// * `state` - The state of the cover
+fn set_payload_cover_state(frame: &mut DataFrame, state: CoverState) {
+    frame.payload_len = 2;
+    frame.payload[0] = state.position;
+    frame.payload[1] = state.motion as u8;
+}
+
+/// Returns the amount of value bytes behind the length of an entry, see [`CMD_SENSOR_VALUES`]
+/// # Arguments
+/// * `len` - The length byte of the entry
+fn entry_value_len(len: u8) -> usize {
+    if len == SENSOR_VALUE_ERROR {
+        1
+    } else {
+        len as usize
+    }
+}
+
+/// Reads the entity index from the first 4 bytes of the payload
+/// # Arguments
+/// * `frame` - The frame to read the index from
+/// * `count` - The amount of entities the index refers to
+fn entity_id(frame: &DataFrame, count: usize) -> Result<usize, ErrorCode> {
+    let id = unpack_u32(&frame.payload[0..frame.payload_len as usize])
+        .ok_or(ErrorCode::BadPayloadLength)? as usize;
+
+    if id >= count {
+        return Err(ErrorCode::EntityOutOfRange);
+    }
+
+    Ok(id)
+}
+
+/// Packs a `u32` value into 4 bytes of `u8`, LSB first
+/// # Arguments
+/// * `value` - The value to pack
+/// * `bytes` - The bytes to pack into, at least 4 bytes long
+fn pack_u32(value: u32, bytes: &mut [u8]) {
+    bytes[0..4].copy_from_slice(&value.to_le_bytes());
+}
+
+/// Fills the payload of `frame` with the UTF-8 bytes of `string`
+/// # Arguments
+/// * `frame` - The frame to fill the payload of
+/// * `string` - The string to use as the payload, gets truncated to 255 bytes
+fn set_payload_str(frame: &mut DataFrame, string: &str) {
+    let bytes = string.as_bytes();
+    let len = bytes.len().min(u8::MAX as usize);
+
+    frame.payload_len = len as u8;
+    frame.payload[0..len].copy_from_slice(&bytes[0..len]);
+}
+
+/// Unpacks a `u32` value from 4 bytes of `u8`
+/// # Arguments
+/// * `bytes` - The bytes to unpack
+fn unpack_u32(bytes: &[u8]) -> Option<u32> {
+    if bytes.len() < 4 {
+        return None;
+    }
+
+    let mut res: u32 = bytes[0] as u32;
+    res |= (bytes[1] as u32) << 8;
+    res |= (bytes[2] as u32) << 16;
+    res |= (bytes[3] as u32) << 24;
+    Some(res)
+}
@@ -0,0 +1,65 @@
+pub mod entity;
+
+pub mod binary_sensor;
+pub mod button;
+pub mod cover;
+pub mod light;
+pub mod number;
+pub mod select;
+pub mod sensor;
+pub mod switch;
+
+use binary_sensor::BinarySensorRef;
+use button::ButtonRef;
+use cover::CoverRef;
+use light::LightRef;
+use number::NumberRef;
+use select::SelectRef;
+use sensor::SensorRef;
+use switch::SwitchRef;
+
+/// All entities of a node, as handed to the handler
+///
+/// Build it once with the entities of the node, unused entity types stay empty:
+/// `Entities { sensors: &sensors, ..Default::default() }`
+#[derive(Default)]
+pub struct Entities<'e, 'a> {
+    /// The sensors of the node
+    pub sensors: &'e [&'e dyn SensorRef<'a>],
+    /// The switches of the node
+    pub switches: &'e mut [&'e mut dyn SwitchRef<'a>],
+    /// The binary sensors of the node
+    pub binary_sensors: &'e [&'e dyn BinarySensorRef<'a>],
+    /// The numbers of the node
+    pub numbers: &'e mut [&'e mut dyn NumberRef<'a>],
+    /// The buttons of the node
+    pub buttons: &'e mut [&'e mut dyn ButtonRef<'a>],
+    /// The lights of the node
+    pub lights: &'e mut [&'e mut dyn LightRef<'a>],
+    /// The covers of the node
+    pub covers: &'e mut [&'e mut dyn CoverRef<'a>],
+    /// The selects of the node
+    pub selects: &'e mut [&'e mut dyn SelectRef<'a>],
+}
+
+impl<'e, 'a> Entities<'e, 'a> {
+    /// Advances the running actions of the entities, e.g. ends relay pulses of buttons,
+    /// fades lights and moves covers
+    ///
+    /// Call it regularly from the main loop, the actions are as precise as the ticks.
+    /// # Arguments
+    /// * `elapsed_ms` - The milliseconds since the last tick
+    pub fn tick(&mut self, elapsed_ms: u16) {
+        for button in self.buttons.iter_mut() {
+            button.tick(elapsed_ms);
+        }
+
+        for light in self.lights.iter_mut() {
+            light.tick(elapsed_ms);
+        }
+
+        for cover in self.covers.iter_mut() {
+            cover.tick(elapsed_ms);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use core::cell::Cell;
+
+mod binary_sensor_ref;
+pub use binary_sensor_ref::*;
+
+mod device_class;
+pub use device_class::*;
+
+/// A HomeAssistant Binary Sensor
+///
+/// https://developers.home-assistant.io/docs/core/entity/binary-sensor for more information
+pub struct BinarySensor<'a> {
+    /// The friendly name for the binary sensor
+    pub name: &'a str,
+    /// The `unique_id` for this binary sensor
+    pub unique_id: &'a str,
+    /// The `device_class` for this binary sensor
+    pub device_class: BinarySensorDeviceClass,
+    /// The current state of this binary sensor, `None` if unknown
+    pub state: Cell<Option<bool>>,
+    /// The state changed since the last event
+    changed: Cell<bool>,
+}
+
+impl<'a> BinarySensor<'a> {
+    /// Create a new binary sensor
+    /// # Arguments
+    /// * `name` - The friendly name for the binary sensor
+    /// * `unique_id` - The unique id for the binary sensor
+    /// * `device_class` - What the binary sensor detects
+    /// * `state` - The initial state, `None` if unknown
+    pub fn new(
+        name: &'a str,
+        unique_id: &'a str,
+        device_class: BinarySensorDeviceClass,
+        state: Option<bool>,
+    ) -> Self {
+        Self {
+            name,
+            unique_id,
+            device_class,
+            state: Cell::new(state),
+            changed: Cell::new(false),
+        }
+    }
+
+    /// Sets the current state of this binary sensor
+    /// # Arguments
+    /// * `state` - The new state to set
+    pub fn set_state(&self, state: Option<bool>) {
+        if self.state.replace(state) != state {
+            self.changed.set(true);
+        }
+    }
+}
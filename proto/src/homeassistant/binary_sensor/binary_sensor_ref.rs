@@ -0,0 +1,41 @@
+use crate::{
+    error::ErrorCode,
+    homeassistant::entity::{DeviceClass, Entity},
+};
+
+use super::*;
+
+pub trait BinarySensorRef<'a>: Entity<'a> {
+    /// Returns the current state
+    /// # Returns
+    /// The state, `None` if unknown, or the error to respond with if it can't be read
+    fn get_state(&self) -> Result<Option<bool>, ErrorCode>;
+    /// Returns true if the state changed since the last call and is to be reported as an event
+    fn take_changed(&self) -> bool {
+        false
+    }
+}
+
+impl<'a> Entity<'a> for BinarySensor<'a> {
+    fn get_unique_id(&self) -> &'a str {
+        self.unique_id
+    }
+
+    fn get_name(&self) -> &'a str {
+        self.name
+    }
+
+    fn get_device_class(&self) -> DeviceClass {
+        DeviceClass::BinarySensor(self.device_class)
+    }
+}
+
+impl<'a> BinarySensorRef<'a> for BinarySensor<'a> {
+    fn get_state(&self) -> Result<Option<bool>, ErrorCode> {
+        Ok(self.state.get())
+    }
+
+    fn take_changed(&self) -> bool {
+        self.changed.replace(false)
+    }
+}
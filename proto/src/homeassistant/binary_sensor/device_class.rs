@@ -0,0 +1,56 @@
+/// Each binary sensor has a `device_class` associated to it, refer to https://developers.home-assistant.io/docs/core/entity/binary-sensor for more information
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BinarySensorDeviceClass {
+    /// A generic on/off sensor without a device class
+    None,
+    Battery,
+    Cold,
+    Connectivity,
+    Door,
+    GarageDoor,
+    Heat,
+    Light,
+    Moisture,
+    Motion,
+    Occupancy,
+    Opening,
+    Plug,
+    Power,
+    Presence,
+    Problem,
+    Running,
+    Safety,
+    Smoke,
+    Vibration,
+    Window,
+}
+
+impl BinarySensorDeviceClass {
+    /// Returns the BinarySensorDeviceClass in string form for transmission and use withing HomeAssistant
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BinarySensorDeviceClass::None => "",
+            BinarySensorDeviceClass::Battery => "BinarySensorDeviceClass.BATTERY",
+            BinarySensorDeviceClass::Cold => "BinarySensorDeviceClass.COLD",
+            BinarySensorDeviceClass::Connectivity => "BinarySensorDeviceClass.CONNECTIVITY",
+            BinarySensorDeviceClass::Door => "BinarySensorDeviceClass.DOOR",
+            BinarySensorDeviceClass::GarageDoor => "BinarySensorDeviceClass.GARAGE_DOOR",
+            BinarySensorDeviceClass::Heat => "BinarySensorDeviceClass.HEAT",
+            BinarySensorDeviceClass::Light => "BinarySensorDeviceClass.LIGHT",
+            BinarySensorDeviceClass::Moisture => "BinarySensorDeviceClass.MOISTURE",
+            BinarySensorDeviceClass::Motion => "BinarySensorDeviceClass.MOTION",
+            BinarySensorDeviceClass::Occupancy => "BinarySensorDeviceClass.OCCUPANCY",
+            BinarySensorDeviceClass::Opening => "BinarySensorDeviceClass.OPENING",
+            BinarySensorDeviceClass::Plug => "BinarySensorDeviceClass.PLUG",
+            BinarySensorDeviceClass::Power => "BinarySensorDeviceClass.POWER",
+            BinarySensorDeviceClass::Presence => "BinarySensorDeviceClass.PRESENCE",
+            BinarySensorDeviceClass::Problem => "BinarySensorDeviceClass.PROBLEM",
+            BinarySensorDeviceClass::Running => "BinarySensorDeviceClass.RUNNING",
+            BinarySensorDeviceClass::Safety => "BinarySensorDeviceClass.SAFETY",
+            BinarySensorDeviceClass::Smoke => "BinarySensorDeviceClass.SMOKE",
+            BinarySensorDeviceClass::Vibration => "BinarySensorDeviceClass.VIBRATION",
+            BinarySensorDeviceClass::Window => "BinarySensorDeviceClass.WINDOW",
+        }
+    }
+}
@@ -0,0 +1,31 @@
+mod button_ref;
+pub use button_ref::*;
+
+use crate::error::ErrorCode;
+
+/// A HomeAssistant Button, triggering a momentary action like a relay pulse
+///
+/// https://developers.home-assistant.io/docs/core/entity/button for more information
+pub struct Button<'a, F: FnMut() -> Result<(), ErrorCode>> {
+    /// The friendly name for the entity
+    pub name: &'a str,
+    /// The `unique_id` for this entity
+    pub unique_id: &'a str,
+    /// Executes the action of the button, must not block
+    pub callback: F,
+}
+
+impl<'a, F: FnMut() -> Result<(), ErrorCode>> Button<'a, F> {
+    /// Create a new button
+    /// # Arguments
+    /// * `name` - The friendly name for the button
+    /// * `unique_id` - The unique id for the button
+    /// * `callback` - The callback to execute when the button is pressed
+    pub fn new(name: &'a str, unique_id: &'a str, callback: F) -> Self {
+        Self {
+            name,
+            unique_id,
+            callback,
+        }
+    }
+}
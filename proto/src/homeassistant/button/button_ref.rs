@@ -0,0 +1,41 @@
+use crate::{
+    error::ErrorCode,
+    homeassistant::entity::{DeviceClass, Entity},
+};
+
+use super::*;
+
+pub trait ButtonRef<'a>: Entity<'a> {
+    /// Presses the button
+    ///
+    /// Actions lasting longer than a frame, like a relay pulse, are to be started here
+    /// and finished by [`ButtonRef::tick`] to not block the receive loop.
+    /// # Returns
+    /// The error to respond with if the button can't be pressed, e.g.
+    /// [`ErrorCode::EntityBusy`] while the previous press is still running
+    fn press(&mut self) -> Result<(), ErrorCode>;
+    /// Advances the running action of the button, called regularly by the firmware
+    /// # Arguments
+    /// * `elapsed_ms` - The milliseconds since the last tick
+    fn tick(&mut self, _elapsed_ms: u16) {}
+}
+
+impl<'a, F: FnMut() -> Result<(), ErrorCode>> Entity<'a> for Button<'a, F> {
+    fn get_unique_id(&self) -> &'a str {
+        self.unique_id
+    }
+
+    fn get_name(&self) -> &'a str {
+        self.name
+    }
+
+    fn get_device_class(&self) -> DeviceClass {
+        DeviceClass::Button
+    }
+}
+
+impl<'a, F: FnMut() -> Result<(), ErrorCode>> ButtonRef<'a> for Button<'a, F> {
+    fn press(&mut self) -> Result<(), ErrorCode> {
+        (self.callback)()
+    }
+}
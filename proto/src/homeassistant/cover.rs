@@ -0,0 +1,101 @@
+mod cover_ref;
+pub use cover_ref::*;
+
+mod device_class;
+pub use device_class::*;
+
+mod travel;
+pub use travel::*;
+
+/// The position of a fully open cover, a closed cover is at `0`
+pub const COVER_POSITION_OPEN: u8 = 100;
+
+/// Requests a cover can execute
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CoverRequest {
+    /// Opens the cover fully
+    Open,
+    /// Closes the cover fully
+    Close,
+    /// Stops a moving cover where it is
+    Stop,
+    /// Moves the cover to a position, `0..=COVER_POSITION_OPEN`
+    SetPosition(u8),
+    /// Returns the current state
+    Get,
+}
+
+/// The direction a cover moves in
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CoverMotion {
+    /// The cover rests at its position
+    #[default]
+    Stopped = 0,
+    /// The cover moves towards open
+    Opening = 1,
+    /// The cover moves towards closed
+    Closing = 2,
+}
+
+impl CoverMotion {
+    /// Converts the motion from its identifier on the wire
+    /// # Arguments
+    /// * `motion` - The identifier to convert
+    pub fn from_u8(motion: u8) -> Option<Self> {
+        match motion {
+            0 => Some(CoverMotion::Stopped),
+            1 => Some(CoverMotion::Opening),
+            2 => Some(CoverMotion::Closing),
+            _ => None,
+        }
+    }
+}
+
+/// The state of a cover
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CoverState {
+    /// The current position, `0` is closed, [`COVER_POSITION_OPEN`] is open
+    pub position: u8,
+    /// The direction the cover moves in
+    pub motion: CoverMotion,
+}
+
+/// A HomeAssistant Cover with a position, e.g. a roller shutter or a blind
+///
+/// https://developers.home-assistant.io/docs/core/entity/cover for more information
+pub struct Cover<'a, F: FnMut(CoverRequest) -> CoverState> {
+    /// The friendly name for the entity
+    pub name: &'a str,
+    /// The `unique_id` for this entity
+    pub unique_id: &'a str,
+    /// The `device_class` for this entity
+    pub device_class: CoverDeviceClass,
+    /// Update the state of the cover
+    pub callback: F,
+    /// The state last reported as an event, `None` before the first poll
+    reported: Option<CoverState>,
+}
+
+impl<'a, F: FnMut(CoverRequest) -> CoverState> Cover<'a, F> {
+    /// Create a new cover
+    /// # Arguments
+    /// * `name` - The friendly name for the cover
+    /// * `unique_id` - The unique id for the cover
+    /// * `device_class` - What kind of cover it is
+    /// * `callback` - The callback to use for incoming CoverRequests
+    pub fn new(
+        name: &'a str,
+        unique_id: &'a str,
+        device_class: CoverDeviceClass,
+        callback: F,
+    ) -> Self {
+        Self {
+            name,
+            unique_id,
+            device_class,
+            callback,
+            reported: None,
+        }
+    }
+}
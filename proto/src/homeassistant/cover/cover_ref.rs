@@ -0,0 +1,52 @@
+use crate::{
+    error::ErrorCode,
+    homeassistant::entity::{DeviceClass, Entity},
+};
+
+use super::*;
+
+pub trait CoverRef<'a>: Entity<'a> {
+    /// Execute a CoverRequest on the cover
+    /// # Arguments
+    /// * `req` - The `CoverRequest` to execute
+    /// # Returns
+    /// The state after the request, the error to respond with
+    /// if the request can't be executed
+    fn exec_request(&mut self, req: CoverRequest) -> Result<CoverState, ErrorCode>;
+    /// Returns true if the state changed since the last call and is to be reported as an event
+    fn take_changed(&mut self) -> bool {
+        false
+    }
+    /// Advances a running movement, called regularly by the firmware
+    /// # Arguments
+    /// * `elapsed_ms` - The milliseconds since the last tick
+    fn tick(&mut self, _elapsed_ms: u16) {}
+}
+
+impl<'a, F: FnMut(CoverRequest) -> CoverState> Entity<'a> for Cover<'a, F> {
+    fn get_unique_id(&self) -> &'a str {
+        self.unique_id
+    }
+
+    fn get_name(&self) -> &'a str {
+        self.name
+    }
+
+    fn get_device_class(&self) -> DeviceClass {
+        DeviceClass::Cover(self.device_class)
+    }
+}
+
+impl<'a, F: FnMut(CoverRequest) -> CoverState> CoverRef<'a> for Cover<'a, F> {
+    fn exec_request(&mut self, req: CoverRequest) -> Result<CoverState, ErrorCode> {
+        Ok((self.callback)(req))
+    }
+
+    fn take_changed(&mut self) -> bool {
+        let state = (self.callback)(CoverRequest::Get);
+        let changed = matches!(self.reported, Some(reported) if reported != state);
+
+        self.reported = Some(state);
+        changed
+    }
+}
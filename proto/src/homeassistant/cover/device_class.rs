@@ -0,0 +1,36 @@
+/// Each cover has a `device_class` associated to it, refer to https://developers.home-assistant.io/docs/core/entity/cover for more information
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CoverDeviceClass {
+    /// A generic cover without a device class
+    None,
+    Awning,
+    Blind,
+    Curtain,
+    Damper,
+    Door,
+    Garage,
+    Gate,
+    Shade,
+    Shutter,
+    Window,
+}
+
+impl CoverDeviceClass {
+    /// Returns the CoverDeviceClass in string form for transmission and use withing HomeAssistant
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CoverDeviceClass::None => "",
+            CoverDeviceClass::Awning => "CoverDeviceClass.AWNING",
+            CoverDeviceClass::Blind => "CoverDeviceClass.BLIND",
+            CoverDeviceClass::Curtain => "CoverDeviceClass.CURTAIN",
+            CoverDeviceClass::Damper => "CoverDeviceClass.DAMPER",
+            CoverDeviceClass::Door => "CoverDeviceClass.DOOR",
+            CoverDeviceClass::Garage => "CoverDeviceClass.GARAGE",
+            CoverDeviceClass::Gate => "CoverDeviceClass.GATE",
+            CoverDeviceClass::Shade => "CoverDeviceClass.SHADE",
+            CoverDeviceClass::Shutter => "CoverDeviceClass.SHUTTER",
+            CoverDeviceClass::Window => "CoverDeviceClass.WINDOW",
+        }
+    }
+}
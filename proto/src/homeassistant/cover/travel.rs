@@ -0,0 +1,159 @@
+use super::{CoverMotion, CoverState, COVER_POSITION_OPEN};
+
+/// Tracks the position of a cover driven by an opening and a closing relay by the
+/// time the relays are energised
+///
+/// The relay is released once the target is reached. Moving to either end keeps the
+/// relay energised for another tenth of the travel time, so the tracked position
+/// catches up with the end stop of the motor. After a relay was released both relays
+/// stay released for [`Travel::RELEASE_MS`], the motor stops before it reverses.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Travel {
+    /// The milliseconds the cover takes from closed to open
+    travel_ms: u32,
+    /// The tracked position in milliseconds of travel from closed
+    position_ms: u32,
+    /// The position to move to in milliseconds of travel from closed
+    target_ms: u32,
+    /// The milliseconds to keep moving at an end
+    overrun_ms: u32,
+    /// The direction the cover moves in, including the wait before the relay is energised
+    motion: CoverMotion,
+    /// The relay that is energised, [`CoverMotion::Stopped`] if none is
+    relay: CoverMotion,
+    /// The milliseconds both relays stay released before either is energised
+    release_ms: u16,
+}
+
+impl Travel {
+    /// The milliseconds both relays stay released after a relay was released
+    pub const RELEASE_MS: u16 = 500;
+
+    /// Creates a resting cover
+    /// # Arguments
+    /// * `travel_ms` - The milliseconds the cover takes from closed to open
+    /// * `position` - The position the cover is assumed at, the first move to an end corrects it
+    pub fn new(travel_ms: u32, position: u8) -> Self {
+        let travel_ms = travel_ms.max(1);
+        let position_ms = Self::to_ms(travel_ms, position);
+
+        Self {
+            travel_ms,
+            position_ms,
+            target_ms: position_ms,
+            overrun_ms: 0,
+            motion: CoverMotion::Stopped,
+            relay: CoverMotion::Stopped,
+            release_ms: 0,
+        }
+    }
+
+    /// Starts moving to a position, replacing a running movement
+    ///
+    /// Reversing a moving cover releases its relay, the other one is energised by a
+    /// tick once [`Travel::RELEASE_MS`] passed.
+    /// # Arguments
+    /// * `position` - The position to move to, clamped to [`COVER_POSITION_OPEN`]
+    pub fn move_to(&mut self, position: u8) {
+        let position = position.min(COVER_POSITION_OPEN);
+        self.target_ms = Self::to_ms(self.travel_ms, position);
+
+        // The ends are always driven to, the tracked position may have drifted
+        let motion = if position == COVER_POSITION_OPEN || self.target_ms > self.position_ms {
+            CoverMotion::Opening
+        } else if position == 0 || self.target_ms < self.position_ms {
+            CoverMotion::Closing
+        } else {
+            self.stop();
+            return;
+        };
+
+        self.overrun_ms = if position == 0 || position == COVER_POSITION_OPEN {
+            self.travel_ms / 10
+        } else {
+            0
+        };
+
+        if self.relay != motion {
+            self.release();
+        }
+        self.motion = motion;
+        if self.release_ms == 0 {
+            self.relay = motion;
+        }
+    }
+
+    /// Stops the cover where it is
+    pub fn stop(&mut self) {
+        self.motion = CoverMotion::Stopped;
+        self.release();
+    }
+
+    /// Advances the movement
+    /// # Arguments
+    /// * `elapsed_ms` - The milliseconds since the last tick
+    pub fn tick(&mut self, elapsed_ms: u16) {
+        let reached = match self.relay {
+            CoverMotion::Stopped => {
+                self.release_ms = self.release_ms.saturating_sub(elapsed_ms);
+                if self.release_ms == 0 {
+                    self.relay = self.motion;
+                }
+                return;
+            }
+            CoverMotion::Opening => {
+                let reached = self.position_ms >= self.target_ms;
+                self.position_ms = self
+                    .position_ms
+                    .saturating_add(elapsed_ms as u32)
+                    .min(self.travel_ms);
+                reached || self.position_ms >= self.target_ms
+            }
+            CoverMotion::Closing => {
+                let reached = self.position_ms <= self.target_ms;
+                self.position_ms = self.position_ms.saturating_sub(elapsed_ms as u32);
+                reached || self.position_ms <= self.target_ms
+            }
+        };
+
+        if !reached {
+            return;
+        }
+
+        if self.overrun_ms == 0 {
+            self.stop();
+        } else if self.position_ms == self.target_ms {
+            self.overrun_ms = self.overrun_ms.saturating_sub(elapsed_ms as u32);
+        }
+    }
+
+    /// Returns the current state, moving while waiting to energise a relay
+    pub fn state(&self) -> CoverState {
+        CoverState {
+            position: ((self.position_ms * COVER_POSITION_OPEN as u32 + self.travel_ms / 2)
+                / self.travel_ms) as u8,
+            motion: self.motion,
+        }
+    }
+
+    /// Returns the relay to energise, never more than one
+    pub fn relay(&self) -> CoverMotion {
+        self.relay
+    }
+
+    /// Releases the energised relay and starts the wait before energising either again
+    fn release(&mut self) {
+        if self.relay != CoverMotion::Stopped {
+            self.relay = CoverMotion::Stopped;
+            self.release_ms = Self::RELEASE_MS;
+        }
+    }
+
+    /// Converts a position to milliseconds of travel from closed
+    /// # Arguments
+    /// * `travel_ms` - The milliseconds the cover takes from closed to open
+    /// * `position` - The position to convert
+    fn to_ms(travel_ms: u32, position: u8) -> u32 {
+        travel_ms * position.min(COVER_POSITION_OPEN) as u32 / COVER_POSITION_OPEN as u32
+    }
+}
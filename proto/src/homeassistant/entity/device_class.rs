@@ -0,0 +1,35 @@
+use crate::homeassistant::{binary_sensor::BinarySensorDeviceClass, cover::CoverDeviceClass};
+
+/// Each sensor has a `device_class` associated to it, refer to https://developers.home-assistant.io/docs/core/entity/sensor for more information
+#[allow(dead_code)]
+#[derive(Copy, Clone)]
+pub enum DeviceClass {
+    /// A sensor without a device class, e.g. one reporting text
+    None,
+    Temperature,
+    Humidity,
+    Pressure,
+    Switch,
+    BinarySensor(BinarySensorDeviceClass),
+    Button,
+    Light,
+    Cover(CoverDeviceClass),
+    Select,
+}
+
+impl DeviceClass {
+    /// Returns the DeviceClass in string form for transmission and use withing HomeAssistant
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeviceClass::None => "",
+            DeviceClass::Temperature => "DeviceClass.TEMPERATURE",
+            DeviceClass::Humidity => "DeviceClass.HUMIDITY",
+            DeviceClass::Pressure => "DeviceClass.PRESSURE",
+            DeviceClass::Switch => "SwitchDeviceClass.SWITCH",
+            DeviceClass::BinarySensor(class) => class.as_str(),
+            DeviceClass::Cover(class) => class.as_str(),
+            // HomeAssistant expects no device class for buttons, lights and selects
+            DeviceClass::Button | DeviceClass::Light | DeviceClass::Select => "",
+        }
+    }
+}
@@ -0,0 +1,70 @@
+mod light_ref;
+pub use light_ref::*;
+
+mod fade;
+pub use fade::*;
+
+/// Requests a light can execute
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LightRequest {
+    /// Turns the light on, at `brightness` if set, else at the last brightness
+    TurnOn {
+        /// The brightness to turn on at, `1..=255`
+        brightness: Option<u8>,
+        /// The milliseconds to fade to the new brightness in
+        transition_ms: u16,
+    },
+    /// Turns the light off, keeping the brightness for the next time it is turned on
+    TurnOff {
+        /// The milliseconds to fade out in
+        transition_ms: u16,
+    },
+    /// Toggles the light
+    Toggle {
+        /// The milliseconds to fade in or out in
+        transition_ms: u16,
+    },
+    /// Returns the current state
+    Get,
+}
+
+/// The state of a light
+///
+/// During a transition this is the state the light fades to.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LightState {
+    /// If the light is on
+    pub on: bool,
+    /// The brightness the light is at when it is on, `1..=255`
+    pub brightness: u8,
+}
+
+/// A HomeAssistant Light with a brightness
+///
+/// https://developers.home-assistant.io/docs/core/entity/light for more information
+pub struct Light<'a, F: FnMut(LightRequest) -> LightState> {
+    /// The friendly name for the entity
+    pub name: &'a str,
+    /// The `unique_id` for this entity
+    pub unique_id: &'a str,
+    /// Update the state of the light
+    pub callback: F,
+    /// The state last reported as an event, `None` before the first poll
+    reported: Option<LightState>,
+}
+
+impl<'a, F: FnMut(LightRequest) -> LightState> Light<'a, F> {
+    /// Create a new light
+    /// # Arguments
+    /// * `name` - The friendly name for the light
+    /// * `unique_id` - The unique id for the light
+    /// * `callback` - The callback to use for incoming LightRequests
+    pub fn new(name: &'a str, unique_id: &'a str, callback: F) -> Self {
+        Self {
+            name,
+            unique_id,
+            callback,
+            reported: None,
+        }
+    }
+}
@@ -0,0 +1,84 @@
+/// Fades an output level linearly to a target, e.g. the PWM duty cycle of a light
+///
+/// The fade advances by the time passed to [`Fade::tick`], so it is as smooth as
+/// the ticks are frequent.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Fade {
+    /// The current level
+    level: u8,
+    /// The level the running fade started at
+    from: u8,
+    /// The level the fade ends at
+    to: u8,
+    /// The duration of the running fade in milliseconds
+    duration_ms: u16,
+    /// The milliseconds the running fade lasted
+    elapsed_ms: u16,
+}
+
+impl Fade {
+    /// Creates a fade resting at a level
+    /// # Arguments
+    /// * `level` - The level to rest at
+    pub const fn new(level: u8) -> Self {
+        Self {
+            level,
+            from: level,
+            to: level,
+            duration_ms: 0,
+            elapsed_ms: 0,
+        }
+    }
+
+    /// Starts fading from the current level to another one, replacing a running fade
+    /// # Arguments
+    /// * `to` - The level to fade to
+    /// * `transition_ms` - The duration of the fade, `0` jumps to `to` at once
+    pub fn start(&mut self, to: u8, transition_ms: u16) {
+        self.from = self.level;
+        self.to = to;
+        self.duration_ms = transition_ms;
+        self.elapsed_ms = 0;
+
+        if transition_ms == 0 {
+            self.level = to;
+        }
+    }
+
+    /// Advances the fade
+    /// # Arguments
+    /// * `elapsed_ms` - The milliseconds since the last tick
+    /// # Returns
+    /// The new level
+    pub fn tick(&mut self, elapsed_ms: u16) -> u8 {
+        if !self.is_running() {
+            return self.level;
+        }
+
+        self.elapsed_ms = self
+            .elapsed_ms
+            .saturating_add(elapsed_ms)
+            .min(self.duration_ms);
+
+        let from = self.from as i32;
+        let to = self.to as i32;
+        self.level = (from + (to - from) * self.elapsed_ms as i32 / self.duration_ms as i32) as u8;
+
+        self.level
+    }
+
+    /// Returns the current level
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    /// Returns the level the fade ends at
+    pub fn target(&self) -> u8 {
+        self.to
+    }
+
+    /// Returns true while the level has not reached the target yet
+    pub fn is_running(&self) -> bool {
+        self.level != self.to
+    }
+}
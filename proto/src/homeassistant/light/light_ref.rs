@@ -0,0 +1,52 @@
+use crate::{
+    error::ErrorCode,
+    homeassistant::entity::{DeviceClass, Entity},
+};
+
+use super::*;
+
+pub trait LightRef<'a>: Entity<'a> {
+    /// Execute a LightRequest on the light
+    /// # Arguments
+    /// * `req` - The `LightRequest` to execute
+    /// # Returns
+    /// The state after the request, the error to respond with
+    /// if the request can't be executed
+    fn exec_request(&mut self, req: LightRequest) -> Result<LightState, ErrorCode>;
+    /// Returns true if the state changed since the last call and is to be reported as an event
+    fn take_changed(&mut self) -> bool {
+        false
+    }
+    /// Advances a running transition, called regularly by the firmware
+    /// # Arguments
+    /// * `elapsed_ms` - The milliseconds since the last tick
+    fn tick(&mut self, _elapsed_ms: u16) {}
+}
+
+impl<'a, F: FnMut(LightRequest) -> LightState> Entity<'a> for Light<'a, F> {
+    fn get_unique_id(&self) -> &'a str {
+        self.unique_id
+    }
+
+    fn get_name(&self) -> &'a str {
+        self.name
+    }
+
+    fn get_device_class(&self) -> DeviceClass {
+        DeviceClass::Light
+    }
+}
+
+impl<'a, F: FnMut(LightRequest) -> LightState> LightRef<'a> for Light<'a, F> {
+    fn exec_request(&mut self, req: LightRequest) -> Result<LightState, ErrorCode> {
+        Ok((self.callback)(req))
+    }
+
+    fn take_changed(&mut self) -> bool {
+        let state = (self.callback)(LightRequest::Get);
+        let changed = matches!(self.reported, Some(reported) if reported != state);
+
+        self.reported = Some(state);
+        changed
+    }
+}
@@ -0,0 +1,86 @@
+use core::ops::RangeInclusive;
+
+mod number_ref;
+pub use number_ref::*;
+
+mod number_mode;
+pub use number_mode::*;
+
+use super::entity::DeviceClass;
+
+/// Requests a number can execute
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum NumberRequest {
+    /// Sets the value, already checked against `min` and `max`
+    Set(f32),
+    /// Returns the current value
+    Get,
+}
+
+/// A HomeAssistant Number, e.g. a thermostat setpoint or a PWM duty cycle
+///
+/// https://developers.home-assistant.io/docs/core/entity/number for more information
+pub struct Number<'a, F: FnMut(NumberRequest) -> f32> {
+    /// The friendly name for the entity
+    pub name: &'a str,
+    /// The `unique_id` for this entity
+    pub unique_id: &'a str,
+    /// The unit of measurement for this number
+    pub native_unit_of_measurement: &'a str,
+    /// The `device_class` for this number
+    pub device_class: DeviceClass,
+    /// The minimum accepted value
+    pub min: f32,
+    /// The maximum accepted value
+    pub max: f32,
+    /// The resolution of the value
+    pub step: f32,
+    /// How HomeAssistant renders the number
+    pub mode: NumberMode,
+    /// Update the value of the number
+    pub callback: F,
+    /// The value last reported as an event, `None` before the first poll
+    reported: Option<f32>,
+}
+
+impl<'a, F: FnMut(NumberRequest) -> f32> Number<'a, F> {
+    /// Create a new number
+    /// # Arguments
+    /// * `name` - The friendly name for the number
+    /// * `unique_id` - The unique id for the number
+    /// * `native_unit_of_measurement` - The unit of the value
+    /// * `device_class` - The device class of the number
+    /// * `range` - The accepted values, `min..=max`
+    /// * `step` - The resolution of the value
+    /// * `callback` - The callback to use for incoming NumberRequests
+    pub fn new(
+        name: &'a str,
+        unique_id: &'a str,
+        native_unit_of_measurement: &'a str,
+        device_class: DeviceClass,
+        range: RangeInclusive<f32>,
+        step: f32,
+        callback: F,
+    ) -> Self {
+        Self {
+            name,
+            unique_id,
+            native_unit_of_measurement,
+            device_class,
+            min: *range.start(),
+            max: *range.end(),
+            step,
+            mode: NumberMode::Auto,
+            callback,
+            reported: None,
+        }
+    }
+
+    /// Sets how HomeAssistant renders the number
+    /// # Arguments
+    /// * `mode` - The mode to use
+    pub fn with_mode(mut self, mode: NumberMode) -> Self {
+        self.mode = mode;
+        self
+    }
+}
@@ -0,0 +1,19 @@
+/// Each number has a `mode` associated to it, refer to https://developers.home-assistant.io/docs/core/entity/number for more information
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[allow(dead_code)]
+pub enum NumberMode {
+    Auto,
+    Box,
+    Slider,
+}
+
+impl NumberMode {
+    /// Returns the NumberMode in string form for transmission and use withing HomeAssistant
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NumberMode::Auto => "auto",
+            NumberMode::Box => "box",
+            NumberMode::Slider => "slider",
+        }
+    }
+}
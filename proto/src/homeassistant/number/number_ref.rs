@@ -0,0 +1,80 @@
+use crate::{
+    error::ErrorCode,
+    homeassistant::entity::{DeviceClass, Entity},
+};
+
+use super::*;
+
+pub trait NumberRef<'a>: Entity<'a> {
+    /// The native unit of measurement
+    ///
+    /// https://developers.home-assistant.io/docs/core/entity/number for more information
+    fn get_native_unit_of_measurement(&self) -> &'a str;
+    /// The minimum accepted value
+    fn get_min(&self) -> f32;
+    /// The maximum accepted value
+    fn get_max(&self) -> f32;
+    /// The resolution of the value
+    fn get_step(&self) -> f32;
+    /// How HomeAssistant renders the number
+    fn get_mode(&self) -> NumberMode;
+    /// Execute a NumberRequest on the number
+    /// # Arguments
+    /// * `req` - The `NumberRequest` to execute
+    /// # Returns
+    /// The value after the request, the error to respond with
+    /// if the request can't be executed
+    fn exec_request(&mut self, req: NumberRequest) -> Result<f32, ErrorCode>;
+    /// Returns true if the value changed since the last call and is to be reported as an event
+    fn take_changed(&mut self) -> bool {
+        false
+    }
+}
+
+impl<'a, F: FnMut(NumberRequest) -> f32> Entity<'a> for Number<'a, F> {
+    fn get_unique_id(&self) -> &'a str {
+        self.unique_id
+    }
+
+    fn get_name(&self) -> &'a str {
+        self.name
+    }
+
+    fn get_device_class(&self) -> DeviceClass {
+        self.device_class
+    }
+}
+
+impl<'a, F: FnMut(NumberRequest) -> f32> NumberRef<'a> for Number<'a, F> {
+    fn get_native_unit_of_measurement(&self) -> &'a str {
+        self.native_unit_of_measurement
+    }
+
+    fn get_min(&self) -> f32 {
+        self.min
+    }
+
+    fn get_max(&self) -> f32 {
+        self.max
+    }
+
+    fn get_step(&self) -> f32 {
+        self.step
+    }
+
+    fn get_mode(&self) -> NumberMode {
+        self.mode
+    }
+
+    fn exec_request(&mut self, req: NumberRequest) -> Result<f32, ErrorCode> {
+        Ok((self.callback)(req))
+    }
+
+    fn take_changed(&mut self) -> bool {
+        let value = (self.callback)(NumberRequest::Get);
+        let changed = matches!(self.reported, Some(reported) if reported != value);
+
+        self.reported = Some(value);
+        changed
+    }
+}
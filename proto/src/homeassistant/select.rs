@@ -0,0 +1,45 @@
+mod select_ref;
+pub use select_ref::*;
+
+/// Requests a select can execute
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SelectRequest {
+    /// Selects the option with the index, already checked against the options
+    Set(u8),
+    /// Returns the index of the selected option
+    Get,
+}
+
+/// A HomeAssistant Select, choosing one of a static list of options, e.g. the mode of a heating
+///
+/// https://developers.home-assistant.io/docs/core/entity/select for more information
+pub struct Select<'a, O: AsRef<[&'a str]>, F: FnMut(SelectRequest) -> u8> {
+    /// The friendly name for the entity
+    pub name: &'a str,
+    /// The `unique_id` for this entity
+    pub unique_id: &'a str,
+    /// The options to choose from, at most 255
+    pub options: O,
+    /// Update the selected option, returns its index
+    pub callback: F,
+    /// The index last reported as an event, `None` before the first poll
+    reported: Option<u8>,
+}
+
+impl<'a, O: AsRef<[&'a str]>, F: FnMut(SelectRequest) -> u8> Select<'a, O, F> {
+    /// Create a new select
+    /// # Arguments
+    /// * `name` - The friendly name for the select
+    /// * `unique_id` - The unique id for the select
+    /// * `options` - The options to choose from, at most 255
+    /// * `callback` - The callback to use for incoming SelectRequests
+    pub fn new(name: &'a str, unique_id: &'a str, options: O, callback: F) -> Self {
+        Self {
+            name,
+            unique_id,
+            options,
+            callback,
+            reported: None,
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use crate::{
+    error::ErrorCode,
+    homeassistant::entity::{DeviceClass, Entity},
+};
+
+use super::*;
+
+pub trait SelectRef<'a>: Entity<'a> {
+    /// The options to choose from, indexed by the requests
+    ///
+    /// https://developers.home-assistant.io/docs/core/entity/select for more information
+    fn get_options(&self) -> &[&'a str];
+    /// Execute a SelectRequest on the select
+    /// # Arguments
+    /// * `req` - The `SelectRequest` to execute
+    /// # Returns
+    /// The index of the selected option after the request, the error to respond with
+    /// if the request can't be executed
+    fn exec_request(&mut self, req: SelectRequest) -> Result<u8, ErrorCode>;
+    /// Returns true if the selected option changed since the last call and is to be
+    /// reported as an event
+    fn take_changed(&mut self) -> bool {
+        false
+    }
+}
+
+impl<'a, O: AsRef<[&'a str]>, F: FnMut(SelectRequest) -> u8> Entity<'a> for Select<'a, O, F> {
+    fn get_unique_id(&self) -> &'a str {
+        self.unique_id
+    }
+
+    fn get_name(&self) -> &'a str {
+        self.name
+    }
+
+    fn get_device_class(&self) -> DeviceClass {
+        DeviceClass::Select
+    }
+}
+
+impl<'a, O: AsRef<[&'a str]>, F: FnMut(SelectRequest) -> u8> SelectRef<'a> for Select<'a, O, F> {
+    fn get_options(&self) -> &[&'a str] {
+        self.options.as_ref()
+    }
+
+    fn exec_request(&mut self, req: SelectRequest) -> Result<u8, ErrorCode> {
+        Ok((self.callback)(req))
+    }
+
+    fn take_changed(&mut self) -> bool {
+        let index = (self.callback)(SelectRequest::Get);
+        let changed = matches!(self.reported, Some(reported) if reported != index);
+
+        self.reported = Some(index);
+        changed
+    }
+}
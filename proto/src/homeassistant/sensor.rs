@@ -1,4 +1,4 @@
-use core::cell::RefCell;
+use core::cell::{Cell, RefCell};
 
 mod state_class;
 pub use state_class::*;
@@ -9,6 +9,9 @@ pub use sensor_ref::*;
 mod sensor_value;
 pub use sensor_value::*;
 
+mod string_buf;
+pub use string_buf::*;
+
 use super::entity::DeviceClass;
 
 /// A HomeAssistant Sensor
@@ -27,6 +30,12 @@ pub struct Sensor<'a, T: SensorValue> {
     pub state_class: StateClass,
     /// The current value of this sensor
     pub value: RefCell<Option<T>>,
+    /// The change of the value that is reported as an event, `None` to not report changes
+    pub deadband: Option<f32>,
+    /// The value last reported as an event
+    reported: RefCell<Option<T>>,
+    /// The value moved beyond the deadband since the last event
+    changed: Cell<bool>,
 }
 
 impl<'a, T: SensorValue> Sensor<'a, T> {
@@ -46,13 +55,35 @@ impl<'a, T: SensorValue> Sensor<'a, T> {
             device_class,
             state_class,
             value: RefCell::new(value),
+            deadband: None,
+            reported: RefCell::new(None),
+            changed: Cell::new(false),
         }
     }
 
+    /// Reports changes of the value as events
+    /// # Arguments
+    /// * `deadband` - The change of the value since the last event to report a new one
+    pub fn with_deadband(mut self, deadband: f32) -> Self {
+        self.deadband = Some(deadband);
+        self
+    }
+}
+
+impl<'a, T: SensorValue + Clone> Sensor<'a, T> {
     /// Sets the current value of this sensor
     /// # Arguments
     /// * `value` - The new value to set
     pub fn set_value(&self, value: Option<T>) {
+        if let Some(deadband) = self.deadband {
+            let mut reported = self.reported.borrow_mut();
+
+            if value.distance(&reported) > deadband {
+                *reported = value.clone();
+                self.changed.set(true);
+            }
+        }
+
         *self.value.borrow_mut() = value;
     }
 }
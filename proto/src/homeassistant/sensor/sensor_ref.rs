@@ -1,4 +1,7 @@
-use crate::homeassistant::entity::{DeviceClass, Entity};
+use crate::{
+    error::ErrorCode,
+    homeassistant::entity::{DeviceClass, Entity},
+};
 
 use super::*;
 
@@ -15,7 +18,17 @@ pub trait SensorRef<'a>: Entity<'a> {
     /// # Arguments
     /// * `len` - A mutable reference to the payload len
     /// * `payload` - A mutable reference to the payload array
-    fn get_payload(&self, len: &mut u8, payload: &mut [u8; u8::MAX as usize + 1]);
+    /// # Returns
+    /// The error to respond with if the value can't be read
+    fn get_payload(
+        &self,
+        len: &mut u8,
+        payload: &mut [u8; u8::MAX as usize + 1],
+    ) -> Result<(), ErrorCode>;
+    /// Returns true if the value changed since the last call and is to be reported as an event
+    fn take_changed(&self) -> bool {
+        false
+    }
 }
 
 impl<'a, T: SensorValue> Entity<'a> for Sensor<'a, T> {
@@ -42,7 +55,16 @@ impl<'a, T: SensorValue> SensorRef<'a> for Sensor<'a, T> {
         self.state_class
     }
 
-    fn get_payload(&self, len: &mut u8, payload: &mut [u8; u8::MAX as usize + 1]) {
-        self.value.borrow().to_payload(len, payload)
+    fn get_payload(
+        &self,
+        len: &mut u8,
+        payload: &mut [u8; u8::MAX as usize + 1],
+    ) -> Result<(), ErrorCode> {
+        self.value.borrow().to_payload(len, payload);
+        Ok(())
+    }
+
+    fn take_changed(&self) -> bool {
+        self.changed.replace(false)
     }
 }
@@ -0,0 +1,348 @@
+use core::fmt::{self, Display, Write};
+
+/// The payload type transmitted
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PayloadType {
+    /// All bytes of the payload are the characters in UTF-8 encoding, no null terminator
+    String = 0,
+    /// 4 byte integer, LSB first
+    Int = 1,
+    /// 4 byte float, LSB first
+    Float = 2,
+    /// 4 byte unsigned integer, LSB first
+    UInt = 3,
+    /// 8 byte integer, LSB first
+    Int64 = 4,
+    /// 8 byte unsigned integer, LSB first
+    UInt64 = 5,
+    /// 1 byte, `0` for false and `1` for true
+    Bool = 6,
+    /// 4 byte integer, LSB first, followed by a 1 byte signed decimal exponent
+    Fixed = 7,
+}
+
+impl PayloadType {
+    /// Returns the PayloadType for its identifier on the wire
+    /// # Arguments
+    /// * `value` - The identifier to look up
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(PayloadType::String),
+            1 => Some(PayloadType::Int),
+            2 => Some(PayloadType::Float),
+            3 => Some(PayloadType::UInt),
+            4 => Some(PayloadType::Int64),
+            5 => Some(PayloadType::UInt64),
+            6 => Some(PayloadType::Bool),
+            7 => Some(PayloadType::Fixed),
+            _ => None,
+        }
+    }
+}
+
+/// The maximum length of a string value in bytes. A value is shorter than
+/// `SENSOR_VALUE_ERROR`, its payload type byte included.
+pub const MAX_STRING_LEN: usize = u8::MAX as usize - 2;
+
+pub trait SensorValue {
+    /// Fills a sensor value into a payload array, adjusting the payload length accordingly
+    /// # Arguments
+    /// * `len` - A mutable reference to the payload len
+    /// * `payload` - A mutable reference to the payload array
+    fn to_payload(&self, len: &mut u8, payload: &mut [u8; u8::MAX as usize + 1]);
+
+    /// How far this value is from another one, compared against the deadband of a sensor
+    /// # Arguments
+    /// * `other` - The value to compare with
+    /// # Returns
+    /// `0.0` if the values are equal, `f32::INFINITY` if they differ but can't be measured
+    fn distance(&self, other: &Self) -> f32;
+}
+
+impl<T: SensorValue> SensorValue for Option<T> {
+    fn to_payload(&self, len: &mut u8, payload: &mut [u8; u8::MAX as usize + 1]) {
+        match self {
+            None => *len = 0,
+            Some(v) => v.to_payload(len, payload),
+        }
+    }
+
+    fn distance(&self, other: &Self) -> f32 {
+        match (self, other) {
+            (None, None) => 0.0,
+            (Some(a), Some(b)) => a.distance(b),
+            _ => f32::INFINITY,
+        }
+    }
+}
+
+impl SensorValue for i32 {
+    fn to_payload(&self, len: &mut u8, payload: &mut [u8; u8::MAX as usize + 1]) {
+        payload[0] = PayloadType::Int as u8;
+
+        let bytes = self.to_le_bytes();
+
+        payload[1] = bytes[0];
+        payload[2] = bytes[1];
+        payload[3] = bytes[2];
+        payload[4] = bytes[3];
+
+        *len = (bytes.len() + 1) as u8;
+    }
+
+    fn distance(&self, other: &Self) -> f32 {
+        abs(*self as f32 - *other as f32)
+    }
+}
+
+impl SensorValue for f32 {
+    fn to_payload(&self, len: &mut u8, payload: &mut [u8; u8::MAX as usize + 1]) {
+        payload[0] = PayloadType::Float as u8;
+
+        let bytes = self.to_le_bytes();
+
+        payload[1] = bytes[0];
+        payload[2] = bytes[1];
+        payload[3] = bytes[2];
+        payload[4] = bytes[3];
+
+        *len = (bytes.len() + 1) as u8;
+    }
+
+    fn distance(&self, other: &Self) -> f32 {
+        abs(self - other)
+    }
+}
+
+impl SensorValue for u32 {
+    fn to_payload(&self, len: &mut u8, payload: &mut [u8; u8::MAX as usize + 1]) {
+        payload[0] = PayloadType::UInt as u8;
+
+        let bytes = self.to_le_bytes();
+        payload[1..1 + bytes.len()].copy_from_slice(&bytes);
+
+        *len = (bytes.len() + 1) as u8;
+    }
+
+    fn distance(&self, other: &Self) -> f32 {
+        self.abs_diff(*other) as f32
+    }
+}
+
+impl SensorValue for i64 {
+    fn to_payload(&self, len: &mut u8, payload: &mut [u8; u8::MAX as usize + 1]) {
+        payload[0] = PayloadType::Int64 as u8;
+
+        let bytes = self.to_le_bytes();
+        payload[1..1 + bytes.len()].copy_from_slice(&bytes);
+
+        *len = (bytes.len() + 1) as u8;
+    }
+
+    fn distance(&self, other: &Self) -> f32 {
+        // Counters are large, their difference is not
+        self.abs_diff(*other) as f32
+    }
+}
+
+impl SensorValue for u64 {
+    fn to_payload(&self, len: &mut u8, payload: &mut [u8; u8::MAX as usize + 1]) {
+        payload[0] = PayloadType::UInt64 as u8;
+
+        let bytes = self.to_le_bytes();
+        payload[1..1 + bytes.len()].copy_from_slice(&bytes);
+
+        *len = (bytes.len() + 1) as u8;
+    }
+
+    fn distance(&self, other: &Self) -> f32 {
+        self.abs_diff(*other) as f32
+    }
+}
+
+impl SensorValue for bool {
+    fn to_payload(&self, len: &mut u8, payload: &mut [u8; u8::MAX as usize + 1]) {
+        payload[0] = PayloadType::Bool as u8;
+        payload[1] = *self as u8;
+
+        *len = 2;
+    }
+
+    fn distance(&self, other: &Self) -> f32 {
+        if self == other {
+            0.0
+        } else {
+            f32::INFINITY
+        }
+    }
+}
+
+/// A decimal fixed-point value, `value * 10^exponent`
+///
+/// Keeps the precision of decimal readings like `21.37` that would suffer from a
+/// conversion to `f32`, e.g. on nodes without a floating point unit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Fixed {
+    /// The value scaled by `10^-exponent`
+    pub value: i32,
+    /// The decimal exponent, e.g. `-2` for hundredths
+    pub exponent: i8,
+}
+
+impl Fixed {
+    /// Creates a fixed-point value
+    /// # Arguments
+    /// * `value` - The scaled value
+    /// * `exponent` - The decimal exponent the value is scaled by
+    pub const fn new(value: i32, exponent: i8) -> Self {
+        Self { value, exponent }
+    }
+
+    /// Returns the value as a float, losing precision
+    pub fn to_f32(&self) -> f32 {
+        scale(self.value as f32, self.exponent)
+    }
+}
+
+impl SensorValue for Fixed {
+    fn to_payload(&self, len: &mut u8, payload: &mut [u8; u8::MAX as usize + 1]) {
+        payload[0] = PayloadType::Fixed as u8;
+
+        let bytes = self.value.to_le_bytes();
+        payload[1..1 + bytes.len()].copy_from_slice(&bytes);
+        payload[1 + bytes.len()] = self.exponent as u8;
+
+        *len = (bytes.len() + 2) as u8;
+    }
+
+    fn distance(&self, other: &Self) -> f32 {
+        if self.exponent == other.exponent {
+            // Exact for equal values, no matter how large
+            scale(self.value.abs_diff(other.value) as f32, self.exponent)
+        } else {
+            abs(self.to_f32() - other.to_f32())
+        }
+    }
+}
+
+/// Returns the absolute value, `f32::abs` is not available without `std` on the
+/// toolchain of the firmware
+/// # Arguments
+/// * `value` - The value
+fn abs(value: f32) -> f32 {
+    if value < 0.0 {
+        -value
+    } else {
+        value
+    }
+}
+
+/// Returns `value * 10^exponent`, `powi` is not available without `std`
+/// # Arguments
+/// * `value` - The value to scale
+/// * `exponent` - The decimal exponent
+fn scale(value: f32, exponent: i8) -> f32 {
+    // Rounds once, the powers of ten are exact up to `10^10`
+    let mut factor = 1.0f32;
+    for _ in 0..exponent.unsigned_abs() {
+        factor *= 10.0;
+    }
+
+    if exponent < 0 {
+        value / factor
+    } else {
+        value * factor
+    }
+}
+
+impl SensorValue for &str {
+    fn to_payload(&self, len: &mut u8, payload: &mut [u8; u8::MAX as usize + 1]) {
+        let value = truncate_str(self, MAX_STRING_LEN);
+
+        payload[0] = PayloadType::String as u8;
+        payload[1..1 + value.len()].copy_from_slice(value.as_bytes());
+
+        *len = (value.len() + 1) as u8;
+    }
+
+    fn distance(&self, other: &Self) -> f32 {
+        if self == other {
+            0.0
+        } else {
+            f32::INFINITY
+        }
+    }
+}
+
+/// A value that is sent as the string it formats to, e.g. firmware diagnostics
+///
+/// The value is formatted straight into the payload, strings longer than
+/// [`MAX_STRING_LEN`] get truncated at a character boundary.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Formatted<T: Display + PartialEq>(pub T);
+
+impl<T: Display + PartialEq> SensorValue for Formatted<T> {
+    fn to_payload(&self, len: &mut u8, payload: &mut [u8; u8::MAX as usize + 1]) {
+        payload[0] = PayloadType::String as u8;
+
+        let mut writer = PayloadWriter {
+            bytes: &mut payload[1..1 + MAX_STRING_LEN],
+            len: 0,
+            full: false,
+        };
+        // A truncated value is an error for the formatter, the start of it is sent anyway
+        let _ = write!(writer, "{}", self.0);
+
+        *len = (writer.len + 1) as u8;
+    }
+
+    fn distance(&self, other: &Self) -> f32 {
+        if self == other {
+            0.0
+        } else {
+            f32::INFINITY
+        }
+    }
+}
+
+/// Writes formatted strings into a byte buffer, cutting them off at a character boundary
+/// once the buffer is full
+struct PayloadWriter<'b> {
+    bytes: &'b mut [u8],
+    len: usize,
+    full: bool,
+}
+
+impl Write for PayloadWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.full {
+            return Err(fmt::Error);
+        }
+
+        let value = truncate_str(s, self.bytes.len() - self.len);
+        self.bytes[self.len..self.len + value.len()].copy_from_slice(value.as_bytes());
+        self.len += value.len();
+
+        if value.len() < s.len() {
+            // Later, shorter strings must not be appended behind the cut
+            self.full = true;
+            return Err(fmt::Error);
+        }
+        Ok(())
+    }
+}
+
+/// Returns the longest start of a string that is at most `max` bytes long and ends
+/// at a character boundary
+/// # Arguments
+/// * `value` - The string to truncate
+/// * `max` - The maximum length in bytes
+pub(crate) fn truncate_str(value: &str, max: usize) -> &str {
+    let mut len = value.len().min(max);
+    while !value.is_char_boundary(len) {
+        len -= 1;
+    }
+
+    &value[0..len]
+}
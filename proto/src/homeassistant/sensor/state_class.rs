@@ -2,6 +2,8 @@
 #[derive(Copy, Clone)]
 #[allow(dead_code)]
 pub enum StateClass {
+    /// A sensor that is not a measurement, e.g. one reporting text
+    None,
     Measurement,
     Total,
     TotalIncreasing,
@@ -11,6 +13,7 @@ impl StateClass {
     /// Returns the StateClass in string form for transmission and use withing HomeAssistant
     pub fn as_str(&self) -> &'static str {
         match self {
+            StateClass::None => "",
             StateClass::Measurement => "measurement",
             StateClass::Total => "total",
             StateClass::TotalIncreasing => "total_increasing",
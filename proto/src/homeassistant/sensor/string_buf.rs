@@ -0,0 +1,112 @@
+use core::fmt::{self, Debug, Write};
+
+use super::{truncate_str, SensorValue};
+
+/// A string with a fixed capacity of `N` bytes, for text values that change at runtime
+///
+/// Strings that do not fit get truncated at a character boundary. Only the first
+/// [`MAX_STRING_LEN`](super::MAX_STRING_LEN) bytes are sent as a sensor value.
+#[derive(Clone)]
+pub struct StringBuf<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> StringBuf<N> {
+    /// Creates an empty string
+    pub const fn new() -> Self {
+        Self {
+            bytes: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Creates a string from formatting arguments, e.g. `format_args!("{} resets", count)`
+    /// # Arguments
+    /// * `args` - The arguments to format, truncated if they do not fit
+    pub fn from_fmt(args: fmt::Arguments) -> Self {
+        let mut buf = Self::new();
+        // A truncated string is an error for the formatter, the start of it is kept
+        let _ = buf.write_fmt(args);
+        buf
+    }
+
+    /// Returns the string
+    pub fn as_str(&self) -> &str {
+        // Only whole strings or starts of them ending at a character boundary are copied in
+        core::str::from_utf8(&self.bytes[0..self.len]).unwrap_or_default()
+    }
+
+    /// Returns the length of the string in bytes
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the string is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Empties the string
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Appends a string, truncated at a character boundary if it does not fit
+    /// # Arguments
+    /// * `value` - The string to append
+    /// # Returns
+    /// `false` if the string got truncated
+    pub fn push_str(&mut self, value: &str) -> bool {
+        let fitting = truncate_str(value, N - self.len);
+
+        self.bytes[self.len..self.len + fitting.len()].copy_from_slice(fitting.as_bytes());
+        self.len += fitting.len();
+
+        fitting.len() == value.len()
+    }
+}
+
+impl<const N: usize> Default for StringBuf<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> PartialEq for StringBuf<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize> Eq for StringBuf<N> {}
+
+impl<const N: usize> Debug for StringBuf<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> Write for StringBuf<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.push_str(s) {
+            Ok(())
+        } else {
+            Err(fmt::Error)
+        }
+    }
+}
+
+impl<const N: usize> SensorValue for StringBuf<N> {
+    fn to_payload(&self, len: &mut u8, payload: &mut [u8; u8::MAX as usize + 1]) {
+        self.as_str().to_payload(len, payload);
+    }
+
+    fn distance(&self, other: &Self) -> f32 {
+        if self == other {
+            0.0
+        } else {
+            f32::INFINITY
+        }
+    }
+}
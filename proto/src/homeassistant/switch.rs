@@ -0,0 +1,45 @@
+mod switch_ref;
+pub use switch_ref::*;
+
+/// Commands a switch can execute
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SwitchRequest {
+    /// Turns the switch on
+    TurnON,
+    /// Turns the switch off
+    TurnOFF,
+    /// Toggles the switch state
+    Toggle,
+    /// Returns the current switch state
+    Get,
+}
+
+/// A HomeAssistant Switch
+///
+/// https://developers.home-assistant.io/docs/core/entity/switch for more information
+pub struct Switch<'a, F: FnMut(SwitchRequest) -> bool> {
+    /// The friendly name for the entity
+    pub name: &'a str,
+    /// The `unique_id` for this entity
+    pub unique_id: &'a str,
+    /// Update the state of the switch
+    pub callback: F,
+    /// The state last reported as an event, `None` before the first poll
+    reported: Option<bool>,
+}
+
+impl<'a, F: FnMut(SwitchRequest) -> bool> Switch<'a, F> {
+    /// Create a new switch
+    /// # Arguments
+    /// * `name` - The friendly name for the switch
+    /// * `unique_id` - The unique id for the switch
+    /// * `callback` - The callback to use for incoming SwitchRequests
+    pub fn new(name: &'a str, unique_id: &'a str, callback: F) -> Self {
+        Self {
+            name,
+            unique_id,
+            callback,
+            reported: None,
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use crate::{
+    error::ErrorCode,
+    homeassistant::entity::{DeviceClass, Entity},
+};
+
+use super::*;
+
+pub trait SwitchRef<'a>: Entity<'a> {
+    /// Execute a SwitchRequest on the switch
+    /// # Arguments
+    /// * `req` - The `SwitchRequest` to execute
+    /// # Returns
+    /// In case of `SwitchRequest::Get` the state, the error to respond with
+    /// if the request can't be executed
+    fn exec_request(&mut self, req: SwitchRequest) -> Result<bool, ErrorCode>;
+    /// Returns true if the state changed since the last call and is to be reported as an event
+    fn take_changed(&mut self) -> bool {
+        false
+    }
+}
+
+impl<'a, F: FnMut(SwitchRequest) -> bool> Entity<'a> for Switch<'a, F> {
+    fn get_unique_id(&self) -> &'a str {
+        self.unique_id
+    }
+
+    fn get_name(&self) -> &'a str {
+        self.name
+    }
+
+    fn get_device_class(&self) -> DeviceClass {
+        DeviceClass::Switch
+    }
+}
+
+impl<'a, F: FnMut(SwitchRequest) -> bool> SwitchRef<'a> for Switch<'a, F> {
+    fn exec_request(&mut self, req: SwitchRequest) -> Result<bool, ErrorCode> {
+        Ok((self.callback)(req))
+    }
+
+    fn take_changed(&mut self) -> bool {
+        let state = (self.callback)(SwitchRequest::Get);
+        let changed = matches!(self.reported, Some(reported) if reported != state);
+
+        self.reported = Some(state);
+        changed
+    }
+}
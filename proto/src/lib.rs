@@ -0,0 +1,15 @@
+#![no_std]
+
+pub mod command;
+pub mod crc;
+pub mod datalink;
+pub mod device;
+pub mod driver;
+pub mod error;
+pub mod event;
+pub mod handler;
+pub mod homeassistant;
+pub mod node;
+pub mod tlv;
+
+pub use datalink::DataFrame;
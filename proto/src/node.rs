@@ -0,0 +1,82 @@
+//! The state of this node, shared by the request handlers
+
+use crate::{
+    datalink::{is_multicast, Delivery, Serial, BROADCAST_ADDR},
+    device::DeviceInfo,
+    error::ErrorCode,
+    event::EventQueue,
+};
+
+/// This node on the bus: the addresses it listens on, the identity used to change
+/// them and the state it reports about itself
+pub struct Node<'a> {
+    /// The unicast address of this node
+    pub addr: u16,
+    /// The multicast groups this node is a member of, see [`GROUP_ADDR_FIRST`](crate::datalink::GROUP_ADDR_FIRST)
+    pub groups: &'a [u16],
+    /// The serial number of this node, `None` if it has not been assigned one
+    pub serial: Option<Serial>,
+    /// If set, the node accepts a new address without a serial number,
+    /// e.g. after a button on the board has been pressed
+    pub commissioning: bool,
+    /// The information reported to the master
+    pub info: DeviceInfo<'a>,
+    /// The changes of entities waiting to be fetched by the master
+    pub events: EventQueue,
+}
+
+impl<'a> Node<'a> {
+    /// Creates a new node that is not a member of any group
+    /// # Arguments
+    /// * `addr` - The unicast address of the node
+    pub const fn new(addr: u16) -> Self {
+        Self {
+            addr,
+            groups: &[],
+            serial: None,
+            commissioning: false,
+            info: DeviceInfo::new(),
+            events: EventQueue::new(),
+        }
+    }
+
+    /// Changes the unicast address if the request is permitted
+    ///
+    /// Commissioning mode ends with a successful change
+    /// # Arguments
+    /// * `addr` - The new address, must be a unicast address
+    /// * `serial` - The serial number the request is guarded by, `None` to rely on commissioning mode
+    pub fn change(&mut self, addr: u16, serial: Option<&[u8]>) -> Result<(), ErrorCode> {
+        if is_multicast(addr) {
+            return Err(ErrorCode::InvalidArgument);
+        }
+
+        let permitted = match serial {
+            Some(serial) => self.serial.as_ref().map(|s| s.as_slice()) == Some(serial),
+            None => self.commissioning,
+        };
+        if !permitted {
+            return Err(ErrorCode::NotPermitted);
+        }
+
+        self.addr = addr;
+        self.commissioning = false;
+
+        Ok(())
+    }
+
+    /// Checks if a frame with the destination `dst` is for this node
+    /// # Arguments
+    /// * `dst` - The destination address of the frame
+    /// # Returns
+    /// How the frame reaches this node, `None` if it is not for this node
+    pub fn delivery(&self, dst: u16) -> Option<Delivery> {
+        if dst == self.addr {
+            Some(Delivery::Unicast)
+        } else if dst == BROADCAST_ADDR || self.groups.contains(&dst) {
+            Some(Delivery::Multicast)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! TLV (tag, length, value) encoded payloads describing an entity in a single frame
+//!
+//! Every entry is a `u8` tag, a `u8` length and the value. Entries a master does not
+//! know are skipped, entries that did not fit into the frame are missing.
+
+use crate::DataFrame;
+
+/// The value of a sensor or number: [`PayloadType`](crate::homeassistant::sensor::PayloadType) + value,
+/// empty if the sensor has no value, missing if reading it failed
+pub const TAG_VALUE: u8 = 0x01;
+/// The `unique_id` of the entity, string
+pub const TAG_UNIQUE_ID: u8 = 0x02;
+/// The name of the entity, string
+pub const TAG_NAME: u8 = 0x03;
+/// The `native_unit_of_measurement` of the entity, string
+pub const TAG_NATIVE_UNIT_OF_MEASUREMENT: u8 = 0x04;
+/// The `device_class` of the entity, string
+pub const TAG_DEVICE_CLASS: u8 = 0x05;
+/// The `state_class` of the entity, string
+pub const TAG_STATE_CLASS: u8 = 0x06;
+/// The state of a switch or binary sensor, `u8`, empty if unknown, missing if reading it failed
+///
+/// Lights report if they are on, covers the [`CoverMotion`](crate::homeassistant::cover::CoverMotion),
+/// selects the index of the selected option
+pub const TAG_STATE: u8 = 0x07;
+/// The minimum value of a number, `f32`
+pub const TAG_MIN: u8 = 0x08;
+/// The maximum value of a number, `f32`
+pub const TAG_MAX: u8 = 0x09;
+/// The step of a number, `f32`
+pub const TAG_STEP: u8 = 0x0a;
+/// The `mode` of a number, string
+pub const TAG_MODE: u8 = 0x0b;
+/// The brightness of a light when it is on, `u8`
+pub const TAG_BRIGHTNESS: u8 = 0x0c;
+/// The position of a cover, `u8` from `0` closed to `100` open
+pub const TAG_POSITION: u8 = 0x0d;
+/// An option of a select, string, repeated in the order of the options
+///
+/// Options are never truncated, the ones that did not fit are missing and fetched with
+/// [`CMD_SELECT_OPTION`](crate::command::CMD_SELECT_OPTION)
+pub const TAG_OPTION: u8 = 0x0e;
+/// The amount of options of a select, `u8`
+pub const TAG_OPTION_COUNT: u8 = 0x0f;
+
+/// The maximum length of a payload
+const MAX_PAYLOAD_LEN: usize = u8::MAX as usize;
+
+/// Appends TLV entries to the payload of a frame
+pub struct TlvWriter<'f> {
+    frame: &'f mut DataFrame,
+}
+
+impl<'f> TlvWriter<'f> {
+    /// Creates a writer appending to the current payload of `frame`
+    /// # Arguments
+    /// * `frame` - The frame to append the entries to
+    pub fn new(frame: &'f mut DataFrame) -> Self {
+        Self { frame }
+    }
+
+    /// Returns the amount of value bytes that still fit into the payload
+    pub fn room(&self) -> usize {
+        MAX_PAYLOAD_LEN.saturating_sub(self.frame.payload_len as usize + 2)
+    }
+
+    /// Appends an entry, the value gets truncated to the room left in the payload
+    /// # Arguments
+    /// * `tag` - The tag of the entry
+    /// * `value` - The value of the entry
+    /// # Returns
+    /// `false` if there was no room for the entry
+    pub fn push(&mut self, tag: u8, value: &[u8]) -> bool {
+        let pos = self.frame.payload_len as usize;
+        if pos + 2 > MAX_PAYLOAD_LEN {
+            return false;
+        }
+
+        let len = value.len().min(self.room());
+        self.frame.payload[pos] = tag;
+        self.frame.payload[pos + 1] = len as u8;
+        self.frame.payload[pos + 2..pos + 2 + len].copy_from_slice(&value[0..len]);
+        self.frame.payload_len = (pos + 2 + len) as u8;
+
+        true
+    }
+
+    /// Appends a string entry, truncated at a character boundary to the room left
+    /// # Arguments
+    /// * `tag` - The tag of the entry
+    /// * `value` - The string to append
+    /// # Returns
+    /// `false` if there was no room for the entry
+    pub fn push_str(&mut self, tag: u8, value: &str) -> bool {
+        let mut len = value.len().min(self.room());
+        while !value.is_char_boundary(len) {
+            len -= 1;
+        }
+
+        self.push(tag, &value.as_bytes()[0..len])
+    }
+}
@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+
+use embedded_hal::blocking::i2c::{Write, WriteRead};
+use ha_buddy_proto::driver::{
+    bme280::{
+        Bme280, Bme280Sensors, Calibration, Chip, Reading, ADDRESS_PRIMARY, ADDRESS_SECONDARY,
+        REG_CALIB_00, REG_CALIB_26, REG_CONFIG, REG_CTRL_HUM, REG_CTRL_MEAS, REG_DATA, REG_ID,
+    },
+    i2c::{measure_all, Error, I2cDriver},
+};
+
+/// No device acknowledged the address
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Nack;
+
+/// An I2C bus with devices simulated as register files
+#[derive(Default)]
+struct MockI2c {
+    devices: HashMap<u8, [u8; 256]>,
+    /// The register writes, by address
+    writes: Vec<(u8, u8, u8)>,
+}
+
+impl Write for MockI2c {
+    type Error = Nack;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Nack> {
+        let registers = self.devices.get_mut(&address).ok_or(Nack)?;
+        // Writes are pairs of register and value
+        for pair in bytes.chunks(2) {
+            registers[pair[0] as usize] = pair[1];
+            self.writes.push((address, pair[0], pair[1]));
+        }
+        Ok(())
+    }
+}
+
+impl WriteRead for MockI2c {
+    type Error = Nack;
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Nack> {
+        let registers = self.devices.get(&address).ok_or(Nack)?;
+        let start = bytes[0] as usize;
+        buffer.copy_from_slice(&registers[start..start + buffer.len()]);
+        Ok(())
+    }
+}
+
+/// The calibration of the example in the BMP280 datasheet, with humidity added
+fn calibration() -> Calibration {
+    Calibration {
+        t1: 27504,
+        t2: 26435,
+        t3: -1000,
+        p1: 36477,
+        p2: -10685,
+        p3: 3024,
+        p4: 2855,
+        p5: 140,
+        p6: -7,
+        p7: 15500,
+        p8: -14600,
+        p9: 6000,
+        h1: 75,
+        h2: 362,
+        h3: 0,
+        h4: 313,
+        h5: 50,
+        h6: 30,
+    }
+}
+
+/// The registers of a chip with the chip id `id`, [`calibration`] and no measurement yet
+fn chip(id: u8) -> [u8; 256] {
+    let c = calibration();
+    let mut registers = [0u8; 256];
+    registers[REG_ID as usize] = id;
+
+    let words = [
+        c.t1,
+        c.t2 as u16,
+        c.t3 as u16,
+        c.p1,
+        c.p2 as u16,
+        c.p3 as u16,
+        c.p4 as u16,
+        c.p5 as u16,
+        c.p6 as u16,
+        c.p7 as u16,
+        c.p8 as u16,
+        c.p9 as u16,
+    ];
+    for (i, word) in words.iter().enumerate() {
+        let at = REG_CALIB_00 as usize + 2 * i;
+        registers[at..at + 2].copy_from_slice(&word.to_le_bytes());
+    }
+    registers[0xa1] = c.h1;
+
+    let at = REG_CALIB_26 as usize;
+    registers[at..at + 2].copy_from_slice(&c.h2.to_le_bytes());
+    registers[at + 2] = c.h3;
+    registers[at + 3] = (c.h4 >> 4) as u8;
+    registers[at + 4] = (c.h4 & 0x0f) as u8 | ((c.h5 & 0x0f) as u8) << 4;
+    registers[at + 5] = (c.h5 >> 4) as u8;
+    registers[at + 6] = c.h6 as u8;
+
+    set_raw(&mut registers, 0x80000, 0x80000, 0x8000);
+    registers
+}
+
+/// Stores raw values in the data registers
+fn set_raw(registers: &mut [u8; 256], adc_t: u32, adc_p: u32, adc_h: u16) {
+    let raw20 = |adc: u32| [(adc >> 12) as u8, (adc >> 4) as u8, (adc << 4) as u8];
+    let at = REG_DATA as usize;
+    registers[at..at + 3].copy_from_slice(&raw20(adc_p));
+    registers[at + 3..at + 6].copy_from_slice(&raw20(adc_t));
+    registers[at + 6..at + 8].copy_from_slice(&adc_h.to_be_bytes());
+}
+
+/// The humidity with the floating point formula of the BME280 datasheet
+fn humidity_reference(c: &Calibration, t_fine: i32, adc_h: i32) -> f64 {
+    let h = t_fine as f64 - 76800.0;
+    let h = (adc_h as f64 - (c.h4 as f64 * 64.0 + c.h5 as f64 / 16384.0 * h))
+        * (c.h2 as f64 / 65536.0
+            * (1.0 + c.h6 as f64 / 67108864.0 * h * (1.0 + c.h3 as f64 / 67108864.0 * h)));
+    let h = h * (1.0 - c.h1 as f64 * h / 524288.0);
+    h.clamp(0.0, 100.0)
+}
+
+fn sensors() -> Bme280Sensors<'static> {
+    Bme280Sensors::new(
+        ("Temperature", "bme280-temperature"),
+        ("Pressure", "bme280-pressure"),
+        ("Humidity", "bme280-humidity"),
+    )
+}
+
+#[test]
+fn parse_calibration() {
+    let registers = chip(0x60);
+    let calib_00 = registers[0x88..0x88 + 26].try_into().unwrap();
+    let calib_26 = registers[0xe1..0xe1 + 7].try_into().unwrap();
+    assert_eq!(
+        Calibration::from_registers(calib_00, calib_26),
+        calibration()
+    );
+
+    // The 12 bit values are signed
+    let calib_26 = [0, 0, 0, 0xff, 0xff, 0xfe, 0];
+    let parsed = Calibration::from_registers(calib_00, &calib_26);
+    assert_eq!(parsed.h4, -1);
+    assert_eq!(parsed.h5, -17);
+}
+
+#[test]
+fn compensation() {
+    // The example of the BMP280 datasheet
+    let c = calibration();
+    let t_fine = c.t_fine(519888);
+    assert_eq!(t_fine, 128422);
+    assert_eq!(c.temperature(t_fine), 25.08);
+    assert!((c.pressure(t_fine, 415148) - 1006.5327).abs() < 0.001);
+
+    for adc_h in [20000, 27000, 35000] {
+        let expected = humidity_reference(&c, t_fine, adc_h);
+        assert!((c.humidity(t_fine, adc_h) as f64 - expected).abs() < 0.01);
+    }
+    assert_eq!(c.humidity(t_fine, 0), 0.0);
+    assert_eq!(c.humidity(t_fine, 0xffff), 100.0);
+
+    // Uncalibrated chips do not divide by zero
+    assert_eq!(Calibration::default().pressure(t_fine, 415148), 0.0);
+}
+
+#[test]
+fn bad_calibration() {
+    for byte in [0x00, 0xff] {
+        let c = Calibration::from_registers(&[byte; 26], &[byte; 7]);
+        assert!(!c.is_valid());
+
+        // Garbage, but no overflow
+        for adc in (0..0x100000).step_by(0x1111) {
+            let t_fine = c.t_fine(adc);
+            c.temperature(t_fine);
+            c.pressure(t_fine, adc);
+            c.humidity(t_fine, adc >> 4);
+        }
+    }
+    assert!(calibration().is_valid());
+}
+
+#[test]
+fn chip_ids() {
+    assert_eq!(Chip::from_id(0x60), Some(Chip::Bme280));
+    assert_eq!(Chip::from_id(0x58), Some(Chip::Bmp280));
+    assert_eq!(Chip::from_id(0x55), None);
+}
+
+#[test]
+fn init_configures_normal_mode() {
+    let mut bus = MockI2c::default();
+    bus.devices.insert(ADDRESS_PRIMARY, chip(0x60));
+    let sensors = sensors();
+    let mut bme280 = Bme280::new(ADDRESS_PRIMARY, &sensors);
+    assert_eq!(bme280.chip(), None);
+
+    assert_eq!(bme280.init(&mut bus), Ok(Chip::Bme280));
+    assert_eq!(bme280.chip(), Some(Chip::Bme280));
+    assert_eq!(
+        bus.writes,
+        [
+            (ADDRESS_PRIMARY, REG_CTRL_MEAS, 0),
+            (ADDRESS_PRIMARY, REG_CTRL_HUM, 0b001),
+            (ADDRESS_PRIMARY, REG_CONFIG, 0b1000_0000),
+            (ADDRESS_PRIMARY, REG_CTRL_MEAS, 0b0010_0111),
+        ]
+    );
+
+    // A BMP280 has no humidity control
+    bus.devices.insert(ADDRESS_SECONDARY, chip(0x58));
+    bus.writes.clear();
+    let mut bmp280 = Bme280::new(ADDRESS_SECONDARY, &sensors);
+    assert_eq!(bmp280.init(&mut bus), Ok(Chip::Bmp280));
+    assert!(bus.writes.iter().all(|w| w.1 != REG_CTRL_HUM));
+}
+
+#[test]
+fn init_unknown_chip() {
+    let mut bus = MockI2c::default();
+    bus.devices.insert(ADDRESS_PRIMARY, chip(0x55));
+    let sensors = sensors();
+    let mut bme280 = Bme280::new(ADDRESS_PRIMARY, &sensors);
+
+    assert_eq!(bme280.init(&mut bus), Err(Error::UnknownChip(0x55)));
+    assert!(bus.writes.is_empty());
+
+    let mut absent = Bme280::new(ADDRESS_SECONDARY, &sensors);
+    assert_eq!(absent.init(&mut bus), Err(Error::Bus(Nack)));
+}
+
+#[test]
+fn init_bad_calibration() {
+    let mut bus = MockI2c::default();
+    let mut registers = chip(0x60);
+    registers[0x88..0x88 + 26].fill(0);
+    bus.devices.insert(ADDRESS_PRIMARY, registers);
+    let sensors = sensors();
+    let mut bme280 = Bme280::new(ADDRESS_PRIMARY, &sensors);
+
+    assert_eq!(bme280.init(&mut bus), Err(Error::InvalidCalibration));
+    assert_eq!(bme280.chip(), None);
+    assert!(bus.writes.is_empty());
+}
+
+#[test]
+fn measure_updates_sensors() {
+    let mut bus = MockI2c::default();
+    bus.devices.insert(ADDRESS_PRIMARY, chip(0x60));
+    let sensors = sensors();
+    assert_eq!(sensors.pressure.native_unit_of_measurement, "hPa");
+    assert_eq!(
+        sensors.pressure.device_class.as_str(),
+        "DeviceClass.PRESSURE"
+    );
+    let mut bme280 = Bme280::new(ADDRESS_PRIMARY, &sensors);
+
+    // The first measurement sets up the chip, which has not measured yet
+    bme280.measure(&mut bus).unwrap();
+    assert_eq!(bme280.chip(), Some(Chip::Bme280));
+    bme280.measure(&mut bus).unwrap();
+    assert_eq!(*sensors.temperature.value.borrow(), None);
+
+    let registers = bus.devices.get_mut(&ADDRESS_PRIMARY).unwrap();
+    set_raw(registers, 519888, 415148, 27000);
+    let reading = bme280.read(&mut bus).unwrap().unwrap();
+    assert_eq!(reading.temperature, 25.08);
+    assert!((reading.pressure - 1006.5327).abs() < 0.001);
+    assert!(reading.humidity.is_some());
+
+    bme280.measure(&mut bus).unwrap();
+    assert_eq!(*sensors.temperature.value.borrow(), Some(Some(25.08)));
+    assert_eq!(
+        *sensors.pressure.value.borrow(),
+        Some(Some(reading.pressure))
+    );
+    assert_eq!(*sensors.humidity.value.borrow(), Some(reading.humidity));
+
+    // A chip that is gone leaves the sensors without a value and is set up again
+    let registers = bus.devices.remove(&ADDRESS_PRIMARY).unwrap();
+    assert_eq!(bme280.measure(&mut bus), Err(Error::Bus(Nack)));
+    assert_eq!(*sensors.temperature.value.borrow(), Some(None));
+    assert_eq!(*sensors.humidity.value.borrow(), Some(None));
+    assert_eq!(bme280.chip(), None);
+
+    bus.devices.insert(ADDRESS_PRIMARY, registers);
+    bus.writes.clear();
+    bme280.measure(&mut bus).unwrap();
+    assert_eq!(bus.writes.len(), 4);
+    bme280.measure(&mut bus).unwrap();
+    assert_eq!(*sensors.temperature.value.borrow(), Some(Some(25.08)));
+}
+
+#[test]
+fn bmp280_without_humidity() {
+    let mut bus = MockI2c::default();
+    let mut registers = chip(0x58);
+    set_raw(&mut registers, 519888, 415148, 27000);
+    bus.devices.insert(ADDRESS_SECONDARY, registers);
+
+    let sensors = sensors();
+    let mut bmp280 = Bme280::new(ADDRESS_SECONDARY, &sensors);
+    bmp280.init(&mut bus).unwrap();
+
+    let reading = bmp280.read(&mut bus).unwrap().unwrap();
+    assert_eq!(
+        reading,
+        Reading {
+            temperature: 25.08,
+            pressure: reading.pressure,
+            humidity: None,
+        }
+    );
+
+    bmp280.measure(&mut bus).unwrap();
+    assert_eq!(*sensors.humidity.value.borrow(), Some(None));
+}
+
+#[test]
+fn measure_all_devices() {
+    let mut bus = MockI2c::default();
+    let mut registers = chip(0x60);
+    set_raw(&mut registers, 519888, 415148, 27000);
+    bus.devices.insert(ADDRESS_SECONDARY, registers);
+
+    let indoor = sensors();
+    let outdoor = sensors();
+    let mut absent = Bme280::new(ADDRESS_PRIMARY, &outdoor);
+    let mut present = Bme280::new(ADDRESS_SECONDARY, &indoor);
+
+    // The missing device does not keep the other one from being read
+    let mut drivers: [&mut dyn I2cDriver<MockI2c, Nack>; 2] = [&mut absent, &mut present];
+    assert_eq!(measure_all(&mut bus, &mut drivers), Err(Error::Bus(Nack)));
+    assert_eq!(measure_all(&mut bus, &mut drivers), Err(Error::Bus(Nack)));
+
+    assert_eq!(*indoor.temperature.value.borrow(), Some(Some(25.08)));
+    assert_eq!(*outdoor.temperature.value.borrow(), Some(None));
+}
@@ -0,0 +1,60 @@
+#![allow(dead_code)]
+
+use core::convert::Infallible;
+
+use ha_buddy_proto::DataFrame;
+
+/// A serial writer that collects all written bytes
+#[derive(Default)]
+pub struct VecWriter {
+    pub bytes: Vec<u8>,
+}
+
+impl embedded_hal::serial::Write<u8> for VecWriter {
+    type Error = Infallible;
+
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        self.bytes.push(word);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Creates a new frame with the supplied header fields and payload
+pub fn frame(src: u16, dst: u16, cmd: u16, payload: &[u8]) -> DataFrame {
+    let mut frame = DataFrame::new();
+    frame.src = src;
+    frame.dst = dst;
+    frame.cmd = cmd;
+    frame.payload_len = payload.len() as u8;
+    frame.payload[0..payload.len()].copy_from_slice(payload);
+    frame
+}
+
+/// Serializes a frame to the bytes that would go over the wire
+pub fn to_bytes(frame: &mut DataFrame) -> Vec<u8> {
+    let mut writer = VecWriter::default();
+    frame.send(&mut writer).unwrap();
+    writer.bytes
+}
+
+/// Feeds bytes into a fresh frame, returning the frame if one was completed
+pub fn from_bytes(bytes: &[u8]) -> Option<DataFrame> {
+    let mut frame = DataFrame::new();
+
+    for byte in bytes {
+        if frame.handle_byte(*byte) {
+            return Some(frame);
+        }
+    }
+
+    None
+}
+
+/// Returns the payload of a frame as a slice
+pub fn payload(frame: &DataFrame) -> &[u8] {
+    &frame.payload[0..frame.payload_len as usize]
+}
@@ -0,0 +1,105 @@
+use ha_buddy_proto::homeassistant::cover::{CoverMotion, CoverState, Travel};
+
+/// Ticks `travel` in steps of 10 ms for `ms` milliseconds
+fn run(travel: &mut Travel, ms: u32) {
+    for _ in 0..ms / 10 {
+        travel.tick(10);
+        // The interlock: at most one relay, and only in the direction of the motion
+        assert!(travel.relay() == CoverMotion::Stopped || travel.relay() == travel.state().motion);
+    }
+}
+
+#[test]
+fn travel_to_position() {
+    let mut travel = Travel::new(10_000, 0);
+    travel.move_to(30);
+
+    // A resting cover starts at once
+    assert_eq!(travel.relay(), CoverMotion::Opening);
+    run(&mut travel, 1_500);
+    assert_eq!(
+        travel.state(),
+        CoverState {
+            position: 15,
+            motion: CoverMotion::Opening
+        }
+    );
+
+    run(&mut travel, 1_500);
+    assert_eq!(
+        travel.state(),
+        CoverState {
+            position: 30,
+            motion: CoverMotion::Stopped
+        }
+    );
+    assert_eq!(travel.relay(), CoverMotion::Stopped);
+}
+
+#[test]
+fn travel_overruns_at_the_ends() {
+    let mut travel = Travel::new(10_000, 90);
+    travel.move_to(100);
+
+    run(&mut travel, 1_000);
+    assert_eq!(travel.state().position, 100);
+    assert_eq!(travel.relay(), CoverMotion::Opening);
+
+    // A tenth of the travel time past the end
+    run(&mut travel, 1_000);
+    assert_eq!(travel.relay(), CoverMotion::Stopped);
+    assert_eq!(travel.state().motion, CoverMotion::Stopped);
+
+    // Already at the end, the end is driven to anyway
+    travel.move_to(100);
+    run(&mut travel, Travel::RELEASE_MS as u32);
+    assert_eq!(travel.relay(), CoverMotion::Opening);
+}
+
+#[test]
+fn travel_reverses_after_release() {
+    let mut travel = Travel::new(10_000, 50);
+    travel.move_to(100);
+    run(&mut travel, 1_000);
+    assert_eq!(travel.state().position, 60);
+
+    // Both relays stay released until the motor stopped
+    travel.move_to(0);
+    assert_eq!(travel.relay(), CoverMotion::Stopped);
+    assert_eq!(travel.state().motion, CoverMotion::Closing);
+    run(&mut travel, Travel::RELEASE_MS as u32 - 10);
+    assert_eq!(travel.relay(), CoverMotion::Stopped);
+    assert_eq!(travel.state().position, 60);
+
+    run(&mut travel, 10);
+    assert_eq!(travel.relay(), CoverMotion::Closing);
+    run(&mut travel, 2_000);
+    assert_eq!(travel.state().position, 40);
+}
+
+#[test]
+fn travel_stop() {
+    let mut travel = Travel::new(20_000, 100);
+    travel.move_to(0);
+    run(&mut travel, 5_000);
+
+    travel.stop();
+    assert_eq!(
+        travel.state(),
+        CoverState {
+            position: 75,
+            motion: CoverMotion::Stopped
+        }
+    );
+    assert_eq!(travel.relay(), CoverMotion::Stopped);
+
+    // Moving on in the same direction waits for the release as well
+    travel.move_to(50);
+    assert_eq!(travel.relay(), CoverMotion::Stopped);
+    run(&mut travel, Travel::RELEASE_MS as u32);
+    assert_eq!(travel.relay(), CoverMotion::Closing);
+
+    // The current position stops the cover
+    travel.move_to(travel.state().position);
+    assert_eq!(travel.state().motion, CoverMotion::Stopped);
+}
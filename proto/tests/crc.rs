@@ -0,0 +1,71 @@
+use ha_buddy_proto::crc::{CRC8Autosar, CRC8Maxim, CRC, CRC8_AUTOSAR_INIT};
+
+#[test]
+fn check_value() {
+    // The standard check value for CRC-8/AUTOSAR
+    let mut crc = CRC8Autosar::new();
+    crc.update(b"123456789");
+    assert_eq!(crc.finalize(), 0xdf);
+}
+
+#[test]
+fn empty_input() {
+    let crc = CRC8Autosar::new();
+    assert_eq!(crc.finalize(), 0x00);
+}
+
+#[test]
+fn incremental_update() {
+    let mut whole = CRC8Autosar::new();
+    whole.update(b"123456789");
+
+    let mut parts = CRC8Autosar::new();
+    parts.update(b"123");
+    parts.update(b"456");
+    parts.update(b"789");
+
+    assert_eq!(whole.finalize(), parts.finalize());
+}
+
+#[test]
+fn finalize_is_non_destructive() {
+    let mut crc = CRC8Autosar::new();
+    crc.update(b"ha-buddy");
+
+    assert_eq!(crc.finalize(), crc.finalize());
+}
+
+#[test]
+fn reset() {
+    let mut crc = CRC8Autosar::new();
+    crc.update(b"123456789");
+    crc.reset();
+
+    assert_eq!(crc.crc, CRC8_AUTOSAR_INIT);
+
+    crc.update(b"123456789");
+    assert_eq!(crc.finalize(), 0xdf);
+}
+
+#[test]
+fn maxim_check_value() {
+    // The standard check value for CRC-8/MAXIM-DOW
+    let mut crc = CRC8Maxim::new();
+    crc.update(b"123456789");
+    assert_eq!(crc.finalize(), 0xa1);
+}
+
+#[test]
+fn maxim_rom_code() {
+    // The example ROM code of Maxim application note 27
+    let rom = [0x02, 0x1c, 0xb8, 0x01, 0x00, 0x00, 0x00, 0xa2];
+
+    let mut crc = CRC8Maxim::new();
+    crc.update(&rom[0..7]);
+    assert_eq!(crc.finalize(), rom[7]);
+
+    // Including the CRC itself results in 0
+    crc.reset();
+    crc.update(&rom);
+    assert_eq!(crc.finalize(), 0);
+}
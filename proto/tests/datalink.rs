@@ -0,0 +1,219 @@
+mod common;
+
+use common::*;
+use ha_buddy_proto::{
+    datalink::{is_multicast, serial_has_prefix, Delivery, BROADCAST_ADDR, GROUP_ADDR_FIRST},
+    node::Node,
+    DataFrame,
+};
+
+#[test]
+fn wire_format() {
+    let mut frame = frame(0x0000, 0x1000, 0x0112, &[1, 2, 3]);
+
+    assert_eq!(
+        to_bytes(&mut frame),
+        [0xaa, 0x55, 0x00, 0x00, 0x00, 0x10, 0x12, 0x01, 0x03, 0x48, 1, 2, 3, 0x90]
+    );
+    assert_eq!(frame.h_crc, 0x48);
+    assert_eq!(frame.f_crc, 0x90);
+}
+
+#[test]
+fn roundtrip() {
+    let mut sent = frame(0x1234, 0xabcd, 0x0208, &[0, 0, 0, 0, 1]);
+    let received = from_bytes(&to_bytes(&mut sent)).expect("No frame received");
+
+    assert_eq!(received.src, 0x1234);
+    assert_eq!(received.dst, 0xabcd);
+    assert_eq!(received.cmd, 0x0208);
+    assert_eq!(payload(&received), [0, 0, 0, 0, 1]);
+    assert!(received.check_crc());
+}
+
+#[test]
+fn roundtrip_empty_payload() {
+    let mut sent = frame(0x0000, 0x1000, 0x0000, &[]);
+    let bytes = to_bytes(&mut sent);
+    assert_eq!(bytes.len(), 11);
+
+    let received = from_bytes(&bytes).expect("No frame received");
+    assert_eq!(received.payload_len, 0);
+    assert!(received.check_crc());
+}
+
+#[test]
+fn roundtrip_max_payload() {
+    let data: Vec<u8> = (0..=254).collect();
+    let mut sent = frame(0x0000, 0x1000, 0x0000, &data);
+    let bytes = to_bytes(&mut sent);
+    assert_eq!(bytes.len(), 11 + 255);
+
+    let received = from_bytes(&bytes).expect("No frame received");
+    assert_eq!(payload(&received), data.as_slice());
+    assert!(received.check_crc());
+}
+
+#[test]
+fn frame_completes_on_last_byte() {
+    let mut sent = frame(0x0000, 0x1000, 0x0000, &[0x42]);
+    let bytes = to_bytes(&mut sent);
+
+    let mut frame = DataFrame::new();
+    assert!(frame.is_idle());
+    for byte in &bytes[0..bytes.len() - 1] {
+        assert!(!frame.handle_byte(*byte));
+        assert!(!frame.is_idle());
+    }
+    assert!(frame.handle_byte(bytes[bytes.len() - 1]));
+    assert!(frame.is_idle());
+}
+
+#[test]
+fn skips_garbage_before_frame() {
+    let mut sent = frame(0x0000, 0x1000, 0x0100, &[]);
+
+    let mut bytes = vec![0x00, 0x13, 0x37, 0xff];
+    bytes.extend(to_bytes(&mut sent));
+
+    let received = from_bytes(&bytes).expect("No frame received");
+    assert_eq!(received.cmd, 0x0100);
+    assert!(received.check_crc());
+}
+
+#[test]
+fn invalid_second_start_byte() {
+    let mut sent = frame(0x0000, 0x1000, 0x0000, &[]);
+    let mut bytes = to_bytes(&mut sent);
+    bytes[1] = 0x56;
+
+    assert!(from_bytes(&bytes).is_none());
+}
+
+#[test]
+fn drops_invalid_header_crc() {
+    let mut sent = frame(0x0000, 0x1000, 0x0000, &[1, 2, 3]);
+    let mut bytes = to_bytes(&mut sent);
+    bytes[9] ^= 0xff;
+
+    assert!(from_bytes(&bytes).is_none());
+}
+
+#[test]
+fn resyncs_after_invalid_header() {
+    let mut broken = frame(0x0000, 0x1000, 0x0000, &[1, 2, 3]);
+    let mut bytes = to_bytes(&mut broken);
+    bytes[9] ^= 0xff;
+    // Only keep the corrupted header
+    bytes.truncate(10);
+
+    let mut sent = frame(0x0000, 0x1000, 0x0200, &[]);
+    bytes.extend(to_bytes(&mut sent));
+
+    let received = from_bytes(&bytes).expect("No frame received");
+    assert_eq!(received.cmd, 0x0200);
+    assert!(received.check_crc());
+}
+
+#[test]
+fn detects_corrupted_payload() {
+    let mut sent = frame(0x0000, 0x1000, 0x0000, &[1, 2, 3]);
+    let mut bytes = to_bytes(&mut sent);
+    bytes[11] ^= 0x01;
+
+    let received = from_bytes(&bytes).expect("No frame received");
+    assert!(!received.check_crc());
+}
+
+#[test]
+fn detects_corrupted_frame_crc() {
+    let mut sent = frame(0x0000, 0x1000, 0x0000, &[1, 2, 3]);
+    let mut bytes = to_bytes(&mut sent);
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0x01;
+
+    let received = from_bytes(&bytes).expect("No frame received");
+    assert!(!received.check_crc());
+}
+
+#[test]
+fn back_to_back_frames() {
+    let mut first = frame(0x0000, 0x1000, 0x0100, &[]);
+    let mut second = frame(0x0000, 0x1001, 0x0200, &[9]);
+
+    let mut bytes = to_bytes(&mut first);
+    bytes.extend(to_bytes(&mut second));
+
+    let mut frame = DataFrame::new();
+    let mut received = Vec::new();
+    for byte in bytes {
+        if frame.handle_byte(byte) {
+            assert!(frame.check_crc());
+            received.push((frame.dst, frame.cmd));
+        }
+    }
+
+    assert_eq!(received, [(0x1000, 0x0100), (0x1001, 0x0200)]);
+}
+
+#[test]
+fn node_delivery() {
+    let node = Node {
+        groups: &[0xff10],
+        ..Node::new(0x1000)
+    };
+
+    assert_eq!(node.delivery(0x1000), Some(Delivery::Unicast));
+    assert_eq!(node.delivery(BROADCAST_ADDR), Some(Delivery::Multicast));
+    assert_eq!(node.delivery(0xff10), Some(Delivery::Multicast));
+    assert_eq!(node.delivery(0xff11), None);
+    assert_eq!(node.delivery(0x1001), None);
+
+    assert_eq!(
+        Node::new(0x1000).delivery(0xff10),
+        None,
+        "Nodes are no group members by default"
+    );
+}
+
+#[test]
+fn multicast_addresses() {
+    assert!(is_multicast(BROADCAST_ADDR));
+    assert!(is_multicast(GROUP_ADDR_FIRST));
+    assert!(!is_multicast(GROUP_ADDR_FIRST - 1));
+    assert!(!is_multicast(0x1000));
+}
+
+#[test]
+fn serial_prefix() {
+    let serial = [0b1010_0000, 0x42, 0, 0, 0, 0, 0, 0xff];
+
+    assert!(serial_has_prefix(&serial, &[0; 8], 0));
+    assert!(serial_has_prefix(
+        &serial,
+        &[0b1000_0000, 0, 0, 0, 0, 0, 0, 0],
+        1
+    ));
+    assert!(!serial_has_prefix(
+        &serial,
+        &[0b1000_0000, 0, 0, 0, 0, 0, 0, 0],
+        3
+    ));
+    assert!(serial_has_prefix(
+        &serial,
+        &[0b1010_1111, 0, 0, 0, 0, 0, 0, 0],
+        4
+    ));
+    assert!(serial_has_prefix(
+        &serial,
+        &[0b1010_0000, 0x42, 0xff, 0, 0, 0, 0, 0],
+        16
+    ));
+    assert!(!serial_has_prefix(
+        &serial,
+        &[0b1010_0000, 0x43, 0, 0, 0, 0, 0, 0],
+        16
+    ));
+    assert!(serial_has_prefix(&serial, &serial, 64));
+    assert!(!serial_has_prefix(&serial, &serial, 65));
+}
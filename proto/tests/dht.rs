@@ -0,0 +1,248 @@
+use std::{cell::RefCell, convert::Infallible, rc::Rc};
+
+use embedded_hal::{
+    blocking::delay::DelayUs,
+    digital::v2::{InputPin, OutputPin},
+};
+use ha_buddy_proto::driver::dht::{Dht, DhtSensors, Error, Model, Reading};
+
+/// The simulated data line, shared by the pin and the delay
+struct Line {
+    /// The simulated time in µs
+    now: u32,
+    /// The node pulls the line low since this time
+    low_since: Option<u32>,
+    /// The bytes the sensor sends, `None` if there is no sensor
+    data: Option<[u8; 5]>,
+    /// The shortest start signal the sensor answers
+    start_us: u32,
+    /// The sensor stops sending after this many bits
+    bits: usize,
+    /// The levels the sensor sends and until when, `true` for high
+    pulses: Vec<(bool, u32)>,
+    /// How many µs a delay of 1 µs takes, a slow core polls less often
+    slowdown: u32,
+}
+
+impl Line {
+    /// Starts sending a reading after the node released the line
+    fn respond(&mut self, data: [u8; 5]) {
+        let mut pulses = vec![(true, 30), (false, 80), (true, 80)];
+        for bit in 0..self.bits {
+            let one = data[bit / 8] & 0x80 >> (bit % 8) != 0;
+            pulses.push((false, 50));
+            pulses.push((true, if one { 70 } else { 27 }));
+        }
+        pulses.push((false, if self.bits < 40 { 1_000 } else { 50 }));
+
+        let mut end = self.now;
+        self.pulses = pulses
+            .into_iter()
+            .map(|(level, us)| {
+                end += us;
+                (level, end)
+            })
+            .collect();
+    }
+}
+
+#[derive(Clone)]
+struct MockPin(Rc<RefCell<Line>>);
+
+impl OutputPin for MockPin {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        let mut line = self.0.borrow_mut();
+        line.low_since = Some(line.now);
+        line.pulses.clear();
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        let mut line = self.0.borrow_mut();
+        if let Some(since) = line.low_since.take() {
+            match line.data {
+                Some(data) if line.now - since >= line.start_us => line.respond(data),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+impl InputPin for MockPin {
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Infallible> {
+        let line = self.0.borrow();
+        if line.low_since.is_some() {
+            return Ok(false);
+        }
+
+        // Released, the pull-up wins unless the sensor pulls low
+        let pulse = line.pulses.iter().find(|(_, end)| *end > line.now);
+        Ok(!matches!(pulse, Some((false, _))))
+    }
+
+    fn is_low(&self) -> Result<bool, Infallible> {
+        self.is_high().map(|high| !high)
+    }
+}
+
+struct MockDelay(Rc<RefCell<Line>>);
+
+impl DelayUs<u16> for MockDelay {
+    fn delay_us(&mut self, us: u16) {
+        let mut line = self.0.borrow_mut();
+        line.now += us as u32 * line.slowdown;
+    }
+}
+
+/// Appends the checksum to a reading
+fn frame(data: [u8; 4]) -> [u8; 5] {
+    let sum = data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+    [data[0], data[1], data[2], data[3], sum]
+}
+
+/// A sensor of the kind `model` sending `data`
+fn sensor(model: Model, data: Option<[u8; 5]>) -> (Dht<MockPin, MockDelay>, Rc<RefCell<Line>>) {
+    let line = Rc::new(RefCell::new(Line {
+        now: 0,
+        low_since: None,
+        data,
+        start_us: match model {
+            Model::Dht11 => 18_000,
+            Model::Dht22 => 1_000,
+        },
+        bits: 40,
+        pulses: Vec::new(),
+        slowdown: 1,
+    }));
+
+    let dht = Dht::new(MockPin(line.clone()), MockDelay(line.clone()), model).unwrap();
+    (dht, line)
+}
+
+#[test]
+fn dht22_reading() {
+    let (mut dht, _) = sensor(Model::Dht22, Some(frame([0x02, 0x8c, 0x01, 0x5f])));
+    assert_eq!(
+        dht.read().unwrap(),
+        Reading {
+            humidity: 65.2,
+            temperature: 35.1,
+        }
+    );
+}
+
+#[test]
+fn dht22_negative_temperature() {
+    let (mut dht, _) = sensor(Model::Dht22, Some(frame([0x03, 0xe8, 0x80, 0x65])));
+    assert_eq!(
+        dht.read().unwrap(),
+        Reading {
+            humidity: 100.0,
+            temperature: -10.1,
+        }
+    );
+}
+
+#[test]
+fn dht11_reading() {
+    let (mut dht, _) = sensor(Model::Dht11, Some(frame([45, 0, 23, 5])));
+    assert_eq!(
+        dht.read().unwrap(),
+        Reading {
+            humidity: 45.0,
+            temperature: 23.5,
+        }
+    );
+
+    assert_eq!(
+        Model::Dht11.decode(&frame([20, 0, 2, 0x83])).temperature,
+        -2.3
+    );
+}
+
+#[test]
+fn slow_polling() {
+    // Every poll takes 3 µs, the pulses are still told apart
+    let (mut dht, line) = sensor(Model::Dht22, Some(frame([0x01, 0xf4, 0x00, 0xdc])));
+    line.borrow_mut().slowdown = 3;
+    assert_eq!(
+        dht.read().unwrap(),
+        Reading {
+            humidity: 50.0,
+            temperature: 22.0,
+        }
+    );
+}
+
+#[test]
+fn start_and_receive() {
+    // The start signal may be stretched, e.g. by interrupts
+    let (mut dht, line) = sensor(Model::Dht22, Some(frame([0x02, 0x8c, 0x01, 0x5f])));
+    dht.start().unwrap();
+    line.borrow_mut().now += 500;
+    assert_eq!(
+        dht.receive().unwrap(),
+        Reading {
+            humidity: 65.2,
+            temperature: 35.1,
+        }
+    );
+}
+
+#[test]
+fn checksum_mismatch() {
+    let mut data = frame([0x02, 0x8c, 0x01, 0x5f]);
+    data[4] ^= 0x10;
+    let (mut dht, _) = sensor(Model::Dht22, Some(data));
+    assert_eq!(dht.read(), Err(Error::Checksum));
+}
+
+#[test]
+fn no_response() {
+    let (mut dht, _) = sensor(Model::Dht22, None);
+    assert_eq!(dht.read(), Err(Error::NoResponse));
+
+    // A DHT11 needs the longer start signal
+    let (dht, line) = sensor(Model::Dht11, Some(frame([45, 0, 23, 5])));
+    let (pin, delay) = dht.release();
+    let mut dht = Dht::new(pin, delay, Model::Dht22).unwrap();
+    assert_eq!(dht.read(), Err(Error::NoResponse));
+    assert!(line.borrow().pulses.is_empty());
+}
+
+#[test]
+fn sensor_stops_sending() {
+    let (mut dht, line) = sensor(Model::Dht22, Some(frame([0x02, 0x8c, 0x01, 0x5f])));
+    line.borrow_mut().bits = 20;
+    assert_eq!(dht.read(), Err(Error::Timeout));
+}
+
+#[test]
+fn measure_updates_sensors() {
+    let sensors = DhtSensors::new(
+        ("Humidity", "living-room-humidity"),
+        ("Temperature", "living-room-temperature"),
+    );
+    assert_eq!(sensors.humidity.native_unit_of_measurement, "%");
+    assert_eq!(
+        sensors.humidity.device_class.as_str(),
+        "DeviceClass.HUMIDITY"
+    );
+    assert_eq!(sensors.temperature.unique_id, "living-room-temperature");
+
+    let (mut dht, line) = sensor(Model::Dht22, Some(frame([0x02, 0x8c, 0x01, 0x5f])));
+    dht.measure(&sensors).unwrap();
+    assert_eq!(*sensors.humidity.value.borrow(), Some(Some(65.2)));
+    assert_eq!(*sensors.temperature.value.borrow(), Some(Some(35.1)));
+
+    // A failed reading leaves both without a value
+    line.borrow_mut().data = None;
+    assert_eq!(dht.measure(&sensors), Err(Error::NoResponse));
+    assert_eq!(*sensors.humidity.value.borrow(), Some(None));
+    assert_eq!(*sensors.temperature.value.borrow(), Some(None));
+}
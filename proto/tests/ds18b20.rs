@@ -0,0 +1,602 @@
+use std::{collections::VecDeque, convert::Infallible};
+
+use ha_buddy_proto::{
+    crc::{CRC8Maxim, CRC},
+    driver::{
+        ds18b20::{
+            self, Config, Measurement, MeasurementState, Probe, Resolution, CMD_CONVERT_T,
+            CMD_COPY_SCRATCHPAD, CMD_READ_SCRATCHPAD, CMD_WRITE_SCRATCHPAD, CONVERSION_TIMEOUT_MS,
+        },
+        onewire::{
+            Error, OneWire, Rom, RomSearch, CMD_ALARM_SEARCH, CMD_MATCH_ROM, CMD_SEARCH_ROM,
+            CMD_SKIP_ROM,
+        },
+    },
+};
+
+/// Appends the CRC to the first 7 bytes of a ROM code
+fn rom(code: [u8; 7]) -> Rom {
+    let mut crc = CRC8Maxim::new();
+    crc.update(&code);
+
+    let mut rom = [0; 8];
+    rom[0..7].copy_from_slice(&code);
+    rom[7] = crc.finalize();
+    Rom(rom)
+}
+
+/// A scratchpad holding the raw temperature `raw` at 12 bits, with a valid CRC
+fn scratchpad(raw: u16) -> [u8; 9] {
+    let [lsb, msb] = raw.to_le_bytes();
+    with_crc([lsb, msb, 0x4b, 0x46, 0x7f, 0xff, 0x0c, 0x10, 0])
+}
+
+/// Updates the CRC of a scratchpad
+fn with_crc(mut scratchpad: [u8; 9]) -> [u8; 9] {
+    let mut crc = CRC8Maxim::new();
+    crc.update(&scratchpad[0..8]);
+    scratchpad[8] = crc.finalize();
+    scratchpad
+}
+
+/// A simulated device on the mock bus
+struct Device {
+    rom: Rom,
+    scratchpad: [u8; 9],
+    alarm: bool,
+}
+
+/// What the devices expect next
+#[derive(Debug, PartialEq)]
+enum State {
+    /// A ROM command
+    Rom,
+    /// Bit `bit` of a ROM search, `phase` 0 and 1 read the bit and its complement,
+    /// 2 writes the direction
+    Search { bit: usize, phase: u8 },
+    /// The ROM code of a Match ROM, the bytes so far
+    Match(Vec<u8>),
+    /// The data of a Write Scratchpad, the bytes so far
+    Write(Vec<u8>),
+    /// A function command for the selected devices
+    Function,
+}
+
+/// A 1-Wire bus simulated at the bit level
+struct MockBus {
+    devices: Vec<Device>,
+    /// The devices taking part in the current transaction
+    selected: Vec<bool>,
+    state: State,
+    /// The bits written since the last full byte
+    written: Vec<bool>,
+    /// The bits the selected devices send next
+    send: VecDeque<bool>,
+    /// How many polls a conversion takes
+    conversion_polls: u32,
+    /// The polls left until the conversion is done
+    converting: u32,
+    /// The function commands received
+    functions: Vec<u8>,
+    /// The devices ignore Write Scratchpad
+    read_only: bool,
+    /// The scratchpads copied to the EEPROM
+    copies: usize,
+}
+
+impl MockBus {
+    fn new(devices: Vec<Device>) -> Self {
+        Self {
+            selected: vec![false; devices.len()],
+            devices,
+            state: State::Rom,
+            written: Vec::new(),
+            send: VecDeque::new(),
+            conversion_polls: 3,
+            converting: 0,
+            functions: Vec::new(),
+            read_only: false,
+            copies: 0,
+        }
+    }
+
+    /// The `bit` of the ROM codes of the selected devices, wired-AND on the bus
+    fn rom_bit(&self, bit: usize, complement: bool) -> bool {
+        self.devices
+            .iter()
+            .zip(&self.selected)
+            .filter(|(_, selected)| **selected)
+            .all(|(device, _)| (device.rom.0[bit / 8] >> (bit % 8) & 1 != 0) != complement)
+    }
+
+    fn handle_byte(&mut self, byte: u8) {
+        match std::mem::replace(&mut self.state, State::Function) {
+            State::Rom => match byte {
+                CMD_SEARCH_ROM => self.state = State::Search { bit: 0, phase: 0 },
+                CMD_ALARM_SEARCH => {
+                    for (selected, device) in self.selected.iter_mut().zip(&self.devices) {
+                        *selected = device.alarm;
+                    }
+                    self.state = State::Search { bit: 0, phase: 0 };
+                }
+                CMD_MATCH_ROM => self.state = State::Match(Vec::new()),
+                CMD_SKIP_ROM => {}
+                _ => panic!("unknown ROM command {:#x}", byte),
+            },
+            State::Match(mut bytes) => {
+                bytes.push(byte);
+                if bytes.len() == 8 {
+                    for (selected, device) in self.selected.iter_mut().zip(&self.devices) {
+                        *selected = device.rom.0[..] == bytes[..];
+                    }
+                } else {
+                    self.state = State::Match(bytes);
+                }
+            }
+            State::Write(mut bytes) => {
+                bytes.push(byte);
+                if bytes.len() < 3 {
+                    self.state = State::Write(bytes);
+                } else if !self.read_only {
+                    let selected = self.devices.iter_mut().zip(&self.selected);
+                    for (device, _) in selected.filter(|d| *d.1) {
+                        // Only the resolution bits of the configuration register are writable
+                        let config = bytes[2] & 0x60 | 0x1f;
+                        device.scratchpad[2..5].copy_from_slice(&[bytes[0], bytes[1], config]);
+                        device.scratchpad = with_crc(device.scratchpad);
+                    }
+                }
+            }
+            State::Function => {
+                self.functions.push(byte);
+                match byte {
+                    CMD_CONVERT_T => self.converting = self.conversion_polls,
+                    CMD_WRITE_SCRATCHPAD => self.state = State::Write(Vec::new()),
+                    CMD_COPY_SCRATCHPAD => {
+                        self.copies += 1;
+                        self.converting = 2;
+                    }
+                    CMD_READ_SCRATCHPAD => {
+                        let mut sent = [0xffu8; 9];
+                        for (device, _) in self.devices.iter().zip(&self.selected).filter(|d| *d.1)
+                        {
+                            for (sent, byte) in sent.iter_mut().zip(device.scratchpad) {
+                                *sent &= byte;
+                            }
+                        }
+                        self.send = sent
+                            .iter()
+                            .flat_map(|byte| (0..8).map(move |i| byte >> i & 1 != 0))
+                            .collect();
+                    }
+                    _ => panic!("unknown function command {:#x}", byte),
+                }
+            }
+            State::Search { .. } => unreachable!(),
+        }
+    }
+}
+
+impl OneWire for MockBus {
+    type Error = Infallible;
+
+    fn reset(&mut self) -> Result<bool, Infallible> {
+        self.selected = vec![true; self.devices.len()];
+        self.state = State::Rom;
+        self.written.clear();
+        self.send.clear();
+        Ok(!self.devices.is_empty())
+    }
+
+    fn write_bit(&mut self, bit: bool) -> Result<(), Infallible> {
+        if let State::Search {
+            bit: position,
+            phase,
+        } = self.state
+        {
+            assert_eq!(phase, 2, "direction written before reading the bits");
+            for (selected, device) in self.selected.iter_mut().zip(&self.devices) {
+                *selected &= (device.rom.0[position / 8] >> (position % 8) & 1 != 0) == bit;
+            }
+            self.state = match position {
+                63 => State::Function,
+                _ => State::Search {
+                    bit: position + 1,
+                    phase: 0,
+                },
+            };
+            return Ok(());
+        }
+
+        self.written.push(bit);
+        if self.written.len() == 8 {
+            let byte = self
+                .written
+                .drain(..)
+                .enumerate()
+                .fold(0, |byte, (i, bit)| byte | (bit as u8) << i);
+            self.handle_byte(byte);
+        }
+        Ok(())
+    }
+
+    fn read_bit(&mut self) -> Result<bool, Infallible> {
+        if let State::Search { bit, phase } = self.state {
+            assert!(phase < 2, "bits read before writing the direction");
+            self.state = State::Search {
+                bit,
+                phase: phase + 1,
+            };
+            return Ok(self.rom_bit(bit, phase == 1));
+        }
+
+        if self.converting > 0 {
+            self.converting -= 1;
+            return Ok(false);
+        }
+
+        Ok(self.send.pop_front().unwrap_or(true))
+    }
+}
+
+/// Three thermometers and a device of another family
+fn bus() -> MockBus {
+    MockBus::new(vec![
+        Device {
+            rom: rom([0x28, 0xff, 0x4b, 0x79, 0xa2, 0x16, 0x03]),
+            scratchpad: scratchpad(0x0191),
+            alarm: false,
+        },
+        Device {
+            rom: rom([0x10, 0x12, 0x34, 0x56, 0x78, 0x9a, 0x00]),
+            scratchpad: scratchpad(0),
+            alarm: true,
+        },
+        Device {
+            rom: rom([0x28, 0x01, 0x00, 0x00, 0x00, 0x00, 0x80]),
+            scratchpad: scratchpad(0x0550),
+            alarm: true,
+        },
+        Device {
+            rom: rom([0x28, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80]),
+            scratchpad: scratchpad(0x0008),
+            alarm: false,
+        },
+    ])
+}
+
+#[test]
+fn rom_search_finds_all_devices() {
+    let mut bus = bus();
+    let mut search = RomSearch::new();
+
+    let mut found = Vec::new();
+    while let Some(rom) = search.next(&mut bus).unwrap() {
+        found.push(rom);
+    }
+
+    let mut expected: Vec<_> = bus.devices.iter().map(|d| d.rom).collect();
+    expected.sort_by_key(|rom| rom.0.map(u8::reverse_bits));
+    found.sort_by_key(|rom| rom.0.map(u8::reverse_bits));
+    assert_eq!(found, expected);
+
+    // The search is over
+    assert_eq!(search.next(&mut bus).unwrap(), None);
+}
+
+#[test]
+fn rom_search_empty_bus() {
+    let mut bus = MockBus::new(Vec::new());
+    assert_eq!(RomSearch::new().next(&mut bus).unwrap(), None);
+}
+
+#[test]
+fn alarm_search() {
+    let mut bus = bus();
+    let mut search = RomSearch::alarmed();
+
+    let mut found = Vec::new();
+    while let Some(rom) = search.next(&mut bus).unwrap() {
+        found.push(rom.family());
+    }
+    found.sort();
+    assert_eq!(found, [0x10, 0x28]);
+
+    for device in &mut bus.devices {
+        device.alarm = false;
+    }
+    assert_eq!(RomSearch::alarmed().next(&mut bus).unwrap(), None);
+}
+
+#[test]
+fn rom_search_invalid_crc() {
+    let mut bus = bus();
+    bus.devices.truncate(1);
+    bus.devices[0].rom.0[7] ^= 1;
+
+    assert_eq!(RomSearch::new().next(&mut bus), Err(Error::Crc));
+}
+
+#[test]
+fn rom_unique_id() {
+    let probe = Probe::new(rom([0x28, 0xff, 0x4b, 0x79, 0xa2, 0x16, 0x03]));
+    assert_eq!(probe.unique_id(), "28-0316a2794bff");
+    assert!(probe.rom.is_valid());
+}
+
+#[test]
+fn find_probes_skips_other_families() {
+    let mut bus = bus();
+    let mut probes = [Probe::default(); 4];
+
+    let (found, error) = ds18b20::find_probes(&mut bus, &mut probes);
+    assert_eq!((found, error), (3, None));
+    assert!(probes[0..found].iter().all(|p| p.rom.family() == 0x28));
+
+    // Stops once the probes are full
+    let mut probes = [Probe::default(); 2];
+    assert_eq!(ds18b20::find_probes(&mut bus, &mut probes), (2, None));
+}
+
+#[test]
+fn find_probes_keeps_probes_before_error() {
+    let mut bus = bus();
+    // The last device found has a broken CRC
+    bus.devices[0].rom.0[7] ^= 0x01;
+    let mut probes = [Probe::default(); 4];
+
+    let (found, error) = ds18b20::find_probes(&mut bus, &mut probes);
+    assert_eq!(error, Some(Error::Crc));
+    assert_eq!(found, 2);
+    assert!(probes[0..found].iter().all(|p| p.rom.is_valid()));
+}
+
+#[test]
+fn read_probes_by_rom() {
+    let mut bus = bus();
+
+    let first = Probe::new(bus.devices[0].rom);
+    assert_eq!(first.read(&mut bus).unwrap(), 25.0625);
+    assert_eq!(bus.functions, [CMD_READ_SCRATCHPAD]);
+
+    let third = Probe::new(bus.devices[2].rom);
+    assert_eq!(third.read(&mut bus).unwrap(), 85.0);
+}
+
+#[test]
+fn read_scratchpad_crc() {
+    let mut bus = bus();
+    bus.devices[0].scratchpad[0] ^= 0x01;
+
+    let probe = Probe::new(bus.devices[0].rom);
+    assert_eq!(probe.read(&mut bus), Err(Error::Crc));
+
+    // A probe that is gone reads as all '1's
+    let gone = Probe::new(rom([0x28, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55]));
+    assert_eq!(gone.read(&mut bus), Err(Error::NoDevice));
+}
+
+#[test]
+fn measurement_updates_sensors() {
+    let mut bus = bus();
+    let mut probes = [Probe::default(); 4];
+    let (found, _) = ds18b20::find_probes(&mut bus, &mut probes);
+    let probes = &probes[0..found];
+
+    let sensors: Vec<_> = probes.iter().map(|p| p.sensor("Temperature")).collect();
+    assert_eq!(sensors[0].unique_id, probes[0].unique_id());
+
+    // Break the probe read last
+    let broken = probes[2].rom;
+    bus.devices
+        .iter_mut()
+        .find(|d| d.rom == broken)
+        .unwrap()
+        .scratchpad[1] ^= 0x01;
+
+    let mut measurement = Measurement::new(probes, &sensors);
+    bus.functions.clear();
+    measurement.start(&mut bus).unwrap();
+    assert_eq!(bus.functions, [CMD_CONVERT_T]);
+
+    // Starting again does not disturb the running conversion
+    measurement.start(&mut bus).unwrap();
+    assert_eq!(bus.functions, [CMD_CONVERT_T]);
+
+    // The probes convert for 3 polls, a tick polls once and returns
+    for elapsed_ms in [10, 20, 30] {
+        measurement.tick(&mut bus, 10).unwrap();
+        assert_eq!(
+            measurement.state(),
+            MeasurementState::Converting { elapsed_ms }
+        );
+    }
+    measurement.tick(&mut bus, 10).unwrap();
+    assert_eq!(measurement.state(), MeasurementState::Reading { next: 0 });
+    assert_eq!(*sensors[0].value.borrow(), None);
+
+    // A probe per tick
+    measurement.tick(&mut bus, 10).unwrap();
+    assert_eq!(measurement.state(), MeasurementState::Reading { next: 1 });
+    assert!(sensors[0].value.borrow().is_some());
+    assert_eq!(*sensors[1].value.borrow(), None);
+    measurement.tick(&mut bus, 10).unwrap();
+    assert_eq!(measurement.tick(&mut bus, 10), Err(Error::Crc));
+    assert_eq!(measurement.state(), MeasurementState::Idle);
+    assert_eq!(bus.functions.len(), 4);
+
+    let values: Vec<_> = sensors.iter().map(|s| *s.value.borrow()).collect();
+    for (probe, value) in probes.iter().zip(&values) {
+        let device = bus.devices.iter().find(|d| d.rom == probe.rom).unwrap();
+        let expected = match probe.rom == broken {
+            true => None,
+            false => Some(ds18b20::temperature(&device.scratchpad)),
+        };
+        assert_eq!(*value, Some(expected));
+    }
+
+    // Idle until started again
+    measurement.tick(&mut bus, 10).unwrap();
+    assert_eq!(bus.functions.len(), 4);
+}
+
+#[test]
+fn measurement_conversion_timeout() {
+    let mut bus = bus();
+    bus.conversion_polls = u32::MAX;
+    let probes = [Probe::new(bus.devices[0].rom)];
+    let sensors = [probes[0].sensor("Temperature")];
+    sensors[0].set_value(Some(Some(21.0)));
+
+    let mut measurement = Measurement::new(&probes, &sensors);
+    measurement.start(&mut bus).unwrap();
+    for _ in 0..CONVERSION_TIMEOUT_MS / 100 - 1 {
+        measurement.tick(&mut bus, 100).unwrap();
+    }
+    assert_eq!(measurement.tick(&mut bus, 100), Err(Error::NoDevice));
+    assert_eq!(measurement.state(), MeasurementState::Idle);
+    assert_eq!(*sensors[0].value.borrow(), Some(None));
+}
+
+#[test]
+fn measurement_without_probes() {
+    let mut bus = MockBus::new(Vec::new());
+    let probes = [Probe::default()];
+    let sensors = [probes[0].sensor("Temperature")];
+
+    // Nothing to measure, an empty bus is no error
+    let mut measurement = Measurement::new(&[], &[]);
+    measurement.start(&mut bus).unwrap();
+    assert_eq!(measurement.state(), MeasurementState::Idle);
+
+    // Probes that left the bus fail the measurement
+    let mut measurement = Measurement::new(&probes, &sensors);
+    assert_eq!(measurement.start(&mut bus), Err(Error::NoDevice));
+    assert_eq!(*sensors[0].value.borrow(), Some(None));
+}
+
+#[test]
+fn negative_temperatures() {
+    assert_eq!(ds18b20::temperature(&scratchpad(0x07d0)), 125.0);
+    assert_eq!(ds18b20::temperature(&scratchpad(0x0000)), 0.0);
+    assert_eq!(ds18b20::temperature(&scratchpad(0xfff8)), -0.5);
+    assert_eq!(ds18b20::temperature(&scratchpad(0xff5e)), -10.125);
+    assert_eq!(ds18b20::temperature(&scratchpad(0xfc90)), -55.0);
+}
+
+#[test]
+fn resolution_masks_undefined_bits() {
+    let at = |raw: u16, resolution: Resolution| {
+        let mut scratchpad = scratchpad(raw);
+        scratchpad[4] = resolution.register();
+        ds18b20::temperature(&scratchpad)
+    };
+
+    assert_eq!(at(0x0191, Resolution::Bits12), 25.0625);
+    assert_eq!(at(0x0191, Resolution::Bits11), 25.0);
+    assert_eq!(at(0x0197, Resolution::Bits10), 25.25);
+    assert_eq!(at(0xff5e, Resolution::Bits9), -10.5);
+
+    assert_eq!(Resolution::from_register(0x3f), Resolution::Bits10);
+    assert_eq!(Resolution::Bits9.conversion_ms(), 94);
+    assert_eq!(Resolution::Bits12.conversion_ms(), 750);
+}
+
+#[test]
+fn configure_probe() {
+    let mut bus = bus();
+    let probe = Probe::new(bus.devices[0].rom);
+    assert_eq!(
+        probe.read_config(&mut bus).unwrap(),
+        Config {
+            resolution: Resolution::Bits12,
+            alarm_high: 75,
+            alarm_low: 70,
+        }
+    );
+
+    let config = Config {
+        resolution: Resolution::Bits10,
+        alarm_high: 30,
+        alarm_low: -10,
+    };
+    bus.functions.clear();
+    probe.configure(&mut bus, &config).unwrap();
+    assert_eq!(
+        bus.functions,
+        [
+            CMD_WRITE_SCRATCHPAD,
+            CMD_READ_SCRATCHPAD,
+            CMD_COPY_SCRATCHPAD
+        ]
+    );
+    assert_eq!(bus.devices[0].scratchpad[2..5], [30, 0xf6, 0x3f]);
+    assert_eq!(bus.copies, 1);
+    assert_eq!(probe.read_config(&mut bus).unwrap(), config);
+
+    // The other probes keep their settings
+    assert_eq!(bus.devices[2].scratchpad[2..5], [0x4b, 0x46, 0x7f]);
+
+    // Settings the probe has are not written again
+    bus.functions.clear();
+    probe.ensure_config(&mut bus, &config).unwrap();
+    assert_eq!(bus.functions, [CMD_READ_SCRATCHPAD]);
+    assert_eq!(bus.copies, 1);
+}
+
+#[test]
+fn configure_probe_verifies() {
+    let mut bus = bus();
+    bus.read_only = true;
+    let probe = Probe::new(bus.devices[0].rom);
+
+    let config = Config {
+        resolution: Resolution::Bits9,
+        alarm_high: 40,
+        alarm_low: 0,
+    };
+    assert_eq!(probe.configure(&mut bus, &config), Err(Error::Verify));
+    assert_eq!(bus.copies, 0);
+}
+
+#[test]
+fn measurement_updates_alarms() {
+    let mut bus = bus();
+    let mut probes = [Probe::default(); 4];
+    let (found, _) = ds18b20::find_probes(&mut bus, &mut probes);
+    let probes = &probes[0..found];
+
+    let sensors: Vec<_> = probes.iter().map(|p| p.sensor("Temperature")).collect();
+    let alarms: Vec<_> = probes.iter().map(|p| p.alarm_sensor("Alarm")).collect();
+    assert_eq!(
+        alarms[0].unique_id,
+        format!("{}-alarm", probes[0].unique_id())
+    );
+
+    let mut measurement = Measurement::new(probes, &sensors).with_alarms(&alarms);
+    measurement.start(&mut bus).unwrap();
+    while measurement.state() != MeasurementState::SearchingAlarms {
+        measurement.tick(&mut bus, 10).unwrap();
+    }
+    assert!(alarms.iter().all(|a| a.state.get().is_none()));
+
+    // The probe with an alarm condition, the device of the other family is no probe
+    measurement.tick(&mut bus, 10).unwrap();
+    measurement.tick(&mut bus, 10).unwrap();
+    assert_eq!(measurement.state(), MeasurementState::SearchingAlarms);
+    measurement.tick(&mut bus, 10).unwrap();
+    assert_eq!(measurement.state(), MeasurementState::Idle);
+
+    for (probe, alarm) in probes.iter().zip(&alarms) {
+        let device = bus.devices.iter().find(|d| d.rom == probe.rom).unwrap();
+        assert_eq!(alarm.state.get(), Some(device.alarm));
+    }
+
+    // A failed measurement leaves the alarms unknown
+    bus.conversion_polls = u32::MAX;
+    measurement.start(&mut bus).unwrap();
+    while measurement.state() != MeasurementState::Idle {
+        let _ = measurement.tick(&mut bus, 100);
+    }
+    assert!(alarms.iter().all(|a| a.state.get().is_none()));
+}
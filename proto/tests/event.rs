@@ -0,0 +1,183 @@
+use std::cell::Cell;
+
+use ha_buddy_proto::{
+    event::{Event, EventQueue, EVENT_QUEUE_LEN},
+    homeassistant::{
+        binary_sensor::{BinarySensor, BinarySensorDeviceClass, BinarySensorRef},
+        entity::DeviceClass,
+        number::{Number, NumberRef},
+        sensor::{Sensor, SensorRef, StateClass},
+        switch::{Switch, SwitchRef, SwitchRequest},
+        Entities,
+    },
+};
+
+/// Creates a temperature sensor reporting changes of more than `deadband`
+fn temperature(deadband: f32) -> Sensor<'static, f32> {
+    Sensor::new(
+        "Temperature",
+        "temp_0",
+        "°C",
+        DeviceClass::Temperature,
+        StateClass::Measurement,
+        None,
+    )
+    .with_deadband(deadband)
+}
+
+#[test]
+fn queue_deduplicates() {
+    let mut queue = EventQueue::new();
+
+    assert!(queue.push(Event::Sensor(1)));
+    assert!(queue.push(Event::Switch(1)));
+    assert!(queue.push(Event::Sensor(1)));
+
+    assert_eq!(queue.events(), [Event::Sensor(1), Event::Switch(1)]);
+}
+
+#[test]
+fn queue_overflow() {
+    let mut queue = EventQueue::new();
+
+    for id in 0..EVENT_QUEUE_LEN as u32 {
+        assert!(queue.push(Event::Sensor(id)));
+    }
+    assert!(!queue.overflowed);
+
+    assert!(!queue.push(Event::Switch(0)));
+    assert!(queue.overflowed);
+    assert_eq!(queue.len(), EVENT_QUEUE_LEN);
+
+    queue.remove(EVENT_QUEUE_LEN - 1);
+    assert_eq!(queue.events(), [Event::Sensor(EVENT_QUEUE_LEN as u32 - 1)]);
+    queue.remove(5);
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn sensor_deadband() {
+    let sensor = temperature(0.5);
+
+    // The first value is a change
+    sensor.set_value(Some(20.0));
+    assert!(sensor.take_changed());
+    assert!(!sensor.take_changed());
+
+    // Small steps add up against the last reported value
+    sensor.set_value(Some(20.3));
+    assert!(!sensor.take_changed());
+    sensor.set_value(Some(20.6));
+    assert!(sensor.take_changed());
+
+    sensor.set_value(Some(20.2));
+    assert!(!sensor.take_changed());
+
+    // Losing the value is a change
+    sensor.set_value(None);
+    assert!(sensor.take_changed());
+}
+
+#[test]
+fn sensor_without_deadband() {
+    let sensor = Sensor::new(
+        "Counter",
+        "counter_0",
+        "",
+        DeviceClass::Temperature,
+        StateClass::TotalIncreasing,
+        Some(0i32),
+    );
+
+    sensor.set_value(Some(100));
+    assert!(!sensor.take_changed());
+}
+
+#[test]
+fn switch_changes() {
+    let state = Cell::new(false);
+    let mut switch = Switch::new("Relay", "relay_0", |req| {
+        if req == SwitchRequest::Toggle {
+            state.set(!state.get());
+        }
+        state.get()
+    });
+
+    // The first poll only learns the state
+    assert!(!switch.take_changed());
+
+    switch.exec_request(SwitchRequest::Toggle).unwrap();
+    assert!(switch.take_changed());
+    assert!(!switch.take_changed());
+}
+
+#[test]
+fn binary_sensor_changes() {
+    let motion = BinarySensor::new("Motion", "pir_0", BinarySensorDeviceClass::Motion, None);
+    assert!(!motion.take_changed());
+
+    motion.set_state(Some(true));
+    assert!(motion.take_changed());
+    assert!(!motion.take_changed());
+
+    // Setting the same state again is no change
+    motion.set_state(Some(true));
+    assert!(!motion.take_changed());
+
+    motion.set_state(None);
+    assert!(motion.take_changed());
+}
+
+#[test]
+fn poll() {
+    let sensor = temperature(1.0);
+    let sensors: [&dyn SensorRef; 2] = [&temperature(1.0), &sensor];
+
+    let state = Cell::new(false);
+    let mut switch = Switch::new("Relay", "relay_0", |_| state.get());
+    let mut switches: [&mut dyn SwitchRef; 1] = [&mut switch];
+
+    let door = BinarySensor::new("Door", "door_0", BinarySensorDeviceClass::Door, Some(false));
+    let binary_sensors: [&dyn BinarySensorRef; 1] = [&door];
+
+    let setpoint = Cell::new(20.0);
+    let mut number = Number::new(
+        "Setpoint",
+        "setpoint_0",
+        "°C",
+        DeviceClass::Temperature,
+        5.0..=30.0,
+        0.5,
+        |_| setpoint.get(),
+    );
+    let mut numbers: [&mut dyn NumberRef; 1] = [&mut number];
+
+    let mut entities = Entities {
+        sensors: &sensors,
+        switches: &mut switches,
+        binary_sensors: &binary_sensors,
+        numbers: &mut numbers,
+        ..Default::default()
+    };
+
+    let mut queue = EventQueue::new();
+    queue.poll(&mut entities);
+    assert!(queue.is_empty());
+
+    sensor.set_value(Some(5.0));
+    state.set(true);
+    door.set_state(Some(true));
+    setpoint.set(21.0);
+    queue.poll(&mut entities);
+    queue.poll(&mut entities);
+
+    assert_eq!(
+        queue.events(),
+        [
+            Event::Sensor(1),
+            Event::Switch(0),
+            Event::BinarySensor(0),
+            Event::Number(0)
+        ]
+    );
+}
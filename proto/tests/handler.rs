@@ -0,0 +1,2218 @@
+mod common;
+
+use std::cell::Cell;
+
+use common::*;
+use ha_buddy_proto::{
+    command::{
+        CMD_BINARY_SENSOR_DESCRIBE, CMD_BUTTON_DESCRIBE, CMD_BUTTON_PRESS, CMD_COVER_DESCRIBE,
+        CMD_COVER_EXEC, CMD_COVER_POSITION, CMD_COVER_STATE, CMD_ERROR_FLAG, CMD_EVENTS_FETCH,
+        CMD_LIGHT_BRIGHTNESS, CMD_LIGHT_DESCRIBE, CMD_LIGHT_EXEC, CMD_NODE_DISCOVER, CMD_NODE_INFO,
+        CMD_NODE_SERIAL, CMD_NODE_SET_ADDR, CMD_NUMBER_DESCRIBE, CMD_NUMBER_SET,
+        CMD_SELECT_DESCRIBE, CMD_SELECT_OPTION, CMD_SELECT_SET, CMD_SELECT_STATE,
+        CMD_SENSOR_DESCRIBE, CMD_SENSOR_VALUES, CMD_SWITCH_DESCRIBE, SENSOR_VALUES_COMPLETE,
+        SENSOR_VALUE_ERROR, SWITCH_ID_ALL,
+    },
+    datalink::BROADCAST_ADDR,
+    device::{DeviceInfo, CAPABILITIES, INFO_MAX_STR_LEN, PROTOCOL_VERSION},
+    error::ErrorCode,
+    event::{
+        Event, EVENTS_OVERFLOWED, EVENTS_PENDING, EVENT_BINARY_SENSOR, EVENT_COVER, EVENT_LIGHT,
+        EVENT_NUMBER, EVENT_QUEUE_LEN, EVENT_SELECT, EVENT_SENSOR, EVENT_SWITCH,
+    },
+    handler::{handle_frame, handle_request, HandlerPins},
+    homeassistant::{
+        binary_sensor::{BinarySensor, BinarySensorDeviceClass, BinarySensorRef},
+        button::{Button, ButtonRef},
+        cover::{Cover, CoverDeviceClass, CoverMotion, CoverRef, CoverRequest, CoverState, Travel},
+        entity::{DeviceClass, Entity},
+        light::{Light, LightRef, LightRequest, LightState},
+        number::{Number, NumberMode, NumberRef, NumberRequest},
+        select::{Select, SelectRef, SelectRequest},
+        sensor::{PayloadType, Sensor, SensorRef, StateClass},
+        switch::{Switch, SwitchRef, SwitchRequest},
+        Entities,
+    },
+    node::Node,
+    tlv::*,
+    DataFrame,
+};
+
+/// Creates a callback for a `Switch` that operates on `state`
+fn switch_callback(state: &Cell<bool>) -> impl FnMut(SwitchRequest) -> bool + '_ {
+    move |req| match req {
+        SwitchRequest::TurnON => {
+            state.set(true);
+            true
+        }
+        SwitchRequest::TurnOFF => {
+            state.set(false);
+            true
+        }
+        SwitchRequest::Toggle => {
+            state.set(!state.get());
+            true
+        }
+        SwitchRequest::Get => state.get(),
+    }
+}
+
+/// Creates a callback for a `Number` that operates on `value`
+fn number_callback(value: &Cell<f32>) -> impl FnMut(NumberRequest) -> f32 + '_ {
+    move |req| {
+        if let NumberRequest::Set(v) = req {
+            value.set(v);
+        }
+        value.get()
+    }
+}
+
+/// Creates a callback for a `Light` that operates on `state`
+fn light_callback(state: &Cell<LightState>) -> impl FnMut(LightRequest) -> LightState + '_ {
+    move |req| {
+        let mut new = state.get();
+        match req {
+            LightRequest::TurnOn { brightness, .. } => {
+                new.on = true;
+                new.brightness = brightness.unwrap_or(new.brightness);
+            }
+            LightRequest::TurnOff { .. } => new.on = false,
+            LightRequest::Toggle { .. } => new.on = !new.on,
+            LightRequest::Get => {}
+        }
+        state.set(new);
+        new
+    }
+}
+
+/// Creates a callback for a `Cover` that operates on `state`, the cover starts moving but
+/// never arrives
+fn cover_callback(state: &Cell<CoverState>) -> impl FnMut(CoverRequest) -> CoverState + '_ {
+    move |req| {
+        let mut new = state.get();
+        new.motion = match req {
+            CoverRequest::Open => CoverMotion::Opening,
+            CoverRequest::Close => CoverMotion::Closing,
+            CoverRequest::Stop => CoverMotion::Stopped,
+            CoverRequest::SetPosition(position) if position > new.position => CoverMotion::Opening,
+            CoverRequest::SetPosition(position) if position < new.position => CoverMotion::Closing,
+            CoverRequest::SetPosition(_) => CoverMotion::Stopped,
+            CoverRequest::Get => new.motion,
+        };
+        state.set(new);
+        new
+    }
+}
+
+/// Creates a callback for a `Select` that operates on `index`
+fn select_callback(index: &Cell<u8>) -> impl FnMut(SelectRequest) -> u8 + '_ {
+    move |req| {
+        if let SelectRequest::Set(i) = req {
+            index.set(i);
+        }
+        index.get()
+    }
+}
+
+/// Runs `handle_frame` on a request with a fixed set of entities
+/// # Returns
+/// The response frame or the error the handler responded with
+fn request(cmd: u16, payload: &[u8], relay: &Cell<bool>) -> Result<DataFrame, ErrorCode> {
+    let temperature = Sensor::new(
+        "Temperature",
+        "temp_0",
+        "°C",
+        DeviceClass::Temperature,
+        StateClass::Measurement,
+        Some(21.5f32),
+    );
+    let counter = Sensor::<i32>::new(
+        "Counter",
+        "counter_0",
+        "",
+        DeviceClass::Temperature,
+        StateClass::TotalIncreasing,
+        None,
+    );
+    let sensors: [&dyn SensorRef; 2] = [&temperature, &counter];
+
+    let mut relay = Switch::new("Relay", "relay_0", switch_callback(relay));
+    let mut switches: [&mut dyn SwitchRef; 1] = [&mut relay];
+
+    let door = BinarySensor::new("Door", "door_0", BinarySensorDeviceClass::Door, Some(true));
+    let contact = BinarySensor::new("Contact", "contact_0", BinarySensorDeviceClass::None, None);
+    let binary_sensors: [&dyn BinarySensorRef; 2] = [&door, &contact];
+
+    let setpoint = Cell::new(20.0);
+    let mut number = Number::new(
+        "Setpoint",
+        "setpoint_0",
+        "°C",
+        DeviceClass::Temperature,
+        5.0..=30.0,
+        0.5,
+        number_callback(&setpoint),
+    )
+    .with_mode(NumberMode::Slider);
+    let mut numbers: [&mut dyn NumberRef; 1] = [&mut number];
+
+    let strip = Cell::new(LightState {
+        on: false,
+        brightness: 200,
+    });
+    let mut light = Light::new("Strip", "strip_0", light_callback(&strip));
+    let mut lights: [&mut dyn LightRef; 1] = [&mut light];
+
+    let shutter = Cell::new(CoverState {
+        position: 40,
+        motion: CoverMotion::Stopped,
+    });
+    let mut cover = Cover::new(
+        "Shutter",
+        "shutter_0",
+        CoverDeviceClass::Shutter,
+        cover_callback(&shutter),
+    );
+    let mut covers: [&mut dyn CoverRef; 1] = [&mut cover];
+
+    let heating = Cell::new(1);
+    let mut select = Select::new(
+        "Heating",
+        "heating_0",
+        ["Off", "Eco", "Comfort"],
+        select_callback(&heating),
+    );
+    let mut selects: [&mut dyn SelectRef; 1] = [&mut select];
+
+    let mut frame = frame(0x0000, 0x1000, cmd, payload);
+
+    handle_frame(
+        &mut frame,
+        &mut HandlerPins {},
+        &mut Entities {
+            sensors: &sensors,
+            switches: &mut switches,
+            binary_sensors: &binary_sensors,
+            numbers: &mut numbers,
+            lights: &mut lights,
+            covers: &mut covers,
+            selects: &mut selects,
+            ..Default::default()
+        },
+    )?;
+    Ok(frame)
+}
+
+/// Builds the payload for an entity id
+fn id(id: u32) -> [u8; 4] {
+    id.to_le_bytes()
+}
+
+/// Builds the payload for a switch exec request
+fn exec(id: u32, req: u8) -> [u8; 5] {
+    let b = id.to_le_bytes();
+    [b[0], b[1], b[2], b[3], req]
+}
+
+#[test]
+fn echo() {
+    let res = request(0x0000, b"hello", &Cell::new(false)).unwrap();
+    assert_eq!(payload(&res), b"hello");
+}
+
+#[test]
+fn unknown_command() {
+    assert_eq!(
+        request(0x7ff0, &[], &Cell::new(false)).err(),
+        Some(ErrorCode::UnknownCommand)
+    );
+}
+
+#[test]
+fn sensor_count() {
+    let res = request(0x0100, &[], &Cell::new(false)).unwrap();
+    assert_eq!(payload(&res), 2u32.to_le_bytes());
+}
+
+#[test]
+fn sensor_unique_id() {
+    let res = request(0x0102, &id(1), &Cell::new(false)).unwrap();
+    assert_eq!(payload(&res), b"counter_0");
+}
+
+#[test]
+fn sensor_native_unit_of_measurement() {
+    let res = request(0x0104, &id(0), &Cell::new(false)).unwrap();
+    assert_eq!(payload(&res), "°C".as_bytes());
+}
+
+#[test]
+fn sensor_device_class() {
+    let res = request(0x0106, &id(0), &Cell::new(false)).unwrap();
+    assert_eq!(payload(&res), b"DeviceClass.TEMPERATURE");
+}
+
+#[test]
+fn sensor_state_class() {
+    let res = request(0x0108, &id(0), &Cell::new(false)).unwrap();
+    assert_eq!(payload(&res), b"measurement");
+
+    let res = request(0x0108, &id(1), &Cell::new(false)).unwrap();
+    assert_eq!(payload(&res), b"total_increasing");
+}
+
+#[test]
+fn sensor_name() {
+    let res = request(0x0110, &id(0), &Cell::new(false)).unwrap();
+    assert_eq!(payload(&res), b"Temperature");
+}
+
+#[test]
+fn sensor_value() {
+    let res = request(0x0112, &id(0), &Cell::new(false)).unwrap();
+
+    let mut expected = vec![PayloadType::Float as u8];
+    expected.extend(21.5f32.to_le_bytes());
+    assert_eq!(payload(&res), expected.as_slice());
+}
+
+#[test]
+fn sensor_value_none() {
+    let res = request(0x0112, &id(1), &Cell::new(false)).unwrap();
+    assert_eq!(res.payload_len, 0);
+}
+
+#[test]
+fn sensor_out_of_range() {
+    for cmd in [0x0102, 0x0104, 0x0106, 0x0108, 0x0110, 0x0112] {
+        assert_eq!(
+            request(cmd, &id(2), &Cell::new(false)).err(),
+            Some(ErrorCode::EntityOutOfRange),
+            "cmd {:#06x}",
+            cmd
+        );
+    }
+}
+
+#[test]
+fn sensor_short_payload() {
+    for cmd in [0x0102, 0x0104, 0x0106, 0x0108, 0x0110, 0x0112] {
+        assert_eq!(
+            request(cmd, &[0, 0, 0], &Cell::new(false)).err(),
+            Some(ErrorCode::BadPayloadLength),
+            "cmd {:#06x}",
+            cmd
+        );
+    }
+}
+
+#[test]
+fn switch_count() {
+    let res = request(0x0200, &[], &Cell::new(false)).unwrap();
+    assert_eq!(payload(&res), 1u32.to_le_bytes());
+}
+
+#[test]
+fn switch_unique_id() {
+    let res = request(0x0202, &id(0), &Cell::new(false)).unwrap();
+    assert_eq!(payload(&res), b"relay_0");
+}
+
+#[test]
+fn switch_name() {
+    let res = request(0x0204, &id(0), &Cell::new(false)).unwrap();
+    assert_eq!(payload(&res), b"Relay");
+}
+
+#[test]
+fn switch_state() {
+    let res = request(0x0206, &id(0), &Cell::new(false)).unwrap();
+    assert_eq!(payload(&res), [0]);
+
+    let res = request(0x0206, &id(0), &Cell::new(true)).unwrap();
+    assert_eq!(payload(&res), [1]);
+}
+
+#[test]
+fn switch_exec() {
+    let relay = Cell::new(false);
+
+    let res = request(0x0208, &exec(0, 1), &relay).unwrap();
+    assert_eq!(payload(&res), [1]);
+    assert!(relay.get());
+
+    let res = request(0x0208, &exec(0, 0), &relay).unwrap();
+    assert_eq!(payload(&res), [0]);
+    assert!(!relay.get());
+
+    let res = request(0x0208, &exec(0, 2), &relay).unwrap();
+    assert_eq!(payload(&res), [1]);
+    assert!(relay.get());
+
+    let res = request(0x0208, &exec(0, 2), &relay).unwrap();
+    assert_eq!(payload(&res), [0]);
+    assert!(!relay.get());
+}
+
+#[test]
+fn switch_exec_invalid_request() {
+    let relay = Cell::new(false);
+
+    assert_eq!(
+        request(0x0208, &exec(0, 3), &relay).err(),
+        Some(ErrorCode::InvalidArgument)
+    );
+    assert!(!relay.get());
+}
+
+#[test]
+fn switch_exec_short_payload() {
+    let relay = Cell::new(false);
+
+    assert_eq!(
+        request(0x0208, &id(0), &relay).err(),
+        Some(ErrorCode::BadPayloadLength)
+    );
+    assert!(!relay.get());
+}
+
+#[test]
+fn switch_out_of_range() {
+    for cmd in [0x0202, 0x0204, 0x0206] {
+        assert_eq!(
+            request(cmd, &id(1), &Cell::new(false)).err(),
+            Some(ErrorCode::EntityOutOfRange),
+            "cmd {:#06x}",
+            cmd
+        );
+    }
+
+    let relay = Cell::new(false);
+    assert_eq!(
+        request(0x0208, &exec(1, 1), &relay).err(),
+        Some(ErrorCode::EntityOutOfRange)
+    );
+    assert!(!relay.get());
+}
+
+/// A switch whose hardware is broken
+struct FaultySwitch;
+
+impl<'a> Entity<'a> for FaultySwitch {
+    fn get_unique_id(&self) -> &'a str {
+        "faulty"
+    }
+
+    fn get_name(&self) -> &'a str {
+        "Faulty"
+    }
+
+    fn get_device_class(&self) -> DeviceClass {
+        DeviceClass::Switch
+    }
+}
+
+impl<'a> SwitchRef<'a> for FaultySwitch {
+    fn exec_request(&mut self, _req: SwitchRequest) -> Result<bool, ErrorCode> {
+        Err(ErrorCode::HardwareFault)
+    }
+}
+
+#[test]
+fn switch_hardware_fault() {
+    let mut faulty = FaultySwitch;
+    let mut switches: [&mut dyn SwitchRef; 1] = [&mut faulty];
+
+    let mut frame = frame(0x0000, 0x1000, 0x0208, &exec(0, 1));
+    assert_eq!(
+        handle_frame(
+            &mut frame,
+            &mut HandlerPins {},
+            &mut Entities {
+                switches: &mut switches,
+                ..Default::default()
+            }
+        ),
+        Err(ErrorCode::HardwareFault)
+    );
+}
+
+#[test]
+fn switch_describe_hardware_fault() {
+    let mut faulty = FaultySwitch;
+    let mut switches: [&mut dyn SwitchRef; 1] = [&mut faulty];
+
+    // The state is left out, the attributes are still described
+    let mut frame = frame(0x0000, 0x1000, CMD_SWITCH_DESCRIBE, &id(0));
+    handle_frame(
+        &mut frame,
+        &mut HandlerPins {},
+        &mut Entities {
+            switches: &mut switches,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        tlv_entries(payload(&frame)),
+        [
+            (TAG_UNIQUE_ID, b"faulty".to_vec()),
+            (TAG_NAME, b"Faulty".to_vec())
+        ]
+    );
+}
+
+#[test]
+fn request_addressed_to_node() {
+    let mut frame = frame(0x0042, 0x1000, 0x0000, b"ping");
+    frame.update_crc();
+
+    assert!(handle_request(
+        &mut frame,
+        &mut Node::new(0x1000),
+        &mut HandlerPins {},
+        &mut Entities::default()
+    ));
+    assert_eq!(frame.src, 0x1000);
+    assert_eq!(frame.dst, 0x0042);
+    assert_eq!(frame.cmd, 0x0001);
+    assert_eq!(payload(&frame), b"ping");
+}
+
+#[test]
+fn request_for_other_node() {
+    let mut frame = frame(0x0000, 0x1001, 0x0000, b"ping");
+    frame.update_crc();
+
+    assert!(!handle_request(
+        &mut frame,
+        &mut Node::new(0x1000),
+        &mut HandlerPins {},
+        &mut Entities::default()
+    ));
+}
+
+#[test]
+fn request_with_invalid_crc() {
+    let mut frame = frame(0x0000, 0x1000, 0x0000, b"ping");
+    frame.update_crc();
+    frame.f_crc ^= 0xff;
+
+    assert!(!handle_request(
+        &mut frame,
+        &mut Node::new(0x1000),
+        &mut HandlerPins {},
+        &mut Entities::default()
+    ));
+}
+
+#[test]
+fn request_error_response() {
+    let mut frame = frame(0x0000, 0x1000, 0x0102, &id(0));
+    frame.update_crc();
+
+    assert!(handle_request(
+        &mut frame,
+        &mut Node::new(0x1000),
+        &mut HandlerPins {},
+        &mut Entities::default()
+    ));
+    assert_eq!(frame.cmd, 0x0103 | CMD_ERROR_FLAG);
+    assert_eq!(payload(&frame), [ErrorCode::EntityOutOfRange as u8]);
+}
+
+#[test]
+fn broadcast_is_handled_without_response() {
+    let relay = Cell::new(true);
+    let mut switch = Switch::new("Relay", "relay_0", switch_callback(&relay));
+    let mut switches: [&mut dyn SwitchRef; 1] = [&mut switch];
+
+    let mut frame = frame(0x0000, BROADCAST_ADDR, 0x0208, &exec(0, 0));
+    frame.update_crc();
+
+    assert!(!handle_request(
+        &mut frame,
+        &mut Node::new(0x1000),
+        &mut HandlerPins {},
+        &mut Entities {
+            switches: &mut switches,
+            ..Default::default()
+        }
+    ));
+    assert!(!relay.get());
+}
+
+#[test]
+fn group_member_is_handled_without_response() {
+    let relay = Cell::new(false);
+    let mut switch = Switch::new("Relay", "relay_0", switch_callback(&relay));
+    let mut switches: [&mut dyn SwitchRef; 1] = [&mut switch];
+
+    let mut addr = Node {
+        groups: &[0xff01, 0xff02],
+        ..Node::new(0x1000)
+    };
+
+    let mut frame = frame(0x0000, 0xff02, 0x0208, &exec(0, 1));
+    frame.update_crc();
+
+    assert!(!handle_request(
+        &mut frame,
+        &mut addr,
+        &mut HandlerPins {},
+        &mut Entities {
+            switches: &mut switches,
+            ..Default::default()
+        }
+    ));
+    assert!(relay.get());
+}
+
+#[test]
+fn other_group_is_ignored() {
+    let relay = Cell::new(false);
+    let mut switch = Switch::new("Relay", "relay_0", switch_callback(&relay));
+    let mut switches: [&mut dyn SwitchRef; 1] = [&mut switch];
+
+    let mut addr = Node {
+        groups: &[0xff01],
+        ..Node::new(0x1000)
+    };
+
+    let mut frame = frame(0x0000, 0xff02, 0x0208, &exec(0, 1));
+    frame.update_crc();
+
+    assert!(!handle_request(
+        &mut frame,
+        &mut addr,
+        &mut HandlerPins {},
+        &mut Entities {
+            switches: &mut switches,
+            ..Default::default()
+        }
+    ));
+    assert!(!relay.get());
+}
+
+#[test]
+fn switch_exec_all() {
+    let first = Cell::new(false);
+    let second = Cell::new(true);
+    let mut first_switch = Switch::new("First", "first", switch_callback(&first));
+    let mut second_switch = Switch::new("Second", "second", switch_callback(&second));
+    let mut switches: [&mut dyn SwitchRef; 2] = [&mut first_switch, &mut second_switch];
+
+    let mut frame = frame(0x0000, 0x1000, 0x0208, &exec(SWITCH_ID_ALL, 2));
+    handle_frame(
+        &mut frame,
+        &mut HandlerPins {},
+        &mut Entities {
+            switches: &mut switches,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(payload(&frame), [1, 0]);
+    assert!(first.get());
+    assert!(!second.get());
+}
+
+/// Runs `handle_request` for a node management command sent to `dst`
+/// # Returns
+/// The response frame if the node responds
+fn node_request(node: &mut Node, dst: u16, cmd: u16, payload: &[u8]) -> Option<DataFrame> {
+    let mut frame = frame(0x0000, dst, cmd, payload);
+    frame.update_crc();
+
+    handle_request(
+        &mut frame,
+        node,
+        &mut HandlerPins {},
+        &mut Entities::default(),
+    )
+    .then_some(frame)
+}
+
+const SERIAL: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+#[test]
+fn node_serial() {
+    let mut node = Node::new(0x1000);
+    let response = node_request(&mut node, 0x1000, CMD_NODE_SERIAL, &[]).unwrap();
+    assert_eq!(payload(&response), []);
+
+    node.serial = Some(SERIAL);
+    let response = node_request(&mut node, 0x1000, CMD_NODE_SERIAL, &[]).unwrap();
+    assert_eq!(response.cmd, CMD_NODE_SERIAL + 1);
+    assert_eq!(payload(&response), SERIAL);
+}
+
+#[test]
+fn set_address_with_serial() {
+    let mut node = Node {
+        serial: Some(SERIAL),
+        ..Node::new(0x1000)
+    };
+
+    let mut request = 0x1234u16.to_le_bytes().to_vec();
+    request.extend_from_slice(&SERIAL);
+
+    // The response comes from the old address
+    let response = node_request(&mut node, 0x1000, CMD_NODE_SET_ADDR, &request).unwrap();
+    assert_eq!(response.src, 0x1000);
+    assert_eq!(response.cmd, CMD_NODE_SET_ADDR + 1);
+    assert_eq!(payload(&response), [0x34, 0x12]);
+    assert_eq!(node.addr, 0x1234);
+
+    // Nodes sharing the factory address are told apart by broadcasting the serial
+    request[0..2].copy_from_slice(&0x1000u16.to_le_bytes());
+    assert!(node_request(&mut node, BROADCAST_ADDR, CMD_NODE_SET_ADDR, &request).is_none());
+    assert_eq!(node.addr, 0x1000);
+}
+
+#[test]
+fn set_address_wrong_serial() {
+    let mut node = Node {
+        serial: Some(SERIAL),
+        ..Node::new(0x1000)
+    };
+
+    let mut request = 0x1234u16.to_le_bytes().to_vec();
+    request.extend_from_slice(&[8, 7, 6, 5, 4, 3, 2, 1]);
+
+    let response = node_request(&mut node, 0x1000, CMD_NODE_SET_ADDR, &request).unwrap();
+    assert_eq!(response.cmd, (CMD_NODE_SET_ADDR + 1) | CMD_ERROR_FLAG);
+    assert_eq!(payload(&response), [ErrorCode::NotPermitted as u8]);
+    assert_eq!(node.addr, 0x1000);
+
+    // A node without a serial number can't be matched
+    node.serial = None;
+    request[2..].copy_from_slice(&SERIAL);
+    node_request(&mut node, 0x1000, CMD_NODE_SET_ADDR, &request).unwrap();
+    assert_eq!(node.addr, 0x1000);
+}
+
+#[test]
+fn set_address_commissioning() {
+    let mut node = Node::new(0x1000);
+    let request = 0x1234u16.to_le_bytes();
+
+    let response = node_request(&mut node, 0x1000, CMD_NODE_SET_ADDR, &request).unwrap();
+    assert_eq!(payload(&response), [ErrorCode::NotPermitted as u8]);
+
+    node.commissioning = true;
+    assert!(node_request(&mut node, BROADCAST_ADDR, CMD_NODE_SET_ADDR, &request).is_none());
+    assert_eq!(node.addr, 0x1234);
+    assert!(!node.commissioning);
+}
+
+#[test]
+fn set_address_invalid() {
+    let mut node = Node {
+        commissioning: true,
+        ..Node::new(0x1000)
+    };
+
+    let response = node_request(&mut node, 0x1000, CMD_NODE_SET_ADDR, &[0x34]).unwrap();
+    assert_eq!(payload(&response), [ErrorCode::BadPayloadLength as u8]);
+
+    let request = BROADCAST_ADDR.to_le_bytes();
+    let response = node_request(&mut node, 0x1000, CMD_NODE_SET_ADDR, &request).unwrap();
+    assert_eq!(payload(&response), [ErrorCode::InvalidArgument as u8]);
+    assert_eq!(node.addr, 0x1000);
+    assert!(node.commissioning);
+}
+
+/// Builds a [`CMD_NODE_DISCOVER`] request payload
+fn discover(bits: u8, prefix: [u8; 8]) -> Vec<u8> {
+    let mut payload = vec![bits];
+    payload.extend_from_slice(&prefix);
+    payload
+}
+
+#[test]
+fn discover_matching_node_responds_to_broadcast() {
+    let mut node = Node {
+        serial: Some(SERIAL),
+        ..Node::new(0x1000)
+    };
+
+    let request = discover(12, [0x01, 0x0f, 0xff, 0, 0, 0, 0, 0]);
+    let response = node_request(&mut node, BROADCAST_ADDR, CMD_NODE_DISCOVER, &request).unwrap();
+    assert_eq!(response.src, 0x1000);
+    assert_eq!(response.cmd, CMD_NODE_DISCOVER + 1);
+    assert_eq!(payload(&response), SERIAL);
+
+    let response = node_request(&mut node, 0x1000, CMD_NODE_DISCOVER, &discover(0, [0; 8]));
+    assert_eq!(payload(&response.unwrap()), SERIAL);
+}
+
+#[test]
+fn discover_is_silent() {
+    let mut node = Node {
+        serial: Some(SERIAL),
+        ..Node::new(0x1000)
+    };
+
+    // Prefix does not match
+    let request = discover(16, [0x01, 0x03, 0, 0, 0, 0, 0, 0]);
+    assert!(node_request(&mut node, 0x1000, CMD_NODE_DISCOVER, &request).is_none());
+
+    // Malformed requests
+    assert!(node_request(&mut node, 0x1000, CMD_NODE_DISCOVER, &[0]).is_none());
+    let request = discover(65, SERIAL);
+    assert!(node_request(&mut node, 0x1000, CMD_NODE_DISCOVER, &request).is_none());
+
+    // Nodes without a serial number can't be discovered
+    node.serial = None;
+    let request = discover(0, [0; 8]);
+    assert!(node_request(&mut node, 0x1000, CMD_NODE_DISCOVER, &request).is_none());
+}
+
+#[test]
+fn node_info() {
+    let mut node = Node {
+        info: DeviceInfo {
+            firmware_version: "0.1.0",
+            board: "mega",
+            name: "Kitchen",
+            uptime: 0x01020304,
+        },
+        ..Node::new(0x1000)
+    };
+
+    let response = node_request(&mut node, 0x1000, CMD_NODE_INFO, &[]).unwrap();
+    assert_eq!(response.cmd, CMD_NODE_INFO + 1);
+
+    let mut expected = vec![PROTOCOL_VERSION];
+    expected.extend_from_slice(&CAPABILITIES.to_le_bytes());
+    expected.extend_from_slice(&[4, 3, 2, 1]);
+    expected.extend_from_slice(b"\x050.1.0\x04mega\x07Kitchen");
+    assert_eq!(payload(&response), expected);
+}
+
+#[test]
+fn node_info_truncates_strings() {
+    let long = "x".repeat(300);
+    let mut node = Node {
+        info: DeviceInfo {
+            firmware_version: &long,
+            board: &long,
+            name: &long,
+            uptime: 0,
+        },
+        ..Node::new(0x1000)
+    };
+
+    let response = node_request(&mut node, 0x1000, CMD_NODE_INFO, &[]).unwrap();
+    let payload = payload(&response);
+    assert_eq!(payload.len(), 9 + 3 * (1 + INFO_MAX_STR_LEN));
+    assert_eq!(payload[9] as usize, INFO_MAX_STR_LEN);
+    assert_eq!(
+        payload[payload.len() - INFO_MAX_STR_LEN - 1] as usize,
+        INFO_MAX_STR_LEN
+    );
+}
+
+#[test]
+fn node_info_truncates_at_char_boundary() {
+    // 'ä' takes two bytes, the last one would be split
+    let name = format!("x{}", "ä".repeat(INFO_MAX_STR_LEN / 2));
+    let mut node = Node {
+        info: DeviceInfo {
+            name: &name,
+            ..DeviceInfo::new()
+        },
+        ..Node::new(0x1000)
+    };
+
+    let response = node_request(&mut node, 0x1000, CMD_NODE_INFO, &[]).unwrap();
+    let payload = payload(&response);
+    assert_eq!(payload[11] as usize, INFO_MAX_STR_LEN - 1);
+    assert_eq!(
+        std::str::from_utf8(&payload[12..]).unwrap(),
+        &name[0..INFO_MAX_STR_LEN - 1]
+    );
+}
+
+/// Splits a TLV encoded payload into its entries
+fn tlv_entries(mut payload: &[u8]) -> Vec<(u8, Vec<u8>)> {
+    let mut entries = Vec::new();
+    while let [tag, len, rest @ ..] = payload {
+        let len = *len as usize;
+        entries.push((*tag, rest[0..len].to_vec()));
+        payload = &rest[len..];
+    }
+    entries
+}
+
+#[test]
+fn sensor_describe() {
+    let relay = Cell::new(false);
+    let frame = request(CMD_SENSOR_DESCRIBE, &id(0), &relay).unwrap();
+
+    let mut value = vec![PayloadType::Float as u8];
+    value.extend_from_slice(&21.5f32.to_le_bytes());
+    assert_eq!(
+        tlv_entries(payload(&frame)),
+        [
+            (TAG_VALUE, value),
+            (TAG_UNIQUE_ID, b"temp_0".to_vec()),
+            (TAG_NAME, b"Temperature".to_vec()),
+            (TAG_NATIVE_UNIT_OF_MEASUREMENT, "°C".as_bytes().to_vec()),
+            (TAG_DEVICE_CLASS, b"DeviceClass.TEMPERATURE".to_vec()),
+            (TAG_STATE_CLASS, b"measurement".to_vec()),
+        ]
+    );
+
+    // A sensor without a value has an empty value entry
+    let frame = request(CMD_SENSOR_DESCRIBE, &id(1), &relay).unwrap();
+    assert_eq!(tlv_entries(payload(&frame))[0], (TAG_VALUE, vec![]));
+
+    assert_eq!(
+        request(CMD_SENSOR_DESCRIBE, &id(2), &relay).err(),
+        Some(ErrorCode::EntityOutOfRange)
+    );
+}
+
+#[test]
+fn sensor_describe_truncates() {
+    let long = "ü".repeat(200);
+    let sensor = Sensor::new(
+        &long,
+        &long,
+        "",
+        DeviceClass::Temperature,
+        StateClass::Measurement,
+        Some(1i32),
+    );
+    let sensors: [&dyn SensorRef; 1] = [&sensor];
+
+    let mut frame = frame(0x0000, 0x1000, CMD_SENSOR_DESCRIBE, &id(0));
+    handle_frame(
+        &mut frame,
+        &mut HandlerPins {},
+        &mut Entities {
+            sensors: &sensors,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let entries = tlv_entries(payload(&frame));
+    assert_eq!(entries[0].0, TAG_VALUE);
+    assert_eq!(entries[1].0, TAG_UNIQUE_ID);
+    // Truncated at a character boundary
+    assert_eq!(entries[1].1.len(), 246);
+    assert!(String::from_utf8(entries[1].1.clone()).is_ok());
+    assert!(frame.payload_len as usize <= 255);
+}
+
+#[test]
+fn switch_describe() {
+    let relay = Cell::new(true);
+    let frame = request(CMD_SWITCH_DESCRIBE, &id(0), &relay).unwrap();
+
+    assert_eq!(
+        tlv_entries(payload(&frame)),
+        [
+            (TAG_STATE, vec![1]),
+            (TAG_UNIQUE_ID, b"relay_0".to_vec()),
+            (TAG_NAME, b"Relay".to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn binary_sensor_count() {
+    let res = request(0x0300, &[], &Cell::new(false)).unwrap();
+    assert_eq!(payload(&res), 2u32.to_le_bytes());
+}
+
+#[test]
+fn binary_sensor_attributes() {
+    let relay = Cell::new(false);
+
+    let res = request(0x0302, &id(0), &relay).unwrap();
+    assert_eq!(payload(&res), b"door_0");
+
+    let res = request(0x0304, &id(0), &relay).unwrap();
+    assert_eq!(payload(&res), b"Door");
+
+    let res = request(0x0306, &id(0), &relay).unwrap();
+    assert_eq!(payload(&res), b"BinarySensorDeviceClass.DOOR");
+
+    // Binary sensors without a device class report an empty one
+    let res = request(0x0306, &id(1), &relay).unwrap();
+    assert_eq!(payload(&res), b"");
+}
+
+#[test]
+fn binary_sensor_state() {
+    let relay = Cell::new(false);
+
+    let res = request(0x0308, &id(0), &relay).unwrap();
+    assert_eq!(payload(&res), [1]);
+
+    // An unknown state is an empty payload
+    let res = request(0x0308, &id(1), &relay).unwrap();
+    assert_eq!(payload(&res), []);
+
+    assert_eq!(
+        request(0x0308, &id(2), &relay).err(),
+        Some(ErrorCode::EntityOutOfRange)
+    );
+}
+
+#[test]
+fn binary_sensor_describe() {
+    let relay = Cell::new(false);
+    let frame = request(CMD_BINARY_SENSOR_DESCRIBE, &id(0), &relay).unwrap();
+
+    assert_eq!(
+        tlv_entries(payload(&frame)),
+        [
+            (TAG_STATE, vec![1]),
+            (TAG_UNIQUE_ID, b"door_0".to_vec()),
+            (TAG_NAME, b"Door".to_vec()),
+            (TAG_DEVICE_CLASS, b"BinarySensorDeviceClass.DOOR".to_vec()),
+        ]
+    );
+
+    let frame = request(CMD_BINARY_SENSOR_DESCRIBE, &id(1), &relay).unwrap();
+    assert_eq!(tlv_entries(payload(&frame))[0], (TAG_STATE, vec![]));
+}
+
+/// Encodes a number value like the handler responds with it
+fn float_value(value: f32) -> Vec<u8> {
+    let mut res = vec![PayloadType::Float as u8];
+    res.extend_from_slice(&value.to_le_bytes());
+    res
+}
+
+/// Builds the payload for a number set request
+fn set(id: u32, value: f32) -> Vec<u8> {
+    [id.to_le_bytes(), value.to_le_bytes()].concat()
+}
+
+#[test]
+fn number_attributes() {
+    let relay = Cell::new(false);
+
+    let res = request(0x0400, &[], &relay).unwrap();
+    assert_eq!(payload(&res), 1u32.to_le_bytes());
+
+    let res = request(0x0402, &id(0), &relay).unwrap();
+    assert_eq!(payload(&res), b"setpoint_0");
+
+    let res = request(0x0404, &id(0), &relay).unwrap();
+    assert_eq!(payload(&res), b"Setpoint");
+
+    let res = request(0x0406, &id(0), &relay).unwrap();
+    assert_eq!(payload(&res), "°C".as_bytes());
+
+    let res = request(0x0408, &id(0), &relay).unwrap();
+    assert_eq!(payload(&res), b"DeviceClass.TEMPERATURE");
+
+    let res = request(0x040a, &id(0), &relay).unwrap();
+    assert_eq!(payload(&res), float_value(20.0));
+}
+
+#[test]
+fn number_set() {
+    let relay = Cell::new(false);
+
+    let res = request(CMD_NUMBER_SET, &set(0, 22.5), &relay).unwrap();
+    assert_eq!(payload(&res), float_value(22.5));
+
+    // The limits are inclusive
+    let res = request(CMD_NUMBER_SET, &set(0, 30.0), &relay).unwrap();
+    assert_eq!(payload(&res), float_value(30.0));
+
+    for value in [4.5, 30.5, f32::NAN] {
+        assert_eq!(
+            request(CMD_NUMBER_SET, &set(0, value), &relay).err(),
+            Some(ErrorCode::InvalidArgument)
+        );
+    }
+
+    assert_eq!(
+        request(CMD_NUMBER_SET, &id(0), &relay).err(),
+        Some(ErrorCode::BadPayloadLength)
+    );
+    assert_eq!(
+        request(CMD_NUMBER_SET, &set(1, 20.0), &relay).err(),
+        Some(ErrorCode::EntityOutOfRange)
+    );
+}
+
+#[test]
+fn number_describe() {
+    let frame = request(CMD_NUMBER_DESCRIBE, &id(0), &Cell::new(false)).unwrap();
+
+    assert_eq!(
+        tlv_entries(payload(&frame)),
+        [
+            (TAG_VALUE, float_value(20.0)),
+            (TAG_UNIQUE_ID, b"setpoint_0".to_vec()),
+            (TAG_NAME, b"Setpoint".to_vec()),
+            (TAG_NATIVE_UNIT_OF_MEASUREMENT, "°C".as_bytes().to_vec()),
+            (TAG_DEVICE_CLASS, b"DeviceClass.TEMPERATURE".to_vec()),
+            (TAG_MIN, 5.0f32.to_le_bytes().to_vec()),
+            (TAG_MAX, 30.0f32.to_le_bytes().to_vec()),
+            (TAG_STEP, 0.5f32.to_le_bytes().to_vec()),
+            (TAG_MODE, b"slider".to_vec()),
+        ]
+    );
+}
+
+/// A button whose action runs for a few ticks, like a relay pulse
+struct TickingButton {
+    /// The milliseconds the running action has left, `None` if it is not running
+    remaining_ms: Option<u16>,
+}
+
+impl<'a> Entity<'a> for TickingButton {
+    fn get_unique_id(&self) -> &'a str {
+        "pulse_0"
+    }
+
+    fn get_name(&self) -> &'a str {
+        "Pulse"
+    }
+
+    fn get_device_class(&self) -> DeviceClass {
+        DeviceClass::Button
+    }
+}
+
+impl<'a> ButtonRef<'a> for TickingButton {
+    fn press(&mut self) -> Result<(), ErrorCode> {
+        if self.remaining_ms.is_some() {
+            return Err(ErrorCode::EntityBusy);
+        }
+
+        self.remaining_ms = Some(500);
+        Ok(())
+    }
+
+    fn tick(&mut self, elapsed_ms: u16) {
+        self.remaining_ms = match self.remaining_ms {
+            Some(remaining) if remaining > elapsed_ms => Some(remaining - elapsed_ms),
+            _ => None,
+        };
+    }
+}
+
+#[test]
+fn button_press() {
+    let presses = Cell::new(0);
+    let mut restart = Button::new("Restart", "restart_0", || {
+        presses.set(presses.get() + 1);
+        Ok(())
+    });
+    let mut failing = Button::new("Failing", "failing_0", || Err(ErrorCode::HardwareFault));
+    let mut buttons: [&mut dyn ButtonRef; 2] = [&mut restart, &mut failing];
+    let mut entities = Entities {
+        buttons: &mut buttons,
+        ..Default::default()
+    };
+
+    let mut request = |cmd: u16, payload: &[u8]| {
+        let mut frame = frame(0x0000, 0x1000, cmd, payload);
+        handle_frame(&mut frame, &mut HandlerPins {}, &mut entities).map(|()| frame)
+    };
+
+    let res = request(0x0500, &[]).unwrap();
+    assert_eq!(payload(&res), 2u32.to_le_bytes());
+
+    let res = request(0x0502, &id(0)).unwrap();
+    assert_eq!(payload(&res), b"restart_0");
+
+    let res = request(0x0504, &id(1)).unwrap();
+    assert_eq!(payload(&res), b"Failing");
+
+    let res = request(CMD_BUTTON_PRESS, &id(0)).unwrap();
+    assert_eq!(payload(&res), []);
+
+    assert_eq!(
+        request(CMD_BUTTON_PRESS, &id(1)).err(),
+        Some(ErrorCode::HardwareFault)
+    );
+    assert_eq!(
+        request(CMD_BUTTON_PRESS, &id(2)).err(),
+        Some(ErrorCode::EntityOutOfRange)
+    );
+
+    let res = request(CMD_BUTTON_DESCRIBE, &id(0)).unwrap();
+    assert_eq!(
+        tlv_entries(payload(&res)),
+        [
+            (TAG_UNIQUE_ID, b"restart_0".to_vec()),
+            (TAG_NAME, b"Restart".to_vec()),
+        ]
+    );
+
+    assert_eq!(presses.get(), 1);
+}
+
+#[test]
+fn button_press_multicast() {
+    let presses = Cell::new(0);
+    let mut button = Button::new("Restart", "restart_0", || {
+        presses.set(presses.get() + 1);
+        Ok(())
+    });
+    let mut buttons: [&mut dyn ButtonRef; 1] = [&mut button];
+
+    let mut frame = frame(0x0000, BROADCAST_ADDR, CMD_BUTTON_PRESS, &id(0));
+    frame.update_crc();
+
+    assert!(!handle_request(
+        &mut frame,
+        &mut Node::new(0x1000),
+        &mut HandlerPins {},
+        &mut Entities {
+            buttons: &mut buttons,
+            ..Default::default()
+        }
+    ));
+    assert_eq!(presses.get(), 1);
+}
+
+#[test]
+fn button_tick() {
+    let mut button = TickingButton { remaining_ms: None };
+    let mut buttons: [&mut dyn ButtonRef; 1] = [&mut button];
+    let mut entities = Entities {
+        buttons: &mut buttons,
+        ..Default::default()
+    };
+
+    let press = |entities: &mut Entities| {
+        let mut frame = frame(0x0000, 0x1000, CMD_BUTTON_PRESS, &id(0));
+        handle_frame(&mut frame, &mut HandlerPins {}, entities)
+    };
+
+    assert_eq!(press(&mut entities), Ok(()));
+    entities.tick(250);
+    assert_eq!(press(&mut entities), Err(ErrorCode::EntityBusy));
+
+    // The action ends with the tick its duration elapsed at
+    entities.tick(250);
+    assert_eq!(press(&mut entities), Ok(()));
+}
+
+/// Builds the payload for a sensor values request with a mask
+fn values_mask(first: u32, mask: u32) -> Vec<u8> {
+    [first.to_le_bytes(), mask.to_le_bytes()].concat()
+}
+
+/// Splits a sensor values response into the sensor id to continue with and the values
+fn sensor_values(payload: &[u8]) -> (u32, Vec<Vec<u8>>) {
+    let next = u32::from_le_bytes(payload[0..4].try_into().unwrap());
+
+    let mut values = Vec::new();
+    let mut rest = &payload[4..];
+    while let [len, tail @ ..] = rest {
+        let len = if *len == SENSOR_VALUE_ERROR {
+            values.push([&[*len], &tail[0..1]].concat());
+            1
+        } else {
+            values.push(tail[0..*len as usize].to_vec());
+            *len as usize
+        };
+        rest = &tail[len..];
+    }
+
+    (next, values)
+}
+
+#[test]
+fn sensor_values_all() {
+    let relay = Cell::new(false);
+    let frame = request(CMD_SENSOR_VALUES, &id(0), &relay).unwrap();
+
+    let mut temperature = vec![PayloadType::Float as u8];
+    temperature.extend_from_slice(&21.5f32.to_le_bytes());
+    assert_eq!(
+        sensor_values(payload(&frame)),
+        (SENSOR_VALUES_COMPLETE, vec![temperature, vec![]])
+    );
+
+    // Starting behind the last sensor is empty
+    let frame = request(CMD_SENSOR_VALUES, &id(2), &relay).unwrap();
+    assert_eq!(payload(&frame), SENSOR_VALUES_COMPLETE.to_le_bytes());
+}
+
+#[test]
+fn sensor_values_mask() {
+    let relay = Cell::new(false);
+
+    let frame = request(CMD_SENSOR_VALUES, &values_mask(0, 0b10), &relay).unwrap();
+    assert_eq!(
+        sensor_values(payload(&frame)),
+        (SENSOR_VALUES_COMPLETE, vec![vec![]])
+    );
+
+    // The mask starts at the first sensor
+    let frame = request(CMD_SENSOR_VALUES, &values_mask(1, 0b1), &relay).unwrap();
+    assert_eq!(
+        sensor_values(payload(&frame)),
+        (SENSOR_VALUES_COMPLETE, vec![vec![]])
+    );
+
+    let frame = request(CMD_SENSOR_VALUES, &values_mask(0, 0), &relay).unwrap();
+    assert_eq!(payload(&frame), SENSOR_VALUES_COMPLETE.to_le_bytes());
+}
+
+#[test]
+fn sensor_values_invalid() {
+    let relay = Cell::new(false);
+
+    assert_eq!(
+        request(CMD_SENSOR_VALUES, &id(3), &relay).err(),
+        Some(ErrorCode::EntityOutOfRange)
+    );
+    assert_eq!(
+        request(CMD_SENSOR_VALUES, &[0, 0, 0, 0, 1], &relay).err(),
+        Some(ErrorCode::BadPayloadLength)
+    );
+}
+
+#[test]
+fn sensor_values_continuation() {
+    let counters: Vec<_> = (0..60i32)
+        .map(|i| {
+            Sensor::new(
+                "Counter",
+                "counter",
+                "",
+                DeviceClass::Temperature,
+                StateClass::TotalIncreasing,
+                Some(i),
+            )
+        })
+        .collect();
+    let sensors: Vec<&dyn SensorRef> = counters.iter().map(|s| s as &dyn SensorRef).collect();
+
+    // 41 values of 6 bytes fit behind the sensor id
+    let mut res = frame(0x0000, 0x1000, CMD_SENSOR_VALUES, &id(0));
+    handle_frame(
+        &mut res,
+        &mut HandlerPins {},
+        &mut Entities {
+            sensors: &sensors,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let (next, values) = sensor_values(payload(&res));
+    assert_eq!(next, 41);
+    assert_eq!(values.len(), 41);
+    assert_eq!(values[40][1..], 40i32.to_le_bytes());
+
+    let mut res = frame(0x0000, 0x1000, CMD_SENSOR_VALUES, &id(next));
+    handle_frame(
+        &mut res,
+        &mut HandlerPins {},
+        &mut Entities {
+            sensors: &sensors,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let (next, values) = sensor_values(payload(&res));
+    assert_eq!(next, SENSOR_VALUES_COMPLETE);
+    assert_eq!(values.len(), 19);
+    assert_eq!(values[0][1..], 41i32.to_le_bytes());
+}
+
+/// A sensor whose hardware is broken
+struct FaultySensor;
+
+impl<'a> Entity<'a> for FaultySensor {
+    fn get_unique_id(&self) -> &'a str {
+        "faulty"
+    }
+
+    fn get_name(&self) -> &'a str {
+        "Faulty"
+    }
+
+    fn get_device_class(&self) -> DeviceClass {
+        DeviceClass::Temperature
+    }
+}
+
+impl<'a> SensorRef<'a> for FaultySensor {
+    fn get_native_unit_of_measurement(&self) -> &'a str {
+        "°C"
+    }
+
+    fn get_state_class(&self) -> StateClass {
+        StateClass::Measurement
+    }
+
+    fn get_payload(
+        &self,
+        _len: &mut u8,
+        _payload: &mut [u8; u8::MAX as usize + 1],
+    ) -> Result<(), ErrorCode> {
+        Err(ErrorCode::HardwareFault)
+    }
+}
+
+#[test]
+fn sensor_values_hardware_fault() {
+    let counter = Sensor::new(
+        "Counter",
+        "counter",
+        "",
+        DeviceClass::Temperature,
+        StateClass::TotalIncreasing,
+        Some(7i32),
+    );
+    let sensors: [&dyn SensorRef; 2] = [&FaultySensor, &counter];
+
+    // The failed sensor does not fail the others
+    let mut frame = frame(0x0000, 0x1000, CMD_SENSOR_VALUES, &id(0));
+    handle_frame(
+        &mut frame,
+        &mut HandlerPins {},
+        &mut Entities {
+            sensors: &sensors,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let (_, values) = sensor_values(payload(&frame));
+    assert_eq!(
+        values,
+        [
+            vec![SENSOR_VALUE_ERROR, ErrorCode::HardwareFault as u8],
+            vec![PayloadType::Int as u8, 7, 0, 0, 0]
+        ]
+    );
+}
+
+#[test]
+fn sensor_values_truncates_string() {
+    let long = "ü".repeat(126);
+    let status = Sensor::new(
+        "Status",
+        "status",
+        "",
+        DeviceClass::None,
+        StateClass::None,
+        Some(long.as_str()),
+    );
+    let sensors: [&dyn SensorRef; 2] = [&status, &status];
+
+    // The first value is sent anyway, cut at a character boundary
+    let mut frame = frame(0x0000, 0x1000, CMD_SENSOR_VALUES, &id(0));
+    handle_frame(
+        &mut frame,
+        &mut HandlerPins {},
+        &mut Entities {
+            sensors: &sensors,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let (next, values) = sensor_values(payload(&frame));
+    assert_eq!(next, 1);
+    assert_eq!(values.len(), 1);
+    assert_eq!(values[0][0], PayloadType::String as u8);
+    assert_eq!(values[0][1..], long.as_bytes()[0..248]);
+}
+
+#[test]
+fn sensor_describe_string() {
+    let long = "x".repeat(300);
+    let status = Sensor::new(
+        "Status",
+        "status",
+        "",
+        DeviceClass::None,
+        StateClass::None,
+        Some(long.as_str()),
+    );
+    let sensors: [&dyn SensorRef; 1] = [&status];
+
+    let mut frame = frame(0x0000, 0x1000, CMD_SENSOR_DESCRIBE, &id(0));
+    handle_frame(
+        &mut frame,
+        &mut HandlerPins {},
+        &mut Entities {
+            sensors: &sensors,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    // The value is cut to fit behind its tag and length, the other entries do not fit
+    let entries = tlv_entries(payload(&frame));
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].0, TAG_VALUE);
+    assert_eq!(
+        entries[0].1,
+        [&[PayloadType::String as u8], &long.as_bytes()[0..252]].concat()
+    );
+}
+
+/// Sends a [`CMD_EVENTS_FETCH`] request to a node with a sensor and a switch
+/// # Returns
+/// The response payload if the node responds
+fn fetch_events(
+    node: &mut Node,
+    dst: u16,
+    sensor: &Sensor<f32>,
+    relay: &Cell<bool>,
+) -> Option<Vec<u8>> {
+    let sensors: [&dyn SensorRef; 1] = [sensor];
+    let mut switch = Switch::new("Relay", "relay_0", switch_callback(relay));
+    let mut switches: [&mut dyn SwitchRef; 1] = [&mut switch];
+
+    let mut frame = frame(0x0000, dst, CMD_EVENTS_FETCH, &[]);
+    frame.update_crc();
+
+    handle_request(
+        &mut frame,
+        node,
+        &mut HandlerPins {},
+        &mut Entities {
+            sensors: &sensors,
+            switches: &mut switches,
+            ..Default::default()
+        },
+    )
+    .then(|| payload(&frame).to_vec())
+}
+
+#[test]
+fn events_fetch() {
+    let mut node = Node::new(0x1000);
+    let sensor = Sensor::new(
+        "Temperature",
+        "temp_0",
+        "°C",
+        DeviceClass::Temperature,
+        StateClass::Measurement,
+        None,
+    )
+    .with_deadband(0.5);
+    let relay = Cell::new(false);
+
+    assert_eq!(
+        fetch_events(&mut node, 0x1000, &sensor, &relay).unwrap(),
+        [0]
+    );
+
+    relay.set(true);
+    sensor.set_value(Some(21.5));
+    node.events.push(Event::Switch(0));
+
+    let mut expected = vec![0, EVENT_SWITCH, 0, 0, 0, 0, 1, 1];
+    expected.extend_from_slice(&[EVENT_SENSOR, 0, 0, 0, 0, 5, PayloadType::Float as u8]);
+    expected.extend_from_slice(&21.5f32.to_le_bytes());
+    assert_eq!(
+        fetch_events(&mut node, 0x1000, &sensor, &relay).unwrap(),
+        expected
+    );
+
+    // Fetched events are removed
+    assert_eq!(
+        fetch_events(&mut node, 0x1000, &sensor, &relay).unwrap(),
+        [0]
+    );
+}
+
+#[test]
+fn events_fetch_is_not_multicast() {
+    let mut node = Node::new(0x1000);
+    node.events.push(Event::Switch(0));
+
+    let sensor = Sensor::new(
+        "Temperature",
+        "temp_0",
+        "°C",
+        DeviceClass::Temperature,
+        StateClass::Measurement,
+        Some(0.0f32),
+    );
+    let relay = Cell::new(false);
+
+    assert!(fetch_events(&mut node, BROADCAST_ADDR, &sensor, &relay).is_none());
+    assert_eq!(node.events.events(), [Event::Switch(0)]);
+}
+
+#[test]
+fn events_fetch_overflow() {
+    let mut node = Node::new(0x1000);
+    let sensor = Sensor::new(
+        "Temperature",
+        "temp_0",
+        "°C",
+        DeviceClass::Temperature,
+        StateClass::Measurement,
+        Some(0.0f32),
+    );
+    let relay = Cell::new(false);
+
+    // Events of unknown entities are dropped
+    for id in 0..EVENT_QUEUE_LEN as u32 {
+        node.events.push(Event::Switch(id));
+    }
+    node.events.push(Event::Sensor(0));
+
+    let payload = fetch_events(&mut node, 0x1000, &sensor, &relay).unwrap();
+    assert_eq!(payload, [EVENTS_OVERFLOWED, EVENT_SWITCH, 0, 0, 0, 0, 1, 0]);
+    assert!(node.events.is_empty());
+
+    // The master is told about the overflow once
+    assert_eq!(
+        fetch_events(&mut node, 0x1000, &sensor, &relay).unwrap(),
+        [0]
+    );
+}
+
+/// A sensor with a 100 byte long value
+struct LongSensor;
+
+impl<'a> Entity<'a> for LongSensor {
+    fn get_unique_id(&self) -> &'a str {
+        "long"
+    }
+
+    fn get_name(&self) -> &'a str {
+        "Long"
+    }
+
+    fn get_device_class(&self) -> DeviceClass {
+        DeviceClass::Temperature
+    }
+}
+
+impl<'a> SensorRef<'a> for LongSensor {
+    fn get_native_unit_of_measurement(&self) -> &'a str {
+        ""
+    }
+
+    fn get_state_class(&self) -> StateClass {
+        StateClass::Measurement
+    }
+
+    fn get_payload(
+        &self,
+        len: &mut u8,
+        payload: &mut [u8; u8::MAX as usize + 1],
+    ) -> Result<(), ErrorCode> {
+        payload[0] = PayloadType::String as u8;
+        payload[1..100].fill(b'x');
+        *len = 100;
+        Ok(())
+    }
+}
+
+#[test]
+fn events_fetch_pending() {
+    let mut node = Node::new(0x1000);
+    let sensors: [&dyn SensorRef; 3] = [&LongSensor, &LongSensor, &LongSensor];
+    for id in 0..3 {
+        node.events.push(Event::Sensor(id));
+    }
+
+    let mut frame = frame(0x0000, 0x1000, CMD_EVENTS_FETCH, &[]);
+    frame.update_crc();
+    assert!(handle_request(
+        &mut frame,
+        &mut node,
+        &mut HandlerPins {},
+        &mut Entities {
+            sensors: &sensors,
+            ..Default::default()
+        }
+    ));
+
+    // Two events of 106 bytes fit, the third stays queued
+    assert_eq!(frame.payload[0], EVENTS_PENDING);
+    assert_eq!(frame.payload_len, 1 + 2 * 106);
+    assert_eq!(node.events.events(), [Event::Sensor(2)]);
+}
+
+#[test]
+fn switch_exec_is_no_event() {
+    let mut node = Node::new(0x1000);
+    let relay = Cell::new(false);
+    let mut switch = Switch::new("Relay", "relay_0", switch_callback(&relay));
+    let mut switches: [&mut dyn SwitchRef; 1] = [&mut switch];
+    let mut entities = Entities {
+        switches: &mut switches,
+        ..Default::default()
+    };
+
+    for req in [exec(0, 1), exec(SWITCH_ID_ALL, 2)] {
+        let mut frame = frame(0x0000, 0x1000, 0x0208, &req);
+        frame.update_crc();
+        assert!(handle_request(
+            &mut frame,
+            &mut node,
+            &mut HandlerPins {},
+            &mut entities
+        ));
+        node.events.poll(&mut entities);
+    }
+
+    assert!(node.events.is_empty());
+}
+
+#[test]
+fn binary_sensor_event() {
+    let mut node = Node::new(0x1000);
+    let door = BinarySensor::new("Door", "door_0", BinarySensorDeviceClass::Door, None);
+    let binary_sensors: [&dyn BinarySensorRef; 1] = [&door];
+    let mut entities = Entities {
+        binary_sensors: &binary_sensors,
+        ..Default::default()
+    };
+
+    door.set_state(Some(true));
+    door.set_state(None);
+
+    let mut frame = frame(0x0000, 0x1000, CMD_EVENTS_FETCH, &[]);
+    frame.update_crc();
+    assert!(handle_request(
+        &mut frame,
+        &mut node,
+        &mut HandlerPins {},
+        &mut entities
+    ));
+
+    // The current state is sent, the unknown state as an empty value
+    assert_eq!(payload(&frame), [0, EVENT_BINARY_SENSOR, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn number_event() {
+    let mut node = Node::new(0x1000);
+    let value = Cell::new(20.0);
+    let mut number = Number::new(
+        "Setpoint",
+        "setpoint_0",
+        "°C",
+        DeviceClass::Temperature,
+        5.0..=30.0,
+        0.5,
+        number_callback(&value),
+    );
+    let mut numbers: [&mut dyn NumberRef; 1] = [&mut number];
+    let mut entities = Entities {
+        numbers: &mut numbers,
+        ..Default::default()
+    };
+    node.events.poll(&mut entities);
+
+    // Values set by the master are no events
+    let mut req = frame(0x0000, 0x1000, CMD_NUMBER_SET, &set(0, 25.0));
+    req.update_crc();
+    assert!(handle_request(
+        &mut req,
+        &mut node,
+        &mut HandlerPins {},
+        &mut entities
+    ));
+    node.events.poll(&mut entities);
+    assert!(node.events.is_empty());
+
+    // Changed on the node, e.g. by a local button
+    value.set(19.5);
+    let mut frame = frame(0x0000, 0x1000, CMD_EVENTS_FETCH, &[]);
+    frame.update_crc();
+    assert!(handle_request(
+        &mut frame,
+        &mut node,
+        &mut HandlerPins {},
+        &mut entities
+    ));
+
+    let mut expected = vec![0, EVENT_NUMBER, 0, 0, 0, 0, 5];
+    expected.extend_from_slice(&float_value(19.5));
+    assert_eq!(payload(&frame), expected);
+}
+
+/// Builds the payload for a light request with an optional transition
+fn light_payload(id: u32, arg: u8, transition_ms: Option<u16>) -> Vec<u8> {
+    let mut payload = id.to_le_bytes().to_vec();
+    payload.push(arg);
+    if let Some(transition_ms) = transition_ms {
+        payload.extend_from_slice(&transition_ms.to_le_bytes());
+    }
+    payload
+}
+
+#[test]
+fn light_attributes() {
+    let relay = Cell::new(false);
+    let res = request(0x0600, &[], &relay).unwrap();
+    assert_eq!(payload(&res), 1u32.to_le_bytes());
+
+    let res = request(0x0602, &id(0), &relay).unwrap();
+    assert_eq!(payload(&res), b"strip_0");
+
+    let res = request(0x0604, &id(0), &relay).unwrap();
+    assert_eq!(payload(&res), b"Strip");
+
+    let res = request(0x0606, &id(0), &relay).unwrap();
+    assert_eq!(payload(&res), [0, 200]);
+}
+
+#[test]
+fn light_exec() {
+    let relay = Cell::new(false);
+
+    let res = request(CMD_LIGHT_EXEC, &light_payload(0, 1, None), &relay).unwrap();
+    assert_eq!(payload(&res), [1, 200]);
+
+    let res = request(CMD_LIGHT_EXEC, &light_payload(0, 2, Some(1000)), &relay).unwrap();
+    assert_eq!(payload(&res), [1, 200]);
+
+    let res = request(CMD_LIGHT_EXEC, &light_payload(0, 0, Some(500)), &relay).unwrap();
+    assert_eq!(payload(&res), [0, 200]);
+
+    assert_eq!(
+        request(CMD_LIGHT_EXEC, &light_payload(0, 3, None), &relay).err(),
+        Some(ErrorCode::InvalidArgument)
+    );
+    assert_eq!(
+        request(CMD_LIGHT_EXEC, &id(0), &relay).err(),
+        Some(ErrorCode::BadPayloadLength)
+    );
+    assert_eq!(
+        request(CMD_LIGHT_EXEC, &light_payload(0, 1, Some(0))[0..6], &relay).err(),
+        Some(ErrorCode::BadPayloadLength)
+    );
+    assert_eq!(
+        request(CMD_LIGHT_EXEC, &light_payload(1, 1, None), &relay).err(),
+        Some(ErrorCode::EntityOutOfRange)
+    );
+}
+
+#[test]
+fn light_brightness() {
+    let relay = Cell::new(false);
+
+    let res = request(
+        CMD_LIGHT_BRIGHTNESS,
+        &light_payload(0, 64, Some(2000)),
+        &relay,
+    )
+    .unwrap();
+    assert_eq!(payload(&res), [1, 64]);
+
+    // Turns off, keeping the brightness for the next time
+    let res = request(CMD_LIGHT_BRIGHTNESS, &light_payload(0, 0, None), &relay).unwrap();
+    assert_eq!(payload(&res), [0, 200]);
+}
+
+#[test]
+fn light_describe() {
+    let frame = request(CMD_LIGHT_DESCRIBE, &id(0), &Cell::new(false)).unwrap();
+
+    assert_eq!(
+        tlv_entries(payload(&frame)),
+        [
+            (TAG_STATE, vec![0]),
+            (TAG_BRIGHTNESS, vec![200]),
+            (TAG_UNIQUE_ID, b"strip_0".to_vec()),
+            (TAG_NAME, b"Strip".to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn light_event() {
+    let mut node = Node::new(0x1000);
+    let state = Cell::new(LightState {
+        on: false,
+        brightness: 255,
+    });
+    let mut light = Light::new("Strip", "strip_0", light_callback(&state));
+    let mut lights: [&mut dyn LightRef; 1] = [&mut light];
+    let mut entities = Entities {
+        lights: &mut lights,
+        ..Default::default()
+    };
+    node.events.poll(&mut entities);
+
+    // States set by the master are no events
+    let mut req = frame(
+        0x0000,
+        0x1000,
+        CMD_LIGHT_BRIGHTNESS,
+        &light_payload(0, 128, None),
+    );
+    req.update_crc();
+    assert!(handle_request(
+        &mut req,
+        &mut node,
+        &mut HandlerPins {},
+        &mut entities
+    ));
+    node.events.poll(&mut entities);
+    assert!(node.events.is_empty());
+
+    // Changed on the node, e.g. by a wall switch
+    state.set(LightState {
+        on: false,
+        brightness: 128,
+    });
+    let mut frame = frame(0x0000, 0x1000, CMD_EVENTS_FETCH, &[]);
+    frame.update_crc();
+    assert!(handle_request(
+        &mut frame,
+        &mut node,
+        &mut HandlerPins {},
+        &mut entities
+    ));
+
+    assert_eq!(payload(&frame), [0, EVENT_LIGHT, 0, 0, 0, 0, 2, 0, 128]);
+}
+
+#[test]
+fn cover_attributes() {
+    let relay = Cell::new(false);
+    let res = request(0x0700, &[], &relay).unwrap();
+    assert_eq!(payload(&res), 1u32.to_le_bytes());
+
+    let res = request(0x0702, &id(0), &relay).unwrap();
+    assert_eq!(payload(&res), b"shutter_0");
+
+    let res = request(0x0704, &id(0), &relay).unwrap();
+    assert_eq!(payload(&res), b"Shutter");
+
+    let res = request(0x0706, &id(0), &relay).unwrap();
+    assert_eq!(payload(&res), b"CoverDeviceClass.SHUTTER");
+
+    let res = request(CMD_COVER_STATE, &id(0), &relay).unwrap();
+    assert_eq!(payload(&res), [40, 0]);
+}
+
+#[test]
+fn cover_exec() {
+    let relay = Cell::new(false);
+
+    let res = request(CMD_COVER_EXEC, &exec(0, 1), &relay).unwrap();
+    assert_eq!(payload(&res), [40, CoverMotion::Opening as u8]);
+
+    let res = request(CMD_COVER_EXEC, &exec(0, 0), &relay).unwrap();
+    assert_eq!(payload(&res), [40, CoverMotion::Closing as u8]);
+
+    let res = request(CMD_COVER_EXEC, &exec(0, 2), &relay).unwrap();
+    assert_eq!(payload(&res), [40, CoverMotion::Stopped as u8]);
+
+    assert_eq!(
+        request(CMD_COVER_EXEC, &exec(0, 3), &relay).err(),
+        Some(ErrorCode::InvalidArgument)
+    );
+    assert_eq!(
+        request(CMD_COVER_EXEC, &id(0), &relay).err(),
+        Some(ErrorCode::BadPayloadLength)
+    );
+    assert_eq!(
+        request(CMD_COVER_EXEC, &exec(1, 1), &relay).err(),
+        Some(ErrorCode::EntityOutOfRange)
+    );
+}
+
+#[test]
+fn cover_position() {
+    let relay = Cell::new(false);
+
+    let res = request(CMD_COVER_POSITION, &exec(0, 100), &relay).unwrap();
+    assert_eq!(payload(&res), [40, CoverMotion::Opening as u8]);
+
+    let res = request(CMD_COVER_POSITION, &exec(0, 0), &relay).unwrap();
+    assert_eq!(payload(&res), [40, CoverMotion::Closing as u8]);
+
+    assert_eq!(
+        request(CMD_COVER_POSITION, &exec(0, 101), &relay).err(),
+        Some(ErrorCode::InvalidArgument)
+    );
+}
+
+#[test]
+fn cover_describe() {
+    let frame = request(CMD_COVER_DESCRIBE, &id(0), &Cell::new(false)).unwrap();
+
+    assert_eq!(
+        tlv_entries(payload(&frame)),
+        [
+            (TAG_POSITION, vec![40]),
+            (TAG_STATE, vec![CoverMotion::Stopped as u8]),
+            (TAG_UNIQUE_ID, b"shutter_0".to_vec()),
+            (TAG_NAME, b"Shutter".to_vec()),
+            (TAG_DEVICE_CLASS, b"CoverDeviceClass.SHUTTER".to_vec()),
+        ]
+    );
+}
+
+/// A cover moved by the ticks, like the relay cover of the firmware
+struct TravelCover {
+    travel: Travel,
+    /// The state last reported as an event
+    reported: CoverState,
+}
+
+impl<'a> Entity<'a> for TravelCover {
+    fn get_unique_id(&self) -> &'a str {
+        "blind_0"
+    }
+
+    fn get_name(&self) -> &'a str {
+        "Blind"
+    }
+
+    fn get_device_class(&self) -> DeviceClass {
+        DeviceClass::Cover(CoverDeviceClass::Blind)
+    }
+}
+
+impl<'a> CoverRef<'a> for TravelCover {
+    fn exec_request(&mut self, req: CoverRequest) -> Result<CoverState, ErrorCode> {
+        match req {
+            CoverRequest::Open => self.travel.move_to(100),
+            CoverRequest::Close => self.travel.move_to(0),
+            CoverRequest::Stop => self.travel.stop(),
+            CoverRequest::SetPosition(position) => self.travel.move_to(position),
+            CoverRequest::Get => {}
+        }
+
+        Ok(self.travel.state())
+    }
+
+    fn take_changed(&mut self) -> bool {
+        let state = self.travel.state();
+        let changed = state != self.reported;
+
+        self.reported = state;
+        changed
+    }
+
+    fn tick(&mut self, elapsed_ms: u16) {
+        self.travel.tick(elapsed_ms);
+    }
+}
+
+#[test]
+fn cover_event() {
+    let mut node = Node::new(0x1000);
+    let mut cover = TravelCover {
+        travel: Travel::new(10_000, 0),
+        reported: CoverState::default(),
+    };
+    let mut covers: [&mut dyn CoverRef; 1] = [&mut cover];
+    let mut entities = Entities {
+        covers: &mut covers,
+        ..Default::default()
+    };
+
+    // Starting to move is no event, the master requested it
+    let mut req = frame(0x0000, 0x1000, CMD_COVER_POSITION, &exec(0, 50));
+    req.update_crc();
+    assert!(handle_request(
+        &mut req,
+        &mut node,
+        &mut HandlerPins {},
+        &mut entities
+    ));
+    assert_eq!(payload(&req), [0, CoverMotion::Opening as u8]);
+    node.events.poll(&mut entities);
+    assert!(node.events.is_empty());
+
+    // The progress and the arrival are
+    entities.tick(2_500);
+    let mut fetch = frame(0x0000, 0x1000, CMD_EVENTS_FETCH, &[]);
+    fetch.update_crc();
+    assert!(handle_request(
+        &mut fetch,
+        &mut node,
+        &mut HandlerPins {},
+        &mut entities
+    ));
+    assert_eq!(
+        payload(&fetch),
+        [
+            0,
+            EVENT_COVER,
+            0,
+            0,
+            0,
+            0,
+            2,
+            25,
+            CoverMotion::Opening as u8
+        ]
+    );
+
+    entities.tick(2_500);
+    node.events.poll(&mut entities);
+    assert_eq!(node.events.events(), [Event::Cover(0)]);
+    let mut state = frame(0x0000, 0x1000, CMD_COVER_STATE, &id(0));
+    handle_frame(&mut state, &mut HandlerPins {}, &mut entities).unwrap();
+    assert_eq!(payload(&state), [50, CoverMotion::Stopped as u8]);
+}
+
+#[test]
+fn select_attributes() {
+    let relay = Cell::new(false);
+    let res = request(0x0800, &[], &relay).unwrap();
+    assert_eq!(payload(&res), 1u32.to_le_bytes());
+
+    let res = request(0x0802, &id(0), &relay).unwrap();
+    assert_eq!(payload(&res), b"heating_0");
+
+    let res = request(0x0804, &id(0), &relay).unwrap();
+    assert_eq!(payload(&res), b"Heating");
+
+    let res = request(CMD_SELECT_STATE, &id(0), &relay).unwrap();
+    assert_eq!(payload(&res), [1]);
+
+    assert_eq!(
+        request(CMD_SELECT_STATE, &id(1), &relay).err(),
+        Some(ErrorCode::EntityOutOfRange)
+    );
+}
+
+#[test]
+fn select_option() {
+    let relay = Cell::new(false);
+
+    let res = request(CMD_SELECT_OPTION, &exec(0, 2), &relay).unwrap();
+    assert_eq!(payload(&res), b"Comfort");
+
+    assert_eq!(
+        request(CMD_SELECT_OPTION, &exec(0, 3), &relay).err(),
+        Some(ErrorCode::InvalidArgument)
+    );
+    assert_eq!(
+        request(CMD_SELECT_OPTION, &id(0), &relay).err(),
+        Some(ErrorCode::BadPayloadLength)
+    );
+}
+
+#[test]
+fn select_set() {
+    let relay = Cell::new(false);
+
+    let res = request(CMD_SELECT_SET, &exec(0, 2), &relay).unwrap();
+    assert_eq!(payload(&res), [2]);
+
+    let res = request(CMD_SELECT_SET, &exec(0, 0), &relay).unwrap();
+    assert_eq!(payload(&res), [0]);
+
+    assert_eq!(
+        request(CMD_SELECT_SET, &exec(0, 3), &relay).err(),
+        Some(ErrorCode::InvalidArgument)
+    );
+    assert_eq!(
+        request(CMD_SELECT_SET, &id(0), &relay).err(),
+        Some(ErrorCode::BadPayloadLength)
+    );
+    assert_eq!(
+        request(CMD_SELECT_SET, &exec(1, 0), &relay).err(),
+        Some(ErrorCode::EntityOutOfRange)
+    );
+}
+
+#[test]
+fn select_describe() {
+    let frame = request(CMD_SELECT_DESCRIBE, &id(0), &Cell::new(false)).unwrap();
+
+    assert_eq!(
+        tlv_entries(payload(&frame)),
+        [
+            (TAG_STATE, vec![1]),
+            (TAG_UNIQUE_ID, b"heating_0".to_vec()),
+            (TAG_NAME, b"Heating".to_vec()),
+            (TAG_OPTION_COUNT, vec![3]),
+            (TAG_OPTION, b"Off".to_vec()),
+            (TAG_OPTION, b"Eco".to_vec()),
+            (TAG_OPTION, b"Comfort".to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn select_describe_leaves_out_options() {
+    let options: Vec<String> = (0..10)
+        .map(|i| format!("{}{}", i, "x".repeat(29)))
+        .collect();
+    let options: Vec<&str> = options.iter().map(String::as_str).collect();
+    let mut select = Select::new("Mode", "mode_0", options.clone(), |_| 0);
+    let mut selects: [&mut dyn SelectRef; 1] = [&mut select];
+
+    let mut frame = frame(0x0000, 0x1000, CMD_SELECT_DESCRIBE, &id(0));
+    handle_frame(
+        &mut frame,
+        &mut HandlerPins {},
+        &mut Entities {
+            selects: &mut selects,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    // Only complete options are sent, the master fetches the rest one by one
+    let entries = tlv_entries(payload(&frame));
+    assert_eq!(entries[3], (TAG_OPTION_COUNT, vec![10]));
+    let sent: Vec<_> = entries[4..].iter().collect();
+    assert_eq!(sent.len(), 7);
+    for (i, (tag, value)) in sent.into_iter().enumerate() {
+        assert_eq!(*tag, TAG_OPTION);
+        assert_eq!(value, options[i].as_bytes());
+    }
+}
+
+#[test]
+fn select_event() {
+    let mut node = Node::new(0x1000);
+    let index = Cell::new(0);
+    let mut select = Select::new(
+        "Pump",
+        "pump_0",
+        ["Auto", "Manual"],
+        select_callback(&index),
+    );
+    let mut selects: [&mut dyn SelectRef; 1] = [&mut select];
+    let mut entities = Entities {
+        selects: &mut selects,
+        ..Default::default()
+    };
+    node.events.poll(&mut entities);
+
+    // Options selected by the master are no events
+    let mut req = frame(0x0000, 0x1000, CMD_SELECT_SET, &exec(0, 1));
+    req.update_crc();
+    assert!(handle_request(
+        &mut req,
+        &mut node,
+        &mut HandlerPins {},
+        &mut entities
+    ));
+    node.events.poll(&mut entities);
+    assert!(node.events.is_empty());
+
+    // Changed on the node, e.g. by a local switch
+    index.set(0);
+    let mut frame = frame(0x0000, 0x1000, CMD_EVENTS_FETCH, &[]);
+    frame.update_crc();
+    assert!(handle_request(
+        &mut frame,
+        &mut node,
+        &mut HandlerPins {},
+        &mut entities
+    ));
+    assert_eq!(payload(&frame), [0, EVENT_SELECT, 0, 0, 0, 0, 1, 0]);
+}
@@ -0,0 +1,50 @@
+use ha_buddy_proto::homeassistant::light::Fade;
+
+#[test]
+fn fade_without_transition_jumps() {
+    let mut fade = Fade::new(0);
+    fade.start(200, 0);
+
+    assert_eq!(fade.level(), 200);
+    assert!(!fade.is_running());
+    assert_eq!(fade.tick(10), 200);
+}
+
+#[test]
+fn fade_is_linear() {
+    let mut fade = Fade::new(0);
+    fade.start(200, 1000);
+    assert_eq!(fade.level(), 0);
+    assert_eq!(fade.target(), 200);
+
+    assert_eq!(fade.tick(250), 50);
+    assert_eq!(fade.tick(250), 100);
+    assert_eq!(fade.tick(400), 180);
+    assert!(fade.is_running());
+
+    // Overshooting ticks end at the target
+    assert_eq!(fade.tick(250), 200);
+    assert!(!fade.is_running());
+}
+
+#[test]
+fn fade_down() {
+    let mut fade = Fade::new(255);
+    fade.start(0, 100);
+
+    assert_eq!(fade.tick(50), 128);
+    assert_eq!(fade.tick(50), 0);
+    assert!(!fade.is_running());
+}
+
+#[test]
+fn fade_restarts_from_current_level() {
+    let mut fade = Fade::new(0);
+    fade.start(100, 100);
+    assert_eq!(fade.tick(50), 50);
+
+    // A new request fades from where the light is, not from where it came from
+    fade.start(0, 500);
+    assert_eq!(fade.tick(250), 25);
+    assert_eq!(fade.tick(u16::MAX), 0);
+}
@@ -0,0 +1,177 @@
+use core::fmt::Write;
+
+use ha_buddy_proto::homeassistant::sensor::{
+    Fixed, Formatted, PayloadType, SensorValue, StringBuf, MAX_STRING_LEN,
+};
+
+/// Encodes `value` and returns the payload
+fn payload(value: &impl SensorValue) -> Vec<u8> {
+    let mut payload = [0u8; u8::MAX as usize + 1];
+    let mut len = 0;
+    value.to_payload(&mut len, &mut payload);
+    payload[0..len as usize].to_vec()
+}
+
+/// Returns the payload of a string value
+fn string(value: &str) -> Vec<u8> {
+    [&[PayloadType::String as u8], value.as_bytes()].concat()
+}
+
+#[test]
+fn str_value() {
+    assert_eq!(payload(&"heating"), string("heating"));
+    assert_eq!(payload(&""), string(""));
+    assert_eq!(payload(&Some("idle")), string("idle"));
+
+    assert_eq!("idle".distance(&"idle"), 0.0);
+    assert_eq!("idle".distance(&"heating"), f32::INFINITY);
+}
+
+#[test]
+fn str_value_truncates() {
+    let long = "x".repeat(300);
+    assert_eq!(payload(&long.as_str()), string(&long[0..MAX_STRING_LEN]));
+
+    // The value stays below SENSOR_VALUE_ERROR, cut at a character boundary
+    let long = "ü".repeat(200);
+    let value = payload(&long.as_str());
+    assert_eq!(value.len(), 1 + 252);
+    assert!(String::from_utf8(value[1..].to_vec()).is_ok());
+}
+
+#[test]
+fn string_buf() {
+    let mut buf = StringBuf::<8>::new();
+    assert!(buf.is_empty());
+    assert!(buf.push_str("idle"));
+    assert_eq!(buf.as_str(), "idle");
+    assert_eq!(payload(&buf), string("idle"));
+
+    // Truncated at a character boundary
+    assert!(!buf.push_str("/üü"));
+    assert_eq!(buf.as_str(), "idle/ü");
+    assert_eq!(buf.len(), 7);
+
+    buf.clear();
+    assert!(write!(buf, "{}°C", 21).is_ok());
+    assert_eq!(buf, StringBuf::from_fmt(format_args!("21°C")));
+    assert!(write!(buf, "{}", 1234).is_err());
+    assert_eq!(buf.as_str(), "21°C123");
+}
+
+#[test]
+fn string_buf_distance() {
+    let idle = StringBuf::<16>::from_fmt(format_args!("idle"));
+    let heating = StringBuf::<16>::from_fmt(format_args!("heating"));
+
+    assert_eq!(idle.distance(&idle.clone()), 0.0);
+    assert_eq!(idle.distance(&heating), f32::INFINITY);
+}
+
+#[test]
+fn string_buf_truncates_payload() {
+    let mut buf = StringBuf::<300>::new();
+    for _ in 0..100 {
+        buf.push_str("ab€");
+    }
+    assert_eq!(buf.len(), 300);
+
+    // 50 times "ab€" are 250 bytes, the next "ab" fit but the "€" does not
+    let value = payload(&buf);
+    assert_eq!(value.len(), 1 + 252);
+    assert_eq!(value[1..], buf.as_str().as_bytes()[0..252]);
+}
+
+#[test]
+fn formatted_value() {
+    assert_eq!(payload(&Formatted(42)), string("42"));
+    assert_eq!(payload(&Formatted("v1.2.3")), string("v1.2.3"));
+
+    assert_eq!(Formatted(1).distance(&Formatted(1)), 0.0);
+    assert_eq!(Formatted(1).distance(&Formatted(2)), f32::INFINITY);
+}
+
+#[test]
+fn formatted_value_truncates() {
+    struct Diagnostics;
+
+    impl core::fmt::Display for Diagnostics {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            for i in 0..100 {
+                write!(f, "{}ü", i % 10)?;
+            }
+            // Would fit behind the cut, but must not be appended
+            write!(f, "!")
+        }
+    }
+
+    impl PartialEq for Diagnostics {
+        fn eq(&self, _other: &Self) -> bool {
+            true
+        }
+    }
+
+    let value = payload(&Formatted(Diagnostics));
+    assert_eq!(value.len(), 1 + 253);
+    let text = String::from_utf8(value[1..].to_vec()).unwrap();
+    assert!(text.ends_with("ü4"));
+}
+
+#[test]
+fn integer_values() {
+    assert_eq!(
+        payload(&4_000_000_000u32),
+        [
+            &[PayloadType::UInt as u8],
+            &4_000_000_000u32.to_le_bytes()[..]
+        ]
+        .concat()
+    );
+    assert_eq!(
+        payload(&-5_000_000_000i64),
+        [
+            &[PayloadType::Int64 as u8],
+            &(-5_000_000_000i64).to_le_bytes()[..]
+        ]
+        .concat()
+    );
+    assert_eq!(
+        payload(&u64::MAX),
+        [&[PayloadType::UInt64 as u8], &u64::MAX.to_le_bytes()[..]].concat()
+    );
+
+    // Large counters still see small steps
+    let energy = 1u64 << 40;
+    assert_eq!(energy.distance(&(energy + 3)), 3.0);
+    assert_eq!((-3i64).distance(&4), 7.0);
+    assert_eq!(1u32.distance(&u32::MAX), u32::MAX as f32 - 1.0);
+}
+
+#[test]
+fn bool_value() {
+    assert_eq!(payload(&true), [PayloadType::Bool as u8, 1]);
+    assert_eq!(payload(&false), [PayloadType::Bool as u8, 0]);
+
+    assert_eq!(true.distance(&true), 0.0);
+    assert_eq!(true.distance(&false), f32::INFINITY);
+}
+
+#[test]
+fn fixed_value() {
+    let value = Fixed::new(-2137, -2);
+    assert_eq!(
+        payload(&value),
+        [
+            &[PayloadType::Fixed as u8],
+            &(-2137i32).to_le_bytes()[..],
+            &[0xfe]
+        ]
+        .concat()
+    );
+    assert_eq!(value.to_f32(), -21.37);
+    assert_eq!(Fixed::new(5, 3).to_f32(), 5000.0);
+
+    assert_eq!(value.distance(&Fixed::new(-2137, -2)), 0.0);
+    assert!((value.distance(&Fixed::new(-2100, -2)) - 0.37).abs() < 1e-6);
+    assert!((value.distance(&Fixed::new(-21, 0)) - 0.37).abs() < 1e-5);
+}
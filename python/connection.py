@@ -3,7 +3,17 @@ import logging
 import time
 import threading
 
-from .frame import Frame, frame_decode, ExpectedBytesCountError, exec_command
+from .frame import (
+    Frame,
+    frame_decode,
+    ExpectedBytesCountError,
+    StartBytesError,
+    HeaderCRCError,
+    FrameCRCError,
+    NodeError,
+    exec_command,
+    discover,
+)
 
 LOGGER = logging.getLogger("ha_buddy")
 
@@ -31,14 +41,25 @@ class BuddyConnection:
         LOGGER.info("Scanning for devices")
 
         self.devices = []
+        self._ser.timeout = 0.05
+        for addr, serial_number in discover(self._ser):
+            LOGGER.info(f"Device {hex(addr)} is online (serial {serial_number:016x})!")
+            self.devices.append(Device(self._domain, addr, self))
+
+        # Nodes without a serial number can't be discovered, look for them at the default addresses
+        known = [device._addr for device in self.devices]
         for addr in range(0x1000, 0x1010):
+            if addr in known:
+                continue
+
             self._ser.timeout = 0.2
             frame = Frame(0x0000, addr, 0x0000, bytes([]))
             self._ser.write(frame.to_bytes())
 
             try:
                 rec = frame_decode(self._ser)
-            except ExpectedBytesCountError:
+            except (ExpectedBytesCountError, StartBytesError, HeaderCRCError, FrameCRCError):
+                # Unassigned nodes sharing an address garble their responses
                 continue
 
             LOGGER.info(f"Device {hex(addr)} is online ({rec.payload})!")
@@ -47,6 +68,24 @@ class BuddyConnection:
 
         return True
 
+    def poll_events(self) -> None:
+        """Fetches the events of all devices supporting them"""
+
+        for device in self.devices or []:
+            if not device.supports_events():
+                continue
+
+            try:
+                device.poll_events()
+            except (
+                NodeError,
+                ExpectedBytesCountError,
+                StartBytesError,
+                HeaderCRCError,
+                FrameCRCError,
+            ) as e:
+                LOGGER.warning(f"Failed to fetch events of device {hex(device._addr)}: {e}")
+
     def get_payload(self, client_addr: int, cmd: int, payload: bytes) -> bytes:
         with self._lock:
             send_frame = Frame(0x0000, client_addr, cmd, payload)
@@ -1,13 +1,94 @@
 import logging
+import time
 
 from homeassistant.helpers import device_registry as dr
-from .entities.sensor import BuddySensor
+from .entities.sensor import BuddySensor, CMD_SENSOR_VALUE
 from .entities.switch import BuddySwitch
+from .entities.binary_sensor import BuddyBinarySensor
+from .entities.number import BuddyNumber
+from .entities.button import BuddyButton
+from .entities.light import BuddyLight
+from .entities.cover import BuddyCover
+from .entities.select import BuddySelect
+from .frame import ExpectedBytesCountError, NodeError
 
 LOGGER = logging.getLogger("ha_buddy")
 
 CMD_SENSOR_DISCOVERY = 0x0100
 CMD_SWITCH_DISCOVERY = 0x0200
+CMD_BINARY_SENSOR_DISCOVERY = 0x0300
+CMD_NUMBER_DISCOVERY = 0x0400
+CMD_BUTTON_DISCOVERY = 0x0500
+CMD_LIGHT_DISCOVERY = 0x0600
+CMD_COVER_DISCOVERY = 0x0700
+CMD_SELECT_DISCOVERY = 0x0800
+CMD_NODE_INFO = 0x0016
+CMD_EVENTS_FETCH = 0x0018
+CMD_SENSOR_VALUES = 0x0116
+
+SENSOR_VALUES_COMPLETE = 0xFFFFFFFF
+SENSOR_VALUE_ERROR = 0xFF
+EVENT_SENSOR = 0x01
+EVENT_SWITCH = 0x02
+EVENT_BINARY_SENSOR = 0x03
+EVENT_NUMBER = 0x04
+EVENT_LIGHT = 0x05
+EVENT_COVER = 0x06
+EVENT_SELECT = 0x07
+EVENTS_OVERFLOWED = 1 << 0
+EVENTS_PENDING = 1 << 1
+
+# Values read in bulk are only handed out to sensors updating within this many seconds
+SENSOR_VALUES_MAX_AGE = 5.0
+
+CAP_SENSOR = 1 << 0
+CAP_SWITCH = 1 << 1
+CAP_BINARY_SENSOR = 1 << 2
+CAP_NUMBER = 1 << 3
+CAP_BUTTON = 1 << 4
+CAP_LIGHT = 1 << 5
+CAP_COVER = 1 << 6
+CAP_SELECT = 1 << 7
+
+
+def decode_node_info(payload: bytes) -> dict:
+    """Decodes the payload of a device information response"""
+
+    info = {
+        "protocol_version": payload[0],
+        "capabilities": int.from_bytes(payload[1:5], byteorder="little"),
+        "uptime": int.from_bytes(payload[5:9], byteorder="little"),
+    }
+
+    pos = 9
+    for key in ["firmware_version", "board", "name"]:
+        length = payload[pos]
+        info[key] = payload[pos + 1 : pos + 1 + length].decode("utf-8")
+        pos += 1 + length
+
+    return info
+
+
+def decode_tlv(payload: bytes, repeated: tuple = ()) -> dict:
+    """
+    Decodes the tag-length-value entries of a describe response,
+    the values of repeated tags are collected into lists
+    """
+
+    entries = {}
+
+    pos = 0
+    while pos + 2 <= len(payload):
+        tag = payload[pos]
+        length = payload[pos + 1]
+        value = payload[pos + 2 : pos + 2 + length]
+        if tag in repeated:
+            entries.setdefault(tag, []).append(value)
+        else:
+            entries[tag] = value
+        pos += 2 + length
+
+    return entries
 
 
 class Device:
@@ -15,18 +96,191 @@ class Device:
         self._addr = addr
         self._con = con
 
+        # Firmware older than protocol version 1 does not report any information, it
+        # responds with an error or, before error frames, not at all
+        try:
+            self._info = decode_node_info(self.get_device_payload(CMD_NODE_INFO, bytes()))
+        except (NodeError, ExpectedBytesCountError):
+            LOGGER.info(f"Device {hex(self._addr)} does not report device information")
+            self._info = {
+                "protocol_version": 0,
+                "capabilities": CAP_SENSOR | CAP_SWITCH,
+                "firmware_version": None,
+                "board": None,
+                "name": "",
+            }
+
+        self._values = {}
+        self._values_time = 0.0
+        self._sensors = []
+        self._switches = []
+        self._binary_sensors = []
+        self._numbers = []
+        self._lights = []
+        self._covers = []
+        self._selects = []
+
+        name = self._info["name"] or "Buddy"
+
         self._device_info = dr.DeviceInfo(
             identifiers={(domain, hex(self._addr))},
-            name=f"Buddy {hex(self._addr)}",
+            name=f"{name} {hex(self._addr)}",
             manufacturer="Max Kofler",
-            model="buddy 1.0",
-            sw_version="1.0",
+            model=self._info["board"],
+            sw_version=self._info["firmware_version"],
         )
 
     def get_device_payload(self, cmd: int, payload: bytes):
         return self._con.get_payload(self._addr, cmd, payload)
 
+    def describe(self, cmd: int, entity_id: int, repeated: tuple = ()) -> dict | None:
+        """
+        Retrieves all attributes of an entity in a single request,
+        returns None if the firmware does not support the describe commands
+        """
+
+        # Older firmware may not even respond with an error, don't ask it
+        if self._info["protocol_version"] < 2:
+            LOGGER.debug(f"Device {hex(self._addr)} does not support {hex(cmd)}")
+            return None
+
+        payload = self.get_device_payload(
+            cmd, entity_id.to_bytes(4, byteorder="little")
+        )
+        return decode_tlv(payload, repeated)
+
+    def read_sensor_values(self) -> dict:
+        """
+        Reads the raw values of all sensors with as few requests as possible,
+        sensors that failed map to the NodeError they reported
+        """
+
+        values = {}
+
+        first = 0
+        while True:
+            payload = self.get_device_payload(
+                CMD_SENSOR_VALUES, first.to_bytes(4, byteorder="little")
+            )
+            next_id = int.from_bytes(payload[0:4], byteorder="little")
+
+            sensor_id = first
+            pos = 4
+            while pos < len(payload):
+                length = payload[pos]
+                if length == SENSOR_VALUE_ERROR:
+                    values[sensor_id] = NodeError(CMD_SENSOR_VALUES, payload[pos + 1])
+                    pos += 2
+                else:
+                    values[sensor_id] = payload[pos + 1 : pos + 1 + length]
+                    pos += 1 + length
+                sensor_id += 1
+
+            if next_id == SENSOR_VALUES_COMPLETE:
+                return values
+            first = next_id
+
+    def get_sensor_value(self, sensor_id: int) -> bytes:
+        """
+        Returns the raw value of a sensor. Firmware since protocol version 3 reads
+        all sensors at once and hands each value out once to the polling sensors.
+        """
+
+        if self._info["protocol_version"] < 3:
+            return self.get_device_payload(
+                CMD_SENSOR_VALUE, sensor_id.to_bytes(4, byteorder="little")
+            )
+
+        if (
+            sensor_id not in self._values
+            or time.monotonic() - self._values_time > SENSOR_VALUES_MAX_AGE
+        ):
+            LOGGER.debug(f"Reading all sensor values of device {hex(self._addr)}")
+            self._values = self.read_sensor_values()
+            self._values_time = time.monotonic()
+
+        value = self._values.pop(sensor_id)
+        if isinstance(value, NodeError):
+            raise value
+
+        return value
+
+    def supports_events(self) -> bool:
+        return self._info["protocol_version"] >= 4
+
+    def fetch_events(self) -> (bool, list):
+        """
+        Fetches the changes the device reported since the last fetch. Returns if the
+        device dropped events and the events as (kind, entity id, raw value), entities
+        that failed carry the NodeError they reported instead of the value
+        """
+
+        overflowed = False
+        events = []
+
+        while True:
+            payload = self.get_device_payload(CMD_EVENTS_FETCH, bytes())
+            flags = payload[0]
+            overflowed |= flags & EVENTS_OVERFLOWED != 0
+
+            pos = 1
+            while pos + 6 <= len(payload):
+                kind = payload[pos]
+                entity_id = int.from_bytes(payload[pos + 1 : pos + 5], byteorder="little")
+                length = payload[pos + 5]
+                if length == SENSOR_VALUE_ERROR:
+                    value = NodeError(CMD_EVENTS_FETCH, payload[pos + 6])
+                    pos += 7
+                else:
+                    value = payload[pos + 6 : pos + 6 + length]
+                    pos += 6 + length
+                events.append((kind, entity_id, value))
+
+            if not flags & EVENTS_PENDING:
+                return overflowed, events
+
+    def poll_events(self) -> None:
+        """Pushes the changes the device reported to its entities"""
+
+        overflowed, events = self.fetch_events()
+
+        if overflowed:
+            LOGGER.warning(f"Device {hex(self._addr)} dropped events, polling all entities")
+            for entity in (
+                self._sensors
+                + self._switches
+                + self._binary_sensors
+                + self._numbers
+                + self._lights
+                + self._covers
+                + self._selects
+            ):
+                entity.schedule_update_ha_state(True)
+
+        for kind, entity_id, value in events:
+            if kind == EVENT_SENSOR and entity_id < len(self._sensors):
+                self._sensors[entity_id].push_value(value)
+            elif kind == EVENT_SWITCH and entity_id < len(self._switches):
+                self._switches[entity_id].push_state(value)
+            elif kind == EVENT_BINARY_SENSOR and entity_id < len(self._binary_sensors):
+                self._binary_sensors[entity_id].push_state(value)
+            elif kind == EVENT_NUMBER and entity_id < len(self._numbers):
+                self._numbers[entity_id].push_value(value)
+            elif kind == EVENT_LIGHT and entity_id < len(self._lights):
+                self._lights[entity_id].push_state(value)
+            elif kind == EVENT_COVER and entity_id < len(self._covers):
+                self._covers[entity_id].push_state(value)
+            elif kind == EVENT_SELECT and entity_id < len(self._selects):
+                self._selects[entity_id].push_state(value)
+            else:
+                LOGGER.debug(
+                    f"Device {hex(self._addr)} reported unknown event {hex(kind)}:{entity_id}"
+                )
+
     def get_sensors(self) -> []:
+        if not self._info["capabilities"] & CAP_SENSOR:
+            return []
+
         num_sensors = int.from_bytes(
             self.get_device_payload(CMD_SENSOR_DISCOVERY, bytes()),
             byteorder="little",
@@ -39,9 +293,13 @@ class Device:
         for i in range(0, num_sensors):
             sensors.append(BuddySensor(self, i))
 
+        self._sensors = sensors
         return sensors
 
     def get_switches(self) -> []:
+        if not self._info["capabilities"] & CAP_SWITCH:
+            return []
+
         num_switchs = int.from_bytes(
             self.get_device_payload(CMD_SWITCH_DISCOVERY, bytes()),
             byteorder="little",
@@ -54,8 +312,124 @@ class Device:
         for i in range(0, num_switchs):
             switches.append(BuddySwitch(self, i))
 
+        self._switches = switches
         return switches
 
+    def get_binary_sensors(self) -> []:
+        if not self._info["capabilities"] & CAP_BINARY_SENSOR:
+            return []
+
+        num_binary_sensors = int.from_bytes(
+            self.get_device_payload(CMD_BINARY_SENSOR_DISCOVERY, bytes()),
+            byteorder="little",
+        )
+
+        LOGGER.info(
+            f"Device {hex(self._addr)} has {num_binary_sensors} available binary sensors"
+        )
+
+        binary_sensors = []
+
+        for i in range(0, num_binary_sensors):
+            binary_sensors.append(BuddyBinarySensor(self, i))
+
+        self._binary_sensors = binary_sensors
+        return binary_sensors
+
+    def get_numbers(self) -> []:
+        if not self._info["capabilities"] & CAP_NUMBER:
+            return []
+
+        num_numbers = int.from_bytes(
+            self.get_device_payload(CMD_NUMBER_DISCOVERY, bytes()),
+            byteorder="little",
+        )
+
+        LOGGER.info(f"Device {hex(self._addr)} has {num_numbers} available numbers")
+
+        numbers = []
+
+        for i in range(0, num_numbers):
+            numbers.append(BuddyNumber(self, i))
+
+        self._numbers = numbers
+        return numbers
+
+    def get_buttons(self) -> []:
+        if not self._info["capabilities"] & CAP_BUTTON:
+            return []
+
+        num_buttons = int.from_bytes(
+            self.get_device_payload(CMD_BUTTON_DISCOVERY, bytes()),
+            byteorder="little",
+        )
+
+        LOGGER.info(f"Device {hex(self._addr)} has {num_buttons} available buttons")
+
+        buttons = []
+
+        for i in range(0, num_buttons):
+            buttons.append(BuddyButton(self, i))
+
+        return buttons
+
+    def get_lights(self) -> []:
+        if not self._info["capabilities"] & CAP_LIGHT:
+            return []
+
+        num_lights = int.from_bytes(
+            self.get_device_payload(CMD_LIGHT_DISCOVERY, bytes()),
+            byteorder="little",
+        )
+
+        LOGGER.info(f"Device {hex(self._addr)} has {num_lights} available lights")
+
+        lights = []
+
+        for i in range(0, num_lights):
+            lights.append(BuddyLight(self, i))
+
+        self._lights = lights
+        return lights
+
+    def get_covers(self) -> []:
+        if not self._info["capabilities"] & CAP_COVER:
+            return []
+
+        num_covers = int.from_bytes(
+            self.get_device_payload(CMD_COVER_DISCOVERY, bytes()),
+            byteorder="little",
+        )
+
+        LOGGER.info(f"Device {hex(self._addr)} has {num_covers} available covers")
+
+        covers = []
+
+        for i in range(0, num_covers):
+            covers.append(BuddyCover(self, i))
+
+        self._covers = covers
+        return covers
+
+    def get_selects(self) -> []:
+        if not self._info["capabilities"] & CAP_SELECT:
+            return []
+
+        num_selects = int.from_bytes(
+            self.get_device_payload(CMD_SELECT_DISCOVERY, bytes()),
+            byteorder="little",
+        )
+
+        LOGGER.info(f"Device {hex(self._addr)} has {num_selects} available selects")
+
+        selects = []
+
+        for i in range(0, num_selects):
+            selects.append(BuddySelect(self, i))
+
+        self._selects = selects
+        return selects
+
     def device_info(self) -> dr.DeviceInfo:
         return self._device_info
 
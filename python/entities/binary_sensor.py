@@ -0,0 +1,106 @@
+import logging
+
+from homeassistant.helpers import device_registry as dr
+from homeassistant.components.binary_sensor import (
+    BinarySensorDeviceClass,
+    BinarySensorEntity,
+)
+
+LOGGER = logging.getLogger("ha_buddy")
+
+CMD_BINARY_SENSOR_STATE = 0x0308
+CMD_BINARY_SENSOR_DESCRIBE = 0x030A
+
+TAG_UNIQUE_ID = 2
+TAG_NAME = 3
+TAG_DEVICE_CLASS = 5
+TAG_STATE = 7
+
+
+def decode_state(value: bytes) -> None | bool:
+    if len(value) == 0:
+        return None
+
+    return value[0] != 0
+
+
+def decode_device_class(value: bytes) -> None | BinarySensorDeviceClass:
+    """Converts a device class like 'BinarySensorDeviceClass.DOOR' for HomeAssistant"""
+
+    name = value.decode().removeprefix("BinarySensorDeviceClass.")
+    if not name:
+        return None
+
+    try:
+        return BinarySensorDeviceClass(name.lower())
+    except ValueError:
+        LOGGER.warning(f"Unknown binary sensor device class '{name}'")
+        return None
+
+
+class BuddyBinarySensor(BinarySensorEntity):
+    """A HA Buddy binary sensor"""
+
+    def __init__(self, device, binary_sensor_id: int) -> None:
+        self._device = device
+        self._binary_sensor_id = binary_sensor_id
+
+        LOGGER.info(
+            f"Retrieving attributes for binary sensor {hex(self._device.addr())}:{hex(self._binary_sensor_id)}"
+        )
+        # Binary sensors were introduced together with the describe commands
+        attributes = self._device.describe(
+            CMD_BINARY_SENSOR_DESCRIBE, self._binary_sensor_id
+        )
+
+        self._attr_name = attributes.get(TAG_NAME, b"").decode()
+        LOGGER.debug(
+            f"  Name for binary sensor {hex(self._device.addr())}:{hex(self._binary_sensor_id)}: {self._attr_name}"
+        )
+
+        unique_id = attributes.get(TAG_UNIQUE_ID, b"").decode()
+        self._attr_unique_id = (
+            f"binary_sensor.ha_buddy_{hex(self._device._addr)}_{unique_id}"
+        )
+        self.entity_id = self._attr_unique_id
+        LOGGER.info(
+            f"Unique id for binary sensor {hex(self._device.addr())}:{hex(self._binary_sensor_id)}: {self._attr_unique_id}"
+        )
+
+        self._attr_device_class = decode_device_class(
+            attributes.get(TAG_DEVICE_CLASS, b"")
+        )
+
+        # A missing state means the binary sensor failed, the state request reports why
+        if TAG_STATE in attributes:
+            self._attr_is_on = decode_state(attributes[TAG_STATE])
+        else:
+            self._attr_is_on = self.get_state()
+
+    def get_state(self) -> None | bool:
+        return decode_state(
+            self._device.get_device_payload(
+                CMD_BINARY_SENSOR_STATE,
+                self._binary_sensor_id.to_bytes(4, byteorder="little"),
+            )
+        )
+
+    def push_state(self, value) -> None:
+        """Takes over a state the device reported as an event"""
+
+        if isinstance(value, Exception):
+            LOGGER.warning(
+                f"Binary sensor {hex(self._device.addr())}:{hex(self._binary_sensor_id)} failed: {value}"
+            )
+            return
+
+        self._attr_is_on = decode_state(value)
+        if self.hass is not None:
+            self.schedule_update_ha_state()
+
+    @property
+    def device_info(self) -> dr.DeviceInfo:
+        return self._device.device_info()
+
+    def update(self) -> None:
+        self._attr_is_on = self.get_state()
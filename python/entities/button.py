@@ -0,0 +1,49 @@
+import logging
+
+from homeassistant.helpers import device_registry as dr
+from homeassistant.components.button import (
+    ButtonEntity,
+)
+
+LOGGER = logging.getLogger("ha_buddy")
+
+CMD_BUTTON_PRESS = 0x0506
+CMD_BUTTON_DESCRIBE = 0x0508
+
+TAG_UNIQUE_ID = 2
+TAG_NAME = 3
+
+
+class BuddyButton(ButtonEntity):
+    """A HA Buddy button"""
+
+    def __init__(self, device, button_id: int) -> None:
+        self._device = device
+        self._button_id = button_id
+
+        LOGGER.info(
+            f"Retrieving attributes for button {hex(self._device.addr())}:{hex(self._button_id)}"
+        )
+        # Buttons were introduced after the describe commands
+        attributes = self._device.describe(CMD_BUTTON_DESCRIBE, self._button_id)
+
+        self._attr_name = attributes.get(TAG_NAME, b"").decode()
+        LOGGER.debug(
+            f"  Name for button {hex(self._device.addr())}:{hex(self._button_id)}: {self._attr_name}"
+        )
+
+        unique_id = attributes.get(TAG_UNIQUE_ID, b"").decode()
+        self._attr_unique_id = f"button.ha_buddy_{hex(self._device._addr)}_{unique_id}"
+        self.entity_id = self._attr_unique_id
+        LOGGER.info(
+            f"Unique id for button {hex(self._device.addr())}:{hex(self._button_id)}: {self._attr_unique_id}"
+        )
+
+    def press(self) -> None:
+        self._device.get_device_payload(
+            CMD_BUTTON_PRESS, self._button_id.to_bytes(4, byteorder="little")
+        )
+
+    @property
+    def device_info(self) -> dr.DeviceInfo:
+        return self._device.device_info()
@@ -0,0 +1,131 @@
+import logging
+
+from homeassistant.helpers import device_registry as dr
+from homeassistant.components.cover import (
+    ATTR_POSITION,
+    CoverDeviceClass,
+    CoverEntity,
+    CoverEntityFeature,
+)
+
+LOGGER = logging.getLogger("ha_buddy")
+
+CMD_COVER_STATE = 0x0708
+CMD_COVER_EXEC = 0x070A
+CMD_COVER_POSITION = 0x070C
+CMD_COVER_DESCRIBE = 0x070E
+
+TAG_UNIQUE_ID = 2
+TAG_NAME = 3
+TAG_DEVICE_CLASS = 5
+TAG_STATE = 7
+TAG_POSITION = 13
+
+CMD_COVER_EXEC_CLOSE = 0
+CMD_COVER_EXEC_OPEN = 1
+CMD_COVER_EXEC_STOP = 2
+
+COVER_MOTION_OPENING = 1
+COVER_MOTION_CLOSING = 2
+
+
+def decode_device_class(value: bytes) -> None | CoverDeviceClass:
+    """Converts a device class like 'CoverDeviceClass.SHUTTER' for HomeAssistant"""
+
+    name = value.decode().removeprefix("CoverDeviceClass.")
+    if not name:
+        return None
+
+    try:
+        return CoverDeviceClass(name.lower())
+    except ValueError:
+        LOGGER.warning(f"Unknown cover device class '{name}'")
+        return None
+
+
+class BuddyCover(CoverEntity):
+    """A HA Buddy cover with a position"""
+
+    _attr_supported_features = (
+        CoverEntityFeature.OPEN
+        | CoverEntityFeature.CLOSE
+        | CoverEntityFeature.STOP
+        | CoverEntityFeature.SET_POSITION
+    )
+
+    def __init__(self, device, cover_id: int) -> None:
+        self._device = device
+        self._cover_id = cover_id
+
+        LOGGER.info(
+            f"Retrieving attributes for cover {hex(self._device.addr())}:{hex(self._cover_id)}"
+        )
+        # Covers were introduced together with the describe commands
+        attributes = self._device.describe(CMD_COVER_DESCRIBE, self._cover_id)
+
+        self._attr_name = attributes.get(TAG_NAME, b"").decode()
+        LOGGER.debug(
+            f"  Name for cover {hex(self._device.addr())}:{hex(self._cover_id)}: {self._attr_name}"
+        )
+
+        unique_id = attributes.get(TAG_UNIQUE_ID, b"").decode()
+        self._attr_unique_id = f"cover.ha_buddy_{hex(self._device._addr)}_{unique_id}"
+        self.entity_id = self._attr_unique_id
+        LOGGER.info(
+            f"Unique id for cover {hex(self._device.addr())}:{hex(self._cover_id)}: {self._attr_unique_id}"
+        )
+
+        self._attr_device_class = decode_device_class(attributes.get(TAG_DEVICE_CLASS, b""))
+
+        # A missing state means the cover failed, the state request reports why
+        if TAG_POSITION in attributes and TAG_STATE in attributes:
+            self.set_state(attributes[TAG_POSITION] + attributes[TAG_STATE])
+        else:
+            self.update()
+
+    def set_state(self, value: bytes) -> None:
+        self._attr_current_cover_position = value[0]
+        self._attr_is_closed = value[0] == 0
+        self._attr_is_opening = value[1] == COVER_MOTION_OPENING
+        self._attr_is_closing = value[1] == COVER_MOTION_CLOSING
+
+    def request(self, cmd: int, arg: int) -> None:
+        payload = bytearray(self._cover_id.to_bytes(4, byteorder="little")) + bytes([arg])
+
+        self.set_state(self._device.get_device_payload(cmd, payload))
+
+    def open_cover(self, **kwargs):
+        self.request(CMD_COVER_EXEC, CMD_COVER_EXEC_OPEN)
+
+    def close_cover(self, **kwargs):
+        self.request(CMD_COVER_EXEC, CMD_COVER_EXEC_CLOSE)
+
+    def stop_cover(self, **kwargs):
+        self.request(CMD_COVER_EXEC, CMD_COVER_EXEC_STOP)
+
+    def set_cover_position(self, **kwargs):
+        self.request(CMD_COVER_POSITION, kwargs[ATTR_POSITION])
+
+    def push_state(self, value) -> None:
+        """Takes over a state the device reported as an event"""
+
+        if isinstance(value, Exception):
+            LOGGER.warning(
+                f"Cover {hex(self._device.addr())}:{hex(self._cover_id)} failed: {value}"
+            )
+            return
+
+        self.set_state(value)
+        if self.hass is not None:
+            self.schedule_update_ha_state()
+
+    @property
+    def device_info(self) -> dr.DeviceInfo:
+        return self._device.device_info()
+
+    def update(self) -> None:
+        self.set_state(
+            self._device.get_device_payload(
+                CMD_COVER_STATE, self._cover_id.to_bytes(4, byteorder="little")
+            )
+        )
@@ -0,0 +1,107 @@
+import logging
+
+from homeassistant.helpers import device_registry as dr
+from homeassistant.components.light import (
+    ATTR_BRIGHTNESS,
+    ATTR_TRANSITION,
+    ColorMode,
+    LightEntity,
+    LightEntityFeature,
+)
+
+LOGGER = logging.getLogger("ha_buddy")
+
+CMD_LIGHT_STATE = 0x0606
+CMD_LIGHT_EXEC = 0x0608
+CMD_LIGHT_BRIGHTNESS = 0x060A
+CMD_LIGHT_DESCRIBE = 0x060C
+
+TAG_UNIQUE_ID = 2
+TAG_NAME = 3
+TAG_STATE = 7
+TAG_BRIGHTNESS = 12
+
+CMD_LIGHT_EXEC_TURN_OFF = 0
+CMD_LIGHT_EXEC_TURN_ON = 1
+
+
+class BuddyLight(LightEntity):
+    """A HA Buddy dimmable light"""
+
+    _attr_color_mode = ColorMode.BRIGHTNESS
+    _attr_supported_color_modes = {ColorMode.BRIGHTNESS}
+    _attr_supported_features = LightEntityFeature.TRANSITION
+
+    def __init__(self, device, light_id: int) -> None:
+        self._device = device
+        self._light_id = light_id
+
+        LOGGER.info(
+            f"Retrieving attributes for light {hex(self._device.addr())}:{hex(self._light_id)}"
+        )
+        # Lights were introduced together with the describe commands
+        attributes = self._device.describe(CMD_LIGHT_DESCRIBE, self._light_id)
+
+        self._attr_name = attributes.get(TAG_NAME, b"").decode()
+        LOGGER.debug(
+            f"  Name for light {hex(self._device.addr())}:{hex(self._light_id)}: {self._attr_name}"
+        )
+
+        unique_id = attributes.get(TAG_UNIQUE_ID, b"").decode()
+        self._attr_unique_id = f"light.ha_buddy_{hex(self._device._addr)}_{unique_id}"
+        self.entity_id = self._attr_unique_id
+        LOGGER.info(
+            f"Unique id for light {hex(self._device.addr())}:{hex(self._light_id)}: {self._attr_unique_id}"
+        )
+
+        # A missing state means the light failed, the state request reports why
+        if TAG_STATE in attributes and TAG_BRIGHTNESS in attributes:
+            self.set_state(attributes[TAG_STATE] + attributes[TAG_BRIGHTNESS])
+        else:
+            self.update()
+
+    def set_state(self, value: bytes) -> None:
+        self._attr_is_on = value[0] != 0
+        self._attr_brightness = value[1]
+
+    def request(self, cmd: int, arg: int, **kwargs) -> None:
+        payload = bytearray(self._light_id.to_bytes(4, byteorder="little")) + bytes([arg])
+        # The transition is given in seconds, the device expects milliseconds
+        transition = kwargs.get(ATTR_TRANSITION)
+        if transition:
+            payload += min(int(transition * 1000), 0xFFFF).to_bytes(2, byteorder="little")
+
+        self.set_state(self._device.get_device_payload(cmd, payload))
+
+    def turn_on(self, **kwargs):
+        if ATTR_BRIGHTNESS in kwargs:
+            self.request(CMD_LIGHT_BRIGHTNESS, kwargs[ATTR_BRIGHTNESS], **kwargs)
+        else:
+            self.request(CMD_LIGHT_EXEC, CMD_LIGHT_EXEC_TURN_ON, **kwargs)
+
+    def turn_off(self, **kwargs):
+        self.request(CMD_LIGHT_EXEC, CMD_LIGHT_EXEC_TURN_OFF, **kwargs)
+
+    def push_state(self, value) -> None:
+        """Takes over a state the device reported as an event"""
+
+        if isinstance(value, Exception):
+            LOGGER.warning(
+                f"Light {hex(self._device.addr())}:{hex(self._light_id)} failed: {value}"
+            )
+            return
+
+        self.set_state(value)
+        if self.hass is not None:
+            self.schedule_update_ha_state()
+
+    @property
+    def device_info(self) -> dr.DeviceInfo:
+        return self._device.device_info()
+
+    def update(self) -> None:
+        self.set_state(
+            self._device.get_device_payload(
+                CMD_LIGHT_STATE, self._light_id.to_bytes(4, byteorder="little")
+            )
+        )
@@ -0,0 +1,106 @@
+import struct
+import logging
+
+from homeassistant.helpers import device_registry as dr
+from homeassistant.components.number import (
+    NumberEntity,
+    NumberMode,
+)
+
+from .sensor import decode_value
+
+LOGGER = logging.getLogger("ha_buddy")
+
+CMD_NUMBER_VALUE = 0x040A
+CMD_NUMBER_SET = 0x040C
+CMD_NUMBER_DESCRIBE = 0x040E
+
+TAG_VALUE = 1
+TAG_UNIQUE_ID = 2
+TAG_NAME = 3
+TAG_NATIVE_UNIT_OF_MEASUREMENT = 4
+TAG_DEVICE_CLASS = 5
+TAG_MIN = 8
+TAG_MAX = 9
+TAG_STEP = 10
+TAG_MODE = 11
+
+
+class BuddyNumber(NumberEntity):
+    """A HA Buddy number"""
+
+    def __init__(self, device, number_id: int) -> None:
+        self._device = device
+        self._number_id = number_id
+
+        LOGGER.info(
+            f"Retrieving attributes for number {hex(self._device.addr())}:{hex(self._number_id)}"
+        )
+        # Numbers were introduced together with the describe commands
+        attributes = self._device.describe(CMD_NUMBER_DESCRIBE, self._number_id)
+
+        self._attr_name = attributes.get(TAG_NAME, b"").decode()
+        LOGGER.debug(
+            f"  Name for number {hex(self._device.addr())}:{hex(self._number_id)}: {self._attr_name}"
+        )
+
+        unique_id = attributes.get(TAG_UNIQUE_ID, b"").decode()
+        self._attr_unique_id = f"number.ha_buddy_{hex(self._device._addr)}_{unique_id}"
+        self.entity_id = self._attr_unique_id
+        LOGGER.info(
+            f"Unique id for number {hex(self._device.addr())}:{hex(self._number_id)}: {self._attr_unique_id}"
+        )
+
+        self._attr_native_unit_of_measurement = attributes.get(
+            TAG_NATIVE_UNIT_OF_MEASUREMENT, b""
+        ).decode()
+        self._attr_device_class = attributes.get(TAG_DEVICE_CLASS, b"").decode()
+        self._attr_native_min_value = struct.unpack("f", attributes[TAG_MIN])[0]
+        self._attr_native_max_value = struct.unpack("f", attributes[TAG_MAX])[0]
+        self._attr_native_step = struct.unpack("f", attributes[TAG_STEP])[0]
+        self._attr_mode = NumberMode(attributes.get(TAG_MODE, b"auto").decode())
+        LOGGER.debug(
+            f"  Range for number {hex(self._device.addr())}:{hex(self._number_id)}: {self._attr_native_min_value} ..= {self._attr_native_max_value}, step {self._attr_native_step}"
+        )
+
+        # A missing value means the number failed, the value request reports why
+        if TAG_VALUE in attributes:
+            self._attr_native_value = decode_value(attributes[TAG_VALUE])
+        else:
+            self._attr_native_value = self.get_value()
+
+    def get_value(self) -> None | float:
+        return decode_value(
+            self._device.get_device_payload(
+                CMD_NUMBER_VALUE, self._number_id.to_bytes(4, byteorder="little")
+            )
+        )
+
+    def set_native_value(self, value: float) -> None:
+        payload = bytearray(self._number_id.to_bytes(4, byteorder="little")) + struct.pack(
+            "<f", value
+        )
+
+        self._attr_native_value = decode_value(
+            self._device.get_device_payload(CMD_NUMBER_SET, payload)
+        )
+
+    def push_value(self, value) -> None:
+        """Takes over a value the device reported as an event"""
+
+        if isinstance(value, Exception):
+            LOGGER.warning(
+                f"Number {hex(self._device.addr())}:{hex(self._number_id)} failed: {value}"
+            )
+            return
+
+        self._attr_native_value = decode_value(value)
+        if self.hass is not None:
+            self.schedule_update_ha_state()
+
+    @property
+    def device_info(self) -> dr.DeviceInfo:
+        return self._device.device_info()
+
+    def update(self) -> None:
+        self._attr_native_value = self.get_value()
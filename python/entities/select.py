@@ -0,0 +1,100 @@
+import logging
+
+from homeassistant.helpers import device_registry as dr
+from homeassistant.components.select import SelectEntity
+
+LOGGER = logging.getLogger("ha_buddy")
+
+CMD_SELECT_OPTION = 0x0806
+CMD_SELECT_STATE = 0x0808
+CMD_SELECT_SET = 0x080A
+CMD_SELECT_DESCRIBE = 0x080C
+
+TAG_UNIQUE_ID = 2
+TAG_NAME = 3
+TAG_STATE = 7
+TAG_OPTION = 14
+TAG_OPTION_COUNT = 15
+
+
+class BuddySelect(SelectEntity):
+    """A HA Buddy select with a static list of options"""
+
+    def __init__(self, device, select_id: int) -> None:
+        self._device = device
+        self._select_id = select_id
+
+        LOGGER.info(
+            f"Retrieving attributes for select {hex(self._device.addr())}:{hex(self._select_id)}"
+        )
+        # Selects were introduced together with the describe commands
+        attributes = self._device.describe(
+            CMD_SELECT_DESCRIBE, self._select_id, repeated=(TAG_OPTION,)
+        )
+
+        self._attr_name = attributes.get(TAG_NAME, b"").decode()
+        LOGGER.debug(
+            f"  Name for select {hex(self._device.addr())}:{hex(self._select_id)}: {self._attr_name}"
+        )
+
+        unique_id = attributes.get(TAG_UNIQUE_ID, b"").decode()
+        self._attr_unique_id = f"select.ha_buddy_{hex(self._device._addr)}_{unique_id}"
+        self.entity_id = self._attr_unique_id
+        LOGGER.info(
+            f"Unique id for select {hex(self._device.addr())}:{hex(self._select_id)}: {self._attr_unique_id}"
+        )
+
+        # Options that did not fit into the describe response are fetched one by one
+        count = attributes[TAG_OPTION_COUNT][0]
+        options = [option.decode() for option in attributes.get(TAG_OPTION, [])[:count]]
+        for index in range(len(options), count):
+            options.append(self.request(CMD_SELECT_OPTION, index).decode())
+        self._attr_options = options
+        LOGGER.debug(
+            f"  Options for select {hex(self._device.addr())}:{hex(self._select_id)}: {self._attr_options}"
+        )
+
+        # A missing state means the select failed, the state request reports why
+        if TAG_STATE in attributes:
+            self.set_state(attributes[TAG_STATE])
+        else:
+            self.update()
+
+    def set_state(self, value: bytes) -> None:
+        index = value[0]
+        if index < len(self._attr_options):
+            self._attr_current_option = self._attr_options[index]
+        else:
+            self._attr_current_option = None
+
+    def request(self, cmd: int, arg: int) -> bytes:
+        payload = bytearray(self._select_id.to_bytes(4, byteorder="little")) + bytes([arg])
+
+        return self._device.get_device_payload(cmd, payload)
+
+    def select_option(self, option: str) -> None:
+        self.set_state(self.request(CMD_SELECT_SET, self._attr_options.index(option)))
+
+    def push_state(self, value) -> None:
+        """Takes over a state the device reported as an event"""
+
+        if isinstance(value, Exception):
+            LOGGER.warning(
+                f"Select {hex(self._device.addr())}:{hex(self._select_id)} failed: {value}"
+            )
+            return
+
+        self.set_state(value)
+        if self.hass is not None:
+            self.schedule_update_ha_state()
+
+    @property
+    def device_info(self) -> dr.DeviceInfo:
+        return self._device.device_info()
+
+    def update(self) -> None:
+        self.set_state(
+            self._device.get_device_payload(
+                CMD_SELECT_STATE, self._select_id.to_bytes(4, byteorder="little")
+            )
+        )
@@ -1,5 +1,6 @@
 import struct
 import logging
+from decimal import Decimal
 
 from homeassistant.helpers import device_registry as dr
 from homeassistant.components.sensor import (
@@ -14,6 +15,40 @@ CMD_SENSOR_DEVICE_CLASS = 0x0106
 CMD_SENSOR_STATE_CLASS = 0x0108
 CMD_SENSOR_NAME = 0x0110
 CMD_SENSOR_VALUE = 0x0112
+CMD_SENSOR_DESCRIBE = 0x0114
+
+TAG_VALUE = 1
+TAG_UNIQUE_ID = 2
+TAG_NAME = 3
+TAG_NATIVE_UNIT_OF_MEASUREMENT = 4
+TAG_DEVICE_CLASS = 5
+TAG_STATE_CLASS = 6
+
+
+def decode_value(value: bytes) -> None | bool | int | str | float | Decimal:
+    if len(value) == 0:
+        value = None
+    elif value[0] == 2:
+        value = struct.unpack("<f", value[1:5])[0]
+    elif value[0] == 1:
+        value = int.from_bytes(value[1:5], byteorder="little", signed=True)
+    elif value[0] == 0:
+        value = value[1:].decode()
+    elif value[0] == 3:
+        value = int.from_bytes(value[1:5], byteorder="little")
+    elif value[0] == 4:
+        value = int.from_bytes(value[1:9], byteorder="little", signed=True)
+    elif value[0] == 5:
+        value = int.from_bytes(value[1:9], byteorder="little")
+    elif value[0] == 6:
+        value = value[1] != 0
+    elif value[0] == 7:
+        # Decimal keeps the precision of fixed-point values
+        mantissa = int.from_bytes(value[1:5], byteorder="little", signed=True)
+        exponent = int.from_bytes(value[5:6], byteorder="little", signed=True)
+        value = Decimal(mantissa).scaleb(exponent)
+
+    return value
 
 
 class BuddySensor(SensorEntity):
@@ -28,62 +63,78 @@ class BuddySensor(SensorEntity):
         LOGGER.info(
             f"Retrieving attributes for sensor {hex(self._device.addr())}:{hex(self._sensor_id)}"
         )
-        self._attr_name = self._device.get_device_payload(
-            CMD_SENSOR_NAME, s_id
-        ).decode()
+        attributes = self._device.describe(CMD_SENSOR_DESCRIBE, self._sensor_id)
+        if attributes is None:
+            attributes = {
+                TAG_NAME: self._device.get_device_payload(CMD_SENSOR_NAME, s_id),
+                TAG_UNIQUE_ID: self._device.get_device_payload(
+                    CMD_SENSOR_UNIQUE_ID, s_id
+                ),
+                TAG_NATIVE_UNIT_OF_MEASUREMENT: self._device.get_device_payload(
+                    CMD_SENSOR_NATIVE_UNIT_OF_MEASUREMENT, s_id
+                ),
+                TAG_DEVICE_CLASS: self._device.get_device_payload(
+                    CMD_SENSOR_DEVICE_CLASS, s_id
+                ),
+                TAG_STATE_CLASS: self._device.get_device_payload(
+                    CMD_SENSOR_STATE_CLASS, s_id
+                ),
+            }
+
+        self._attr_name = attributes.get(TAG_NAME, b"").decode()
         LOGGER.debug(
             f"  Name for sensor {hex(self._device.addr())}:{hex(self._sensor_id)}: {self._attr_name}"
         )
 
-        unique_id = self._device.get_device_payload(CMD_SENSOR_UNIQUE_ID, s_id).decode()
-        # unique_id = self._device.get_device_frame(0x0102, s_id).payload.decode()
+        unique_id = attributes.get(TAG_UNIQUE_ID, b"").decode()
         self._attr_unique_id = f"sensor.ha_buddy_{hex(self._device._addr)}_{unique_id}"
         self.entity_id = self._attr_unique_id
         LOGGER.info(
             f"Unique id for sensor {hex(self._device.addr())}:{hex(self._sensor_id)}: {self._attr_unique_id}"
         )
 
-        self._attr_native_unit_of_measurement = self._device.get_device_payload(
-            CMD_SENSOR_NATIVE_UNIT_OF_MEASUREMENT, s_id
+        self._attr_native_unit_of_measurement = attributes.get(
+            TAG_NATIVE_UNIT_OF_MEASUREMENT, b""
         ).decode()
         LOGGER.debug(
             f"  Native unit of measurement for sensor {hex(self._device.addr())}:{hex(self._sensor_id)}: {self._attr_native_unit_of_measurement}"
         )
 
-        self._attr_device_class = self._device.get_device_payload(
-            CMD_SENSOR_DEVICE_CLASS, s_id
-        ).decode()
+        # Text sensors have neither a device class nor a state class
+        self._attr_device_class = attributes.get(TAG_DEVICE_CLASS, b"").decode() or None
         LOGGER.debug(
             f"  Device class for sensor {hex(self._device.addr())}:{hex(self._sensor_id)}: {self._attr_device_class}"
         )
 
-        self._attr_state_class = self._device.get_device_payload(
-            CMD_SENSOR_STATE_CLASS, s_id
-        ).decode()
+        self._attr_state_class = attributes.get(TAG_STATE_CLASS, b"").decode() or None
         LOGGER.debug(
             f"  State class for sensor {hex(self._device.addr())}:{hex(self._sensor_id)}: {self._attr_state_class}"
         )
 
-        self._attr_native_value = self.get_value()
+        # A missing value means the sensor failed, the value request reports why
+        if TAG_VALUE in attributes:
+            self._attr_native_value = decode_value(attributes[TAG_VALUE])
+        else:
+            self._attr_native_value = self.get_value()
         LOGGER.debug(
             f"  Value for sensor {hex(self._device.addr())}:{hex(self._sensor_id)}: {self._attr_native_value}"
         )
 
-    def get_value(self) -> None | int | str | float:
-        value = self._device.get_device_payload(
-            CMD_SENSOR_VALUE, self._sensor_id.to_bytes(4, byteorder="little")
-        )
+    def get_value(self) -> None | bool | int | str | float | Decimal:
+        return decode_value(self._device.get_sensor_value(self._sensor_id))
+
+    def push_value(self, value) -> None:
+        """Takes over a value the device reported as an event"""
 
-        if len(value) == 0:
-            value = None
-        elif value[0] == 2:
-            value = struct.unpack("f", value[1:5])[0]
-        elif value[0] == 1:
-            value = int.from_bytes(value[1:5], byteorder="little")
-        elif value[0] == 0:
-            value = value[1:].decode()
+        if isinstance(value, Exception):
+            LOGGER.warning(
+                f"Sensor {hex(self._device.addr())}:{hex(self._sensor_id)} failed: {value}"
+            )
+            return
 
-        return value
+        self._attr_native_value = decode_value(value)
+        if self.hass is not None:
+            self.schedule_update_ha_state()
 
     @property
     def device_info(self) -> dr.DeviceInfo:
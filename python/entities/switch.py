@@ -12,6 +12,11 @@ CMD_SWITCH_UNIQUE_ID = 0x0202
 CMD_SWITCH_NAME = 0x0204
 CMD_SWITCH_STATE = 0x0206
 CMD_SWITCH_EXEC = 0x0208
+CMD_SWITCH_DESCRIBE = 0x020A
+
+TAG_UNIQUE_ID = 2
+TAG_NAME = 3
+TAG_STATE = 7
 
 CMD_SWITCH_EXEC_TURN_OFF = 0
 CMD_SWITCH_EXEC_TURN_ON = 1
@@ -31,20 +36,30 @@ class BuddySwitch(SwitchEntity):
         LOGGER.info(
             f"Retrieving attributes for switch {hex(self._device.addr())}:{hex(self._switch_id)}"
         )
-        self._attr_name = self._device.get_device_payload(
-            CMD_SWITCH_NAME, s_id
-        ).decode()
+        attributes = self._device.describe(CMD_SWITCH_DESCRIBE, self._switch_id)
+        if attributes is None:
+            attributes = {
+                TAG_NAME: self._device.get_device_payload(CMD_SWITCH_NAME, s_id),
+                TAG_UNIQUE_ID: self._device.get_device_payload(
+                    CMD_SWITCH_UNIQUE_ID, s_id
+                ),
+            }
+
+        self._attr_name = attributes.get(TAG_NAME, b"").decode()
         LOGGER.debug(
             f"  Name for switch {hex(self._device.addr())}:{hex(self._switch_id)}: {self._attr_name}"
         )
 
-        unique_id = self._device.get_device_payload(CMD_SWITCH_UNIQUE_ID, s_id).decode()
+        unique_id = attributes.get(TAG_UNIQUE_ID, b"").decode()
         self._attr_unique_id = f"switch.ha_buddy_{hex(self._device._addr)}_{unique_id}"
         self.entity_id = self._attr_unique_id
         LOGGER.info(
             f"Unique id for switch {hex(self._device.addr())}:{hex(self._switch_id)}: {self._attr_unique_id}"
         )
 
+        if TAG_STATE in attributes:
+            self._is_on = attributes[TAG_STATE][0] != 0
+
     def turn_on(self, **kwargs):
         payload = bytearray(self._switch_id.to_bytes(4, byteorder="little")) + bytes(
             [CMD_SWITCH_EXEC_TURN_ON]
@@ -63,6 +78,19 @@ class BuddySwitch(SwitchEntity):
 
         self._is_on = value[0] != 0
 
+    def push_state(self, value) -> None:
+        """Takes over a state the device reported as an event"""
+
+        if isinstance(value, Exception):
+            LOGGER.warning(
+                f"Switch {hex(self._device.addr())}:{hex(self._switch_id)} failed: {value}"
+            )
+            return
+
+        self._is_on = value[0] != 0
+        if self.hass is not None:
+            self.schedule_update_ha_state()
+
     @property
     def device_info(self) -> dr.DeviceInfo:
         return self._device.device_info()
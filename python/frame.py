@@ -2,6 +2,7 @@
 
 import serial
 import logging
+import time
 
 LOGGER = logging.getLogger("ha_buddy")
 
@@ -10,6 +11,22 @@ from crc import Calculator, Crc8
 START_BYTE_0 = 0xAA
 START_BYTE_1 = 0x55
 
+CMD_ERROR_FLAG = 0x8000
+
+BROADCAST_ADDR = 0xFFFF
+CMD_NODE_DISCOVER = 0x0014
+SERIAL_LEN = 8
+
+ERROR_CODES = {
+    1: "unknown command",
+    2: "bad payload length",
+    3: "entity index out of range",
+    4: "entity busy",
+    5: "hardware fault",
+    6: "invalid argument",
+    7: "not permitted",
+}
+
 
 class Frame:
     """A frame in the datalink layer"""
@@ -57,6 +74,7 @@ class ExpectedBytesCountError(Exception):
     def __init__(self, expected: int, got: int, reason: str) -> None:
         self.expected = expected
         self.got = got
+        self.reason = reason
         self.message = f"Expected {self.expected} bytes {reason}, got {self.got}"
         super().__init__(self.message)
 
@@ -96,6 +114,19 @@ class FrameCRCError(Exception):
         super().__init__(self.message)
 
 
+class NodeError(Exception):
+    """
+    An error that gets raised if the node responded with an error frame
+    """
+
+    def __init__(self, cmd: int, code: int) -> None:
+        self.cmd = cmd
+        self.code = code
+        reason = ERROR_CODES.get(code, f"unknown error {code}")
+        self.message = f"Node failed command {hex(self.cmd)}: {reason}"
+        super().__init__(self.message)
+
+
 def rec_bytes(count: int, ser: serial.Serial, reason: str) -> bytes:
     """Receive n bytes from the serial instance"""
 
@@ -162,7 +193,8 @@ def frame_decode(ser: serial.Serial) -> Frame:
 def exec_command(ser: serial.Serial, out_frame: Frame) -> bytes:
     """
     Tries to execute a command and return the payload.
-    If the client does not return the right command, it gets thrown away
+    If the client does not return the right command, it gets thrown away.
+    Raises a NodeError if the client responds with an error
     """
 
     ser.write(out_frame.to_bytes())
@@ -173,6 +205,76 @@ def exec_command(ser: serial.Serial, out_frame: Frame) -> bytes:
         if in_frame.cmd == out_frame.cmd + 1:
             return in_frame.payload
 
+        if in_frame.cmd == (out_frame.cmd + 1) | CMD_ERROR_FLAG:
+            code = in_frame.payload[0] if len(in_frame.payload) > 0 else 0
+            raise NodeError(out_frame.cmd, code)
+
         LOGGER.error(
             f"Invalid response command: Expected {hex(out_frame.cmd+1)}, got {hex(in_frame.cmd)}"
         )
+
+
+def probe(ser: serial.Serial, prefix: int, bits: int):
+    """
+    Sends a discovery request for a serial number prefix to all nodes and listens
+    until the timeout of `ser`.
+    Returns the address and serial number of a single responding node,
+    True if many nodes responded at once or None if no node responded
+    """
+
+    payload = bytes([bits]) + prefix.to_bytes(SERIAL_LEN, byteorder="big")
+    ser.reset_input_buffer()
+    ser.write(Frame(0x0000, BROADCAST_ADDR, CMD_NODE_DISCOVER, payload).to_bytes())
+    deadline = time.monotonic() + ser.timeout
+
+    try:
+        in_frame = frame_decode(ser)
+    except ExpectedBytesCountError as e:
+        return True if e.got > 0 or e.reason != "for start bytes" else None
+    except (StartBytesError, HeaderCRCError, FrameCRCError):
+        return True
+
+    if in_frame.cmd != CMD_NODE_DISCOVER + 1 or len(in_frame.payload) != SERIAL_LEN:
+        return True
+
+    serial_number = int.from_bytes(in_frame.payload, byteorder="big")
+    if (serial_number ^ prefix) >> (SERIAL_LEN * 8 - bits) != 0:
+        return True
+
+    # A node sends nothing after its response, more bytes are another node answering
+    timeout = ser.timeout
+    try:
+        ser.timeout = max(deadline - time.monotonic(), 0)
+        trailing = ser.read(1)
+    finally:
+        ser.timeout = timeout
+
+    if len(trailing) > 0:
+        return True
+
+    return (in_frame.src, serial_number)
+
+
+def discover(ser: serial.Serial, prefix: int = 0, bits: int = 0) -> list:
+    """
+    Discovers all nodes with a serial number starting with the first `bits` bits
+    of `prefix` by a binary search, colliding responses split the prefix in halves.
+    Returns a list of (address, serial number) tuples
+    """
+
+    serial_bits = SERIAL_LEN * 8
+    res = probe(ser, prefix, bits)
+
+    if res is None:
+        return []
+
+    # Colliding responses can form a valid frame by chance, let the node confirm it
+    if res is not True and (bits == serial_bits or probe(ser, res[1], serial_bits) == res):
+        return [res]
+
+    if bits == serial_bits:
+        LOGGER.warning(f"Many nodes respond to serial number {hex(prefix)}")
+        return []
+
+    bit = 1 << (serial_bits - 1 - bits)
+    return discover(ser, prefix & ~bit, bits + 1) + discover(ser, prefix | bit, bits + 1)
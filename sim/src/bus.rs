@@ -0,0 +1,141 @@
+use std::{
+    collections::VecDeque,
+    io::{self, ErrorKind, Read, Write},
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::Duration,
+};
+
+use crate::VirtualNode;
+
+/// A simulated bus with many nodes attached to it
+///
+/// Writing to the bus is what the master sends, reading from
+/// it returns the responses of the nodes.
+#[derive(Default)]
+pub struct Bus<'a> {
+    /// The nodes attached to this bus
+    pub nodes: Vec<VirtualNode<'a>>,
+    /// The bytes the nodes sent that have not been read yet
+    rx: VecDeque<u8>,
+}
+
+impl<'a> Bus<'a> {
+    /// Creates a new bus without any nodes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attaches a node to this bus
+    /// # Arguments
+    /// * `node` - The node to attach
+    pub fn add_node(&mut self, node: VirtualNode<'a>) {
+        self.nodes.push(node);
+    }
+
+    /// Returns the node with the supplied address
+    /// # Arguments
+    /// * `addr` - The address to look for
+    pub fn node_mut(&mut self, addr: u16) -> Option<&mut VirtualNode<'a>> {
+        self.nodes.iter_mut().find(|n| n.addr == addr)
+    }
+
+    /// Puts bytes from the master on the bus
+    ///
+    /// If many nodes respond to the same frame, their responses collide
+    /// like on a real line: the overlapping bytes are combined with a bitwise AND,
+    /// a driven `0` bit wins over an idle `1` bit.
+    /// # Arguments
+    /// * `bytes` - The bytes the master sent
+    /// # Returns
+    /// The bytes the nodes sent in response
+    pub fn process(&mut self, bytes: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+
+        for byte in bytes {
+            let mut line: Vec<u8> = Vec::new();
+
+            for node in &mut self.nodes {
+                let mut response = Vec::new();
+                if !node.handle_byte(*byte, &mut response) {
+                    continue;
+                }
+
+                for (i, byte) in response.into_iter().enumerate() {
+                    match line.get_mut(i) {
+                        Some(b) => *b &= byte,
+                        None => line.push(byte),
+                    }
+                }
+            }
+
+            out.extend(line);
+        }
+
+        out
+    }
+}
+
+impl Read for Bus<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.rx.is_empty() {
+            return Err(ErrorKind::TimedOut.into());
+        }
+
+        let len = buf.len().min(self.rx.len());
+        for (dst, src) in buf.iter_mut().zip(self.rx.drain(0..len)) {
+            *dst = src;
+        }
+
+        Ok(len)
+    }
+}
+
+impl Write for Bus<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let out = self.process(buf);
+        self.rx.extend(out);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Connects a bus to a port, e.g. the master side of a pseudo terminal,
+/// until `stop` is set
+/// # Arguments
+/// * `bus` - The bus to serve
+/// * `port` - The port the master is connected to, should have a read timeout
+/// * `stop` - Stops serving once set
+pub fn serve<P: Read + Write>(bus: &mut Bus, port: &mut P, stop: &AtomicBool) -> io::Result<()> {
+    let mut buf = [0u8; 256];
+
+    while !stop.load(Ordering::Relaxed) {
+        let len = match port.read(&mut buf) {
+            Ok(0) => {
+                thread::sleep(Duration::from_millis(1));
+                continue;
+            }
+            Ok(len) => len,
+            Err(e) => match e.kind() {
+                ErrorKind::TimedOut | ErrorKind::WouldBlock | ErrorKind::Interrupted => continue,
+                // The master side reports an error while no one has the terminal open
+                _ => {
+                    thread::sleep(Duration::from_millis(10));
+                    continue;
+                }
+            },
+        };
+
+        let out = bus.process(&buf[0..len]);
+        if !out.is_empty() {
+            port.write_all(&out)?;
+            port.flush()?;
+        }
+    }
+
+    Ok(())
+}
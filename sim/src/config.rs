@@ -0,0 +1,629 @@
+use ha_buddy_proto::{
+    datalink::{is_multicast, Serial, FACTORY_ADDR, SERIAL_LEN},
+    homeassistant::{
+        binary_sensor::{BinarySensor, BinarySensorDeviceClass},
+        button::Button,
+        cover::{Cover, CoverDeviceClass, CoverRequest, CoverState, COVER_POSITION_OPEN},
+        entity::DeviceClass,
+        light::{Light, LightRequest, LightState},
+        number::{Number, NumberMode, NumberRequest},
+        select::{Select, SelectRequest},
+        sensor::{Sensor, StateClass},
+        switch::{Switch, SwitchRequest},
+    },
+};
+use serde::{Deserialize, Deserializer};
+
+use crate::{Bus, VirtualNode};
+
+/// The configuration of a simulated bus
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    /// The nodes on the bus
+    pub nodes: Vec<NodeConfig>,
+}
+
+/// The configuration of a simulated node
+#[derive(Debug, Deserialize)]
+pub struct NodeConfig {
+    /// The address of the node, either a number or a string like `"0x1000"`
+    #[serde(deserialize_with = "deserialize_addr")]
+    pub addr: u16,
+    /// The multicast groups of the node, given like `addr`
+    #[serde(default, deserialize_with = "deserialize_groups")]
+    pub groups: Vec<u16>,
+    /// The serial number of the node as 16 hex digits, e.g. `"0011223344556677"`
+    #[serde(default, deserialize_with = "deserialize_serial")]
+    pub serial: Option<Serial>,
+    /// If set, the node accepts a new address without a serial number
+    #[serde(default)]
+    pub commissioning: bool,
+    /// The name reported in the device information
+    #[serde(default)]
+    pub name: Option<String>,
+    /// The sensors of the node
+    #[serde(default)]
+    pub sensors: Vec<SensorConfig>,
+    /// The switches of the node
+    #[serde(default)]
+    pub switches: Vec<SwitchConfig>,
+    /// The binary sensors of the node
+    #[serde(default)]
+    pub binary_sensors: Vec<BinarySensorConfig>,
+    /// The numbers of the node
+    #[serde(default)]
+    pub numbers: Vec<NumberConfig>,
+    /// The buttons of the node
+    #[serde(default)]
+    pub buttons: Vec<ButtonConfig>,
+    /// The lights of the node
+    #[serde(default)]
+    pub lights: Vec<LightConfig>,
+    /// The covers of the node
+    #[serde(default)]
+    pub covers: Vec<CoverConfig>,
+    /// The selects of the node
+    #[serde(default)]
+    pub selects: Vec<SelectConfig>,
+}
+
+/// The configuration of a simulated sensor
+#[derive(Debug, Deserialize)]
+pub struct SensorConfig {
+    pub name: String,
+    pub unique_id: String,
+    #[serde(default)]
+    pub native_unit_of_measurement: String,
+    /// The `device_class`, e.g. `"temperature"`, none if missing
+    #[serde(default)]
+    pub device_class: String,
+    /// The `state_class`, e.g. `"measurement"`, none if missing
+    #[serde(default)]
+    pub state_class: String,
+    /// The value of the sensor, integers are reported as `Int` or `Int64` if they do not fit,
+    /// all other numbers as `Float`, booleans as `Bool` and strings as `String`
+    #[serde(default)]
+    pub value: Option<SensorValueConfig>,
+}
+
+/// The value of a simulated sensor
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum SensorValueConfig {
+    Int(i32),
+    Int64(i64),
+    Float(f32),
+    Bool(bool),
+    String(String),
+}
+
+/// The configuration of a simulated switch
+#[derive(Debug, Deserialize)]
+pub struct SwitchConfig {
+    pub name: String,
+    pub unique_id: String,
+    /// The initial state of the switch
+    #[serde(default)]
+    pub state: bool,
+}
+
+/// The configuration of a simulated binary sensor
+#[derive(Debug, Deserialize)]
+pub struct BinarySensorConfig {
+    pub name: String,
+    pub unique_id: String,
+    /// The `device_class`, e.g. `"door"`, none if missing
+    #[serde(default)]
+    pub device_class: Option<String>,
+    /// The state of the binary sensor, unknown if missing
+    #[serde(default)]
+    pub state: Option<bool>,
+}
+
+/// The configuration of a simulated number
+#[derive(Debug, Deserialize)]
+pub struct NumberConfig {
+    pub name: String,
+    pub unique_id: String,
+    #[serde(default)]
+    pub native_unit_of_measurement: String,
+    /// The `device_class`, e.g. `"temperature"`
+    pub device_class: String,
+    pub min: f32,
+    pub max: f32,
+    #[serde(default = "default_step")]
+    pub step: f32,
+    /// The `mode`, e.g. `"slider"`, `"auto"` if missing
+    #[serde(default)]
+    pub mode: Option<String>,
+    /// The initial value, `min` if missing
+    #[serde(default)]
+    pub value: Option<f32>,
+}
+
+/// The configuration of a simulated button, presses are printed
+#[derive(Debug, Deserialize)]
+pub struct ButtonConfig {
+    pub name: String,
+    pub unique_id: String,
+}
+
+/// The configuration of a simulated light, transitions are not simulated
+#[derive(Debug, Deserialize)]
+pub struct LightConfig {
+    pub name: String,
+    pub unique_id: String,
+    /// The initial state of the light
+    #[serde(default)]
+    pub state: bool,
+    /// The initial brightness of the light, full brightness if missing
+    #[serde(default = "default_brightness")]
+    pub brightness: u8,
+}
+
+/// The configuration of a simulated cover, covers arrive at once instead of moving
+#[derive(Debug, Deserialize)]
+pub struct CoverConfig {
+    pub name: String,
+    pub unique_id: String,
+    /// The `device_class`, e.g. `"shutter"`, none if missing
+    #[serde(default)]
+    pub device_class: Option<String>,
+    /// The initial position from `0` closed to `100` open, closed if missing
+    #[serde(default)]
+    pub position: u8,
+}
+
+/// The configuration of a simulated select
+#[derive(Debug, Deserialize)]
+pub struct SelectConfig {
+    pub name: String,
+    pub unique_id: String,
+    /// The options to choose from, at least one and at most 255
+    pub options: Vec<String>,
+    /// The index of the initially selected option, the first if missing
+    #[serde(default)]
+    pub selected: u8,
+}
+
+/// The brightness of a light if none is configured
+fn default_brightness() -> u8 {
+    u8::MAX
+}
+
+/// The step of a number if none is configured
+fn default_step() -> f32 {
+    1.0
+}
+
+impl Config {
+    /// Creates a configuration of `count` nodes with consecutive addresses,
+    /// each having a temperature sensor and a switch
+    /// # Arguments
+    /// * `base_addr` - The address of the first node
+    /// * `count` - The amount of nodes
+    pub fn generate(base_addr: u16, count: u16) -> Self {
+        let nodes = (0..count)
+            .map(|i| NodeConfig {
+                addr: base_addr.wrapping_add(i),
+                groups: Vec::new(),
+                serial: Some((base_addr.wrapping_add(i) as u64).to_be_bytes()),
+                commissioning: false,
+                name: None,
+                sensors: vec![SensorConfig {
+                    name: "Temperature".to_string(),
+                    unique_id: "temp_0".to_string(),
+                    native_unit_of_measurement: "°C".to_string(),
+                    device_class: "temperature".to_string(),
+                    state_class: "measurement".to_string(),
+                    value: Some(SensorValueConfig::Float(20.0 + i as f32 / 10.0)),
+                }],
+                switches: vec![SwitchConfig {
+                    name: "Relay".to_string(),
+                    unique_id: "relay_0".to_string(),
+                    state: false,
+                }],
+                binary_sensors: Vec::new(),
+                numbers: Vec::new(),
+                buttons: Vec::new(),
+                lights: Vec::new(),
+                covers: Vec::new(),
+                selects: Vec::new(),
+            })
+            .collect();
+
+        Self { nodes }
+    }
+
+    /// Adds `count` nodes at [`FACTORY_ADDR`] that have not been assigned an address,
+    /// each having a temperature sensor and a switch
+    /// # Arguments
+    /// * `count` - The amount of nodes
+    pub fn add_unassigned(&mut self, count: u16) {
+        for mut node in Self::generate(FACTORY_ADDR, count).nodes {
+            // Serial numbers of assigned nodes are their addresses, stay clear of them
+            let serial = u64::from_be_bytes(node.serial.unwrap()) | 1 << 32;
+            node.serial = Some(serial.to_be_bytes());
+            node.addr = FACTORY_ADDR;
+            self.nodes.push(node);
+        }
+    }
+
+    /// Builds the bus described by this configuration
+    ///
+    /// Nodes may only share an address if all of them have a serial number,
+    /// like boards that have not been assigned an address yet
+    pub fn build(&self) -> Result<Bus<'_>, String> {
+        let mut bus = Bus::new();
+
+        for node_config in &self.nodes {
+            if let Some(serial) = node_config.serial {
+                if bus.nodes.iter().any(|n| n.serial == Some(serial)) {
+                    return Err(format!("Duplicate serial number {:02x?}", serial));
+                }
+            }
+
+            let shared = bus.nodes.iter().any(|n| {
+                n.addr == node_config.addr && (n.serial.is_none() || node_config.serial.is_none())
+            });
+            if shared {
+                return Err(format!("Duplicate node address {:#06x}", node_config.addr));
+            }
+
+            bus.add_node(node_config.build()?);
+        }
+
+        Ok(bus)
+    }
+}
+
+impl NodeConfig {
+    /// Builds the node described by this configuration
+    pub fn build(&self) -> Result<VirtualNode<'_>, String> {
+        let mut node = VirtualNode::new(self.addr);
+        node.groups = self.groups.clone();
+        node.serial = self.serial;
+        node.commissioning = self.commissioning;
+        if let Some(name) = &self.name {
+            node.name = name.clone();
+        }
+
+        for sensor in &self.sensors {
+            let device_class = parse_device_class(&sensor.device_class)?;
+            let state_class = parse_state_class(&sensor.state_class)?;
+
+            match sensor.value {
+                Some(SensorValueConfig::Int(value)) => node.add_sensor(Sensor::new(
+                    &sensor.name,
+                    &sensor.unique_id,
+                    &sensor.native_unit_of_measurement,
+                    device_class,
+                    state_class,
+                    Some(value),
+                )),
+                Some(SensorValueConfig::Float(value)) => node.add_sensor(Sensor::new(
+                    &sensor.name,
+                    &sensor.unique_id,
+                    &sensor.native_unit_of_measurement,
+                    device_class,
+                    state_class,
+                    Some(value),
+                )),
+                Some(SensorValueConfig::Int64(value)) => node.add_sensor(Sensor::new(
+                    &sensor.name,
+                    &sensor.unique_id,
+                    &sensor.native_unit_of_measurement,
+                    device_class,
+                    state_class,
+                    Some(value),
+                )),
+                Some(SensorValueConfig::Bool(value)) => node.add_sensor(Sensor::new(
+                    &sensor.name,
+                    &sensor.unique_id,
+                    &sensor.native_unit_of_measurement,
+                    device_class,
+                    state_class,
+                    Some(value),
+                )),
+                Some(SensorValueConfig::String(ref value)) => node.add_sensor(Sensor::new(
+                    &sensor.name,
+                    &sensor.unique_id,
+                    &sensor.native_unit_of_measurement,
+                    device_class,
+                    state_class,
+                    Some(value.as_str()),
+                )),
+                None => node.add_sensor(Sensor::<f32>::new(
+                    &sensor.name,
+                    &sensor.unique_id,
+                    &sensor.native_unit_of_measurement,
+                    device_class,
+                    state_class,
+                    None,
+                )),
+            }
+        }
+
+        for switch in &self.switches {
+            let mut state = switch.state;
+            node.add_switch(Switch::new(&switch.name, &switch.unique_id, move |req| {
+                match req {
+                    SwitchRequest::TurnON => state = true,
+                    SwitchRequest::TurnOFF => state = false,
+                    SwitchRequest::Toggle => state = !state,
+                    SwitchRequest::Get => {}
+                }
+                state
+            }));
+        }
+
+        for binary_sensor in &self.binary_sensors {
+            let device_class = match &binary_sensor.device_class {
+                Some(device_class) => parse_binary_sensor_device_class(device_class)?,
+                None => BinarySensorDeviceClass::None,
+            };
+
+            node.add_binary_sensor(BinarySensor::new(
+                &binary_sensor.name,
+                &binary_sensor.unique_id,
+                device_class,
+                binary_sensor.state,
+            ));
+        }
+
+        for number in &self.numbers {
+            let device_class = parse_device_class(&number.device_class)?;
+            let mode = match &number.mode {
+                Some(mode) => parse_number_mode(mode)?,
+                None => NumberMode::Auto,
+            };
+
+            let mut value = number.value.unwrap_or(number.min);
+            node.add_number(
+                Number::new(
+                    &number.name,
+                    &number.unique_id,
+                    &number.native_unit_of_measurement,
+                    device_class,
+                    number.min..=number.max,
+                    number.step,
+                    move |req| {
+                        if let NumberRequest::Set(v) = req {
+                            value = v;
+                        }
+                        value
+                    },
+                )
+                .with_mode(mode),
+            );
+        }
+
+        for button in &self.buttons {
+            let unique_id = &button.unique_id;
+            node.add_button(Button::new(&button.name, unique_id, move || {
+                println!("Button {} pressed", unique_id);
+                Ok(())
+            }));
+        }
+
+        for light in &self.lights {
+            let mut state = LightState {
+                on: light.state,
+                brightness: light.brightness,
+            };
+            node.add_light(Light::new(&light.name, &light.unique_id, move |req| {
+                match req {
+                    LightRequest::TurnOn { brightness, .. } => {
+                        state.on = true;
+                        if let Some(brightness) = brightness {
+                            state.brightness = brightness;
+                        }
+                    }
+                    LightRequest::TurnOff { .. } => state.on = false,
+                    LightRequest::Toggle { .. } => state.on = !state.on,
+                    LightRequest::Get => {}
+                }
+                state
+            }));
+        }
+
+        for cover in &self.covers {
+            let device_class = match &cover.device_class {
+                Some(device_class) => parse_cover_device_class(device_class)?,
+                None => CoverDeviceClass::None,
+            };
+
+            let mut state = CoverState {
+                position: cover.position.min(COVER_POSITION_OPEN),
+                ..Default::default()
+            };
+            node.add_cover(Cover::new(
+                &cover.name,
+                &cover.unique_id,
+                device_class,
+                move |req| {
+                    match req {
+                        CoverRequest::Open => state.position = COVER_POSITION_OPEN,
+                        CoverRequest::Close => state.position = 0,
+                        CoverRequest::SetPosition(position) => state.position = position,
+                        CoverRequest::Stop | CoverRequest::Get => {}
+                    }
+                    state
+                },
+            ));
+        }
+
+        for select in &self.selects {
+            if select.options.is_empty() || select.options.len() > u8::MAX as usize {
+                return Err(format!(
+                    "Select '{}' needs 1 to 255 options",
+                    select.unique_id
+                ));
+            }
+            if select.selected as usize >= select.options.len() {
+                return Err(format!(
+                    "Select '{}' has no option {}",
+                    select.unique_id, select.selected
+                ));
+            }
+
+            let options: Vec<&str> = select.options.iter().map(String::as_str).collect();
+            let mut selected = select.selected;
+            node.add_select(Select::new(
+                &select.name,
+                &select.unique_id,
+                options,
+                move |req| {
+                    if let SelectRequest::Set(index) = req {
+                        selected = index;
+                    }
+                    selected
+                },
+            ));
+        }
+
+        Ok(node)
+    }
+}
+
+/// Parses the `device_class` of a sensor
+fn parse_device_class(s: &str) -> Result<DeviceClass, String> {
+    match s {
+        "" => Ok(DeviceClass::None),
+        "temperature" => Ok(DeviceClass::Temperature),
+        "humidity" => Ok(DeviceClass::Humidity),
+        "pressure" => Ok(DeviceClass::Pressure),
+        _ => Err(format!("Unknown device class '{}'", s)),
+    }
+}
+
+/// Parses the `device_class` of a binary sensor
+fn parse_binary_sensor_device_class(s: &str) -> Result<BinarySensorDeviceClass, String> {
+    match s {
+        "battery" => Ok(BinarySensorDeviceClass::Battery),
+        "cold" => Ok(BinarySensorDeviceClass::Cold),
+        "connectivity" => Ok(BinarySensorDeviceClass::Connectivity),
+        "door" => Ok(BinarySensorDeviceClass::Door),
+        "garage_door" => Ok(BinarySensorDeviceClass::GarageDoor),
+        "heat" => Ok(BinarySensorDeviceClass::Heat),
+        "light" => Ok(BinarySensorDeviceClass::Light),
+        "moisture" => Ok(BinarySensorDeviceClass::Moisture),
+        "motion" => Ok(BinarySensorDeviceClass::Motion),
+        "occupancy" => Ok(BinarySensorDeviceClass::Occupancy),
+        "opening" => Ok(BinarySensorDeviceClass::Opening),
+        "plug" => Ok(BinarySensorDeviceClass::Plug),
+        "power" => Ok(BinarySensorDeviceClass::Power),
+        "presence" => Ok(BinarySensorDeviceClass::Presence),
+        "problem" => Ok(BinarySensorDeviceClass::Problem),
+        "running" => Ok(BinarySensorDeviceClass::Running),
+        "safety" => Ok(BinarySensorDeviceClass::Safety),
+        "smoke" => Ok(BinarySensorDeviceClass::Smoke),
+        "vibration" => Ok(BinarySensorDeviceClass::Vibration),
+        "window" => Ok(BinarySensorDeviceClass::Window),
+        _ => Err(format!("Unknown binary sensor device class '{}'", s)),
+    }
+}
+
+/// Parses the `device_class` of a cover
+fn parse_cover_device_class(s: &str) -> Result<CoverDeviceClass, String> {
+    match s {
+        "awning" => Ok(CoverDeviceClass::Awning),
+        "blind" => Ok(CoverDeviceClass::Blind),
+        "curtain" => Ok(CoverDeviceClass::Curtain),
+        "damper" => Ok(CoverDeviceClass::Damper),
+        "door" => Ok(CoverDeviceClass::Door),
+        "garage" => Ok(CoverDeviceClass::Garage),
+        "gate" => Ok(CoverDeviceClass::Gate),
+        "shade" => Ok(CoverDeviceClass::Shade),
+        "shutter" => Ok(CoverDeviceClass::Shutter),
+        "window" => Ok(CoverDeviceClass::Window),
+        _ => Err(format!("Unknown cover device class '{}'", s)),
+    }
+}
+
+/// Parses the `mode` of a number
+fn parse_number_mode(s: &str) -> Result<NumberMode, String> {
+    match s {
+        "auto" => Ok(NumberMode::Auto),
+        "box" => Ok(NumberMode::Box),
+        "slider" => Ok(NumberMode::Slider),
+        _ => Err(format!("Unknown number mode '{}'", s)),
+    }
+}
+
+/// Parses the `state_class` of a sensor
+fn parse_state_class(s: &str) -> Result<StateClass, String> {
+    match s {
+        "" => Ok(StateClass::None),
+        "measurement" => Ok(StateClass::Measurement),
+        "total" => Ok(StateClass::Total),
+        "total_increasing" => Ok(StateClass::TotalIncreasing),
+        _ => Err(format!("Unknown state class '{}'", s)),
+    }
+}
+
+/// An address in the configuration, either a number or a string
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Addr {
+    Number(u16),
+    String(String),
+}
+
+impl Addr {
+    /// Parses the address, strings may have a `0x` prefix
+    fn parse<E: serde::de::Error>(self) -> Result<u16, E> {
+        match self {
+            Addr::Number(addr) => Ok(addr),
+            Addr::String(s) => {
+                let res = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+                    Some(hex) => u16::from_str_radix(hex, 16),
+                    None => s.parse(),
+                };
+                res.map_err(|_| E::custom(format!("Invalid address '{}'", s)))
+            }
+        }
+    }
+}
+
+/// Deserializes an address from a number or a string with an optional `0x` prefix
+fn deserialize_addr<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u16, D::Error> {
+    Addr::deserialize(deserializer)?.parse()
+}
+
+/// Deserializes a list of group addresses, which must be multicast addresses
+fn deserialize_groups<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u16>, D::Error> {
+    Vec::<Addr>::deserialize(deserializer)?
+        .into_iter()
+        .map(|addr| {
+            let addr = addr.parse()?;
+            if !is_multicast(addr) {
+                return Err(serde::de::Error::custom(format!(
+                    "Group address {:#06x} is not a multicast address",
+                    addr
+                )));
+            }
+            Ok(addr)
+        })
+        .collect()
+}
+
+/// Deserializes a serial number from a string of hex digits
+fn deserialize_serial<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Serial>, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    let invalid = || serde::de::Error::custom(format!("Invalid serial number '{}'", s));
+
+    if s.len() != SERIAL_LEN * 2 {
+        return Err(invalid());
+    }
+
+    let mut serial = Serial::default();
+    for (i, byte) in serial.iter_mut().enumerate() {
+        let digits = s.get(i * 2..i * 2 + 2).ok_or_else(invalid)?;
+        *byte = u8::from_str_radix(digits, 16).map_err(|_| invalid())?;
+    }
+
+    Ok(Some(serial))
+}
@@ -0,0 +1,12 @@
+//! A software simulation of the HA-Buddy bus
+//!
+//! Many [`VirtualNode`]s share one [`Bus`] that runs the same frame
+//! handling as the firmware, so masters can be tested without hardware.
+
+pub mod config;
+
+mod bus;
+pub use bus::*;
+
+mod node;
+pub use node::*;
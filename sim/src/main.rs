@@ -0,0 +1,107 @@
+//! `ha-buddy-sim` - exposes a simulated HA-Buddy bus on a pseudo terminal
+
+use std::{
+    fs, os::unix, path::PathBuf, process::ExitCode, sync::atomic::AtomicBool, time::Duration,
+};
+
+use clap::Parser;
+use ha_buddy_sim::{config::Config, serve};
+use serialport::{SerialPort, TTYPort};
+
+#[derive(Parser)]
+#[command(version, about = "Simulate a HA-Buddy bus on a pseudo terminal")]
+struct Cli {
+    /// A JSON file describing the nodes on the bus
+    #[arg(short, long, conflicts_with_all = ["nodes", "base_addr"])]
+    config: Option<PathBuf>,
+
+    /// The amount of generated nodes, each with a temperature sensor and a switch
+    #[arg(short, long, default_value_t = 1)]
+    nodes: u16,
+
+    /// The address of the first generated node
+    #[arg(short, long, default_value = "0x1000", value_parser = parse_addr)]
+    base_addr: u16,
+
+    /// The amount of generated nodes that have not been assigned an address yet
+    #[arg(short, long, default_value_t = 0)]
+    unassigned: u16,
+
+    /// Create a symlink to the pseudo terminal at this path
+    #[arg(short, long)]
+    link: Option<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let mut config = match &cli.config {
+        Some(path) => {
+            let content = fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            serde_json::from_str(&content)
+                .map_err(|e| format!("Invalid configuration {}: {}", path.display(), e))?
+        }
+        None => Config::generate(cli.base_addr, cli.nodes),
+    };
+
+    config.add_unassigned(cli.unassigned);
+
+    let mut bus = config.build()?;
+
+    let (mut master, slave) = TTYPort::pair()?;
+    master.set_timeout(Duration::from_millis(100))?;
+    let path = slave.name().ok_or("Pseudo terminal has no name")?;
+
+    if let Some(link) = &cli.link {
+        // Replace stale links from earlier runs
+        if fs::symlink_metadata(link).is_ok() {
+            fs::remove_file(link)?;
+        }
+        unix::fs::symlink(&path, link)?;
+        println!("Bus available at {} ({})", link.display(), path);
+    } else {
+        println!("Bus available at {}", path);
+    }
+
+    for node in &bus.nodes {
+        println!(
+            "  Node {:#06x}: {} sensor(s), {} switch(es), {} binary sensor(s), {} number(s), {} button(s), {} light(s), {} cover(s), {} select(s)",
+            node.addr,
+            node.sensors.len(),
+            node.switches.len(),
+            node.binary_sensors.len(),
+            node.numbers.len(),
+            node.buttons.len(),
+            node.lights.len(),
+            node.covers.len(),
+            node.selects.len()
+        );
+    }
+
+    let stop = AtomicBool::new(false);
+    serve(&mut bus, &mut master, &stop)?;
+
+    // Keep the slave side open while serving, so the terminal stays alive without a master
+    drop(slave);
+
+    Ok(())
+}
+
+/// Parses an address with an optional `0x` prefix
+fn parse_addr(s: &str) -> Result<u16, String> {
+    let res = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u16::from_str_radix(hex, 16),
+        None => s.parse(),
+    };
+
+    res.map_err(|e| format!("Invalid address '{}': {}", s, e))
+}
@@ -0,0 +1,251 @@
+use std::{convert::Infallible, time::Instant};
+
+use ha_buddy_proto::{
+    datalink::Serial,
+    device::DeviceInfo,
+    event::EventQueue,
+    handler::{handle_request, HandlerPins},
+    homeassistant::{
+        binary_sensor::BinarySensorRef, button::ButtonRef, cover::CoverRef, light::LightRef,
+        number::NumberRef, select::SelectRef, sensor::SensorRef, switch::SwitchRef, Entities,
+    },
+    node::Node,
+    DataFrame,
+};
+
+/// The board simulated nodes report
+pub const SIM_BOARD: &str = "ha-buddy-sim";
+
+/// A simulated node on the bus
+///
+/// Received bytes go through [`DataFrame::handle_byte`] and [`handle_request`],
+/// exactly like the firmware main loop does it.
+pub struct VirtualNode<'a> {
+    /// The address of this node
+    pub addr: u16,
+    /// The multicast groups this node is a member of
+    pub groups: Vec<u16>,
+    /// The serial number of this node
+    pub serial: Option<Serial>,
+    /// If set, the node accepts a new address without a serial number,
+    /// like after pressing the commissioning button of a board
+    pub commissioning: bool,
+    /// The name reported in the device information
+    pub name: String,
+    /// The sensors of this node
+    pub sensors: Vec<Box<dyn SensorRef<'a> + 'a>>,
+    /// The switches of this node
+    pub switches: Vec<Box<dyn SwitchRef<'a> + 'a>>,
+    /// The binary sensors of this node
+    pub binary_sensors: Vec<Box<dyn BinarySensorRef<'a> + 'a>>,
+    /// The numbers of this node
+    pub numbers: Vec<Box<dyn NumberRef<'a> + 'a>>,
+    /// The buttons of this node
+    pub buttons: Vec<Box<dyn ButtonRef<'a> + 'a>>,
+    /// The lights of this node
+    pub lights: Vec<Box<dyn LightRef<'a> + 'a>>,
+    /// The covers of this node
+    pub covers: Vec<Box<dyn CoverRef<'a> + 'a>>,
+    /// The selects of this node
+    pub selects: Vec<Box<dyn SelectRef<'a> + 'a>>,
+    /// The frame incoming bytes are assembled in
+    frame: DataFrame,
+    /// The pins exposed to the handler
+    pins: HandlerPins,
+    /// When the node was started, for the uptime
+    started: Instant,
+    /// The changes of entities waiting to be fetched by the master
+    events: EventQueue,
+}
+
+impl<'a> VirtualNode<'a> {
+    /// Creates a new node without any entities
+    /// # Arguments
+    /// * `addr` - The address of the node
+    pub fn new(addr: u16) -> Self {
+        Self {
+            addr,
+            groups: Vec::new(),
+            serial: None,
+            commissioning: false,
+            name: "Simulated node".to_string(),
+            sensors: Vec::new(),
+            switches: Vec::new(),
+            binary_sensors: Vec::new(),
+            numbers: Vec::new(),
+            buttons: Vec::new(),
+            lights: Vec::new(),
+            covers: Vec::new(),
+            selects: Vec::new(),
+            frame: DataFrame::new(),
+            pins: HandlerPins {},
+            started: Instant::now(),
+            events: EventQueue::new(),
+        }
+    }
+
+    /// Adds a sensor to this node
+    /// # Arguments
+    /// * `sensor` - The sensor to add
+    pub fn add_sensor(&mut self, sensor: impl SensorRef<'a> + 'a) {
+        self.sensors.push(Box::new(sensor));
+    }
+
+    /// Adds a switch to this node
+    /// # Arguments
+    /// * `switch` - The switch to add
+    pub fn add_switch(&mut self, switch: impl SwitchRef<'a> + 'a) {
+        self.switches.push(Box::new(switch));
+    }
+
+    /// Adds a binary sensor to this node
+    /// # Arguments
+    /// * `binary_sensor` - The binary sensor to add
+    pub fn add_binary_sensor(&mut self, binary_sensor: impl BinarySensorRef<'a> + 'a) {
+        self.binary_sensors.push(Box::new(binary_sensor));
+    }
+
+    /// Adds a number to this node
+    /// # Arguments
+    /// * `number` - The number to add
+    pub fn add_number(&mut self, number: impl NumberRef<'a> + 'a) {
+        self.numbers.push(Box::new(number));
+    }
+
+    /// Adds a button to this node
+    /// # Arguments
+    /// * `button` - The button to add
+    pub fn add_button(&mut self, button: impl ButtonRef<'a> + 'a) {
+        self.buttons.push(Box::new(button));
+    }
+
+    /// Adds a light to this node
+    /// # Arguments
+    /// * `light` - The light to add
+    pub fn add_light(&mut self, light: impl LightRef<'a> + 'a) {
+        self.lights.push(Box::new(light));
+    }
+
+    /// Adds a cover to this node
+    /// # Arguments
+    /// * `cover` - The cover to add
+    pub fn add_cover(&mut self, cover: impl CoverRef<'a> + 'a) {
+        self.covers.push(Box::new(cover));
+    }
+
+    /// Adds a select to this node
+    /// # Arguments
+    /// * `select` - The select to add
+    pub fn add_select(&mut self, select: impl SelectRef<'a> + 'a) {
+        self.selects.push(Box::new(select));
+    }
+
+    /// Feeds a byte from the bus into this node
+    /// # Arguments
+    /// * `byte` - The byte on the bus
+    /// * `out` - The buffer to append the response of this node to
+    /// # Returns
+    /// `true` if this node responded
+    pub fn handle_byte(&mut self, byte: u8, out: &mut Vec<u8>) -> bool {
+        if !self.frame.handle_byte(byte) {
+            return false;
+        }
+
+        let sensors: Vec<&dyn SensorRef<'a>> = self.sensors.iter().map(|s| s.as_ref()).collect();
+        let mut switches: Vec<&mut dyn SwitchRef<'a>> = self
+            .switches
+            .iter_mut()
+            .map(|s| s.as_mut() as &mut dyn SwitchRef<'a>)
+            .collect();
+        let binary_sensors: Vec<&dyn BinarySensorRef<'a>> =
+            self.binary_sensors.iter().map(|s| s.as_ref()).collect();
+        let mut numbers: Vec<&mut dyn NumberRef<'a>> = self
+            .numbers
+            .iter_mut()
+            .map(|n| n.as_mut() as &mut dyn NumberRef<'a>)
+            .collect();
+        let mut buttons: Vec<&mut dyn ButtonRef<'a>> = self
+            .buttons
+            .iter_mut()
+            .map(|b| b.as_mut() as &mut dyn ButtonRef<'a>)
+            .collect();
+        let mut lights: Vec<&mut dyn LightRef<'a>> = self
+            .lights
+            .iter_mut()
+            .map(|l| l.as_mut() as &mut dyn LightRef<'a>)
+            .collect();
+        let mut covers: Vec<&mut dyn CoverRef<'a>> = self
+            .covers
+            .iter_mut()
+            .map(|c| c.as_mut() as &mut dyn CoverRef<'a>)
+            .collect();
+        let mut selects: Vec<&mut dyn SelectRef<'a>> = self
+            .selects
+            .iter_mut()
+            .map(|s| s.as_mut() as &mut dyn SelectRef<'a>)
+            .collect();
+
+        let mut node = Node {
+            addr: self.addr,
+            groups: &self.groups,
+            serial: self.serial,
+            commissioning: self.commissioning,
+            info: DeviceInfo {
+                firmware_version: env!("CARGO_PKG_VERSION"),
+                board: SIM_BOARD,
+                name: &self.name,
+                uptime: self.started.elapsed().as_secs() as u32,
+            },
+            events: std::mem::take(&mut self.events),
+        };
+
+        let respond = handle_request(
+            &mut self.frame,
+            &mut node,
+            &mut self.pins,
+            &mut Entities {
+                sensors: &sensors,
+                switches: &mut switches,
+                binary_sensors: &binary_sensors,
+                numbers: &mut numbers,
+                buttons: &mut buttons,
+                lights: &mut lights,
+                covers: &mut covers,
+                selects: &mut selects,
+            },
+        );
+
+        self.addr = node.addr;
+        self.commissioning = node.commissioning;
+        self.events = node.events;
+
+        if !respond {
+            return false;
+        }
+
+        let mut writer = BusWriter { out };
+        match self.frame.send(&mut writer) {
+            Ok(()) => true,
+            Err(nb::Error::WouldBlock) => unreachable!(),
+            Err(nb::Error::Other(e)) => match e {},
+        }
+    }
+}
+
+/// Writes the bytes of a frame to the simulated bus
+struct BusWriter<'o> {
+    out: &'o mut Vec<u8>,
+}
+
+impl embedded_hal::serial::Write<u8> for BusWriter<'_> {
+    type Error = Infallible;
+
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        self.out.push(word);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,503 @@
+use std::{
+    io::{ErrorKind, Read},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use ha_buddy_client::{Client, Error, Value};
+use ha_buddy_proto::{
+    datalink::{BROADCAST_ADDR, FACTORY_ADDR},
+    error::ErrorCode,
+    homeassistant::{
+        cover::{CoverMotion, CoverRequest, CoverState},
+        light::{LightRequest, LightState},
+        switch::SwitchRequest,
+    },
+};
+use ha_buddy_sim::{config::Config, serve, Bus, SIM_BOARD};
+use serialport::{SerialPort, TTYPort};
+
+const CONFIG: &str = r#"{
+    "nodes": [
+        {
+            "addr": "0x1000",
+            "sensors": [
+                {
+                    "name": "Temperature",
+                    "unique_id": "temp_0",
+                    "native_unit_of_measurement": "°C",
+                    "device_class": "temperature",
+                    "state_class": "measurement",
+                    "value": 21.5
+                },
+                {
+                    "name": "Counter",
+                    "unique_id": "counter_0",
+                    "device_class": "temperature",
+                    "state_class": "total_increasing",
+                    "value": 42
+                },
+                {
+                    "name": "Offline",
+                    "unique_id": "offline_0",
+                    "device_class": "temperature",
+                    "state_class": "measurement"
+                },
+                { "name": "Status", "unique_id": "status_0", "value": "heating" },
+                { "name": "Energy", "unique_id": "energy_0", "state_class": "total_increasing", "value": 5000000000 },
+                { "name": "Window", "unique_id": "window_0", "value": true }
+            ]
+        },
+        {
+            "addr": 4097,
+            "name": "Garage",
+            "switches": [
+                { "name": "Relay", "unique_id": "relay_0", "state": true }
+            ],
+            "binary_sensors": [
+                { "name": "Door", "unique_id": "door_0", "device_class": "door", "state": false },
+                { "name": "Contact", "unique_id": "contact_0" }
+            ],
+            "numbers": [
+                {
+                    "name": "Setpoint",
+                    "unique_id": "setpoint_0",
+                    "native_unit_of_measurement": "°C",
+                    "device_class": "temperature",
+                    "min": 5,
+                    "max": 30,
+                    "step": 0.5,
+                    "mode": "box",
+                    "value": 21
+                }
+            ],
+            "buttons": [
+                { "name": "Garage door", "unique_id": "garage_0" }
+            ],
+            "lights": [
+                { "name": "Porch", "unique_id": "porch_0", "brightness": 64 }
+            ],
+            "covers": [
+                { "name": "Garage gate", "unique_id": "gate_0", "device_class": "gate", "position": 100 }
+            ],
+            "selects": [
+                { "name": "Heating", "unique_id": "heating_0", "options": ["off", "eco", "comfort"], "selected": 1 }
+            ]
+        }
+    ]
+}"#;
+
+fn client(bus: Bus) -> Client<Bus> {
+    let mut client = Client::new(bus);
+    client.set_timeout(Duration::from_millis(10));
+    client.set_retries(0);
+    client
+}
+
+#[test]
+fn config_file() {
+    let config: Config = serde_json::from_str(CONFIG).unwrap();
+    let mut client = client(config.build().unwrap());
+
+    assert_eq!(client.sensor_count(0x1000).unwrap(), 6);
+    assert_eq!(client.switch_count(0x1000).unwrap(), 0);
+    assert_eq!(
+        client.read_sensor(0x1000, 0).unwrap(),
+        Some(Value::Float(21.5))
+    );
+    assert_eq!(client.read_sensor(0x1000, 1).unwrap(), Some(Value::Int(42)));
+    assert_eq!(client.read_sensor(0x1000, 2).unwrap(), None);
+    let (info, value) = client.describe_sensor(0x1000, 3).unwrap();
+    assert_eq!(
+        (info.device_class.as_str(), info.state_class.as_str()),
+        ("", "")
+    );
+    assert_eq!(value, Some(Value::String("heating".to_string())));
+    assert_eq!(
+        client.read_all_sensors(0x1000).unwrap()[3]
+            .value
+            .as_ref()
+            .unwrap(),
+        &Some(Value::String("heating".to_string()))
+    );
+    assert_eq!(
+        client.read_sensor(0x1000, 4).unwrap(),
+        Some(Value::Int64(5_000_000_000))
+    );
+    assert_eq!(
+        client.read_sensor(0x1000, 5).unwrap(),
+        Some(Value::Bool(true))
+    );
+    assert_eq!(
+        client.sensor_info(0x1000, 1).unwrap().state_class,
+        "total_increasing"
+    );
+
+    assert_eq!(client.sensor_count(0x1001).unwrap(), 0);
+    assert_eq!(client.switch_count(0x1001).unwrap(), 1);
+    assert!(client.switch_state(0x1001, 0).unwrap());
+    assert_eq!(client.binary_sensor_count(0x1001).unwrap(), 2);
+    assert_eq!(
+        client.describe_binary_sensor(0x1001, 0).unwrap().1,
+        Some(false)
+    );
+    assert_eq!(
+        client.binary_sensor_info(0x1001, 1).unwrap().device_class,
+        ""
+    );
+    assert_eq!(client.binary_sensor_state(0x1001, 1).unwrap(), None);
+    let (info, value) = client.describe_number(0x1001, 0).unwrap();
+    assert_eq!((info.step, info.mode.as_str(), value), (0.5, "box", 21.0));
+    assert_eq!(client.set_number(0x1001, 0, 5.0).unwrap(), 5.0);
+    assert_eq!(
+        client.describe_button(0x1001, 0).unwrap().unique_id,
+        "garage_0"
+    );
+    client.press_button(0x1001, 0).unwrap();
+    let (info, state) = client.describe_light(0x1001, 0).unwrap();
+    assert_eq!(info.unique_id, "porch_0");
+    assert_eq!(
+        state,
+        LightState {
+            on: false,
+            brightness: 64
+        }
+    );
+    let req = LightRequest::Toggle { transition_ms: 0 };
+    assert!(client.light_exec(0x1001, 0, req).unwrap().on);
+    let (info, state) = client.describe_cover(0x1001, 0).unwrap();
+    assert_eq!(info.device_class, "CoverDeviceClass.GATE");
+    assert_eq!(state.position, 100);
+    assert_eq!(
+        client.cover_exec(0x1001, 0, CoverRequest::Close).unwrap(),
+        CoverState {
+            position: 0,
+            motion: CoverMotion::Stopped
+        }
+    );
+    let (info, index) = client.describe_select(0x1001, 0).unwrap();
+    assert_eq!(info.options, ["off", "eco", "comfort"]);
+    assert_eq!(index, 1);
+    assert_eq!(client.set_select(0x1001, 0, 2).unwrap(), 2);
+
+    let info = client.node_info(0x1001).unwrap();
+    assert_eq!(info.name, "Garage");
+    assert_eq!(info.board, SIM_BOARD);
+    assert_eq!(client.node_info(0x1000).unwrap().name, "Simulated node");
+}
+
+#[test]
+fn many_nodes() {
+    let config = Config::generate(0x1000, 16);
+    let mut client = client(config.build().unwrap());
+
+    for addr in 0x1000..0x1010 {
+        assert_eq!(
+            client.echo(addr, &addr.to_le_bytes()).unwrap(),
+            addr.to_le_bytes()
+        );
+        assert_eq!(client.sensor_count(addr).unwrap(), 1);
+    }
+
+    assert!(matches!(
+        client.echo(0x1010, &[]),
+        Err(Error::Timeout { addr: 0x1010, .. })
+    ));
+}
+
+#[test]
+fn nodes_are_independent() {
+    let config = Config::generate(0x1000, 2);
+    let mut client = client(config.build().unwrap());
+
+    assert!(client
+        .switch_exec(0x1001, 0, SwitchRequest::TurnON)
+        .unwrap());
+    assert!(!client.switch_state(0x1000, 0).unwrap());
+    assert!(client.switch_state(0x1001, 0).unwrap());
+
+    assert_ne!(
+        client.read_sensor(0x1000, 0).unwrap(),
+        client.read_sensor(0x1001, 0).unwrap()
+    );
+}
+
+#[test]
+fn broadcast_reaches_all_nodes() {
+    let config = Config::generate(0x1000, 3);
+    let mut client = client(config.build().unwrap());
+    for addr in 0x1000..0x1003 {
+        assert!(client.switch_exec(addr, 0, SwitchRequest::TurnON).unwrap());
+    }
+
+    client.all_switches_off().unwrap();
+    assert_eq!(
+        client
+            .transport_mut()
+            .read(&mut [0; 16])
+            .unwrap_err()
+            .kind(),
+        ErrorKind::TimedOut
+    );
+
+    for addr in 0x1000..0x1003 {
+        assert!(!client.switch_state(addr, 0).unwrap());
+    }
+}
+
+#[test]
+fn multicast_groups() {
+    let config: Config = serde_json::from_str(
+        r#"{ "nodes": [
+            { "addr": "0x1000", "groups": ["0xff01"], "switches": [ { "name": "a", "unique_id": "a" } ] },
+            { "addr": "0x1001", "groups": ["0xff01", 65282], "switches": [ { "name": "b", "unique_id": "b" } ] },
+            { "addr": "0x1002", "switches": [ { "name": "c", "unique_id": "c" } ] }
+        ] }"#,
+    )
+    .unwrap();
+    let mut client = client(config.build().unwrap());
+
+    client
+        .multicast_switch_exec(0xff01, 0, SwitchRequest::TurnON)
+        .unwrap();
+    assert!(client.switch_state(0x1000, 0).unwrap());
+    assert!(client.switch_state(0x1001, 0).unwrap());
+    assert!(!client.switch_state(0x1002, 0).unwrap());
+
+    client
+        .multicast_switch_exec(0xff02, 0, SwitchRequest::Toggle)
+        .unwrap();
+    assert!(client.switch_state(0x1000, 0).unwrap());
+    assert!(!client.switch_state(0x1001, 0).unwrap());
+
+    assert!(matches!(
+        client.switch_state(0xff01, 0),
+        Err(Error::MulticastRequest(0xff01))
+    ));
+}
+
+#[test]
+fn group_must_be_multicast() {
+    assert!(
+        serde_json::from_str::<Config>(r#"{ "nodes": [ { "addr": 1, "groups": [2] } ] }"#).is_err()
+    );
+}
+
+#[test]
+fn assign_address_by_serial() {
+    let config: Config = serde_json::from_str(
+        r#"{ "nodes": [
+            { "addr": "0x1000", "serial": "0000000000000001" },
+            { "addr": "0x1000", "serial": "0000000000000002" }
+        ] }"#,
+    )
+    .unwrap();
+    let mut bus = Bus::new();
+    for node in &config.nodes {
+        bus.add_node(node.build().unwrap());
+    }
+    let mut client = client(bus);
+
+    let serial = [0, 0, 0, 0, 0, 0, 0, 2];
+    client
+        .assign_address(BROADCAST_ADDR, 0x1001, Some(&serial))
+        .unwrap();
+
+    assert_eq!(
+        client.node_serial(0x1000).unwrap(),
+        Some([0, 0, 0, 0, 0, 0, 0, 1])
+    );
+    assert_eq!(client.node_serial(0x1001).unwrap(), Some(serial));
+
+    client
+        .assign_address(0x1001, 0x1002, Some(&serial))
+        .unwrap();
+    assert_eq!(client.echo(0x1002, b"moved").unwrap(), b"moved");
+    assert!(matches!(
+        client.echo(0x1001, &[]),
+        Err(Error::Timeout { addr: 0x1001, .. })
+    ));
+}
+
+#[test]
+fn assign_address_commissioning() {
+    let config: Config = serde_json::from_str(
+        r#"{ "nodes": [
+            { "addr": "0x1000" },
+            { "addr": "0x1001", "commissioning": true }
+        ] }"#,
+    )
+    .unwrap();
+    let mut client = client(config.build().unwrap());
+
+    client.assign_address(BROADCAST_ADDR, 0x2000, None).unwrap();
+    assert_eq!(client.echo(0x1000, b"").unwrap(), b"");
+    assert_eq!(client.echo(0x2000, b"").unwrap(), b"");
+    assert!(
+        !client
+            .transport_mut()
+            .node_mut(0x2000)
+            .unwrap()
+            .commissioning
+    );
+
+    let err = client.assign_address(0x2000, 0x2001, None).unwrap_err();
+    assert_eq!(err.node_error(), Some(ErrorCode::NotPermitted));
+}
+
+/// Nodes sharing the factory address, `0x...01` and `0x...03` form a valid
+/// frame when colliding, and an assigned node
+const FACTORY_CONFIG: &str = r#"{ "nodes": [
+    { "addr": "0x1000", "serial": "0000000000000001" },
+    { "addr": "0x1000", "serial": "0000000000000003" },
+    { "addr": "0x1000", "serial": "8000000000000000" },
+    { "addr": "0x1000", "serial": "8000000000000001" },
+    { "addr": "0x1000", "serial": "c0ffee0000000042" },
+    { "addr": "0x1001", "serial": "0011223344556677" }
+] }"#;
+
+#[test]
+fn discover_nodes() {
+    let config: Config = serde_json::from_str(FACTORY_CONFIG).unwrap();
+    let mut client = client(config.build().unwrap());
+    // The simulated bus responds instantly, only silent probes wait
+    client.set_timeout(Duration::from_millis(1));
+
+    let mut serials: Vec<u64> = client
+        .discover(FACTORY_ADDR)
+        .unwrap()
+        .iter()
+        .map(|node| {
+            assert_eq!(node.addr, FACTORY_ADDR);
+            u64::from_be_bytes(node.serial)
+        })
+        .collect();
+    serials.sort();
+    assert_eq!(
+        serials,
+        [
+            1,
+            3,
+            0x8000000000000000,
+            0x8000000000000001,
+            0xc0ffee0000000042
+        ]
+    );
+
+    assert_eq!(client.discover(BROADCAST_ADDR).unwrap().len(), 6);
+    assert_eq!(client.discover(0x1001).unwrap().len(), 1);
+    assert_eq!(client.discover(0x2000).unwrap(), []);
+}
+
+#[test]
+fn enumerate_nodes() {
+    let config: Config = serde_json::from_str(FACTORY_CONFIG).unwrap();
+    let mut client = client(config.build().unwrap());
+    // The simulated bus responds instantly, only silent probes wait
+    client.set_timeout(Duration::from_millis(1));
+
+    let assigned = client.enumerate(0x1000..0x1010).unwrap();
+    let mut addrs: Vec<u16> = assigned.iter().map(|node| node.addr).collect();
+    addrs.sort();
+    assert_eq!(addrs, [0x1002, 0x1003, 0x1004, 0x1005, 0x1006]);
+
+    for node in &assigned {
+        assert_eq!(client.node_serial(node.addr).unwrap(), Some(node.serial));
+    }
+    assert_eq!(client.discover(FACTORY_ADDR).unwrap(), []);
+}
+
+#[test]
+fn enumerate_runs_out_of_addresses() {
+    let mut config = Config::generate(0x1001, 1);
+    config.add_unassigned(3);
+    let mut client = client(config.build().unwrap());
+    // The simulated bus responds instantly, only silent probes wait
+    client.set_timeout(Duration::from_millis(1));
+
+    assert!(matches!(
+        client.enumerate(0x1001..0x1003),
+        Err(Error::AddressesExhausted)
+    ));
+}
+
+#[test]
+fn duplicate_serial() {
+    let config: Config = serde_json::from_str(
+        r#"{ "nodes": [
+            { "addr": 1, "serial": "0000000000000001" },
+            { "addr": 2, "serial": "0000000000000001" }
+        ] }"#,
+    )
+    .unwrap();
+    assert!(config.build().is_err());
+}
+
+#[test]
+fn duplicate_address() {
+    let config: Config =
+        serde_json::from_str(r#"{ "nodes": [ { "addr": 1 }, { "addr": "0x0001" } ] }"#).unwrap();
+    assert!(config.build().is_err());
+}
+
+#[test]
+fn unknown_device_class() {
+    let config: Config = serde_json::from_str(
+        r#"{ "nodes": [ { "addr": 1, "sensors": [ {
+            "name": "n", "unique_id": "u", "device_class": "nope", "state_class": "total"
+        } ] } ] }"#,
+    )
+    .unwrap();
+    assert!(config.build().is_err());
+}
+
+#[test]
+fn pseudo_terminal() {
+    let (mut master, slave) = TTYPort::pair().unwrap();
+    master.set_timeout(Duration::from_millis(10)).unwrap();
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread_stop = stop.clone();
+    let server = thread::spawn(move || {
+        let config = Config::generate(0x2000, 3);
+        let mut bus = config.build().unwrap();
+        serve(&mut bus, &mut master, &thread_stop).unwrap();
+    });
+
+    let port = serialport::new(slave.name().unwrap(), 57600)
+        .timeout(Duration::from_millis(10))
+        .open()
+        .unwrap();
+    port.clear(serialport::ClearBuffer::All).unwrap();
+
+    let mut client = Client::new(port);
+    client.set_timeout(Duration::from_millis(200));
+    for addr in 0x2000..0x2003 {
+        assert_eq!(client.echo(addr, b"sim").unwrap(), b"sim");
+    }
+    assert!(client
+        .switch_exec(0x2002, 0, SwitchRequest::Toggle)
+        .unwrap());
+
+    stop.store(true, Ordering::Relaxed);
+    server.join().unwrap();
+}
+
+#[test]
+fn invalid_select() {
+    for select in [
+        r#"{ "name": "n", "unique_id": "u", "options": [] }"#,
+        r#"{ "name": "n", "unique_id": "u", "options": ["a", "b"], "selected": 2 }"#,
+    ] {
+        let config: Config = serde_json::from_str(&format!(
+            r#"{{ "nodes": [ {{ "addr": 1, "selects": [ {} ] }} ] }}"#,
+            select
+        ))
+        .unwrap();
+        assert!(config.build().is_err());
+    }
+}
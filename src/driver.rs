@@ -1,3 +0,0 @@
-mod common;
-
-pub mod ds18b20;
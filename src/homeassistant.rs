@@ -1,4 +0,0 @@
-pub mod entity;
-
-pub mod sensor;
-pub mod switch;